@@ -0,0 +1,192 @@
+use clap::Parser;
+use colored::*;
+use cpudetect::bench;
+use cpudetect::*;
+
+#[derive(Parser)]
+#[command(name = "cpubench", version, about = "Measure cache latency and memory bandwidth across working-set sizes")]
+struct Cli {
+    /// Smallest working set in the sweep (e.g. 4K, 32K, 1M)
+    #[arg(long, value_name = "SIZE", default_value = "4K", value_parser = parse_size)]
+    min: usize,
+
+    /// Largest working set in the sweep
+    #[arg(long, value_name = "SIZE", default_value = "256M", value_parser = parse_size)]
+    max: usize,
+
+    /// Measure a single working-set size instead of a sweep
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, conflicts_with_all = ["min", "max"])]
+    size: Option<usize>,
+
+    /// Measure each detected cache level and DRAM instead of a size sweep
+    #[arg(long, conflicts_with_all = ["min", "max", "size"])]
+    levels: bool,
+
+    /// Emit JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (digits, shift) = match value.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&value[..i], 10),
+        Some((i, 'M' | 'm')) => (&value[..i], 20),
+        Some((i, 'G' | 'g')) => (&value[..i], 30),
+        _ => (value, 0),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("invalid size '{}' (expected e.g. 4096, 32K, 8M, 1G)", value))
+}
+
+fn format_size(bytes: usize) -> String {
+    match bytes {
+        b if b >= 1 << 30 && b % (1 << 30) == 0 => format!("{}G", b >> 30),
+        b if b >= 1 << 20 && b % (1 << 20) == 0 => format!("{}M", b >> 20),
+        b if b >= 1 << 10 && b % (1 << 10) == 0 => format!("{}K", b >> 10),
+        b => b.to_string(),
+    }
+}
+
+/// Smallest declared data cache the working set fits in, or "DRAM".
+fn fits_in(size: usize, caches: &[CacheInfo]) -> String {
+    caches
+        .iter()
+        .filter(|c| c.cache_type != CacheType::Instruction && size as u64 <= c.size)
+        .min_by_key(|c| c.level as u32)
+        .map_or("DRAM".to_string(), |c| format!("{:?}", c.level))
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if cli.levels {
+        let results = cache::benchmark();
+        if cli.json {
+            print_levels_json(&results);
+        } else {
+            print_levels_table(&results);
+        }
+        return;
+    }
+
+    let caches = CacheInfo::detect_all();
+
+    let results = match cli.size {
+        Some(size) => vec![BenchResult {
+            size,
+            latency_ns: bench::measure_latency(size),
+            bandwidth_gbs: bench::measure_bandwidth(size),
+        }],
+        None => bench::sweep(cli.min, cli.max),
+    };
+
+    if cli.json {
+        print_json(&caches, &results);
+    } else {
+        print_table(&caches, &results);
+    }
+}
+
+fn print_table(caches: &[CacheInfo], results: &[BenchResult]) {
+    println!("{}", "Declared caches:".bright_white().bold());
+    for cache in caches {
+        println!("  {}", cache);
+    }
+
+    println!(
+        "\n{:>10}  {:>6}  {:>12}  {:>14}",
+        "Size".bright_white().bold(),
+        "Fits".bright_white().bold(),
+        "Latency (ns)".bright_white().bold(),
+        "Bandwidth GB/s".bright_white().bold()
+    );
+    for result in results {
+        println!(
+            "{:>10}  {:>6}  {:>12}  {:>14}",
+            format_size(result.size),
+            fits_in(result.size, caches).bright_cyan(),
+            format!("{:.2}", result.latency_ns).bright_yellow(),
+            format!("{:.2}", result.bandwidth_gbs).bright_green()
+        );
+    }
+}
+
+fn print_json(caches: &[CacheInfo], results: &[BenchResult]) {
+    println!("{{");
+    println!("  \"caches\": [");
+    for (i, cache) in caches.iter().enumerate() {
+        let comma = if i + 1 < caches.len() { "," } else { "" };
+        println!(
+            "    {{\"level\": \"{:?}\", \"type\": \"{:?}\", \"size\": {}, \"line_size\": {}, \"ways\": {}}}{}",
+            cache.level, cache.cache_type, cache.size, cache.line_size, cache.ways, comma
+        );
+    }
+    println!("  ],");
+    println!("  \"results\": [");
+    for (i, result) in results.iter().enumerate() {
+        let comma = if i + 1 < results.len() { "," } else { "" };
+        println!(
+            "    {{\"size\": {}, \"fits\": \"{}\", \"latency_ns\": {:.3}, \"bandwidth_gbs\": {:.3}}}{}",
+            result.size,
+            fits_in(result.size, caches),
+            result.latency_ns,
+            result.bandwidth_gbs,
+            comma
+        );
+    }
+    println!("  ]");
+    println!("}}");
+}
+
+fn level_name(result: &LevelResult) -> String {
+    result.level.map_or("DRAM".to_string(), |level| format!("{:?}", level))
+}
+
+fn print_levels_table(results: &[LevelResult]) {
+    println!(
+        "{:>6}  {:>10}  {:>12}  {:>12}  {:>14}",
+        "Level".bright_white().bold(),
+        "Declared".bright_white().bold(),
+        "Latency (ns)".bright_white().bold(),
+        "Cycles".bright_white().bold(),
+        "Bandwidth GB/s".bright_white().bold()
+    );
+    for result in results {
+        let declared = match result.declared_size {
+            0 => "-".to_string(),
+            size => format_size(size as usize),
+        };
+        let cycles = result.latency_cycles.map_or("-".to_string(), |c| format!("{:.1}", c));
+        println!(
+            "{:>6}  {:>10}  {:>12}  {:>12}  {:>14}",
+            level_name(result).bright_cyan(),
+            declared,
+            format!("{:.2}", result.latency_ns).bright_yellow(),
+            cycles.bright_magenta(),
+            format!("{:.2}", result.bandwidth_gbs).bright_green()
+        );
+    }
+}
+
+fn print_levels_json(results: &[LevelResult]) {
+    println!("[");
+    for (i, result) in results.iter().enumerate() {
+        let comma = if i + 1 < results.len() { "," } else { "" };
+        let cycles = result.latency_cycles.map_or("null".to_string(), |c| format!("{:.3}", c));
+        println!(
+            "  {{\"level\": \"{}\", \"declared_size\": {}, \"working_set\": {}, \"latency_ns\": {:.3}, \"latency_cycles\": {}, \"bandwidth_gbs\": {:.3}}}{}",
+            level_name(result),
+            result.declared_size,
+            result.working_set,
+            result.latency_ns,
+            cycles,
+            result.bandwidth_gbs,
+            comma
+        );
+    }
+    println!("]");
+}
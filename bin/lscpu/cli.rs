@@ -0,0 +1,105 @@
+//! Command-line interface definition.
+//!
+//! Only needs `Baseline` and `parse_baseline` from the including crate, so
+//! build.rs can include it with stand-ins to generate the man page.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+use super::{parse_baseline, Baseline};
+
+#[derive(Parser)]
+#[command(name = "lscpu", version, about = "Display CPU information detected via CPUID")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Compare against a feature baseline (x86-64-v2, x86-64-v3, haswell, znver4, ...).
+    /// Exits with status 1 if baseline features are missing.
+    #[arg(long, value_name = "NAME", value_parser = parse_baseline)]
+    pub baseline: Option<&'static Baseline>,
+
+    /// Use plain ASCII instead of emoji, box-drawing and unicode bullets
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// List every decoded feature grouped by its CPUID leaf/subleaf
+    #[arg(long)]
+    pub by_leaf: bool,
+
+    /// Detect features on every online CPU and report cores whose ISA differs
+    #[arg(long)]
+    pub per_core: bool,
+
+    /// Print a one-line capability summary
+    #[arg(long, conflicts_with_all = ["baseline", "by_leaf", "per_core", "format", "output"])]
+    pub summary: bool,
+
+    /// Print a raw CPUID dump in `cpuid -r` format instead of a report
+    #[arg(long, conflicts_with_all = ["baseline", "by_leaf", "per_core", "format", "replay"])]
+    pub dump: bool,
+
+    /// Detect from a `cpuid -r` dump FILE instead of this machine
+    #[arg(long, value_name = "FILE", conflicts_with = "per_core")]
+    pub replay: Option<PathBuf>,
+
+    /// Compare a `cpuid -r` dump FILE (the old state) against this machine or --replay
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["baseline", "by_leaf", "per_core", "format", "output"])]
+    pub diff: Option<PathBuf>,
+
+    /// Output format [default: inferred from --output, otherwise pretty]
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+
+    /// Where cache geometry comes from; `merged` also lists where CPUID and the OS disagree
+    #[arg(long, value_enum, value_name = "SOURCE", conflicts_with = "replay")]
+    pub cache_source: Option<CacheSource>,
+
+    /// Write the report to FILE; the format follows the extension (.json, .yaml, .md, .csv)
+    #[arg(short, long, value_name = "FILE", conflicts_with_all = ["baseline", "by_leaf", "per_core"])]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Colored, sectioned report
+    Pretty,
+    /// Linux /proc/cpuinfo layout
+    Cpuinfo,
+    Json,
+    Yaml,
+    Markdown,
+    Csv,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CacheSource {
+    /// CPUID leaf 4 / 0x8000_001D (the default)
+    Cpuid,
+    /// Linux /sys/devices/system/cpu/cpu0/cache
+    Sysfs,
+    /// CPUID overridden by sysfs where both report a cache
+    Merged,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "md" | "markdown" => Some(Format::Markdown),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
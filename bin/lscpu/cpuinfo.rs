@@ -0,0 +1,161 @@
+//! Linux /proc/cpuinfo compatible output.
+
+use cpudetect::*;
+use std::fmt::Write;
+
+/// Leaf 0x8000_0007 EDX bits in Linux's "power management" line, by bit.
+/// Bit 8 (invariant TSC) has an empty name and is skipped; unnamed set bits
+/// print as `[n]`.
+const POWER_FLAGS: &[&str] = &[
+    "ts", "fid", "vid", "ttp", "tm", "stc", "100mhzsteps", "hwpstate", "", "cpb", "eff_freq_ro",
+    "proc_feedback", "acc_power",
+];
+
+/// Where one online logical CPU sits, by OS number and APIC ID.
+struct Processor {
+    cpu: usize,
+    apic_id: u32,
+    package: u32,
+    core: u32,
+}
+
+/// Every online CPU, pinned in turn; empty when pinning fails.
+#[cfg(target_os = "linux")]
+fn processors() -> Vec<Processor> {
+    let mut processors: Vec<Processor> = TopologyMap::detect()
+        .map(|map| {
+            map.cpus()
+                .map(|logical| Processor {
+                    cpu: logical.cpu,
+                    apic_id: logical.apic_id,
+                    package: logical.location.package,
+                    core: logical.location.core,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    processors.sort_by_key(|processor| processor.cpu);
+    processors
+}
+
+#[cfg(not(target_os = "linux"))]
+fn processors() -> Vec<Processor> {
+    Vec::new()
+}
+
+/// `replay` is the dump `cpu` came from; per-CPU APIC IDs, the microcode and
+/// IA32_ARCH_CAPABILITIES are only read for the live machine, and lines that
+/// depend on them are left out otherwise.
+pub fn render(cpu: &CpuInfo, replay: Option<&CpuidDump>, microcode: Option<u32>) -> String {
+    let mut out = String::new();
+
+    let (src, security, processors): (&dyn CpuidSource, _, _) = match replay {
+        Some(dump) => (dump, MitigationReport::detect_with(dump), Vec::new()),
+        None => (&HardwareCpuid, MitigationReport::detect(), processors()),
+    };
+
+    let flags = cpu.features.linux_flags().join(" ");
+    // Linux shows the L2 on AMD and Hygon, the last level elsewhere
+    let mut unified = cpu.cache.iter().filter(|c| c.cache_type != CacheType::Instruction);
+    let cache_kb = match cpu.vendor.vendor {
+        CpuVendor::Amd | CpuVendor::Hygon => unified.find(|c| c.level == CacheLevel::L2),
+        _ => unified.max_by_key(|c| c.level as u32),
+    }
+    .map(|c| c.size / 1024);
+    // Leaf 1 EBX[15:8], in 8-byte units
+    let clflush = if src.is_leaf_supported(1) { ((src.cpuid(1, 0).ebx >> 8) & 0xFF) * 8 } else { 0 };
+    let mhz = cpu.frequency.base_mhz.or(cpu.frequency.tsc_mhz);
+    // The kernel calibrates its delay loop against the TSC: two loops per cycle
+    let bogomips = cpu.frequency.tsc_mhz.map(|mhz| mhz * 2);
+    // A bug the CPU may be immune to cannot be listed either way
+    let bugs = security
+        .vulnerabilities
+        .iter()
+        .all(|v| v.status != VulnerabilityStatus::Unknown)
+        .then(|| {
+            security
+                .vulnerabilities
+                .iter()
+                .filter(|v| v.status != VulnerabilityStatus::NotAffected)
+                .map(|v| v.vulnerability.linux_name())
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+    let power = if src.is_leaf_supported(0x8000_0007) { src.cpuid(0x8000_0007, 0).edx } else { 0 };
+    let power_flags: String = (0..32)
+        .filter(|bit| power & (1 << bit) != 0)
+        .filter_map(|bit| match POWER_FLAGS.get(bit as usize) {
+            Some(&"") => None,
+            Some(name) => Some(format!(" {}", name)),
+            None => Some(format!(" [{}]", bit)),
+        })
+        .collect();
+    let fpu = if cpu.features.basic.contains(FeatureSet::FPU) { "yes" } else { "no" };
+
+    // Without per-CPU data, list one package's worth and leave out its location
+    let count = match processors.len() {
+        0 => cpu.topology.logical_processors.max(1) as usize,
+        len => len,
+    };
+
+    for index in 0..count {
+        let processor = processors.get(index);
+        let _ = writeln!(out, "processor\t: {}", processor.map_or(index, |p| p.cpu));
+        let _ = writeln!(out, "vendor_id\t: {}", cpu.vendor.vendor_string);
+        let _ = writeln!(out, "cpu family\t: {}", cpu.vendor.family);
+        let _ = writeln!(out, "model\t\t: {}", cpu.vendor.model);
+        let _ = writeln!(out, "model name\t: {}", cpu.vendor.brand_string);
+        let _ = writeln!(out, "stepping\t: {}", cpu.vendor.stepping);
+        if let Some(revision) = microcode {
+            let _ = writeln!(out, "microcode\t: {:#x}", revision);
+        }
+        if let Some(mhz) = mhz {
+            let _ = writeln!(out, "cpu MHz\t\t: {}.000", mhz);
+        }
+        if let Some(kb) = cache_kb {
+            let _ = writeln!(out, "cache size\t: {} KB", kb);
+        }
+        match processor {
+            Some(p) => {
+                let package = processors.iter().filter(|other| other.package == p.package);
+                let mut cores: Vec<u32> = package.clone().map(|other| other.core).collect();
+                cores.sort_unstable();
+                cores.dedup();
+                let _ = writeln!(out, "physical id\t: {}", p.package);
+                let _ = writeln!(out, "siblings\t: {}", package.count());
+                let _ = writeln!(out, "core id\t\t: {}", p.core);
+                let _ = writeln!(out, "cpu cores\t: {}", cores.len());
+                let _ = writeln!(out, "apicid\t\t: {}", p.apic_id);
+                let _ = writeln!(out, "initial apicid\t: {}", p.apic_id);
+            }
+            None => {
+                let _ = writeln!(out, "siblings\t: {}", cpu.topology.logical_processors);
+                let _ = writeln!(out, "cpu cores\t: {}", cpu.topology.physical_cores);
+            }
+        }
+        let _ = writeln!(out, "fpu\t\t: {}", fpu);
+        let _ = writeln!(out, "fpu_exception\t: {}", fpu);
+        let _ = writeln!(out, "cpuid level\t: {}", cpu.platform.max_cpuid_leaf);
+        let _ = writeln!(out, "wp\t\t: yes");
+        let _ = writeln!(out, "flags\t\t: {}", flags);
+        if let Some(bugs) = &bugs {
+            let _ = writeln!(out, "bugs\t\t: {}", bugs);
+        }
+        if let Some(bogomips) = bogomips {
+            let _ = writeln!(out, "bogomips\t: {}.00", bogomips);
+        }
+        if clflush != 0 {
+            let _ = writeln!(out, "clflush size\t: {}", clflush);
+            let _ = writeln!(out, "cache_alignment\t: {}", clflush);
+        }
+        let _ = writeln!(
+            out,
+            "address sizes\t: {} bits physical, {} bits virtual",
+            cpu.address.physical_bits, cpu.address.virtual_bits
+        );
+        let _ = writeln!(out, "power management:{}", power_flags);
+        let _ = writeln!(out);
+    }
+
+    out
+}
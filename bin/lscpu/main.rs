@@ -0,0 +1,1029 @@
+mod cli;
+mod cpuinfo;
+mod report;
+
+use clap::{CommandFactory, Parser};
+use cli::{Cli, Command, Format};
+use cpudetect::*;
+use colored::*;
+use std::sync::OnceLock;
+
+impl Format {
+    fn render(self, cpu: &CpuInfo, replay: Option<&CpuidDump>, microcode: Option<u32>) -> Option<String> {
+        let render: fn(&[report::Section]) -> String = match self {
+            Format::Pretty => return None,
+            Format::Cpuinfo => return Some(cpuinfo::render(cpu, replay, microcode)),
+            Format::Json => report::to_json,
+            Format::Yaml => report::to_yaml,
+            Format::Markdown => report::to_markdown,
+            Format::Csv => report::to_csv,
+        };
+        Some(render(&report::sections(cpu)))
+    }
+}
+
+struct Glyphs {
+    emoji: bool,
+    double: &'static str,
+    single: &'static str,
+    vertical: &'static str,
+    top_left: &'static str,
+    top_right: &'static str,
+    bottom_left: &'static str,
+    bottom_right: &'static str,
+    branch: &'static str,
+    last_branch: &'static str,
+    bullet: &'static str,
+    diamond: &'static str,
+    arrow: &'static str,
+    square: &'static str,
+    circle: &'static str,
+    bolt: &'static str,
+    check: &'static str,
+    cross: &'static str,
+    warning: &'static str,
+}
+
+impl Glyphs {
+    const UNICODE: Glyphs = Glyphs {
+        emoji: true,
+        double: "═",
+        single: "─",
+        vertical: "║",
+        top_left: "╔",
+        top_right: "╗",
+        bottom_left: "╚",
+        bottom_right: "╝",
+        branch: "├─",
+        last_branch: "└─",
+        bullet: "●",
+        diamond: "◆",
+        arrow: "▸",
+        square: "▣",
+        circle: "◉",
+        bolt: "⚡",
+        check: "✓",
+        cross: "✗",
+        warning: "⚠",
+    };
+
+    const ASCII: Glyphs = Glyphs {
+        emoji: false,
+        double: "=",
+        single: "-",
+        vertical: "|",
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        branch: "|-",
+        last_branch: "`-",
+        bullet: "*",
+        diamond: "*",
+        arrow: ">",
+        square: "#",
+        circle: "o",
+        bolt: "*",
+        check: "+",
+        cross: "x",
+        warning: "!",
+    };
+
+    fn icon<'a>(&self, emoji: &'a str) -> &'a str {
+        if self.emoji { emoji } else { "" }
+    }
+}
+
+static GLYPHS: OnceLock<Glyphs> = OnceLock::new();
+
+fn glyphs() -> &'static Glyphs {
+    GLYPHS.get_or_init(|| Glyphs::UNICODE)
+}
+
+fn parse_baseline(name: &str) -> Result<&'static Baseline, String> {
+    Baseline::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = baseline::BASELINES.iter().map(|b| b.name).collect();
+        format!("unknown baseline '{}' (known: {})", name, known.join(", "))
+    })
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    // stderr, so RUST_LOG output never mixes with --json/--dump on stdout
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    if let Some(Command::Completions { shell }) = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "lscpu", &mut std::io::stdout());
+        return;
+    }
+
+    if cli.dump {
+        let dump = CpuidDump::capture().to_string();
+        match &cli.output {
+            Some(path) => write_output(path, &dump),
+            None => print!("{}", dump),
+        }
+        return;
+    }
+
+    let _ = GLYPHS.set(if cli.ascii { Glyphs::ASCII } else { Glyphs::UNICODE });
+    let replay = cli.replay.as_deref().map(load_dump);
+    let mut cpu = match &replay {
+        Some(dump) => CpuInfo::from_dump(dump),
+        None => CpuInfo::detect(),
+    };
+    // Socket counts need every CPU's APIC ID; a replayed dump has only one
+    #[cfg(target_os = "linux")]
+    if cli.replay.is_none() {
+        let _ = cpu.topology.detect_packages();
+    }
+    let mut cache_conflicts = Vec::new();
+    if let Some(source) = cli.cache_source {
+        let detection = CacheInfo::detect_with_source(match source {
+            cli::CacheSource::Cpuid => cpudetect::CacheSource::Cpuid,
+            cli::CacheSource::Sysfs => cpudetect::CacheSource::Sysfs,
+            cli::CacheSource::Merged => cpudetect::CacheSource::Merged,
+        });
+        cpu.cache = detection.caches;
+        cache_conflicts = detection.conflicts;
+    }
+
+    // The loaded microcode belongs to this machine, not to a replayed dump
+    let microcode = match cli.replay {
+        Some(_) => None,
+        None => cpu.vendor.microcode_revision(),
+    };
+
+    if cli.summary {
+        println!("{}", cpu.summary());
+        return;
+    }
+
+    if let Some(path) = &cli.diff {
+        let diff = CpuInfo::from_dump(&load_dump(path)).diff(&cpu);
+        print!("{}", diff);
+        if !diff.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let format = match (cli.format, &cli.output) {
+        (Some(format), _) => format,
+        (None, Some(path)) => Format::from_path(path).unwrap_or_else(|| {
+            eprintln!(
+                "error: cannot infer a format from '{}' (use .json, .yaml, .md, .csv or --format)",
+                path.display()
+            );
+            std::process::exit(2);
+        }),
+        (None, None) => Format::Pretty,
+    };
+
+    if cli.output.is_some() {
+        colored::control::set_override(false);
+    }
+
+    if cli.baseline.is_none() && !cli.by_leaf && !cli.per_core {
+        if let Some(rendered) = format.render(&cpu, replay.as_ref(), microcode) {
+            match &cli.output {
+                Some(path) => write_output(path, &rendered),
+                None => print!("{}", rendered),
+            }
+            return;
+        } else if let Some(path) = &cli.output {
+            eprintln!(
+                "error: the pretty report cannot be written to '{}'; pick a structured --format",
+                path.display()
+            );
+            std::process::exit(2);
+        }
+    }
+
+    print_header();
+
+    if !cpu.quality.is_trusted() {
+        let g = glyphs();
+        println!("\n  {} {}", g.warning.bright_red().bold(), "CPUID data looks filtered or faulted; values below may be defaults:".bright_red().bold());
+        for issue in cpu.quality.issues() {
+            println!("    {} {}", g.cross.bright_red(), issue.to_string().bright_white());
+        }
+    }
+
+    // XCR0 describes this machine, so it is meaningless for a replayed dump
+    let warnings = match &cli.replay {
+        Some(_) => cpu.validate_with_xcr0(None),
+        None => cpu.validate(),
+    };
+    if !warnings.is_empty() {
+        let g = glyphs();
+        println!("\n  {} {}", g.warning.bright_yellow().bold(), "Detected values contradict each other:".bright_yellow().bold());
+        for warning in &warnings {
+            println!("    {} {}", g.cross.bright_yellow(), warning.to_string().bright_white());
+        }
+    }
+
+    if !cache_conflicts.is_empty() {
+        let g = glyphs();
+        println!("\n  {} {}", g.warning.bright_yellow().bold(), "CPUID and sysfs disagree about the caches (sysfs used):".bright_yellow().bold());
+        for conflict in &cache_conflicts {
+            println!("    {} {}", g.cross.bright_yellow(), conflict.to_string().bright_white());
+        }
+    }
+
+    if cli.by_leaf {
+        print_vendor_info(&cpu.vendor, microcode);
+        print_features_by_leaf(&cpu.features);
+        return;
+    }
+
+    if cli.per_core {
+        print_vendor_info(&cpu.vendor, microcode);
+        print_per_core_info();
+        return;
+    }
+
+    if let Some(baseline) = cli.baseline {
+        print_vendor_info(&cpu.vendor, microcode);
+        let report = baseline.compare(&cpu.features);
+        print_baseline_info(&report);
+        if !report.is_satisfied() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    print_vendor_info(&cpu.vendor, microcode);
+    print_topology_info(&cpu.topology);
+    // Like the hugetlb pools, NUMA nodes describe this machine only
+    if cli.replay.is_none() && let Some(mut numa) = NumaInfo::detect() {
+        numa.link_packages(&cpu.topology);
+        print_numa_info(&numa);
+    }
+    print_frequency_info(&cpu.frequency);
+    print_address_info(&cpu.address);
+    print_cache_info(&cpu.cache);
+    // Kernel hugetlb pools belong to this machine, not to a replayed dump
+    let hugepages = match cli.replay {
+        Some(_) => cpu.tlb.hugepage_support(),
+        None => cpu.tlb.hugepage_support_with_kernel(),
+    };
+    print_tlb_info(&cpu.tlb, &hugepages);
+    // MSRs belong to this machine, not to a replayed dump
+    let prefetchers = match cli.replay {
+        Some(_) => None,
+        None => PrefetcherInfo::read(0).ok(),
+    };
+    print_power_info(&cpu.power, prefetchers.as_ref());
+    print_platform_info(&cpu.platform);
+    print_msr_info(&cpu.msr);
+    print_rdt_info(&cpu.rdt);
+    print_features(&cpu.features, &cpu.vendor.vendor);
+}
+
+fn load_dump(path: &std::path::Path) -> CpuidDump {
+    CpuidDump::load(path).unwrap_or_else(|err| {
+        eprintln!("error: cannot read dump '{}': {}", path.display(), err);
+        std::process::exit(1);
+    })
+}
+
+fn write_output(path: &std::path::Path, contents: &str) {
+    if let Err(err) = std::fs::write(path, contents) {
+        eprintln!("error: cannot write '{}': {}", path.display(), err);
+        std::process::exit(1);
+    }
+}
+
+fn print_gradient_header(title: &str, icon: &str, color: Color) {
+    let g = glyphs();
+    let width = 70;
+    let title_with_icon = match g.icon(icon) {
+        "" => title.to_string(),
+        icon => format!("{} {}", icon, title),
+    };
+    let padding = (width - title_with_icon.len() - 2) / 2;
+    
+    println!("\n{}", g.double.repeat(width).color(color).bold());
+    println!("{}{}{}", 
+        " ".repeat(padding),
+        title_with_icon.color(color).bold(),
+        " ".repeat(width.saturating_sub(padding + title_with_icon.len())));
+    println!("{}", g.double.repeat(width).color(color).bold());
+}
+
+fn print_header() {
+    let g = glyphs();
+    let width = 70;
+    let border = g.double.repeat(width);
+    let lines = ["", "CPUDETECT - lscpu rust re-implementation", "Modern System Analysis", ""];
+
+    println!("\n{}", format!("{}{}{}", g.top_left, border, g.top_right).bright_cyan().bold());
+    for line in lines {
+        println!("{}", format!("{}{:^width$}{}", g.vertical, line, g.vertical).bright_cyan().bold());
+    }
+    println!("{}", format!("{}{}{}", g.bottom_left, border, g.bottom_right).bright_cyan().bold());
+}
+
+fn print_vendor_info(vendor: &VendorInfo, microcode: Option<u32>) {
+    let g = glyphs();
+    print_gradient_header("CPU IDENTIFICATION", "🔍", Color::BrightMagenta);
+    
+    println!("\n  {} {:<12} {}", g.bullet.bright_magenta(), "Vendor:".bright_white().bold(), vendor.vendor_string.bright_yellow());
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Brand:".bright_white().bold(), vendor.brand_or_signature().bright_green());
+    if let Some(model) = vendor.brand_model_number() {
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Model No.:".bright_white().bold(), model.bright_green());
+    }
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Family:".bright_white().bold(), format!("{:#x}", vendor.family).bright_cyan());
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Model:".bright_white().bold(), format!("{:#x}", vendor.model).bright_cyan());
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Stepping:".bright_white().bold(), vendor.stepping.to_string().bright_cyan());
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Signature:".bright_white().bold(), format!("{:#07x} ({})", vendor.signature().to_raw(), vendor.signature()).bright_cyan());
+    if let Some(revision) = microcode {
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Microcode:".bright_white().bold(), format!("{:#x}", revision).bright_cyan());
+    }
+    println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Microarch:".bright_white().bold(), vendor.microarchitecture().to_string().bright_green());
+    if let Some(identity) = vendor.identify() {
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Codename:".bright_white().bold(), identity.codename.bright_green());
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Process:".bright_white().bold(), identity.process.bright_cyan());
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Series:".bright_white().bold(), identity.generation.bright_cyan());
+    }
+    if let Some(hypervisor) = &vendor.hypervisor {
+        println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Hypervisor:".bright_white().bold(), hypervisor.to_string().bright_yellow());
+        if let Some(kvm) = &hypervisor.kvm {
+            println!("  {} {:<12} {}", g.bullet.bright_magenta(), "KVM PV:".bright_white().bold(), kvm.names().join(", ").bright_cyan());
+        }
+        if let Some(hyperv) = &hypervisor.hyperv {
+            println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Hyper-V:".bright_white().bold(), hyperv.to_string().bright_yellow());
+            println!("  {} {:<12} {}", g.bullet.bright_magenta(), "Enlightened:".bright_white().bold(), hyperv.names().join(", ").bright_cyan());
+        }
+    }
+}
+
+fn print_topology_info(topology: &CpuTopology) {
+    let g = glyphs();
+    print_gradient_header("CPU TOPOLOGY", "⚙️", Color::BrightBlue);
+    
+    println!("\n  {} {:<22} {}", g.diamond.bright_blue(), "Logical Processors:".bright_white().bold(), topology.logical_processors.to_string().bright_yellow().bold());
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "Physical Cores:".bright_white().bold(), topology.physical_cores.to_string().bright_green().bold());
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "Threads per Core:".bright_white().bold(), topology.threads_per_core.to_string().bright_cyan());
+    if let Some(reconciliation) = &topology.reconciliation {
+        let detail = match reconciliation.confidence {
+            CountConfidence::Corrected => format!(" (CPUID said {})", reconciliation.cpuid),
+            _ => String::new(),
+        };
+        let color = match reconciliation.confidence {
+            CountConfidence::Confirmed => Color::BrightGreen,
+            CountConfidence::Unverified => Color::BrightYellow,
+            CountConfidence::Corrected => Color::BrightRed,
+        };
+        println!("  {} {:<22} {}{}", g.diamond.bright_blue(), "Count Check:".bright_white().bold(), reconciliation.confidence.to_string().color(color), detail.truecolor(150, 150, 150));
+    }
+    if !topology.packages.is_empty() {
+        let cores: Vec<String> = topology.packages.iter().map(|package| package.cores.to_string()).collect();
+        println!("  {} {:<22} {} {}", g.diamond.bright_blue(), "Sockets:".bright_white().bold(), topology.package_count().to_string().bright_green().bold(), format!("({} cores per socket, {} in total)", cores.join("/"), topology.total_cores()).truecolor(150, 150, 150));
+    }
+    if let Some(levels) = &topology.levels {
+        println!("  {} {:<22} {}", g.diamond.bright_blue(), "Topology Levels:".bright_white().bold(), levels.to_string().bright_cyan());
+    }
+    if let Some(amd) = &topology.amd {
+        println!("  {} {:<22} {}", g.diamond.bright_blue(), "AMD Topology:".bright_white().bold(), amd.to_string().bright_cyan());
+    }
+    println!("  {} {:<22} {} {}", g.diamond.bright_blue(), "APIC ID:".bright_white().bold(), format!("{:#x}", topology.apic_id()).bright_cyan(), format!("({})", topology.location()).truecolor(150, 150, 150));
+    let current = CpuTopology::current_core_type();
+    if current.core_type != CoreType::Unknown {
+        println!("  {} {:<22} {}", g.diamond.bright_blue(), "Current Core:".bright_white().bold(), current.to_string().bright_cyan());
+    }
+    let layout = &topology.apic_layout;
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "APIC ID Layout:".bright_white().bold(), format!("{} SMT bits, {} core bits, package from bit {}", layout.smt_bits, layout.core_bits, layout.package_shift()).bright_cyan());
+    
+    let ht_status = if topology.has_hyperthreading {
+        format!("{} Enabled", g.check.bright_green())
+    } else {
+        format!("{} Disabled", g.cross.bright_red())
+    };
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "Hyper-Threading:".bright_white().bold(), ht_status);
+    
+    let hybrid_status = if topology.hybrid {
+        format!("{} Yes (P-cores + E-cores)", g.check.bright_green())
+    } else {
+        format!("{} No", g.cross.truecolor(100, 100, 100))
+    };
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "Hybrid Architecture:".bright_white().bold(), hybrid_status);
+}
+
+fn print_numa_info(numa: &NumaInfo) {
+    let g = glyphs();
+    print_gradient_header("NUMA TOPOLOGY", "🗺️", Color::BrightBlue);
+
+    println!("\n  {} {:<22} {}", g.diamond.bright_blue(), "NUMA Nodes:".bright_white().bold(), numa.node_count().to_string().bright_yellow().bold());
+    for node in &numa.nodes {
+        let cpus: Vec<String> = node.cpus.iter().map(|cpu| cpu.to_string()).collect();
+        let memory = node.memory_bytes.map_or("unknown".to_string(), |bytes| format!("{} MiB", bytes >> 20));
+        let packages: Vec<String> = node.packages.iter().map(|id| id.to_string()).collect();
+        println!("  {} {:<22} {} {}", g.diamond.bright_blue(), format!("Node {}:", node.id).bright_white().bold(), memory.bright_green(), format!("CPUs {}", if cpus.is_empty() { "none".to_string() } else { cpus.join(",") }).bright_cyan());
+        if !packages.is_empty() {
+            println!("    {} {}", "Packages:".truecolor(150, 150, 150), packages.join(",").bright_cyan());
+        }
+        let distances: Vec<String> = node.distances.iter().map(|distance| distance.to_string()).collect();
+        println!("    {} {}", "Distances:".truecolor(150, 150, 150), distances.join(" ").bright_cyan());
+    }
+}
+
+fn print_frequency_info(freq: &FrequencyInfo) {
+    let g = glyphs();
+    print_gradient_header("FREQUENCY INFORMATION", "⚡", Color::BrightYellow);
+    
+    println!();
+    if let Some(base) = freq.base_mhz {
+        println!("  {} {:<18} {} {}", g.bolt.bright_yellow(), "Base Frequency:".bright_white().bold(), base.to_string().bright_green().bold(), "MHz".truecolor(150, 150, 150));
+    }
+    if let Some(max) = freq.max_mhz {
+        println!("  {} {:<18} {} {}", g.bolt.bright_yellow(), "Max Frequency:".bright_white().bold(), max.to_string().bright_red().bold(), "MHz".truecolor(150, 150, 150));
+    }
+    if let Some(bus) = freq.bus_mhz {
+        println!("  {} {:<18} {} {}", g.bolt.bright_yellow(), "Bus Frequency:".bright_white().bold(), bus.to_string().bright_cyan(), "MHz".truecolor(150, 150, 150));
+    }
+    if let Some(tsc) = freq.tsc_mhz {
+        println!("  {} {:<18} {} {}", g.bolt.bright_yellow(), "TSC Frequency:".bright_white().bold(), tsc.to_string().bright_magenta(), "MHz".truecolor(150, 150, 150));
+    }
+
+    if freq.policies.is_empty() {
+        return;
+    }
+
+    // intel_pstate exposes one policy per CPU; fold identical ones together
+    let mut groups: Vec<(&CpufreqPolicy, Vec<usize>)> = Vec::new();
+    for policy in &freq.policies {
+        let same = |p: &CpufreqPolicy| {
+            p.driver == policy.driver
+                && p.governor == policy.governor
+                && p.scaling_min_mhz == policy.scaling_min_mhz
+                && p.scaling_max_mhz == policy.scaling_max_mhz
+                && p.hardware_max_mhz == policy.hardware_max_mhz
+                && p.energy_performance_preference == policy.energy_performance_preference
+        };
+        match groups.iter_mut().find(|(p, _)| same(p)) {
+            Some((_, cpus)) => cpus.extend(&policy.cpus),
+            None => groups.push((policy, policy.cpus.clone())),
+        }
+    }
+
+    let mhz = |value: Option<u32>| value.map_or("?".to_string(), |v| v.to_string());
+    println!();
+    for (policy, cpus) in groups {
+        let cpus: Vec<String> = cpus.iter().map(|cpu| cpu.to_string()).collect();
+        println!("  {} {:<18} {} {} {}",
+            g.arrow.bright_yellow(),
+            "Scaling Policy:".bright_white().bold(),
+            policy.driver.bright_cyan(),
+            g.single.truecolor(100, 100, 100),
+            policy.governor.bright_green().bold());
+        println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "CPUs:".truecolor(150, 150, 150), cpus.join(",").bright_white());
+        if let Some(epp) = &policy.energy_performance_preference {
+            println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "EPP:".truecolor(150, 150, 150), epp.bright_white());
+        }
+        println!("    {} {:<16} {}-{} MHz", g.branch.truecolor(100, 100, 100), "Hardware Range:".truecolor(150, 150, 150), mhz(policy.hardware_min_mhz), mhz(policy.hardware_max_mhz));
+        let limit = format!("{}-{} MHz", mhz(policy.scaling_min_mhz), mhz(policy.scaling_max_mhz));
+        if policy.is_capped() {
+            println!("    {} {:<16} {} {}", g.last_branch.truecolor(100, 100, 100), "Scaling Limits:".truecolor(150, 150, 150), limit.bright_red(), format!("{} capped by OS", g.warning).bright_red());
+        } else {
+            println!("    {} {:<16} {}", g.last_branch.truecolor(100, 100, 100), "Scaling Limits:".truecolor(150, 150, 150), limit);
+        }
+    }
+}
+
+fn print_address_info(addr: &AddressInfo) {
+    let g = glyphs();
+    print_gradient_header("ADDRESS SIZES", "📍", Color::BrightCyan);
+    
+    println!("\n  {} {:<20} {} {}", g.arrow.bright_cyan(), "Physical Address:".bright_white().bold(), addr.physical_bits.to_string().bright_yellow().bold(), "bits".truecolor(150, 150, 150));
+    println!("  {} {:<20} {} {}", g.arrow.bright_cyan(), "Virtual Address:".bright_white().bold(), addr.virtual_bits.to_string().bright_green().bold(), "bits".truecolor(150, 150, 150));
+    if let Some(guest) = addr.guest_physical_bits {
+        println!("  {} {:<20} {} {}", g.arrow.bright_cyan(), "Guest Physical:".bright_white().bold(), guest.to_string().bright_magenta(), "bits".truecolor(150, 150, 150));
+    }
+}
+
+fn print_cache_info(caches: &[CacheInfo]) {
+    let g = glyphs();
+    print_gradient_header("CACHE HIERARCHY", "💾", Color::BrightGreen);
+    
+    println!();
+    for cache in caches {
+        let type_str = format!("{:?}", cache.cache_type);
+        
+        let (icon, color) = match cache.level {
+            cpudetect::CacheLevel::L1 => ("L1", Color::BrightRed),
+            cpudetect::CacheLevel::L2 => ("L2", Color::BrightYellow),
+            cpudetect::CacheLevel::L3 => ("L3", Color::BrightGreen),
+            cpudetect::CacheLevel::L4 => ("L4", Color::BrightCyan),
+        };
+        
+        println!("  {} {} {} Cache", g.square.color(color).bold(), icon.color(color).bold(), type_str.bright_white().bold());
+        println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "Size:".truecolor(200, 200, 200), cache.size_human().bright_cyan());
+        println!("    {} {:<16} {}-way", g.branch.truecolor(100, 100, 100), "Associativity:".truecolor(200, 200, 200), cache.ways.to_string().bright_yellow());
+        println!("    {} {:<16} {} bytes", g.branch.truecolor(100, 100, 100), "Line Size:".truecolor(200, 200, 200), cache.line_size.to_string().bright_magenta());
+        println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "Sets:".truecolor(200, 200, 200), cache.sets.to_string().bright_green());
+        if cache.instances > 0 {
+            println!("    {} {:<16} {} per package (one per {})", g.branch.truecolor(100, 100, 100), "Instances:".truecolor(200, 200, 200), cache.instances.to_string().bright_cyan(), cache.scope);
+        }
+        let flags = cache.flags();
+        if !flags.is_empty() {
+            println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "Flags:".truecolor(200, 200, 200), flags.join(", ").bright_white());
+        }
+        if let Some(note) = cache.note() {
+            println!("    {} {:<16} {}", g.branch.truecolor(100, 100, 100), "Note:".truecolor(200, 200, 200), note.truecolor(150, 150, 150));
+        }
+        println!("    {} {:<16} {} threads\n", g.last_branch.truecolor(100, 100, 100), "Shared by:".truecolor(200, 200, 200), cache.shared_by.to_string().bright_blue());
+    }
+
+    let total_cache: u64 = caches.iter().map(|c| c.size).sum();
+    println!("  {} {} {} KB {} MB {}",
+        g.double.repeat(3).bright_green(),
+        "Total Cache:".bright_white().bold(),
+        (total_cache / 1024).to_string().bright_yellow().bold(),
+        format!("({:.2}", total_cache as f64 / 1024.0 / 1024.0).bright_green(),
+        ")".bright_green());
+}
+
+fn print_tlb_info(tlb: &TlbInfo, hugepages: &[HugepageSupport]) {
+    let g = glyphs();
+    if tlb.entries.is_empty() && tlb.management == TlbManagement::default() {
+        return;
+    }
+
+    print_gradient_header("TLB INFORMATION", "🗂️", Color::BrightMagenta);
+    println!();
+    for entry in &tlb.entries {
+        println!("  {} {} TLB {} {} {} entries {} {}",
+            g.circle.bright_magenta(),
+            entry.name().bright_white().bold(),
+            "(".truecolor(100, 100, 100),
+            entry.page_sizes.to_string().bright_cyan(),
+            "pages):".truecolor(100, 100, 100),
+            entry.entries.to_string().bright_yellow(),
+            entry.associativity.to_string().truecolor(150, 150, 150));
+    }
+
+    let reach = tlb.reach();
+    if !reach.is_empty() {
+        println!();
+    }
+    for reach in reach {
+        println!("  {} {}", g.arrow.bright_magenta(), reach.to_string().truecolor(200, 200, 200));
+    }
+    println!("  {} {:<12} {}", g.arrow.bright_magenta(), "Management:".bright_white().bold(), tlb.management.to_string().bright_cyan());
+    for support in hugepages {
+        let mark = if support.architectural { g.check.bright_green() } else { g.cross.bright_red() };
+        println!("  {} {}", mark, support.to_string().truecolor(200, 200, 200));
+    }
+}
+
+fn print_power_info(power: &PowerInfo, prefetchers: Option<&PrefetcherInfo>) {
+    let g = glyphs();
+    print_gradient_header("POWER MANAGEMENT", "🔋", Color::BrightYellow);
+    
+    let features = [
+        (power.digital_thermal_sensor, "Digital Thermal Sensor"),
+        (power.turbo_boost, "Turbo Boost"),
+        (power.turbo_boost_max_3, "Turbo Boost Max 3.0"),
+        (power.arat, "APIC Timer Always Running"),
+        (power.hwp, "Hardware P-States (HWP)"),
+        (power.hwp_notification, "HWP Notification"),
+        (power.hwp_activity_window, "HWP Activity Window"),
+        (power.hwp_epp, "HWP Energy Performance Preference"),
+        (power.hwp_package, "HWP Package Level Control"),
+        (power.hdc, "Hardware Duty Cycling"),
+        (power.thread_director, "Thread Director"),
+        (power.pln, "Power Limit Notification"),
+        (power.pts, "Package Thermal Status"),
+    ];
+    
+    println!();
+    for (enabled, name) in features {
+        if enabled {
+            println!("  {} {}", g.check.bright_green().bold(), name.bright_white());
+        }
+    }
+    if let Some(hfi) = &power.hfi {
+        println!("\n  {} {:<22} {}", g.diamond.bright_yellow(), "HFI Table:".bright_white().bold(), format!("{} KiB, {} class(es), {}-byte rows, this CPU at row {}", hfi.table_size / 1024, hfi.table_classes(), hfi.row_size(), hfi.table_index).bright_cyan());
+    }
+
+    // Needs root and the msr driver; stay quiet when the MSR is not readable
+    if let Some(info) = prefetchers {
+        println!("\n  {} {} {}",
+            "Hardware Prefetchers".bright_magenta().bold(),
+            format!("(MSR {:#x} = {:#x})", info.msr, info.raw).truecolor(100, 100, 100),
+            g.single.repeat(30).truecolor(60, 60, 60));
+        for prefetcher in &info.prefetchers {
+            if prefetcher.enabled {
+                println!("    {} {:<14} {}", g.check.bright_green().bold(), prefetcher.name.bright_white(), prefetcher.description.truecolor(150, 150, 150));
+            } else {
+                println!("    {} {:<14} {} {}", g.cross.bright_red().bold(), prefetcher.name.bright_red(), prefetcher.description.truecolor(150, 150, 150), "(disabled)".bright_red());
+            }
+        }
+    }
+}
+
+fn print_platform_info(platform: &PlatformInfo) {
+    let g = glyphs();
+    print_gradient_header("PLATFORM INFORMATION", "🖥️", Color::BrightCyan);
+    
+    println!("\n  {} {:<22} {}", g.diamond.bright_cyan(), "Max CPUID Leaf:".bright_white().bold(), format!("{:#x}", platform.max_cpuid_leaf).bright_yellow());
+    println!("  {} {:<22} {}", g.diamond.bright_cyan(), "Max Extended Leaf:".bright_white().bold(), format!("{:#x}", platform.max_extended_leaf).bright_yellow());
+    
+    println!();
+    let features = [
+        (platform.time_stamp_counter, "Time Stamp Counter"),
+        (platform.model_specific_registers, "Model Specific Registers"),
+        (platform.apic_on_chip, "APIC On-Chip"),
+        (platform.x2apic, "x2APIC"),
+        (platform.tsc_invariant, "TSC Invariant"),
+        (platform.tsc_deadline, "TSC Deadline Timer"),
+    ];
+    
+    for (enabled, name) in features {
+        if enabled {
+            println!("  {} {}", g.check.bright_green().bold(), name.bright_white());
+        }
+    }
+}
+
+fn print_msr_info(msr: &MsrInfo) {
+    let g = glyphs();
+    print_gradient_header("MSR SUPPORT", "📊", Color::BrightMagenta);
+    
+    let features = [
+        (msr.msr_support, "Model-Specific Registers Supported"),
+        (msr.rdmsr_wrmsr, "RDMSR/WRMSR Instructions"),
+        (msr.msr_platform_info, "Platform Info MSR"),
+        (msr.msr_temperature, "Temperature MSR"),
+        (msr.msr_perf_status, "Performance Status MSR"),
+        (msr.msr_perf_ctl, "Performance Control MSR"),
+        (msr.msr_energy_perf_bias, "Energy Performance Bias MSR"),
+    ];
+    
+    println!();
+    for (enabled, name) in features {
+        if enabled {
+            println!("  {} {}", g.check.bright_green().bold(), name.bright_white());
+        }
+    }
+}
+
+fn print_rdt_info(rdt: &RdtInfo) {
+    print_rdt_allocation(rdt);
+    let Some(mon) = &rdt.monitoring else {
+        return;
+    };
+
+    let g = glyphs();
+    print_gradient_header("RESOURCE MONITORING", "📈", Color::BrightBlue);
+
+    println!("\n  {} {:<22} {}", g.diamond.bright_blue(), "Max RMID:".bright_white().bold(), mon.max_rmid.to_string().bright_yellow());
+    println!("  {} {:<22} {}", g.diamond.bright_blue(), "L3 Max RMID:".bright_white().bold(), mon.l3_max_rmid.to_string().bright_yellow());
+    println!("  {} {:<22} {} {}", g.diamond.bright_blue(), "Scaling Factor:".bright_white().bold(), mon.scaling_factor.to_string().bright_cyan(), "bytes".truecolor(150, 150, 150));
+    println!("  {} {:<22} {} {}", g.diamond.bright_blue(), "Counter Width:".bright_white().bold(), mon.counter_width.to_string().bright_cyan(), "bits".truecolor(150, 150, 150));
+
+    println!();
+    let features = [
+        (mon.l3_occupancy, "L3 Occupancy Monitoring (CMT)"),
+        (mon.mbm_total, "Total Memory Bandwidth Monitoring"),
+        (mon.mbm_local, "Local Memory Bandwidth Monitoring"),
+        (mon.overflow_bit, "Counter Overflow Bit"),
+    ];
+    for (enabled, name) in features {
+        if enabled {
+            println!("  {} {}", g.check.bright_green().bold(), name.bright_white());
+        }
+    }
+}
+
+fn print_rdt_allocation(rdt: &RdtInfo) {
+    if rdt.l3_allocation.is_none() && rdt.l2_allocation.is_none() && rdt.bandwidth_allocation.is_none() {
+        return;
+    }
+
+    let g = glyphs();
+    print_gradient_header("RESOURCE ALLOCATION", "🧩", Color::BrightBlue);
+    println!();
+
+    for (name, cat) in [("L3 CAT:", rdt.l3_allocation), ("L2 CAT:", rdt.l2_allocation)] {
+        let Some(cat) = cat else { continue };
+        let mut details = format!("{}-bit CBM, shareable {:#x}", cat.cbm_length, cat.shareable_mask);
+        if cat.cdp {
+            details.push_str(", CDP");
+        }
+        if cat.noncontiguous_cbm {
+            details.push_str(", non-contiguous CBM");
+        }
+        println!("  {} {:<22} {} {} {}", g.diamond.bright_blue(), name.bright_white().bold(), cat.clos_count.to_string().bright_yellow(), "CLOS,".truecolor(150, 150, 150), details.bright_cyan());
+    }
+
+    if let Some(mba) = rdt.bandwidth_allocation {
+        let step = mba.granularity().map_or("non-linear".to_string(), |step| format!("{}% steps", step));
+        println!("  {} {:<22} {} {} {}", g.diamond.bright_blue(), "MBA:".bright_white().bold(), mba.clos_count.to_string().bright_yellow(), "CLOS,".truecolor(150, 150, 150), format!("max delay {}%, {}", mba.max_delay, step).bright_cyan());
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn print_per_core_info() {
+    let g = glyphs();
+    print_gradient_header("PER-CORE ISA", "🧬", Color::BrightYellow);
+
+    let per_core = match PerCoreFeatures::detect() {
+        Ok(per_core) => per_core,
+        Err(err) => {
+            println!("\n  {} {}", g.warning.bright_red().bold(), format!("Per-CPU detection failed: {}", err).bright_red());
+            return;
+        }
+    };
+
+    println!("\n  {} {:<22} {}", g.diamond.bright_yellow(), "CPUs Checked:".bright_white().bold(), per_core.cores.len().to_string().bright_cyan());
+    for core_type in [CoreType::Performance, CoreType::Efficient] {
+        let cpus: Vec<String> = per_core
+            .cores
+            .iter()
+            .filter(|core| core.core_type == core_type)
+            .map(|core| core.cpu.to_string())
+            .collect();
+        if !cpus.is_empty() {
+            println!("  {} {:<22} {}", g.diamond.bright_yellow(), format!("{:?} Cores:", core_type).bright_white().bold(), cpus.join(",").bright_cyan());
+        }
+    }
+
+    // Caches and frequency per CPU; hybrid and multi-socket parts differ here
+    if let Ok(per_cpu) = CpuInfo::detect_per_cpu() {
+        println!("\n    {:>4}  {:>8}  {:<12} {:>8} {:>8} {:>9}",
+            "CPU".bright_white().bold(), "APIC ID".bright_white().bold(), "Core Type".bright_white().bold(),
+            "L2 KB".bright_white().bold(), "L3 KB".bright_white().bold(), "Max MHz".bright_white().bold());
+        for info in &per_cpu {
+            let cache_kb = |level: CacheLevel| {
+                info.caches
+                    .iter()
+                    .find(|c| c.level == level && c.cache_type != CacheType::Instruction)
+                    .map_or("-".to_string(), |c| (c.size / 1024).to_string())
+            };
+            let max_mhz = info.frequency.policies.first()
+                .and_then(|p| p.hardware_max_mhz)
+                .or(info.frequency.max_mhz)
+                .map_or("-".to_string(), |mhz| mhz.to_string());
+            println!("    {:>4}  {:>8}  {:<12} {:>8} {:>8} {:>9}",
+                info.cpu.to_string().bright_cyan(),
+                format!("{:#x}", info.apic_id).truecolor(150, 150, 150),
+                format!("{:?}", info.core_type).bright_white(),
+                cache_kb(CacheLevel::L2).bright_yellow(),
+                cache_kb(CacheLevel::L3).bright_yellow(),
+                max_mhz.bright_green());
+        }
+
+        let mut topology = CpuTopology::detect();
+        topology.apply_per_cpu(&per_cpu);
+        if let (Some(p_cores), Some(e_cores)) = (topology.performance_cores, topology.efficient_cores) {
+            println!("\n  {} {:<22} {}", g.diamond.bright_yellow(), "Core Counts:".bright_white().bold(), format!("{} P-cores, {} E-cores", p_cores, e_cores).bright_cyan());
+        }
+        let smt_cpus: Vec<String> = topology.smt_cpus.iter().map(|cpu| cpu.to_string()).collect();
+        println!("  {} {:<22} {}", g.diamond.bright_yellow(), "SMT CPUs:".bright_white().bold(), if smt_cpus.is_empty() { "none".to_string() } else { smt_cpus.join(",") }.bright_cyan());
+        if let Ok(siblings) = topology.smt_siblings() {
+            // Each core once, listed under its first thread
+            let groups: Vec<String> = siblings
+                .iter()
+                .filter(|(cpu, group)| group.len() > 1 && group.first() == Some(cpu))
+                .map(|(_, group)| group.iter().map(|cpu| cpu.to_string()).collect::<Vec<_>>().join(","))
+                .collect();
+            if !groups.is_empty() {
+                println!("  {} {:<22} {}", g.diamond.bright_yellow(), "SMT Siblings:".bright_white().bold(), groups.join(" ").bright_cyan());
+            }
+        }
+
+        println!("\n  {} {}", g.diamond.bright_yellow(), "Topology Map:".bright_white().bold());
+        for line in TopologyMap::from_per_cpu(&per_cpu, &topology).to_string().lines() {
+            println!("    {}", line.bright_cyan());
+        }
+
+        let tlb_asymmetries = PerCoreTlb::from_per_cpu(&per_cpu).asymmetries();
+        if !tlb_asymmetries.is_empty() {
+            println!("\n  {} {}", g.warning.bright_yellow().bold(), "TLBs that differ across core types:".bright_yellow().bold());
+            for asymmetry in &tlb_asymmetries {
+                let present: Vec<String> = asymmetry.present_on.iter().map(|core_type| format!("{:?}", core_type)).collect();
+                println!("    {} {} {} {}",
+                    g.cross.bright_red(),
+                    asymmetry.entry.to_string().bright_white(),
+                    "only on".truecolor(150, 150, 150),
+                    present.join(",").bright_cyan());
+            }
+        }
+    }
+
+    let asymmetries = per_core.asymmetries();
+    if asymmetries.is_empty() {
+        println!("\n  {} {}", g.check.bright_green().bold(), "All CPUs expose identical features".bright_green());
+    } else {
+        println!("\n  {} {}", g.warning.bright_yellow().bold(), "Features that differ across CPUs:".bright_yellow().bold());
+        for asymmetry in &asymmetries {
+            let missing: Vec<String> = asymmetry.missing_on.iter().map(|cpu| cpu.to_string()).collect();
+            println!("    {} {:<18} {} {}",
+                g.cross.bright_red(),
+                asymmetry.name.bright_white(),
+                "missing on CPUs".truecolor(150, 150, 150),
+                missing.join(",").bright_red());
+        }
+    }
+
+    let intersection = per_core.intersection();
+    println!("\n  {} {:<22} {}", g.arrow.bright_yellow(), "Safe SIMD Level:".bright_white().bold(), intersection.simd_level().to_string().bright_green().bold());
+    println!();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn print_per_core_info() {
+    let g = glyphs();
+    print_gradient_header("PER-CORE ISA", "🧬", Color::BrightYellow);
+    println!("\n  {} {}", g.warning.bright_red().bold(), "Per-CPU detection requires Linux".bright_red());
+}
+
+fn print_features_by_leaf(features: &CpuFeatures) {
+    let g = glyphs();
+    print_gradient_header("CPU FEATURES BY LEAF", "🧾", Color::BrightGreen);
+
+    for (source, group) in features.features_by_leaf() {
+        let mut entries: Vec<(&str, bool)> = Vec::new();
+        if source == FeatureSource::new(1, 0) {
+            entries.extend(
+                FeatureSet::all()
+                    .iter_names()
+                    .map(|(name, flag)| (name, features.basic.contains(flag))),
+            );
+        }
+        entries.extend(group.iter().map(|f| (f.name.as_str(), f.supported)));
+
+        let supported = entries.iter().filter(|(_, s)| *s).count();
+        println!("\n  {} {} {}",
+            format!("CPUID {}", source).bright_cyan().bold(),
+            format!("({}/{})", supported, entries.len()).truecolor(100, 100, 100),
+            g.single.repeat(30).truecolor(60, 60, 60));
+
+        for (count, (name, supported)) in entries.into_iter().enumerate() {
+            if count % 4 == 0 {
+                print!("\n    ");
+            }
+            if supported {
+                print!("{} {:<18}", g.check.bright_green(), name.bright_white());
+            } else {
+                print!("{} {:<18}", g.cross.truecolor(150, 150, 150), name.truecolor(120, 120, 120));
+            }
+        }
+        println!();
+    }
+
+    println!("\n{}", g.double.repeat(70).truecolor(60, 60, 60));
+    println!();
+}
+
+fn print_features(features: &CpuFeatures, vendor: &CpuVendor) {
+    let g = glyphs();
+    print_gradient_header("CPU FEATURES", "✨", Color::BrightGreen);
+
+    let categories = [
+        (features::FeatureCategory::Simd, "SIMD & Vector", "🎯", Color::BrightRed),
+        (features::FeatureCategory::Cryptography, "Cryptography", "🔐", Color::BrightYellow),
+        (features::FeatureCategory::Security, "Security", "🛡️", Color::BrightMagenta),
+        (features::FeatureCategory::Virtualization, "Virtualization", "☁️", Color::BrightCyan),
+        (features::FeatureCategory::Performance, "Performance", "⚡", Color::BrightGreen),
+        (features::FeatureCategory::Memory, "Memory", "💾", Color::BrightBlue),
+        (features::FeatureCategory::Debug, "Debug", "🐛", Color::Yellow),
+        (features::FeatureCategory::Power, "Power", "🔋", Color::Green),
+        (features::FeatureCategory::System, "System", "⚙️", Color::Cyan),
+    ];
+
+    for (category, name, icon, color) in &categories {
+        // Skip features only another vendor's CPUs implement
+        let all_category_features: Vec<&features::Feature> = features.all_features
+            .iter()
+            .filter(|f| f.category == *category && (f.supported || f.applies_to(vendor)))
+            .collect();
+        
+        if !all_category_features.is_empty() {
+            let supported_count = all_category_features.iter().filter(|f| f.supported).count();
+            let total_count = all_category_features.len();
+            
+            println!("\n  {} {} {} {}", 
+                g.icon(icon),
+                name.color(*color).bold(),
+                format!("({}/{})", supported_count, total_count).truecolor(100, 100, 100),
+                g.single.repeat(50).truecolor(60, 60, 60));
+
+            // Print supported features
+            let mut count = 0;
+            for feature in all_category_features.iter().filter(|f| f.supported) {
+                if count % 4 == 0 {
+                    print!("\n    ");
+                }
+                if feature.disabled_by_platform {
+                    print!("{} {:<18}", g.cross.red(), feature.name.red());
+                } else if feature.deprecated {
+                    print!("{} {:<18}", g.warning.yellow(), feature.name.yellow());
+                } else {
+                    print!("{} {:<18}", g.check.bright_green(), feature.name.bright_white());
+                }
+                count += 1;
+            }
+            if count > 0 {
+                println!();
+            }
+
+            // Print missing features
+            let missing: Vec<&&features::Feature> = all_category_features.iter()
+                .filter(|f| !f.supported)
+                .collect();
+            
+            if !missing.is_empty() {
+                println!("\n    {} Missing features:", g.warning.bright_yellow());
+                for (count, feature) in missing.into_iter().enumerate() {
+                    if count % 4 == 0 {
+                        print!("\n    ");
+                    }
+                    print!("{} {:<18}", g.cross.truecolor(150, 150, 150), feature.name.truecolor(120, 120, 120));
+                }
+                println!();
+            }
+        }
+    }
+
+    let total_features = features.all_supported().len();
+    let missing_features = features.missing_for_vendor(vendor).len();
+    let total_checked = total_features + missing_features;
+    
+    println!("\n\n  {} {} {}",
+        g.double.repeat(3).bright_green().bold(),
+        "Features Supported:".bright_white().bold(),
+        format!("{}/{}", total_features, total_checked).bright_yellow().bold());
+    
+    if missing_features > 0 {
+        println!("  {} {} {}",
+            g.double.repeat(3).truecolor(150, 150, 150),
+            "Features Not Supported:".truecolor(150, 150, 150),
+            missing_features.to_string().truecolor(120, 120, 120));
+    }
+
+    let disabled = features.disabled_by_platform();
+    if !disabled.is_empty() {
+        let names: Vec<&str> = disabled.iter().map(|f| f.name.as_str()).collect();
+        println!("\n  {} {} {}",
+            g.cross.red().bold(),
+            "Reported by CPUID but disabled by microcode or firmware:".red().bold(),
+            names.join(", ").red());
+    }
+
+    let deprecated = features.deprecated_supported();
+    if !deprecated.is_empty() {
+        println!("\n  {} {}", g.warning.yellow().bold(), "Deprecated features present (avoid in new code):".yellow().bold());
+        for feature in deprecated {
+            println!("    {} {:<20} {}",
+                g.arrow.yellow(),
+                feature.name.yellow(),
+                feature.deprecation_note().unwrap_or_default().truecolor(150, 150, 150));
+        }
+    }
+    
+    println!("\n{}", g.double.repeat(70).truecolor(60, 60, 60));
+    println!();
+}
+
+fn print_baseline_info(report: &BaselineReport) {
+    let g = glyphs();
+    let title = format!("BASELINE {}", report.baseline.to_uppercase());
+    print_gradient_header(&title, "📐", Color::BrightBlue);
+
+    println!();
+    if report.is_satisfied() {
+        println!("  {} {}", g.check.bright_green().bold(), format!("All {} features present", report.baseline).bright_white().bold());
+    } else {
+        println!("  {} {}", g.cross.bright_red().bold(), format!("{} required features missing", report.missing.len()).bright_white().bold());
+        for (count, name) in report.missing.iter().enumerate() {
+            if count % 4 == 0 {
+                print!("\n    ");
+            }
+            print!("{} {:<18}", g.cross.bright_red(), name.bright_white());
+        }
+        println!();
+    }
+
+    if !report.extra.is_empty() {
+        println!("\n  {} {}", "+".bright_cyan().bold(), format!("{} features beyond {}", report.extra.len(), report.baseline).bright_white().bold());
+        for (count, name) in report.extra.iter().enumerate() {
+            if count % 4 == 0 {
+                print!("\n    ");
+            }
+            print!("{} {:<18}", "+".bright_cyan(), name.bright_white());
+        }
+        println!();
+    }
+
+    println!("\n{}", g.double.repeat(70).truecolor(60, 60, 60));
+    println!();
+}
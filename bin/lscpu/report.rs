@@ -0,0 +1,283 @@
+//! Structured report output (JSON, YAML, Markdown, CSV).
+
+use cpudetect::*;
+use std::fmt::Write;
+
+pub struct Section {
+    pub key: &'static str,
+    pub title: &'static str,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Section {
+    fn new(key: &'static str, title: &'static str) -> Self {
+        Self {
+            key,
+            title,
+            entries: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, key: impl Into<String>, value: impl ToString) {
+        self.entries.push((key.into(), value.to_string()));
+    }
+
+    fn add_opt(&mut self, key: &str, value: Option<impl ToString>) {
+        if let Some(value) = value {
+            self.add(key, value);
+        }
+    }
+}
+
+pub fn sections(cpu: &CpuInfo) -> Vec<Section> {
+    let mut vendor = Section::new("vendor", "CPU Identification");
+    vendor.add("vendor", &cpu.vendor.vendor_string);
+    vendor.add("brand", &cpu.vendor.brand_string);
+    vendor.add_opt("model_number", cpu.vendor.brand_model_number());
+    vendor.add_opt("brand_mhz", cpu.vendor.brand_frequency_mhz());
+    vendor.add("family", format!("{:#x}", cpu.vendor.family));
+    vendor.add("model", format!("{:#x}", cpu.vendor.model));
+    vendor.add("stepping", cpu.vendor.stepping);
+    vendor.add("signature", format!("{:#x}", cpu.vendor.signature().to_raw()));
+    vendor.add("microarchitecture", cpu.microarchitecture);
+    if let Some(identity) = cpu.vendor.identify() {
+        vendor.add("codename", identity.codename);
+        vendor.add("process", identity.process);
+        vendor.add("generation", identity.generation);
+    }
+    if let Some(hypervisor) = &cpu.vendor.hypervisor {
+        vendor.add("hypervisor", hypervisor.vendor);
+        vendor.add("hypervisor_signature", &hypervisor.signature);
+        vendor.add("hypervisor_max_leaf", format!("{:#x}", hypervisor.max_leaf));
+        if let Some(kvm) = &hypervisor.kvm {
+            vendor.add("kvm_features", kvm.names().join(","));
+        }
+        if let Some(hyperv) = &hypervisor.hyperv {
+            vendor.add("hyperv_version", format!("{}.{}.{}", hyperv.major_version, hyperv.minor_version, hyperv.build));
+            vendor.add("hyperv_enlightenments", hyperv.names().join(","));
+            vendor.add_opt("hyperv_tsc_hz", hyperv.tsc_frequency_hz);
+            vendor.add_opt("hyperv_apic_hz", hyperv.apic_frequency_hz);
+        }
+    }
+
+    let mut topology = Section::new("topology", "CPU Topology");
+    topology.add("logical_processors", cpu.topology.logical_processors);
+    topology.add("physical_cores", cpu.topology.physical_cores);
+    topology.add("threads_per_core", cpu.topology.threads_per_core);
+    if let Some(reconciliation) = &cpu.topology.reconciliation {
+        topology.add("count_confidence", format!("{:?}", reconciliation.confidence));
+        topology.add("cpuid_logical_processors", reconciliation.cpuid.logical_processors);
+        topology.add("cpuid_physical_cores", reconciliation.cpuid.physical_cores);
+        topology.add_opt("os_online_cpus", reconciliation.os.online);
+        topology.add_opt("os_physical_cores", reconciliation.os.physical_cores);
+    }
+    topology.add("packages", cpu.topology.package_count());
+    topology.add("total_cores", cpu.topology.total_cores());
+    topology.add("hyperthreading", cpu.topology.has_hyperthreading);
+    topology.add("hybrid", cpu.topology.hybrid);
+    topology.add("initial_apic_id", cpu.topology.initial_apic_id);
+    topology.add_opt("x2apic_id", cpu.topology.x2apic_id);
+    topology.add("apic_smt_bits", cpu.topology.apic_layout.smt_bits);
+    topology.add("apic_core_bits", cpu.topology.apic_layout.core_bits);
+    topology.add_opt("levels", cpu.topology.levels.as_ref());
+    topology.add_opt("performance_cores", cpu.topology.performance_cores);
+    topology.add_opt("efficient_cores", cpu.topology.efficient_cores);
+    let amd = cpu.topology.amd.as_ref();
+    topology.add_opt("node_id", amd.map(|amd| amd.node_id));
+    topology.add_opt("nodes_per_processor", amd.map(|amd| amd.nodes_per_processor));
+    topology.add_opt("ccd_id", amd.and_then(|amd| amd.ccd_id));
+    topology.add_opt("ccx_id", amd.and_then(|amd| amd.ccx_id));
+    topology.add_opt("threads_per_ccx", amd.and_then(|amd| amd.threads_per_ccx));
+
+    let mut frequency = Section::new("frequency", "Frequency (MHz)");
+    frequency.add_opt("base_mhz", cpu.frequency.base_mhz);
+    frequency.add_opt("max_mhz", cpu.frequency.max_mhz);
+    frequency.add_opt("bus_mhz", cpu.frequency.bus_mhz);
+    frequency.add_opt("tsc_mhz", cpu.frequency.tsc_mhz);
+    for policy in &cpu.frequency.policies {
+        let mhz = |value: Option<u32>| value.map_or("?".to_string(), |v| v.to_string());
+        frequency.add(
+            format!("policy{}", policy.id),
+            format!(
+                "{} {}, {}-{} MHz (hardware {}-{} MHz)",
+                policy.driver,
+                policy.governor,
+                mhz(policy.scaling_min_mhz),
+                mhz(policy.scaling_max_mhz),
+                mhz(policy.hardware_min_mhz),
+                mhz(policy.hardware_max_mhz)
+            ),
+        );
+    }
+
+    let mut address = Section::new("address", "Address Sizes (bits)");
+    address.add("physical_bits", cpu.address.physical_bits);
+    address.add("virtual_bits", cpu.address.virtual_bits);
+    address.add_opt("guest_physical_bits", cpu.address.guest_physical_bits);
+
+    let mut cache = Section::new("cache", "Cache Hierarchy");
+    for c in &cpu.cache {
+        let mut value = format!(
+            "{}, {}-way, {}-byte lines, {} sets, shared by {}",
+            c.size_human(),
+            c.ways,
+            c.line_size,
+            c.sets,
+            c.shared_by
+        );
+        if c.instances > 0 {
+            value.push_str(&format!(", {} per package ({} scope)", c.instances, c.scope));
+        }
+        let flags = c.flags();
+        if !flags.is_empty() {
+            value.push_str(&format!(" ({})", flags.join(", ")));
+        }
+        if let Some(note) = c.note() {
+            value.push_str(&format!("; {}", note));
+        }
+        cache.add(format!("{:?} {:?}", c.level, c.cache_type), value);
+    }
+
+    let mut tlb = Section::new("tlb", "TLB");
+    for entry in &cpu.tlb.entries {
+        tlb.add(
+            format!("{} {}", entry.name(), entry.page_sizes),
+            format!("{} entries, {}", entry.entries, entry.associativity),
+        );
+    }
+    tlb.add("management", cpu.tlb.management);
+    tlb.add("page_sizes", cpu.tlb.page_sizes);
+
+    let mut platform = Section::new("platform", "Platform");
+    platform.add("max_cpuid_leaf", format!("{:#x}", cpu.platform.max_cpuid_leaf));
+    platform.add("max_extended_leaf", format!("{:#x}", cpu.platform.max_extended_leaf));
+    platform.add("tsc_invariant", cpu.platform.tsc_invariant);
+    platform.add("tsc_deadline", cpu.platform.tsc_deadline);
+    platform.add("xapic", cpu.platform.xapic);
+
+    let mut features = Section::new("features", "Features");
+    let categories = [
+        (FeatureCategory::Simd, "simd"),
+        (FeatureCategory::Cryptography, "cryptography"),
+        (FeatureCategory::Security, "security"),
+        (FeatureCategory::Virtualization, "virtualization"),
+        (FeatureCategory::Performance, "performance"),
+        (FeatureCategory::Memory, "memory"),
+        (FeatureCategory::Debug, "debug"),
+        (FeatureCategory::Power, "power"),
+        (FeatureCategory::System, "system"),
+    ];
+    features.add(
+        "basic",
+        cpu.features
+            .basic
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    for (category, key) in categories {
+        let names: Vec<&str> = cpu
+            .features
+            .features_by_category(category)
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        features.add(key, names.join(" "));
+    }
+    let deprecated: Vec<&str> = cpu
+        .features
+        .deprecated_supported()
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    features.add("deprecated", deprecated.join(" "));
+    let disabled: Vec<&str> = cpu
+        .features
+        .disabled_by_platform()
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    features.add("disabled_by_platform", disabled.join(" "));
+
+    vec![vendor, topology, frequency, address, cache, tlb, platform, features]
+}
+
+pub fn to_json(sections: &[Section]) -> String {
+    let mut out = String::from("{\n");
+    for (i, section) in sections.iter().enumerate() {
+        let _ = writeln!(out, "  {}: {{", json_string(section.key));
+        for (j, (key, value)) in section.entries.iter().enumerate() {
+            let comma = if j + 1 < section.entries.len() { "," } else { "" };
+            let _ = writeln!(out, "    {}: {}{}", json_string(key), json_string(value), comma);
+        }
+        let comma = if i + 1 < sections.len() { "," } else { "" };
+        let _ = writeln!(out, "  }}{}", comma);
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub fn to_yaml(sections: &[Section]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        if section.entries.is_empty() {
+            let _ = writeln!(out, "{}: {{}}", section.key);
+            continue;
+        }
+        let _ = writeln!(out, "{}:", section.key);
+        for (key, value) in &section.entries {
+            let _ = writeln!(out, "  {}: {}", json_string(key), json_string(value));
+        }
+    }
+    out
+}
+
+pub fn to_markdown(sections: &[Section]) -> String {
+    let mut out = String::from("# CPU Report\n");
+    for section in sections {
+        let _ = writeln!(out, "\n## {}\n", section.title);
+        let _ = writeln!(out, "| Key | Value |");
+        let _ = writeln!(out, "| --- | --- |");
+        for (key, value) in &section.entries {
+            let _ = writeln!(out, "| {} | {} |", key.replace('|', "\\|"), value.replace('|', "\\|"));
+        }
+    }
+    out
+}
+
+pub fn to_csv(sections: &[Section]) -> String {
+    let mut out = String::from("section,key,value\n");
+    for section in sections {
+        for (key, value) in &section.entries {
+            let _ = writeln!(out, "{},{},{}", section.key, csv_field(key), csv_field(value));
+        }
+    }
+    out
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,56 @@
+//! Generates the lscpu man page from the clap definition.
+//!
+//! Written to `$OUT_DIR/man/lscpu.1`; set `CPUDETECT_MAN_DIR` to also copy it
+//! somewhere stable for packaging. Skipped without the `cli` feature, which
+//! is what pulls in clap and builds lscpu.
+
+use std::io;
+
+#[cfg(feature = "cli")]
+#[path = "bin/lscpu/cli.rs"]
+#[allow(dead_code)]
+mod cli;
+
+// Stand-ins for the library items cli.rs borrows from the binary
+#[cfg(feature = "cli")]
+struct Baseline;
+
+#[cfg(feature = "cli")]
+fn parse_baseline(_name: &str) -> Result<&'static Baseline, String> {
+    Ok(&Baseline)
+}
+
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed=bin/lscpu/cli.rs");
+    println!("cargo:rerun-if-env-changed=CPUDETECT_MAN_DIR");
+
+    if std::env::var_os("CARGO_FEATURE_CLI").is_none() {
+        return Ok(());
+    }
+    write_man_page()
+}
+
+#[cfg(feature = "cli")]
+fn write_man_page() -> io::Result<()> {
+    use std::path::PathBuf;
+    use std::{env, fs};
+
+    let mut page = Vec::new();
+    clap_mangen::Man::new(<cli::Cli as clap::CommandFactory>::command()).render(&mut page)?;
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo")).join("man");
+    fs::create_dir_all(&out_dir)?;
+    fs::write(out_dir.join("lscpu.1"), &page)?;
+
+    if let Some(dir) = env::var_os("CPUDETECT_MAN_DIR") {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("lscpu.1"), &page)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "cli"))]
+fn write_man_page() -> io::Result<()> {
+    Ok(())
+}
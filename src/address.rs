@@ -2,9 +2,11 @@
 //!
 //! Detects physical and virtual address bit widths.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::error::{self, DetectError};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddressInfo {
     pub physical_bits: u32,
     pub virtual_bits: u32,
@@ -13,18 +15,23 @@ pub struct AddressInfo {
 
 impl AddressInfo {
     pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "address", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
         let mut info = Self {
             physical_bits: 36,
             virtual_bits: 48,
-            guest_physical_bits: None,
+            ..Self::default()
         };
 
-        if is_leaf_supported(0x8000_0008) {
-            let result = cpuid(0x8000_0008, 0);
-            info.physical_bits = (result.eax & 0xFF) as u32;
-            info.virtual_bits = ((result.eax >> 8) & 0xFF) as u32;
+        if src.is_leaf_supported(0x8000_0008) {
+            let result = src.cpuid(0x8000_0008, 0);
+            info.physical_bits = result.eax & 0xFF;
+            info.virtual_bits = (result.eax >> 8) & 0xFF;
 
-            let guest_phys = ((result.eax >> 16) & 0xFF) as u32;
+            let guest_phys = (result.eax >> 16) & 0xFF;
             if guest_phys > 0 {
                 info.guest_physical_bits = Some(guest_phys);
             }
@@ -32,4 +39,17 @@ impl AddressInfo {
 
         info
     }
+
+    /// Like `detect`, but fails instead of assuming 36/48 bits.
+    pub fn try_detect() -> Result<Self, DetectError> {
+        Self::try_detect_with(&HardwareCpuid)
+    }
+
+    pub fn try_detect_with(src: &dyn CpuidSource) -> Result<Self, DetectError> {
+        let result = error::require_leaf(src, 0x8000_0008, 0)?;
+        if result.eax & 0xFFFF == 0 {
+            return Err(error::empty_leaf(src, 0x8000_0008, 0));
+        }
+        Ok(Self::detect_with(src))
+    }
 }
@@ -0,0 +1,272 @@
+//! Microarchitecture Baseline Profiles
+//!
+//! Named x86-64 feature levels and microarchitecture targets (as used by
+//! `-march`), and comparison of detected features against them.
+
+use crate::features::CpuFeatures;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Baseline {
+    pub name: &'static str,
+    pub parent: Option<&'static Baseline>,
+    pub features: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BaselineReport {
+    pub baseline: &'static str,
+    pub missing: Vec<&'static str>,
+    pub extra: Vec<String>,
+}
+
+pub static X86_64: Baseline = Baseline {
+    name: "x86-64",
+    parent: None,
+    features: &["CMOV", "CX8", "FPU", "FXSR", "MMX", "SSE", "SSE2", "SYSCALL"],
+};
+
+pub static X86_64_V2: Baseline = Baseline {
+    name: "x86-64-v2",
+    parent: Some(&X86_64),
+    features: &[
+        "CMPXCHG16B", "LAHF_LM", "POPCNT", "SSE3", "SSE4.1", "SSE4.2", "SSSE3",
+    ],
+};
+
+pub static X86_64_V3: Baseline = Baseline {
+    name: "x86-64-v3",
+    parent: Some(&X86_64_V2),
+    features: &[
+        "AVX", "AVX2", "BMI1", "BMI2", "F16C", "FMA", "ABM", "MOVBE", "OSXSAVE",
+    ],
+};
+
+pub static X86_64_V4: Baseline = Baseline {
+    name: "x86-64-v4",
+    parent: Some(&X86_64_V3),
+    features: &["AVX512F", "AVX512BW", "AVX512CD", "AVX512DQ", "AVX512VL"],
+};
+
+pub static NEHALEM: Baseline = Baseline {
+    name: "nehalem",
+    parent: Some(&X86_64_V2),
+    features: &[],
+};
+
+pub static WESTMERE: Baseline = Baseline {
+    name: "westmere",
+    parent: Some(&NEHALEM),
+    features: &["PCLMULQDQ"],
+};
+
+pub static SANDYBRIDGE: Baseline = Baseline {
+    name: "sandybridge",
+    parent: Some(&WESTMERE),
+    features: &["AVX", "XSAVE"],
+};
+
+pub static IVYBRIDGE: Baseline = Baseline {
+    name: "ivybridge",
+    parent: Some(&SANDYBRIDGE),
+    features: &["FSGSBASE", "RDRAND", "F16C"],
+};
+
+pub static HASWELL: Baseline = Baseline {
+    name: "haswell",
+    parent: Some(&IVYBRIDGE),
+    features: &["MOVBE", "AVX2", "FMA", "BMI1", "BMI2", "ABM"],
+};
+
+pub static BROADWELL: Baseline = Baseline {
+    name: "broadwell",
+    parent: Some(&HASWELL),
+    features: &["RDSEED", "ADX", "3DNOWPREFETCH"],
+};
+
+pub static SKYLAKE: Baseline = Baseline {
+    name: "skylake",
+    parent: Some(&BROADWELL),
+    features: &["AES", "CLFLUSHOPT", "XSAVEC", "XSAVES"],
+};
+
+pub static SKYLAKE_AVX512: Baseline = Baseline {
+    name: "skylake-avx512",
+    parent: Some(&SKYLAKE),
+    features: &[
+        "PKU", "CLWB", "AVX512F", "AVX512VL", "AVX512BW", "AVX512DQ", "AVX512CD",
+    ],
+};
+
+pub static ICELAKE_SERVER: Baseline = Baseline {
+    name: "icelake-server",
+    parent: Some(&SKYLAKE_AVX512),
+    features: &[
+        "AVX512_VBMI",
+        "AVX512_IFMA",
+        "SHA",
+        "RDPID",
+        "GFNI",
+        "AVX512_VBMI2",
+        "AVX512_VPOPCNTDQ",
+        "AVX512_BITALG",
+        "AVX512_VNNI",
+        "VPCLMULQDQ",
+        "VAES",
+        "PCONFIG",
+        "WBNOINVD",
+    ],
+};
+
+pub static SAPPHIRERAPIDS: Baseline = Baseline {
+    name: "sapphirerapids",
+    parent: Some(&ICELAKE_SERVER),
+    features: &[
+        "AVX512_BF16",
+        "AVX512_FP16",
+        "AVX_VNNI",
+        "AMX_TILE",
+        "AMX_BF16",
+        "AMX_INT8",
+        "SERIALIZE",
+        "TSXLDTRK",
+        "ENQCMD",
+        "MOVDIRI",
+        "MOVDIR64B",
+        "WAITPKG",
+        "UINTR",
+    ],
+};
+
+pub static ZNVER1: Baseline = Baseline {
+    name: "znver1",
+    parent: Some(&X86_64_V3),
+    features: &[
+        "ADX",
+        "AES",
+        "CLFLUSHOPT",
+        "CLZERO",
+        "FSGSBASE",
+        "MONITORX",
+        "PCLMULQDQ",
+        "3DNOWPREFETCH",
+        "RDRAND",
+        "RDSEED",
+        "SHA",
+        "SSE4A",
+        "XSAVEC",
+        "XSAVEOPT",
+        "XSAVES",
+    ],
+};
+
+pub static ZNVER2: Baseline = Baseline {
+    name: "znver2",
+    parent: Some(&ZNVER1),
+    features: &["CLWB", "RDPID", "WBNOINVD"],
+};
+
+pub static ZNVER3: Baseline = Baseline {
+    name: "znver3",
+    parent: Some(&ZNVER2),
+    features: &["PKU", "VAES", "VPCLMULQDQ"],
+};
+
+pub static ZNVER4: Baseline = Baseline {
+    name: "znver4",
+    parent: Some(&ZNVER3),
+    features: &[
+        "AVX512F",
+        "AVX512DQ",
+        "AVX512_IFMA",
+        "AVX512CD",
+        "AVX512BW",
+        "AVX512VL",
+        "AVX512_BF16",
+        "AVX512_VBMI",
+        "AVX512_VBMI2",
+        "GFNI",
+        "AVX512_VNNI",
+        "AVX512_BITALG",
+        "AVX512_VPOPCNTDQ",
+    ],
+};
+
+pub static BASELINES: &[&Baseline] = &[
+    &X86_64,
+    &X86_64_V2,
+    &X86_64_V3,
+    &X86_64_V4,
+    &NEHALEM,
+    &WESTMERE,
+    &SANDYBRIDGE,
+    &IVYBRIDGE,
+    &HASWELL,
+    &BROADWELL,
+    &SKYLAKE,
+    &SKYLAKE_AVX512,
+    &ICELAKE_SERVER,
+    &SAPPHIRERAPIDS,
+    &ZNVER1,
+    &ZNVER2,
+    &ZNVER3,
+    &ZNVER4,
+];
+
+impl Baseline {
+    pub fn from_name(name: &str) -> Option<&'static Baseline> {
+        BASELINES
+            .iter()
+            .copied()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+    }
+
+    /// All features required by this baseline, including inherited ones.
+    pub fn required_features(&self) -> Vec<&'static str> {
+        let mut required = match self.parent {
+            Some(parent) => parent.required_features(),
+            None => Vec::new(),
+        };
+        for &name in self.features {
+            if !required.contains(&name) {
+                required.push(name);
+            }
+        }
+        required
+    }
+
+    pub fn compare(&self, features: &CpuFeatures) -> BaselineReport {
+        let required = self.required_features();
+
+        let missing = required
+            .iter()
+            .copied()
+            .filter(|name| !features.has_feature(name))
+            .collect();
+
+        let mut extra: Vec<String> = Vec::new();
+        let supported = features
+            .basic
+            .iter_names()
+            .map(|(name, _)| name)
+            .chain(features.all_supported().into_iter().map(|f| f.name.as_str()));
+        for name in supported {
+            if !required.contains(&name) && !extra.iter().any(|e| e == name) {
+                extra.push(name.to_string());
+            }
+        }
+
+        BaselineReport {
+            baseline: self.name,
+            missing,
+            extra,
+        }
+    }
+}
+
+impl BaselineReport {
+    pub fn is_satisfied(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
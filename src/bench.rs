@@ -0,0 +1,169 @@
+//! Memory Hierarchy Benchmarks
+//!
+//! Measures load-to-use latency (dependent pointer chase) and sequential read
+//! bandwidth for a given working-set size, to compare against declared caches.
+
+use crate::cache::{CacheInfo, CacheLevel, CacheType};
+use std::hint::black_box;
+use std::time::Instant;
+
+const LINE: usize = 64;
+const WORDS_PER_LINE: usize = LINE / size_of::<usize>();
+
+/// Minimum dependent loads per latency measurement.
+const LATENCY_LOADS: usize = 1 << 22;
+
+/// Minimum bytes streamed per bandwidth measurement.
+const BANDWIDTH_BYTES: usize = 1 << 29;
+
+/// DRAM working set: four times the largest cache, within these bounds. The
+/// cap keeps large-L3 server parts from allocating several GiB.
+const DRAM_MIN: usize = 1 << 26;
+const DRAM_MAX: usize = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BenchResult {
+    pub size: usize,
+    pub latency_ns: f64,
+    pub bandwidth_gbs: f64,
+}
+
+/// Average latency in nanoseconds of a dependent load over a `size`-byte working set.
+pub fn measure_latency(size: usize) -> f64 {
+    let lines = (size / LINE).max(2);
+
+    // Random cyclic permutation of cache lines defeats the hardware prefetchers
+    let mut order: Vec<usize> = (0..lines).collect();
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for i in (1..lines).rev() {
+        order.swap(i, rng.next() as usize % (i + 1));
+    }
+
+    let mut chain = vec![0usize; lines * WORDS_PER_LINE];
+    for pair in order.windows(2) {
+        chain[pair[0] * WORDS_PER_LINE] = pair[1] * WORDS_PER_LINE;
+    }
+    chain[order[lines - 1] * WORDS_PER_LINE] = order[0] * WORDS_PER_LINE;
+
+    let loads = LATENCY_LOADS.max(lines * 4);
+    let mut index = order[0] * WORDS_PER_LINE;
+
+    // Warm the working set once before timing
+    for _ in 0..lines {
+        index = chain[index];
+    }
+
+    let start = Instant::now();
+    for _ in 0..loads {
+        index = chain[index];
+    }
+    let elapsed = start.elapsed();
+    black_box(index);
+
+    elapsed.as_nanos() as f64 / loads as f64
+}
+
+/// Sequential read bandwidth in GB/s over a `size`-byte working set.
+pub fn measure_bandwidth(size: usize) -> f64 {
+    let words = (size / size_of::<u64>()).max(4);
+    let data: Vec<u64> = (0..words as u64).collect();
+    let passes = (BANDWIDTH_BYTES / (words * size_of::<u64>())).max(1);
+
+    black_box(read_sum(&data));
+
+    let start = Instant::now();
+    for _ in 0..passes {
+        black_box(read_sum(black_box(&data)));
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    (passes * words * size_of::<u64>()) as f64 / elapsed / 1e9
+}
+
+/// Measured latency and bandwidth of one cache level or of DRAM.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevelResult {
+    /// `None` for DRAM.
+    pub level: Option<CacheLevel>,
+    /// Declared capacity of the level; 0 for DRAM.
+    pub declared_size: u64,
+    /// Working set the kernels ran over: half the level, or 4x the largest
+    /// cache for DRAM, so it misses every level above.
+    pub working_set: usize,
+    pub latency_ns: f64,
+    /// `latency_ns` at the given clock; `None` when the frequency is unknown.
+    pub latency_cycles: Option<f64>,
+    pub bandwidth_gbs: f64,
+}
+
+/// One pointer-chase and one streaming run per data cache level in `caches`
+/// and for DRAM. Cycle counts assume the core runs at `cpu_mhz`, which turbo
+/// and power management make approximate.
+pub fn measure_levels(caches: &[CacheInfo], cpu_mhz: Option<u32>) -> Vec<LevelResult> {
+    let mut levels: Vec<&CacheInfo> =
+        caches.iter().filter(|c| c.cache_type != CacheType::Instruction).collect();
+    levels.sort_by_key(|c| c.level);
+    levels.dedup_by_key(|c| c.level);
+
+    let largest = levels.iter().map(|c| c.size as usize).max().unwrap_or(0);
+    let targets = levels
+        .iter()
+        .map(|c| (Some(c.level), c.size, (c.size as usize / 2).max(LINE * 2)))
+        .chain([(None, 0, largest.saturating_mul(4).clamp(DRAM_MIN, DRAM_MAX))]);
+
+    targets
+        .map(|(level, declared_size, working_set)| {
+            let latency_ns = measure_latency(working_set);
+            LevelResult {
+                level,
+                declared_size,
+                working_set,
+                latency_ns,
+                latency_cycles: cpu_mhz.map(|mhz| latency_ns * mhz as f64 / 1000.0),
+                bandwidth_gbs: measure_bandwidth(working_set),
+            }
+        })
+        .collect()
+}
+
+/// Latency and bandwidth for every power-of-two size from `min` to `max` bytes.
+pub fn sweep(min: usize, max: usize) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    let mut size = min.max(LINE * 2).next_power_of_two();
+    while size <= max {
+        results.push(BenchResult {
+            size,
+            latency_ns: measure_latency(size),
+            bandwidth_gbs: measure_bandwidth(size),
+        });
+        size *= 2;
+    }
+    results
+}
+
+fn read_sum(data: &[u64]) -> u64 {
+    // Independent accumulators keep the loop bound by loads, not by the add chain
+    let mut acc = [0u64; 4];
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        acc[0] = acc[0].wrapping_add(chunk[0]);
+        acc[1] = acc[1].wrapping_add(chunk[1]);
+        acc[2] = acc[2].wrapping_add(chunk[2]);
+        acc[3] = acc[3].wrapping_add(chunk[3]);
+    }
+    let tail: u64 = chunks.remainder().iter().fold(0, |a, &b| a.wrapping_add(b));
+    acc.iter().fold(tail, |a, &b| a.wrapping_add(b))
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
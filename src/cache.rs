@@ -2,25 +2,140 @@
 //!
 //! Detects CPU cache hierarchy, sizes, and associativity.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::bench::{self, LevelResult};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::descriptors;
+use crate::error::{self, DetectError};
+use crate::frequency::FrequencyInfo;
+#[cfg(target_os = "linux")]
+use crate::percpu;
+use crate::topology::CpuTopology;
+use crate::vendor::CpuVendor;
+use crate::vendor_ops;
+use crate::CpuInfo;
+use std::collections::BTreeMap;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CacheLevel {
+    #[default]
     L1,
     L2,
     L3,
     L4,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CacheType {
     Data,
     Instruction,
+    #[default]
     Unified,
 }
 
-#[derive(Debug, Clone)]
+/// Which part of the package shares one instance of a cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CacheScope {
+    /// One core and its SMT siblings.
+    #[default]
+    Core,
+    /// A cluster of cores below the package, e.g. the L2 of an Intel E-core module.
+    Module,
+    /// An L3 serving one AMD core complex rather than the whole package.
+    Ccx,
+    Package,
+}
+
+impl fmt::Display for CacheScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Core => "core",
+            Self::Module => "module",
+            Self::Ccx => "CCX",
+            Self::Package => "package",
+        })
+    }
+}
+
+/// Identifies one cache instance the same way on every machine: `L1d#0`,
+/// `L2#3`, `L3#1`. Ordering follows level, then type, then instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheId {
+    pub level: CacheLevel,
+    pub cache_type: CacheType,
+    pub instance: u32,
+}
+
+impl CacheId {
+    /// Level and type without the instance, e.g. `L1d` or `L3`.
+    pub fn name(&self) -> String {
+        let kind = match self.cache_type {
+            CacheType::Data => "d",
+            CacheType::Instruction => "i",
+            CacheType::Unified => "",
+        };
+        format!("{:?}{}", self.level, kind)
+    }
+}
+
+impl fmt::Display for CacheId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.name(), self.instance)
+    }
+}
+
+/// Where `CacheInfo::detect_with_source` takes its cache list from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheSource {
+    #[default]
+    Cpuid,
+    /// `/sys/devices/system/cpu/cpu0/cache` on Linux; empty elsewhere.
+    Sysfs,
+    /// CPUID, with sysfs geometry taking precedence and filling in caches
+    /// CPUID does not report. Disagreements are listed as conflicts.
+    Merged,
+}
+
+/// A cache whose CPUID and sysfs descriptions disagree on one field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConflict {
+    pub level: CacheLevel,
+    pub cache_type: CacheType,
+    pub field: &'static str,
+    /// `None` when only the other source reports this cache.
+    pub cpuid: Option<u64>,
+    pub sysfs: Option<u64>,
+}
+
+impl fmt::Display for CacheConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: Option<u64>| value.map_or("missing".to_string(), |v| v.to_string());
+        write!(
+            f,
+            "{:?} {:?} {}: CPUID {}, sysfs {}",
+            self.level,
+            self.cache_type,
+            self.field,
+            show(self.cpuid),
+            show(self.sysfs)
+        )
+    }
+}
+
+/// Result of `CacheInfo::detect_with_source`.
+#[derive(Debug, Clone, Default)]
+pub struct CacheDetection {
+    pub caches: Vec<CacheInfo>,
+    /// Empty unless the source is `Merged`.
+    pub conflicts: Vec<CacheConflict>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CacheInfo {
     pub level: CacheLevel,
     pub cache_type: CacheType,
@@ -29,20 +144,475 @@ pub struct CacheInfo {
     pub line_size: u32,
     pub sets: u32,
     pub shared_by: u32,
+    /// Includes the lower levels; `None` when the legacy AMD leaves or sysfs
+    /// are the source.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inclusive: Option<bool>,
+    /// Addresses map to sets through a hash rather than plain index bits.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub complex_indexing: bool,
+    /// Needs no software initialization after reset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub self_initializing: bool,
+    /// WBINVD/INVD by another thread sharing this cache need not reach the lower
+    /// levels of caches this one includes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wbinvd_no_guarantee: bool,
+    /// Filled only with lines evicted from the level above, so its capacity
+    /// adds to that level's: AMD L3s and the eDRAM L4 of Haswell/Broadwell.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub victim: bool,
+    /// Copies of this cache in the package; 0 if the topology was unknown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub instances: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scope: CacheScope,
+    /// Which copy of this cache a logical processor uses: its APIC ID with
+    /// the bits of the threads sharing the cache shifted out, or the sysfs
+    /// `id`. 0 from `detect_all`, whose thread is not pinned; set per CPU by
+    /// `assign_instance` under `CpuInfo::detect_per_cpu`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub instance_id: u32,
 }
 
 impl CacheInfo {
+    /// Every cache, sorted by `id()` so lists compare equal across runs and
+    /// machines regardless of enumeration order.
     pub fn detect_all() -> Vec<Self> {
-        let mut caches = Vec::new();
+        Self::detect_all_with(&HardwareCpuid)
+    }
 
-        if is_leaf_supported(4) {
-            detect_intel_caches(&mut caches);
-        } else if is_leaf_supported(0x8000_0005) {
-            detect_amd_caches(&mut caches);
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "cache", level = "debug", skip_all))]
+    pub fn detect_all_with(src: &dyn CpuidSource) -> Vec<Self> {
+        let mut caches = vendor_ops::detect(src).caches(src);
+        let topology = CpuTopology::detect_with(src);
+        for cache in &mut caches {
+            cache.assign_scope(&topology);
         }
-
+        Self::sort(&mut caches);
         caches
     }
+
+    /// Level, type and `instance_id`; without `assign_instance` this only
+    /// identifies the level and type.
+    pub fn id(&self) -> CacheId {
+        CacheId {
+            level: self.level,
+            cache_type: self.cache_type,
+            instance: self.instance_id,
+        }
+    }
+
+    /// Size in the largest binary unit, to two decimals: "48 KiB", "1.25 MiB".
+    pub fn size_human(&self) -> String {
+        human_bytes(self.size)
+    }
+
+    /// Caches from CPUID, the OS, or both. Hypervisors often virtualize leaf 4
+    /// badly (host sharing counts, or nothing at all) while the guest kernel
+    /// was told the real layout through firmware tables.
+    pub fn detect_with_source(source: CacheSource) -> CacheDetection {
+        let (mut caches, conflicts) = match source {
+            CacheSource::Cpuid => (Self::detect_all(), Vec::new()),
+            CacheSource::Sysfs => (detect_sysfs(), Vec::new()),
+            CacheSource::Merged => merge(Self::detect_all(), detect_sysfs()),
+        };
+        let topology = CpuTopology::detect();
+        for cache in &mut caches {
+            cache.assign_scope(&topology);
+        }
+        Self::sort(&mut caches);
+        CacheDetection { caches, conflicts }
+    }
+
+    /// Set `instance_id` for the logical processor with `apic_id`. Leaf 4
+    /// reserves ceil(log2(shared_by)) APIC ID bits for the sharers.
+    pub fn assign_instance(&mut self, apic_id: u32) {
+        let shift = self.shared_by.max(1).next_power_of_two().trailing_zeros();
+        self.instance_id = apic_id >> shift;
+    }
+
+    /// Set `scope` and `instances` from `shared_by` and the package topology.
+    /// CPUID rounds sharing counts up to a power of two, so partial packages
+    /// still count as one instance.
+    pub fn assign_scope(&mut self, topology: &CpuTopology) {
+        let logical = topology.logical_processors.max(1);
+        let shared_by = self.shared_by.max(1);
+        self.instances = logical.div_ceil(shared_by);
+        self.scope = if shared_by <= topology.threads_per_core.max(1) {
+            CacheScope::Core
+        } else if shared_by >= logical {
+            CacheScope::Package
+        } else if self.level == CacheLevel::L3 {
+            CacheScope::Ccx
+        } else {
+            CacheScope::Module
+        };
+    }
+
+    /// Order by `id()`: level, then data/instruction/unified, then instance.
+    pub fn sort(caches: &mut [Self]) {
+        caches.sort_by_key(|c| (c.id(), c.size));
+    }
+
+    /// Short labels for the set flags, e.g. `["inclusive", "complex indexing"]`.
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = Vec::new();
+        match self.inclusive {
+            Some(true) => flags.push("inclusive"),
+            Some(false) => flags.push("non-inclusive"),
+            None => {}
+        }
+        if self.complex_indexing {
+            flags.push("complex indexing");
+        }
+        if self.self_initializing {
+            flags.push("self-initializing");
+        }
+        if self.wbinvd_no_guarantee {
+            flags.push("WBINVD not guaranteed");
+        }
+        if self.victim {
+            flags.push("victim");
+        }
+        flags
+    }
+
+    /// Caveat about how this cache is built or sized, for display next to it.
+    pub fn note(&self) -> Option<&'static str> {
+        match (self.level, self.inclusive) {
+            (CacheLevel::L4, _) => Some("eDRAM, shared with the integrated GPU"),
+            // Only the legacy AMD leaves mark a victim L3 without knowing inclusivity
+            (CacheLevel::L3, None) if self.victim => {
+                Some("size rounded down to 512 KB by leaf 0x8000_0006")
+            }
+            _ => None,
+        }
+    }
+
+    /// Like `detect_all`, but fails instead of returning no caches.
+    pub fn try_detect_all() -> Result<Vec<Self>, DetectError> {
+        Self::try_detect_all_with(&HardwareCpuid)
+    }
+
+    pub fn try_detect_all_with(src: &dyn CpuidSource) -> Result<Vec<Self>, DetectError> {
+        let caches = Self::detect_all_with(src);
+        if caches.is_empty() {
+            let leaf = match CpuVendor::detect_with(src) {
+                CpuVendor::Amd | CpuVendor::Hygon => 0x8000_0006,
+                _ => 4,
+            };
+            return Err(error::empty_leaf(src, leaf, 0));
+        }
+        Ok(caches)
+    }
+}
+
+/// `bytes` in the largest binary unit, to two decimals.
+pub(crate) fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut unit = 0;
+    let mut value = bytes as f64;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let digits = format!("{:.2}", value);
+    format!("{} {}", digits.trim_end_matches('0').trim_end_matches('.'), UNITS[unit])
+}
+
+/// Overlay sysfs caches on CPUID ones, matching by level and type. Sysfs wins
+/// on geometry; CPUID-only attributes such as `inclusive` are kept. With no
+/// sysfs caches at all (not Linux, or no cacheinfo) CPUID stands unchallenged.
+fn merge(cpuid: Vec<CacheInfo>, sysfs: Vec<CacheInfo>) -> (Vec<CacheInfo>, Vec<CacheConflict>) {
+    if sysfs.is_empty() {
+        return (cpuid, Vec::new());
+    }
+
+    let mut conflicts = Vec::new();
+    let mut merged = Vec::new();
+    let conflict = |cache: &CacheInfo, field, cpuid, sysfs| CacheConflict {
+        level: cache.level,
+        cache_type: cache.cache_type,
+        field,
+        cpuid,
+        sysfs,
+    };
+
+    for cache in &cpuid {
+        if !sysfs.iter().any(|os| os.level == cache.level && os.cache_type == cache.cache_type) {
+            conflicts.push(conflict(cache, "size", Some(cache.size), None));
+        }
+    }
+
+    for os in sysfs {
+        let Some(mut cache) = cpuid
+            .iter()
+            .find(|c| c.level == os.level && c.cache_type == os.cache_type)
+            .cloned()
+        else {
+            conflicts.push(conflict(&os, "size", None, Some(os.size)));
+            merged.push(os);
+            continue;
+        };
+
+        let fields = [
+            ("size", cache.size, os.size),
+            ("ways", cache.ways as u64, os.ways as u64),
+            ("line_size", cache.line_size as u64, os.line_size as u64),
+            ("sets", cache.sets as u64, os.sets as u64),
+            ("shared_by", cache.shared_by as u64, os.shared_by as u64),
+        ];
+        for (field, ours, theirs) in fields {
+            // A missing sysfs file reads as 0, which is no disagreement
+            if theirs != 0 && ours != theirs {
+                conflicts.push(conflict(&os, field, Some(ours), Some(theirs)));
+            }
+        }
+
+        let pick = |ours: u32, theirs: u32| if theirs != 0 { theirs } else { ours };
+        if os.size != 0 {
+            cache.size = os.size;
+        }
+        cache.ways = pick(cache.ways, os.ways);
+        cache.line_size = pick(cache.line_size, os.line_size);
+        cache.sets = pick(cache.sets, os.sets);
+        cache.shared_by = pick(cache.shared_by, os.shared_by);
+        merged.push(cache);
+    }
+
+    // CPUID caches the OS does not list are kept; the conflict above records them
+    let unlisted: Vec<CacheInfo> = cpuid
+        .into_iter()
+        .filter(|c| !merged.iter().any(|m| m.level == c.level && m.cache_type == c.cache_type))
+        .collect();
+    merged.extend(unlisted);
+    CacheInfo::sort(&mut merged);
+    (merged, conflicts)
+}
+
+/// Caches of cpu0 as the kernel's cacheinfo lists them.
+#[cfg(target_os = "linux")]
+fn detect_sysfs() -> Vec<CacheInfo> {
+    use std::path::Path;
+
+    let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+        return Vec::new();
+    };
+
+    let read = |dir: &Path, file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    let read_u32 = |dir: &Path, file: &str| {
+        read(dir, file).and_then(|s| s.parse().ok()).unwrap_or(0)
+    };
+
+    let mut caches: Vec<CacheInfo> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            entry.file_name().to_str()?.strip_prefix("index")?;
+            let dir = entry.path();
+            let level = match read(&dir, "level")?.as_str() {
+                "1" => CacheLevel::L1,
+                "2" => CacheLevel::L2,
+                "3" => CacheLevel::L3,
+                "4" => CacheLevel::L4,
+                _ => return None,
+            };
+            let cache_type = match read(&dir, "type")?.as_str() {
+                "Data" => CacheType::Data,
+                "Instruction" => CacheType::Instruction,
+                "Unified" => CacheType::Unified,
+                _ => return None,
+            };
+            // Sizes are written as e.g. "48K" or "32M"
+            let size = read(&dir, "size").and_then(|s| {
+                let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+                let (digits, unit) = s.split_at(split);
+                let shift = match unit {
+                    "" => 0,
+                    "K" => 10,
+                    "M" => 20,
+                    "G" => 30,
+                    _ => return None,
+                };
+                Some(digits.parse::<u64>().ok()? << shift)
+            });
+            let shared_by = read(&dir, "shared_cpu_list")
+                .and_then(|list| percpu::parse_cpu_list(&list))
+                .map_or(0, |cpus| cpus.len() as u32);
+            Some(CacheInfo {
+                level,
+                cache_type,
+                size: size.unwrap_or(0),
+                ways: read_u32(&dir, "ways_of_associativity"),
+                line_size: read_u32(&dir, "coherency_line_size"),
+                sets: read_u32(&dir, "number_of_sets"),
+                shared_by,
+                instance_id: read_u32(&dir, "id"),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    CacheInfo::sort(&mut caches);
+    caches
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_sysfs() -> Vec<CacheInfo> {
+    Vec::new()
+}
+
+/// Coherency line size in bytes, e.g. for padding against false sharing.
+/// Takes the larger of the CLFLUSH size (leaf 1 EBX[15:8]) and the L1 data
+/// cache line, and 64 if neither is reported.
+pub fn line_size() -> u32 {
+    line_size_with(&HardwareCpuid)
+}
+
+pub fn line_size_with(src: &dyn CpuidSource) -> u32 {
+    let clflush = if src.is_leaf_supported(1) {
+        ((src.cpuid(1, 0).ebx >> 8) & 0xFF) * 8
+    } else {
+        0
+    };
+    let l1d = CacheHierarchy::from(vendor_ops::detect(src).caches(src))
+        .l1d()
+        .map_or(0, |c| c.line_size);
+    match clflush.max(l1d) {
+        0 => 64,
+        size => size,
+    }
+}
+
+/// Bytes the hardware prefetcher brings in per request, from the leaf 2
+/// descriptors; `None` where the CPU does not report it (AMD, most VMs).
+pub fn prefetch_size() -> Option<u32> {
+    prefetch_size_with(&HardwareCpuid)
+}
+
+pub fn prefetch_size_with(src: &dyn CpuidSource) -> Option<u32> {
+    descriptors::prefetch_size(src)
+}
+
+/// Measure load latency and streaming bandwidth at each detected cache level
+/// and in DRAM on the calling thread. Takes seconds; pin the thread first for
+/// stable numbers.
+pub fn benchmark() -> Vec<LevelResult> {
+    let frequency = FrequencyInfo::detect();
+    bench::measure_levels(&CacheInfo::detect_all(), frequency.max_mhz.or(frequency.base_mhz))
+}
+
+/// The caches one logical processor sees, with lookups by level and totals.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct CacheHierarchy {
+    pub caches: Vec<CacheInfo>,
+}
+
+impl CacheHierarchy {
+    pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self::from(CacheInfo::detect_all_with(src))
+    }
+
+    pub fn find(&self, level: CacheLevel, cache_type: CacheType) -> Option<&CacheInfo> {
+        self.caches.iter().find(|c| c.level == level && c.cache_type == cache_type)
+    }
+
+    /// L1 data cache, or a unified L1 on CPUs without a split one.
+    pub fn l1d(&self) -> Option<&CacheInfo> {
+        self.find(CacheLevel::L1, CacheType::Data)
+            .or_else(|| self.find(CacheLevel::L1, CacheType::Unified))
+    }
+
+    pub fn l1i(&self) -> Option<&CacheInfo> {
+        self.find(CacheLevel::L1, CacheType::Instruction)
+    }
+
+    pub fn l2(&self) -> Option<&CacheInfo> {
+        self.find(CacheLevel::L2, CacheType::Unified)
+    }
+
+    pub fn l3(&self) -> Option<&CacheInfo> {
+        self.find(CacheLevel::L3, CacheType::Unified)
+    }
+
+    /// Bytes at each level, data and instruction caches added together.
+    pub fn total_per_level(&self) -> BTreeMap<CacheLevel, u64> {
+        let mut totals = BTreeMap::new();
+        for cache in &self.caches {
+            *totals.entry(cache.level).or_default() += cache.size;
+        }
+        totals
+    }
+
+    /// Bytes across every cache one logical processor sees, shared ones in full.
+    pub fn total_bytes(&self) -> u64 {
+        self.caches.iter().map(|c| c.size).sum()
+    }
+
+    /// Distinct bytes one logical processor can keep cached: levels covered
+    /// by an inclusive level below them add nothing, victim and non-inclusive
+    /// levels add their full size.
+    pub fn effective_bytes(&self) -> u64 {
+        self.caches
+            .iter()
+            .filter(|c| !self.caches.iter().any(|o| o.level > c.level && o.inclusive == Some(true)))
+            .map(|c| c.size)
+            .sum()
+    }
+
+    /// Coherency line size, taken from the L1 data cache.
+    pub fn line_size(&self) -> Option<u32> {
+        self.l1d().or(self.caches.first()).map(|c| c.line_size)
+    }
+
+    /// One core's share: private caches in full, shared ones divided by the
+    /// cores sharing them.
+    pub fn per_core_bytes(&self, topology: &CpuTopology) -> u64 {
+        let threads_per_core = topology.threads_per_core.max(1);
+        self.caches
+            .iter()
+            .map(|c| {
+                let cores = c.shared_by.max(1).div_ceil(threads_per_core);
+                c.size / cores as u64
+            })
+            .sum()
+    }
+
+    /// Every cache instance in the package, e.g. each core's L2 counted once
+    /// per core. Uses `instances` where set.
+    pub fn per_package_bytes(&self, topology: &CpuTopology) -> u64 {
+        let logical = topology.logical_processors.max(1);
+        self.caches
+            .iter()
+            .map(|c| match c.instances {
+                0 => c.size * logical.div_ceil(c.shared_by.max(1)) as u64,
+                instances => c.size * instances as u64,
+            })
+            .sum()
+    }
+}
+
+impl CpuInfo {
+    /// A copy of `cache` with the `CacheHierarchy` lookups.
+    pub fn cache_hierarchy(&self) -> CacheHierarchy {
+        CacheHierarchy::from(self.cache.clone())
+    }
+}
+
+impl From<Vec<CacheInfo>> for CacheHierarchy {
+    fn from(caches: Vec<CacheInfo>) -> Self {
+        Self { caches }
+    }
 }
 
 impl fmt::Display for CacheInfo {
@@ -62,21 +632,33 @@ impl fmt::Display for CacheInfo {
 
         write!(
             f,
-            "{} {} Cache: {} KB, {}-way, {}-byte lines, {} sets, shared by {} threads",
+            "{} {} Cache: {}, {}-way, {}-byte lines, {} sets, shared by {} threads",
             level,
             cache_type,
-            self.size / 1024,
+            self.size_human(),
             self.ways,
             self.line_size,
             self.sets,
             self.shared_by
-        )
+        )?;
+        let flags = self.flags();
+        if !flags.is_empty() {
+            write!(f, " ({})", flags.join(", "))?;
+        }
+        Ok(())
     }
 }
 
-fn detect_intel_caches(caches: &mut Vec<CacheInfo>) {
+/// Deterministic cache parameters: leaf 4 (Intel) or leaf 0x8000_001D (AMD TOPOEXT),
+/// which share the same register layout. AMD leaves EDX[2] (complex indexing) reserved.
+pub(crate) fn detect_deterministic(src: &dyn CpuidSource, leaf: u32) -> Vec<CacheInfo> {
+    let mut caches = Vec::new();
+    if !src.is_leaf_supported(leaf) {
+        return caches;
+    }
+
     for index in 0..32 {
-        let result = cpuid(4, index);
+        let result = src.cpuid(leaf, index);
         let cache_type_bits = result.eax & 0x1F;
 
         if cache_type_bits == 0 {
@@ -104,7 +686,14 @@ fn detect_intel_caches(caches: &mut Vec<CacheInfo>) {
         let sets = result.ecx + 1;
         let shared_by = ((result.eax >> 14) & 0xFFF) + 1;
 
-        let size = (ways * partitions * line_size * sets) as u64;
+        let size = ways as u64 * partitions as u64 * line_size as u64 * sets as u64;
+        let inclusive = result.edx & (1 << 1) != 0;
+        // AMD L3s and the Intel eDRAM L4 are filled from evictions of the level above
+        let victim = match level {
+            CacheLevel::L3 => leaf == 0x8000_001D && !inclusive,
+            CacheLevel::L4 => true,
+            _ => false,
+        };
 
         caches.push(CacheInfo {
             level,
@@ -114,18 +703,56 @@ fn detect_intel_caches(caches: &mut Vec<CacheInfo>) {
             line_size,
             sets,
             shared_by,
+            inclusive: Some(inclusive),
+            complex_indexing: result.edx & (1 << 2) != 0,
+            self_initializing: result.eax & (1 << 8) != 0,
+            wbinvd_no_guarantee: result.edx & 1 != 0,
+            victim,
+            ..Default::default()
         });
     }
+
+    caches
+}
+
+/// Intel caches from leaf 4, or from the leaf 2 descriptors on CPUs that
+/// predate it.
+pub(crate) fn detect_intel(src: &dyn CpuidSource) -> Vec<CacheInfo> {
+    let caches = detect_deterministic(src, 4);
+    if caches.is_empty() {
+        descriptors::caches(src)
+    } else {
+        caches
+    }
 }
 
-fn detect_amd_caches(caches: &mut Vec<CacheInfo>) {
-    if is_leaf_supported(0x8000_0005) {
-        let result = cpuid(0x8000_0005, 0);
+/// AMD caches from leaf 0x8000_001D when TOPOEXT (0x8000_0001 ECX[22]) is set,
+/// which reports sharing and exact geometry; leaf 4 is reserved on AMD. Falls
+/// back to the legacy leaves, e.g. under hypervisors that hide 0x8000_001D.
+pub(crate) fn detect_amd(src: &dyn CpuidSource) -> Vec<CacheInfo> {
+    let topoext =
+        src.is_leaf_supported(0x8000_0001) && src.cpuid(0x8000_0001, 0).ecx & (1 << 22) != 0;
+    if topoext {
+        let caches = detect_deterministic(src, 0x8000_001D);
+        if !caches.is_empty() {
+            return caches;
+        }
+    }
+    detect_amd_legacy(src)
+}
+
+/// Legacy AMD L1/L2/L3 descriptors from leaves 0x8000_0005 and 0x8000_0006.
+/// These carry no sharing information, so `shared_by` is 1 throughout.
+pub(crate) fn detect_amd_legacy(src: &dyn CpuidSource) -> Vec<CacheInfo> {
+    let mut caches = Vec::new();
+
+    if src.is_leaf_supported(0x8000_0005) {
+        let result = src.cpuid(0x8000_0005, 0);
 
         // L1 Data Cache
         let l1d_size = ((result.ecx >> 24) & 0xFF) as u64 * 1024;
-        let l1d_ways = ((result.ecx >> 16) & 0xFF) as u32;
-        let l1d_line_size = (result.ecx & 0xFF) as u32;
+        let l1d_ways = (result.ecx >> 16) & 0xFF;
+        let l1d_line_size = result.ecx & 0xFF;
 
         if l1d_size > 0 {
             caches.push(CacheInfo {
@@ -134,15 +761,20 @@ fn detect_amd_caches(caches: &mut Vec<CacheInfo>) {
                 size: l1d_size,
                 ways: l1d_ways,
                 line_size: l1d_line_size,
-                sets: (l1d_size / (l1d_ways as u64 * l1d_line_size as u64)) as u32,
+                sets: match l1d_ways as u64 * l1d_line_size as u64 {
+                    // Associativity or line size filtered out by a hypervisor
+                    0 => 0,
+                    way_bytes => (l1d_size / way_bytes) as u32,
+                },
                 shared_by: 1,
+                ..Default::default()
             });
         }
 
         // L1 Instruction Cache
         let l1i_size = ((result.edx >> 24) & 0xFF) as u64 * 1024;
-        let l1i_ways = ((result.edx >> 16) & 0xFF) as u32;
-        let l1i_line_size = (result.edx & 0xFF) as u32;
+        let l1i_ways = (result.edx >> 16) & 0xFF;
+        let l1i_line_size = result.edx & 0xFF;
 
         if l1i_size > 0 {
             caches.push(CacheInfo {
@@ -151,47 +783,68 @@ fn detect_amd_caches(caches: &mut Vec<CacheInfo>) {
                 size: l1i_size,
                 ways: l1i_ways,
                 line_size: l1i_line_size,
-                sets: (l1i_size / (l1i_ways as u64 * l1i_line_size as u64)) as u32,
+                sets: match l1i_ways as u64 * l1i_line_size as u64 {
+                    0 => 0,
+                    way_bytes => (l1i_size / way_bytes) as u32,
+                },
                 shared_by: 1,
+                ..Default::default()
             });
         }
     }
 
-    if is_leaf_supported(0x8000_0006) {
-        let result = cpuid(0x8000_0006, 0);
+    if src.is_leaf_supported(0x8000_0006) {
+        let result = src.cpuid(0x8000_0006, 0);
 
         // L2 Cache
         let l2_size = ((result.ecx >> 16) & 0xFFFF) as u64 * 1024;
-        let l2_ways = ((result.ecx >> 12) & 0xF) as u32;
-        let l2_line_size = (result.ecx & 0xFF) as u32;
-
         if l2_size > 0 {
-            caches.push(CacheInfo {
-                level: CacheLevel::L2,
-                cache_type: CacheType::Unified,
-                size: l2_size,
-                ways: l2_ways,
-                line_size: l2_line_size,
-                sets: (l2_size / (l2_ways as u64 * l2_line_size as u64)) as u32,
-                shared_by: 1,
-            });
+            caches.push(legacy_cache(CacheLevel::L2, l2_size, result.ecx));
         }
 
         // L3 Cache
         let l3_size = ((result.edx >> 18) & 0x3FFF) as u64 * 512 * 1024;
-        let l3_ways = ((result.edx >> 12) & 0xF) as u32;
-        let l3_line_size = (result.edx & 0xFF) as u32;
-
         if l3_size > 0 {
-            caches.push(CacheInfo {
-                level: CacheLevel::L3,
-                cache_type: CacheType::Unified,
-                size: l3_size,
-                ways: l3_ways,
-                line_size: l3_line_size,
-                sets: (l3_size / (l3_ways as u64 * l3_line_size as u64)) as u32,
-                shared_by: 1,
-            });
+            caches.push(legacy_cache(CacheLevel::L3, l3_size, result.edx));
         }
     }
+
+    caches
+}
+
+/// L2 or L3 from a leaf 0x8000_0006 register: associativity code in bits
+/// 15:12, line size in bits 7:0.
+fn legacy_cache(level: CacheLevel, size: u64, reg: u32) -> CacheInfo {
+    let line_size = reg & 0xFF;
+    let ways = match (reg >> 12) & 0xF {
+        code @ (0x1..=0x4) => code,
+        0x5 => 6,
+        0x6 => 8,
+        0x8 => 16,
+        0xA => 32,
+        0xB => 48,
+        0xC => 64,
+        0xD => 96,
+        0xE => 128,
+        // Fully associative: a single set
+        0xF if line_size > 0 => (size / line_size as u64) as u32,
+        // 0x9 defers to leaf 0x8000_001D; others are reserved
+        _ => 0,
+    };
+    let sets = match ways as u64 * line_size as u64 {
+        0 => 0,
+        way_bytes => (size / way_bytes) as u32,
+    };
+
+    CacheInfo {
+        level,
+        cache_type: CacheType::Unified,
+        size,
+        ways,
+        line_size,
+        sets,
+        shared_by: 1,
+        victim: level == CacheLevel::L3,
+        ..Default::default()
+    }
 }
@@ -2,10 +2,44 @@
 //!
 //! Provides safe wrappers around the x86_64 CPUID instruction.
 //! This module does one thing: execute CPUID and return results.
+//! `CpuidSource` abstracts where results come from (this core, `/dev/cpu/N/cpuid`,
+//! recorded data).
 
-use std::arch::x86_64::__cpuid_count;
+use alloc::collections::BTreeMap;
+use core::arch::x86_64::__cpuid_count;
+#[cfg(feature = "std")]
+use std::cell::Cell;
+#[cfg(all(feature = "std", target_os = "linux"))]
+use std::io;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "std")]
+thread_local! {
+    static CPUID_READS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Count one CPUID read for `cpuid_read_count`; without `std` reads are not counted.
+fn record_read() {
+    #[cfg(feature = "std")]
+    CPUID_READS.with(|reads| reads.set(reads.get() + 1));
+}
+
+/// Debug event with the raw registers, so users can report exactly what their CPU returned.
+#[cfg(feature = "tracing")]
+fn trace_leaf(source: &str, leaf: u32, subleaf: u32, result: &CpuidResult) {
+    tracing::debug!(
+        source,
+        leaf = format_args!("{:#x}", leaf),
+        subleaf,
+        eax = format_args!("{:#010x}", result.eax),
+        ebx = format_args!("{:#010x}", result.ebx),
+        ecx = format_args!("{:#010x}", result.ecx),
+        edx = format_args!("{:#010x}", result.edx),
+        "cpuid"
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpuidResult {
     pub eax: u32,
     pub ebx: u32,
@@ -36,7 +70,8 @@ impl CpuidResult {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Register {
     Eax,
     Ebx,
@@ -44,30 +79,235 @@ pub enum Register {
     Edx,
 }
 
-pub fn cpuid(leaf: u32, subleaf: u32) -> CpuidResult {
-    unsafe {
-        let result = __cpuid_count(leaf, subleaf);
-        CpuidResult {
-            eax: result.eax,
-            ebx: result.ebx,
-            ecx: result.ecx,
-            edx: result.edx,
-        }
+impl core::fmt::Display for Register {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Eax => "EAX",
+            Self::Ebx => "EBX",
+            Self::Ecx => "ECX",
+            Self::Edx => "EDX",
+        })
     }
 }
 
+pub fn cpuid(leaf: u32, subleaf: u32) -> CpuidResult {
+    record_read();
+    let result = __cpuid_count(leaf, subleaf);
+    let result = CpuidResult {
+        eax: result.eax,
+        ebx: result.ebx,
+        ecx: result.ecx,
+        edx: result.edx,
+    };
+    #[cfg(feature = "tracing")]
+    trace_leaf("cpuid", leaf, subleaf, &result);
+    result
+}
+
 pub fn max_cpuid_leaf() -> u32 {
     cpuid(0, 0).eax
 }
 
+/// XCR0 of the executing CPU (the XSAVE state components the OS enabled),
+/// or `None` when the OS has not set CR4.OSXSAVE and XGETBV would fault.
+pub fn xcr0() -> Option<u64> {
+    if cpuid(1, 0).ecx & (1 << 27) == 0 {
+        return None;
+    }
+    // SAFETY: OSXSAVE guarantees XGETBV is enabled; XCR0 always exists
+    Some(unsafe { core::arch::x86_64::_xgetbv(0) })
+}
+
 pub fn max_extended_leaf() -> u32 {
     cpuid(0x8000_0000, 0).eax
 }
 
+/// Highest Centaur/Zhaoxin leaf; see `CpuidSource::max_centaur_leaf`.
+pub fn max_centaur_leaf() -> u32 {
+    centaur_range(cpuid(0xC000_0000, 0).eax)
+}
+
 pub fn is_leaf_supported(leaf: u32) -> bool {
     if leaf < 0x8000_0000 {
         leaf <= max_cpuid_leaf()
-    } else {
+    } else if leaf < 0xC000_0000 {
         leaf <= max_extended_leaf()
+    } else {
+        leaf <= max_centaur_leaf()
+    }
+}
+
+/// Other vendors return the highest basic leaf's data for 0xC000_0000, so
+/// only a value inside the range counts as its maximum.
+fn centaur_range(eax: u32) -> u32 {
+    if (0xC000_0001..=0xC000_00FF).contains(&eax) { eax } else { 0 }
+}
+
+/// Where CPUID results come from: the executing core, another core, or recorded data.
+pub trait CpuidSource {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> CpuidResult;
+
+    fn max_cpuid_leaf(&self) -> u32 {
+        self.cpuid(0, 0).eax
+    }
+
+    fn max_extended_leaf(&self) -> u32 {
+        self.cpuid(0x8000_0000, 0).eax
+    }
+
+    /// Highest Centaur/Zhaoxin leaf, or 0 when the 0xC000_0000 range is absent.
+    fn max_centaur_leaf(&self) -> u32 {
+        centaur_range(self.cpuid(0xC000_0000, 0).eax)
+    }
+
+    fn is_leaf_supported(&self, leaf: u32) -> bool {
+        if leaf < 0x8000_0000 {
+            leaf <= self.max_cpuid_leaf()
+        } else if leaf < 0xC000_0000 {
+            leaf <= self.max_extended_leaf()
+        } else {
+            leaf <= self.max_centaur_leaf()
+        }
+    }
+}
+
+/// The CPUID instruction on whichever core the calling thread runs on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HardwareCpuid;
+
+impl CpuidSource for HardwareCpuid {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        cpuid(leaf, subleaf)
+    }
+}
+
+/// Recorded CPUID results keyed by `(leaf, subleaf)`, for detecting against
+/// captured data (other machines, regression fixtures).
+///
+/// Leaves recorded only at subleaf 0 answer every subleaf, as the hardware
+/// ignores ECX for them. Anything else unrecorded reads as all-zero registers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordedCpuid {
+    leaves: BTreeMap<(u32, u32), CpuidResult>,
+}
+
+impl RecordedCpuid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, leaf: u32, subleaf: u32, result: CpuidResult) {
+        self.leaves.insert((leaf, subleaf), result);
+    }
+
+    pub fn get(&self, leaf: u32, subleaf: u32) -> Option<CpuidResult> {
+        self.leaves.get(&(leaf, subleaf)).copied()
+    }
+
+    /// Recorded entries in `(leaf, subleaf)` order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u32, CpuidResult)> + '_ {
+        self.leaves
+            .iter()
+            .map(|(&(leaf, subleaf), &result)| (leaf, subleaf, result))
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+}
+
+impl CpuidSource for RecordedCpuid {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        if let Some(result) = self.get(leaf, subleaf) {
+            return result;
+        }
+
+        let has_subleaves = self
+            .leaves
+            .range((leaf, 1)..=(leaf, u32::MAX))
+            .next()
+            .is_some();
+        if has_subleaves {
+            CpuidResult::default()
+        } else {
+            self.get(leaf, 0).unwrap_or_default()
+        }
     }
 }
+
+impl FromIterator<(u32, u32, CpuidResult)> for RecordedCpuid {
+    fn from_iter<I: IntoIterator<Item = (u32, u32, CpuidResult)>>(iter: I) -> Self {
+        let mut recorded = Self::new();
+        for (leaf, subleaf, result) in iter {
+            recorded.insert(leaf, subleaf, result);
+        }
+        recorded
+    }
+}
+
+/// CPUID of a specific core through the Linux `cpuid` driver (`/dev/cpu/N/cpuid`).
+///
+/// Needs no affinity changes, so it also works inside cpuset-restricted
+/// containers. Failed reads return all-zero registers.
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[derive(Debug)]
+pub struct DevCpuid {
+    cpu: usize,
+    file: std::fs::File,
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl DevCpuid {
+    pub fn open(cpu: usize) -> io::Result<Self> {
+        let file = std::fs::File::open(format!("/dev/cpu/{}/cpuid", cpu))?;
+        Ok(Self { cpu, file })
+    }
+
+    pub fn cpu(&self) -> usize {
+        self.cpu
+    }
+
+    pub fn try_cpuid(&self, leaf: u32, subleaf: u32) -> io::Result<CpuidResult> {
+        use std::os::unix::fs::FileExt;
+
+        record_read();
+
+        // The driver takes the leaf in the low and the subleaf in the high 32 bits of the offset
+        let mut buf = [0u8; 16];
+        let read = self
+            .file
+            .read_exact_at(&mut buf, ((subleaf as u64) << 32) | leaf as u64);
+        #[cfg(feature = "tracing")]
+        if let Err(err) = &read {
+            tracing::debug!(cpu = self.cpu, leaf = format_args!("{:#x}", leaf), subleaf, %err, "cpuid read failed");
+        }
+        read?;
+        let reg = |i: usize| u32::from_le_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+        let result = CpuidResult {
+            eax: reg(0),
+            ebx: reg(4),
+            ecx: reg(8),
+            edx: reg(12),
+        };
+        #[cfg(feature = "tracing")]
+        trace_leaf("/dev/cpu", leaf, subleaf, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+impl CpuidSource for DevCpuid {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        self.try_cpuid(leaf, subleaf).unwrap_or_default()
+    }
+}
+
+/// Number of CPUID instructions executed so far on the calling thread.
+#[cfg(feature = "std")]
+pub fn cpuid_read_count() -> u64 {
+    CPUID_READS.with(Cell::get)
+}
@@ -0,0 +1,227 @@
+//! Leaf 2 Descriptors
+//!
+//! Intel's original cache and TLB enumeration: CPUID leaf 2 packs one-byte
+//! descriptors into its registers, each naming a fixed configuration from
+//! the SDM table. Pre-Nehalem parts lack leaf 4 or 0x18, and some Atoms
+//! still report TLBs only here.
+
+use crate::cache::CacheLevel::{self, L1, L2, L3};
+use crate::cache::CacheType::{self, Data, Instruction, Unified};
+use crate::cache::CacheInfo;
+use crate::cpuid::CpuidSource;
+use crate::tlb::Associativity::{self, Full, Unknown, Ways};
+use crate::tlb::{PageSizes, TlbEntry, TlbKind, TlbLevel};
+
+/// Descriptor meaning "no cache data here, use leaf 4".
+const USE_LEAF4: u8 = 0xFF;
+
+/// Descriptors giving the hardware prefetch size, 64 and 128 bytes.
+const PREFETCH_64: u8 = 0xF0;
+const PREFETCH_128: u8 = 0xF1;
+
+/// Descriptor, level, type, size in KB, ways, line size.
+const CACHES: &[(u8, CacheLevel, CacheType, u32, u32, u32)] = &[
+    (0x06, L1, Instruction, 8, 4, 32),
+    (0x08, L1, Instruction, 16, 4, 32),
+    (0x09, L1, Instruction, 32, 4, 64),
+    (0x0A, L1, Data, 8, 2, 32),
+    (0x0C, L1, Data, 16, 4, 32),
+    (0x0D, L1, Data, 16, 4, 64),
+    (0x0E, L1, Data, 24, 6, 64),
+    (0x1D, L2, Unified, 128, 2, 64),
+    (0x21, L2, Unified, 256, 8, 64),
+    (0x22, L3, Unified, 512, 4, 64),
+    (0x23, L3, Unified, 1024, 8, 64),
+    (0x24, L2, Unified, 1024, 16, 64),
+    (0x25, L3, Unified, 2048, 8, 64),
+    (0x29, L3, Unified, 4096, 8, 64),
+    (0x2C, L1, Data, 32, 8, 64),
+    (0x30, L1, Instruction, 32, 8, 64),
+    (0x41, L2, Unified, 128, 4, 32),
+    (0x42, L2, Unified, 256, 4, 32),
+    (0x43, L2, Unified, 512, 4, 32),
+    (0x44, L2, Unified, 1024, 4, 32),
+    (0x45, L2, Unified, 2048, 4, 32),
+    (0x46, L3, Unified, 4096, 4, 64),
+    (0x47, L3, Unified, 8192, 8, 64),
+    (0x48, L2, Unified, 3072, 12, 64),
+    (0x49, L2, Unified, 4096, 16, 64),
+    (0x4A, L3, Unified, 6144, 12, 64),
+    (0x4B, L3, Unified, 8192, 16, 64),
+    (0x4C, L3, Unified, 12288, 12, 64),
+    (0x4D, L3, Unified, 16384, 16, 64),
+    (0x4E, L2, Unified, 6144, 24, 64),
+    (0x60, L1, Data, 16, 8, 64),
+    (0x66, L1, Data, 8, 4, 64),
+    (0x67, L1, Data, 16, 4, 64),
+    (0x68, L1, Data, 32, 4, 64),
+    (0x78, L2, Unified, 1024, 4, 64),
+    (0x79, L2, Unified, 128, 8, 64),
+    (0x7A, L2, Unified, 256, 8, 64),
+    (0x7B, L2, Unified, 512, 8, 64),
+    (0x7C, L2, Unified, 1024, 8, 64),
+    (0x7D, L2, Unified, 2048, 8, 64),
+    (0x7F, L2, Unified, 512, 2, 64),
+    (0x80, L2, Unified, 512, 8, 64),
+    (0x82, L2, Unified, 256, 8, 32),
+    (0x83, L2, Unified, 512, 8, 32),
+    (0x84, L2, Unified, 1024, 8, 32),
+    (0x85, L2, Unified, 2048, 8, 32),
+    (0x86, L2, Unified, 512, 4, 64),
+    (0x87, L2, Unified, 1024, 8, 64),
+    (0xD0, L3, Unified, 512, 4, 64),
+    (0xD1, L3, Unified, 1024, 4, 64),
+    (0xD2, L3, Unified, 2048, 4, 64),
+    (0xD6, L3, Unified, 1024, 8, 64),
+    (0xD7, L3, Unified, 2048, 8, 64),
+    (0xD8, L3, Unified, 4096, 8, 64),
+    (0xDC, L3, Unified, 1536, 12, 64),
+    (0xDD, L3, Unified, 3072, 12, 64),
+    (0xDE, L3, Unified, 6144, 12, 64),
+    (0xE2, L3, Unified, 2048, 16, 64),
+    (0xE3, L3, Unified, 4096, 16, 64),
+    (0xE4, L3, Unified, 8192, 16, 64),
+    (0xEA, L3, Unified, 12288, 24, 64),
+    (0xEB, L3, Unified, 18432, 24, 64),
+    (0xEC, L3, Unified, 24576, 24, 64),
+];
+
+const K4: PageSizes = PageSizes::K4;
+const M2: PageSizes = PageSizes::M2;
+const M4: PageSizes = PageSizes::M4;
+const G1: PageSizes = PageSizes::G1;
+const K4_M2: PageSizes = K4.union(M2);
+const K4_M4: PageSizes = K4.union(M4);
+const M2_M4: PageSizes = M2.union(M4);
+const K4_M2_M4: PageSizes = K4_M2.union(M4);
+
+/// Descriptor, level, kind, page sizes, entries, associativity. DTLB0 and uTLB
+/// are the small first-level data TLBs, DTLB1 the larger one behind them, STLB
+/// the shared second level. A few descriptors name two TLBs and have two rows.
+const TLBS: &[(u8, TlbLevel, TlbKind, PageSizes, u32, Associativity)] = &[
+    (0x01, TlbLevel::L1, TlbKind::Instruction, K4, 32, Ways(4)),
+    (0x02, TlbLevel::L1, TlbKind::Instruction, M4, 2, Full),
+    (0x03, TlbLevel::L1, TlbKind::Data, K4, 64, Ways(4)),
+    (0x04, TlbLevel::L1, TlbKind::Data, M4, 8, Ways(4)),
+    (0x05, TlbLevel::L2, TlbKind::Data, M4, 32, Ways(4)),
+    (0x0B, TlbLevel::L1, TlbKind::Instruction, M4, 4, Ways(4)),
+    (0x4F, TlbLevel::L1, TlbKind::Instruction, K4, 32, Unknown),
+    (0x50, TlbLevel::L1, TlbKind::Instruction, K4_M2_M4, 64, Full),
+    (0x51, TlbLevel::L1, TlbKind::Instruction, K4_M2_M4, 128, Full),
+    (0x52, TlbLevel::L1, TlbKind::Instruction, K4_M2_M4, 256, Full),
+    (0x55, TlbLevel::L1, TlbKind::Instruction, M2_M4, 7, Full),
+    (0x56, TlbLevel::L1, TlbKind::Data, M4, 16, Ways(4)),
+    (0x57, TlbLevel::L1, TlbKind::Data, K4, 16, Ways(4)),
+    (0x59, TlbLevel::L1, TlbKind::Data, K4, 16, Full),
+    (0x5A, TlbLevel::L1, TlbKind::Data, M2_M4, 32, Ways(4)),
+    (0x5B, TlbLevel::L1, TlbKind::Data, K4_M4, 64, Full),
+    (0x5C, TlbLevel::L1, TlbKind::Data, K4_M4, 128, Full),
+    (0x5D, TlbLevel::L1, TlbKind::Data, K4_M4, 256, Full),
+    (0x61, TlbLevel::L1, TlbKind::Instruction, K4, 48, Full),
+    (0x63, TlbLevel::L1, TlbKind::Data, M2_M4, 32, Ways(4)),
+    (0x63, TlbLevel::L1, TlbKind::Data, G1, 4, Ways(4)),
+    (0x64, TlbLevel::L1, TlbKind::Data, K4, 512, Ways(4)),
+    (0x6A, TlbLevel::L1, TlbKind::Data, K4, 64, Ways(8)),
+    (0x6B, TlbLevel::L1, TlbKind::Data, K4, 256, Ways(8)),
+    (0x6C, TlbLevel::L1, TlbKind::Data, M2_M4, 128, Ways(8)),
+    (0x6D, TlbLevel::L1, TlbKind::Data, G1, 16, Full),
+    (0x76, TlbLevel::L1, TlbKind::Instruction, M2_M4, 8, Full),
+    (0xA0, TlbLevel::L1, TlbKind::Data, K4, 32, Full),
+    (0xB0, TlbLevel::L1, TlbKind::Instruction, K4, 128, Ways(4)),
+    (0xB1, TlbLevel::L1, TlbKind::Instruction, M2, 8, Ways(4)),
+    (0xB1, TlbLevel::L1, TlbKind::Instruction, M4, 4, Ways(4)),
+    (0xB2, TlbLevel::L1, TlbKind::Instruction, K4, 64, Ways(4)),
+    (0xB3, TlbLevel::L1, TlbKind::Data, K4, 128, Ways(4)),
+    (0xB4, TlbLevel::L2, TlbKind::Data, K4, 256, Ways(4)),
+    (0xB5, TlbLevel::L1, TlbKind::Instruction, K4, 64, Ways(8)),
+    (0xB6, TlbLevel::L1, TlbKind::Instruction, K4, 128, Ways(8)),
+    (0xBA, TlbLevel::L2, TlbKind::Data, K4, 64, Ways(4)),
+    (0xC0, TlbLevel::L1, TlbKind::Data, K4_M4, 8, Ways(4)),
+    (0xC1, TlbLevel::L2, TlbKind::Unified, K4_M2, 1024, Ways(8)),
+    (0xC2, TlbLevel::L1, TlbKind::Data, K4_M2, 16, Ways(4)),
+    (0xC3, TlbLevel::L2, TlbKind::Unified, K4_M2, 1536, Ways(6)),
+    (0xC3, TlbLevel::L2, TlbKind::Unified, G1, 16, Ways(4)),
+    (0xC4, TlbLevel::L1, TlbKind::Data, M2_M4, 32, Ways(4)),
+    (0xCA, TlbLevel::L2, TlbKind::Unified, K4, 512, Ways(4)),
+];
+
+/// Every descriptor byte of leaf 2, in register order. Registers with bit 31
+/// set hold no descriptors; the low byte of EAX is an iteration count that is
+/// 1 on every shipping CPU, not a descriptor.
+fn descriptors(src: &dyn CpuidSource) -> Vec<u8> {
+    if !src.is_leaf_supported(2) {
+        return Vec::new();
+    }
+
+    let result = src.cpuid(2, 0);
+    let mut bytes = Vec::new();
+    for (index, reg) in [result.eax, result.ebx, result.ecx, result.edx].into_iter().enumerate() {
+        if reg & (1 << 31) != 0 {
+            continue;
+        }
+        let skip = if index == 0 { 1 } else { 0 };
+        bytes.extend(reg.to_le_bytes().into_iter().skip(skip).filter(|b| *b != 0));
+    }
+    bytes
+}
+
+/// Caches named by leaf 2; empty if it defers to leaf 4. Descriptors carry no
+/// sharing information, so `shared_by` is 1.
+pub(crate) fn caches(src: &dyn CpuidSource) -> Vec<CacheInfo> {
+    let bytes = descriptors(src);
+    if bytes.contains(&USE_LEAF4) {
+        return Vec::new();
+    }
+
+    // 0x49 is an L3 on the family 0xF model 6 Xeon MP and an L2 everywhere else
+    let signature = if src.is_leaf_supported(1) { src.cpuid(1, 0).eax } else { 0 };
+    let xeon_mp = (signature >> 8) & 0xF == 0xF && (signature >> 4) & 0xF == 6;
+
+    bytes
+        .iter()
+        .filter_map(|byte| CACHES.iter().find(|entry| entry.0 == *byte))
+        .map(|&(byte, level, cache_type, size_kb, ways, line_size)| {
+            let size = size_kb as u64 * 1024;
+            CacheInfo {
+                level: if byte == 0x49 && xeon_mp { L3 } else { level },
+                cache_type,
+                size,
+                ways,
+                line_size,
+                sets: (size / (ways as u64 * line_size as u64)) as u32,
+                shared_by: 1,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// TLBs named by leaf 2, for Intel CPUs before leaf 0x18 (Skylake and older
+/// Core parts) and Atoms that leave it empty.
+pub(crate) fn tlbs(src: &dyn CpuidSource) -> Vec<TlbEntry> {
+    descriptors(src)
+        .iter()
+        .flat_map(|byte| TLBS.iter().filter(move |entry| entry.0 == *byte))
+        .map(|&(_, level, kind, page_sizes, entries, associativity)| TlbEntry {
+            level,
+            kind,
+            page_sizes,
+            entries,
+            associativity,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Bytes the hardware prefetcher fetches at a time, from descriptor 0xF0 or
+/// 0xF1; CPUs that enumerate caches through leaf 4 usually still list one.
+pub(crate) fn prefetch_size(src: &dyn CpuidSource) -> Option<u32> {
+    let bytes = descriptors(src);
+    if bytes.contains(&PREFETCH_128) {
+        Some(128)
+    } else if bytes.contains(&PREFETCH_64) {
+        Some(64)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,223 @@
+//! Detection Snapshot Comparison
+//!
+//! Structured differences between two `CpuInfo` results, e.g. before and after
+//! a microcode update, a BIOS change or a VM migration.
+
+use crate::cache::CacheInfo;
+use crate::features::{CpuFeatures, FeatureSet};
+use crate::CpuInfo;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A scalar field whose rendered value differs between the two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FrequencyDelta {
+    pub field: &'static str,
+    pub before: Option<u32>,
+    pub after: Option<u32>,
+}
+
+impl FrequencyDelta {
+    /// Change in MHz when both snapshots report the value.
+    pub fn delta_mhz(&self) -> Option<i64> {
+        Some(self.after? as i64 - self.before? as i64)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CpuInfoDiff {
+    pub added_features: Vec<String>,
+    pub removed_features: Vec<String>,
+    pub vendor: Vec<FieldChange>,
+    pub topology: Vec<FieldChange>,
+    pub cache: Vec<FieldChange>,
+    pub address: Vec<FieldChange>,
+    pub frequency: Vec<FrequencyDelta>,
+}
+
+impl CpuInfoDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_features.is_empty()
+            && self.removed_features.is_empty()
+            && self.vendor.is_empty()
+            && self.topology.is_empty()
+            && self.cache.is_empty()
+            && self.address.is_empty()
+            && self.frequency.is_empty()
+    }
+}
+
+impl CpuInfo {
+    /// What changed going from `self` to `other`.
+    pub fn diff(&self, other: &CpuInfo) -> CpuInfoDiff {
+        let before = supported_names(&self.features);
+        let after = supported_names(&other.features);
+
+        let mut vendor = Vec::new();
+        compare(&mut vendor, "vendor", &self.vendor.vendor_string, &other.vendor.vendor_string);
+        compare(&mut vendor, "brand", &self.vendor.brand_string, &other.vendor.brand_string);
+        compare(&mut vendor, "family", &self.vendor.family, &other.vendor.family);
+        compare(&mut vendor, "model", &self.vendor.model, &other.vendor.model);
+        compare(&mut vendor, "stepping", &self.vendor.stepping, &other.vendor.stepping);
+
+        let (a, b) = (&self.topology, &other.topology);
+        let mut topology = Vec::new();
+        compare(&mut topology, "logical_processors", &a.logical_processors, &b.logical_processors);
+        compare(&mut topology, "physical_cores", &a.physical_cores, &b.physical_cores);
+        compare(&mut topology, "threads_per_core", &a.threads_per_core, &b.threads_per_core);
+        compare(&mut topology, "has_hyperthreading", &a.has_hyperthreading, &b.has_hyperthreading);
+        compare(&mut topology, "hybrid", &a.hybrid, &b.hybrid);
+
+        let (a, b) = (&self.address, &other.address);
+        let mut address = Vec::new();
+        compare(&mut address, "physical_bits", &a.physical_bits, &b.physical_bits);
+        compare(&mut address, "virtual_bits", &a.virtual_bits, &b.virtual_bits);
+        compare(
+            &mut address,
+            "guest_physical_bits",
+            &optional(a.guest_physical_bits),
+            &optional(b.guest_physical_bits),
+        );
+
+        let (a, b) = (&self.frequency, &other.frequency);
+        let frequency = [
+            ("base_mhz", a.base_mhz, b.base_mhz),
+            ("max_mhz", a.max_mhz, b.max_mhz),
+            ("bus_mhz", a.bus_mhz, b.bus_mhz),
+            ("tsc_mhz", a.tsc_mhz, b.tsc_mhz),
+        ]
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| FrequencyDelta { field, before, after })
+        .collect();
+
+        CpuInfoDiff {
+            added_features: after.difference(&before).cloned().collect(),
+            removed_features: before.difference(&after).cloned().collect(),
+            vendor,
+            topology,
+            cache: diff_caches(&self.cache, &other.cache),
+            address,
+            frequency,
+        }
+    }
+}
+
+impl fmt::Display for CpuInfoDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        for name in &self.added_features {
+            writeln!(f, "+ {}", name)?;
+        }
+        for name in &self.removed_features {
+            writeln!(f, "- {}", name)?;
+        }
+
+        let sections = [
+            ("vendor", &self.vendor),
+            ("topology", &self.topology),
+            ("cache", &self.cache),
+            ("address", &self.address),
+        ];
+        for (section, changes) in sections {
+            for change in changes {
+                writeln!(
+                    f,
+                    "~ {}.{}: {} -> {}",
+                    section, change.field, change.before, change.after
+                )?;
+            }
+        }
+
+        for delta in &self.frequency {
+            write!(
+                f,
+                "~ frequency.{}: {} -> {}",
+                delta.field,
+                optional(delta.before),
+                optional(delta.after)
+            )?;
+            match delta.delta_mhz() {
+                Some(mhz) => writeln!(f, " ({:+} MHz)", mhz)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compare<T: PartialEq + ToString + ?Sized>(
+    changes: &mut Vec<FieldChange>,
+    field: &str,
+    before: &T,
+    after: &T,
+) {
+    if before != after {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            before: before.to_string(),
+            after: after.to_string(),
+        });
+    }
+}
+
+fn optional(value: Option<u32>) -> String {
+    value.map_or("-".to_string(), |v| v.to_string())
+}
+
+/// Names of all supported features, basic leaf-1 EDX flags included.
+fn supported_names(features: &CpuFeatures) -> BTreeSet<String> {
+    let basic = FeatureSet::all()
+        .iter_names()
+        .filter(|(_, flag)| features.basic.contains(*flag))
+        .map(|(name, _)| name.to_string());
+    let extended = features.all_supported().into_iter().map(|f| f.name.clone());
+    basic.chain(extended).collect()
+}
+
+/// Caches are matched by level and type; each changed parameter is one entry.
+fn diff_caches(before: &[CacheInfo], after: &[CacheInfo]) -> Vec<FieldChange> {
+    let key = |c: &CacheInfo| (c.level as u8, c.cache_type as u8);
+    let keys: BTreeSet<(u8, u8)> = before.iter().chain(after).map(key).collect();
+
+    let mut changes = Vec::new();
+    for k in keys {
+        let a = before.iter().find(|c| key(c) == k);
+        let b = after.iter().find(|c| key(c) == k);
+        let name = a.or(b).map_or_else(String::new, |c| c.id().name());
+
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                compare(&mut changes, &format!("{}.size", name), &a.size, &b.size);
+                compare(&mut changes, &format!("{}.ways", name), &a.ways, &b.ways);
+                compare(&mut changes, &format!("{}.line_size", name), &a.line_size, &b.line_size);
+                compare(&mut changes, &format!("{}.sets", name), &a.sets, &b.sets);
+                compare(&mut changes, &format!("{}.shared_by", name), &a.shared_by, &b.shared_by);
+                compare(&mut changes, &format!("{}.instances", name), &a.instances, &b.instances);
+                compare(&mut changes, &format!("{}.scope", name), &a.scope, &b.scope);
+                let (a_flags, b_flags) = (a.flags().join(", "), b.flags().join(", "));
+                compare(&mut changes, &format!("{}.flags", name), &a_flags, &b_flags);
+            }
+            (a, b) => compare(
+                &mut changes,
+                &name,
+                &a.map_or("absent".to_string(), |c| c.size.to_string()),
+                &b.map_or("absent".to_string(), |c| c.size.to_string()),
+            ),
+        }
+    }
+    changes
+}
@@ -0,0 +1,217 @@
+//! Raw CPUID Dumps
+//!
+//! Captures every standard, hypervisor, extended and Centaur leaf with its
+//! subleaves, in the text format of `cpuid -r`, so detection can be replayed offline.
+
+use crate::cpuid::{CpuidResult, CpuidSource, HardwareCpuid, RecordedCpuid};
+use crate::CpuInfo;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Guards against garbage maximum-leaf values from broken hypervisors.
+const MAX_LEAVES_PER_RANGE: u32 = 0x100;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CpuidDump {
+    /// Logical CPU the dump was taken on, as printed in the `CPU n:` header.
+    pub cpu: usize,
+    pub leaves: RecordedCpuid,
+}
+
+impl CpuidDump {
+    /// Dump the CPU the calling thread runs on. On Linux the capture stays
+    /// pinned there, so a hybrid part cannot mix leaves from two core types,
+    /// and the header records that CPU.
+    pub fn capture() -> Self {
+        #[cfg(target_os = "linux")]
+        // SAFETY: sched_getcpu has no preconditions
+        if let Ok(cpu) = usize::try_from(unsafe { libc::sched_getcpu() })
+            && let Ok(dump) = crate::percpu::run_on_cpu(cpu, || Self::capture_from(&HardwareCpuid))
+        {
+            return Self { cpu, ..dump };
+        }
+        Self::capture_from(&HardwareCpuid)
+    }
+
+    /// Dump `src`; the header says CPU 0 since a source has no CPU number.
+    pub fn capture_from(src: &dyn CpuidSource) -> Self {
+        let mut leaves = RecordedCpuid::new();
+
+        capture_range(src, &mut leaves, 0);
+
+        // Leaf 1 ECX bit 31: hypervisor leaves at 0x4000_0000
+        if (src.cpuid(1, 0).ecx & (1 << 31)) != 0 {
+            capture_range(src, &mut leaves, 0x4000_0000);
+        }
+
+        capture_range(src, &mut leaves, 0x8000_0000);
+
+        // Centaur/Zhaoxin leaves, only when the range reports itself
+        let centaur = src.cpuid(0xC000_0000, 0).eax;
+        if (0xC000_0001..0xC000_0000 + MAX_LEAVES_PER_RANGE).contains(&centaur) {
+            capture_range(src, &mut leaves, 0xC000_0000);
+        }
+
+        Self { cpu: 0, leaves }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl CpuidSource for CpuidDump {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        self.leaves.cpuid(leaf, subleaf)
+    }
+}
+
+impl CpuInfo {
+    /// Rebuild a full report from a dump taken on another machine.
+    pub fn from_dump(dump: &CpuidDump) -> Self {
+        Self::detect_with(dump)
+    }
+}
+
+/// `cpuid -r` layout: a `CPU n:` header, then one line per leaf/subleaf.
+impl fmt::Display for CpuidDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CPU {}:", self.cpu)?;
+        for (leaf, subleaf, r) in self.leaves.iter() {
+            writeln!(
+                f,
+                "   {:#010x} {:#04x}: eax={:#010x} ebx={:#010x} ecx={:#010x} edx={:#010x}",
+                leaf, subleaf, r.eax, r.ebx, r.ecx, r.edx
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses `cpuid -r` output. Multi-CPU dumps keep only the first CPU.
+impl FromStr for CpuidDump {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> io::Result<Self> {
+        let mut dump = Self::default();
+        let mut seen_header = false;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(cpu) = line.strip_prefix("CPU ").and_then(|rest| rest.strip_suffix(':')) {
+                if seen_header {
+                    break;
+                }
+                seen_header = true;
+                dump.cpu = cpu.trim().parse().map_err(|_| invalid(number, "bad CPU header"))?;
+                continue;
+            }
+
+            let (leaf, subleaf, result) = parse_line(line).ok_or_else(|| invalid(number, line))?;
+            dump.leaves.insert(leaf, subleaf, result);
+        }
+
+        if dump.leaves.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "no CPUID leaves in dump"));
+        }
+        Ok(dump)
+    }
+}
+
+fn invalid(number: usize, detail: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: cannot parse '{}'", number + 1, detail),
+    )
+}
+
+/// `0x00000007 0x00: eax=0x00000002 ebx=0x... ecx=0x... edx=0x...`
+fn parse_line(line: &str) -> Option<(u32, u32, CpuidResult)> {
+    let (key, registers) = line.split_once(':')?;
+    let mut key = key.split_whitespace();
+    let leaf = parse_hex(key.next()?)?;
+    let subleaf = parse_hex(key.next()?)?;
+
+    let mut result = CpuidResult::default();
+    for field in registers.split_whitespace() {
+        let (name, value) = field.split_once('=')?;
+        let value = parse_hex(value)?;
+        match name {
+            "eax" => result.eax = value,
+            "ebx" => result.ebx = value,
+            "ecx" => result.ecx = value,
+            "edx" => result.edx = value,
+            _ => return None,
+        }
+    }
+    Some((leaf, subleaf, result))
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Record every leaf from `base` to the range's maximum with its subleaves.
+fn capture_range(src: &dyn CpuidSource, leaves: &mut RecordedCpuid, base: u32) {
+    let max = src.cpuid(base, 0).eax;
+    if max < base || max - base >= MAX_LEAVES_PER_RANGE {
+        leaves.insert(base, 0, src.cpuid(base, 0));
+        return;
+    }
+
+    for leaf in base..=max {
+        for subleaf in 0..subleaf_count(src, leaf) {
+            leaves.insert(leaf, subleaf, src.cpuid(leaf, subleaf));
+        }
+    }
+}
+
+/// Number of subleaves to record for `leaf`, following each leaf's own
+/// enumeration rule. Terminating (type 0) subleaves are recorded too.
+fn subleaf_count(src: &dyn CpuidSource, leaf: u32) -> u32 {
+    match leaf {
+        // Deterministic cache parameters: until cache type 0
+        4 | 0x8000_001D => count_until(src, leaf, |r| r.eax & 0x1F == 0),
+        // Extended topology: until level type 0
+        0xB | 0x1F | 0x8000_0026 => count_until(src, leaf, |r| (r.ecx >> 8) & 0xFF == 0),
+        // EAX of subleaf 0 is the highest subleaf
+        7 | 0x14 | 0x17 | 0x18 | 0x1D | 0x20 | 0x24 => src.cpuid(leaf, 0).eax.min(63) + 1,
+        // XSAVE state components
+        0xD => 64,
+        // RDT monitoring / allocation resources
+        0xF => 2,
+        0x10 | 0x23 | 0x8000_0020 => 4,
+        // SGX EPC sections: until sub-leaf type 0
+        0x12 => 2 + count_until_from(src, leaf, 2, |r| r.eax & 0xF == 0),
+        _ => 1,
+    }
+}
+
+fn count_until(src: &dyn CpuidSource, leaf: u32, done: impl Fn(&CpuidResult) -> bool) -> u32 {
+    count_until_from(src, leaf, 0, done)
+}
+
+fn count_until_from(
+    src: &dyn CpuidSource,
+    leaf: u32,
+    start: u32,
+    done: impl Fn(&CpuidResult) -> bool,
+) -> u32 {
+    for subleaf in start..start + 64 {
+        if done(&src.cpuid(leaf, subleaf)) {
+            return subleaf - start + 1;
+        }
+    }
+    64
+}
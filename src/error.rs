@@ -0,0 +1,73 @@
+//! Detection Errors
+//!
+//! Why a `try_detect` call could not determine a value, as opposed to the
+//! silent defaults returned by `detect`.
+
+use crate::cpuid::{CpuidResult, CpuidSource};
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DetectError {
+    /// The leaf is above the maximum reported by leaf 0, 0x8000_0000, ...
+    LeafNotSupported { leaf: u32 },
+    /// The leaf is in range but reports nothing (reserved on this vendor or unimplemented).
+    ZeroedLeaf { leaf: u32, subleaf: u32 },
+    /// The leaf is in range but blanked out by the hypervisor.
+    HypervisorMasked { leaf: u32 },
+}
+
+impl DetectError {
+    pub fn leaf(&self) -> u32 {
+        match *self {
+            Self::LeafNotSupported { leaf }
+            | Self::ZeroedLeaf { leaf, .. }
+            | Self::HypervisorMasked { leaf } => leaf,
+        }
+    }
+}
+
+impl fmt::Display for DetectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::LeafNotSupported { leaf } => write!(f, "CPUID leaf {:#x} is not supported", leaf),
+            Self::ZeroedLeaf { leaf, subleaf } => {
+                write!(f, "CPUID leaf {:#x} subleaf {} is all zeros", leaf, subleaf)
+            }
+            Self::HypervisorMasked { leaf } => {
+                write!(f, "CPUID leaf {:#x} is masked by the hypervisor", leaf)
+            }
+        }
+    }
+}
+
+impl core::error::Error for DetectError {}
+
+/// Read a leaf, failing if it is out of range or reads as all zeros.
+pub(crate) fn require_leaf(
+    src: &dyn CpuidSource,
+    leaf: u32,
+    subleaf: u32,
+) -> Result<CpuidResult, DetectError> {
+    if !src.is_leaf_supported(leaf) {
+        return Err(DetectError::LeafNotSupported { leaf });
+    }
+
+    let result = src.cpuid(leaf, subleaf);
+    if result == CpuidResult::default() {
+        return Err(empty_leaf(src, leaf, subleaf));
+    }
+    Ok(result)
+}
+
+/// Classify a supported leaf that yielded nothing useful.
+pub(crate) fn empty_leaf(src: &dyn CpuidSource, leaf: u32, subleaf: u32) -> DetectError {
+    if !src.is_leaf_supported(leaf) {
+        DetectError::LeafNotSupported { leaf }
+    } else if src.is_leaf_supported(1) && (src.cpuid(1, 0).ecx & (1 << 31)) != 0 {
+        // Leaf 1 ECX bit 31: running under a hypervisor
+        DetectError::HypervisorMasked { leaf }
+    } else {
+        DetectError::ZeroedLeaf { leaf, subleaf }
+    }
+}
@@ -0,0 +1,472 @@
+//! Typed Feature Flags
+//!
+//! One bitflags type per decoded CPUID feature register, with bit positions
+//! matching the register, so callers can test features through checked
+//! constants such as `Leaf7Ebx::AVX2` instead of strings. `CpuFeatures::flags`
+//! builds a `FeatureFlags` from the decoded features.
+
+use crate::cpuid::Register;
+use crate::features::FeatureSet;
+use bitflags::{Flags, bitflags};
+
+bitflags! {
+    /// Leaf 1 ECX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf1Ecx: u32 {
+        const SSE3         = 1 << 0;
+        const PCLMULQDQ    = 1 << 1;
+        const DTES64       = 1 << 2;
+        const MONITOR      = 1 << 3;
+        const DS_CPL       = 1 << 4;
+        const VMX          = 1 << 5;
+        const SMX          = 1 << 6;
+        const EIST         = 1 << 7;
+        const TM2          = 1 << 8;
+        const SSSE3        = 1 << 9;
+        const CNXT_ID      = 1 << 10;
+        const SDBG         = 1 << 11;
+        const FMA          = 1 << 12;
+        const CMPXCHG16B   = 1 << 13;
+        const XTPR         = 1 << 14;
+        const PDCM         = 1 << 15;
+        const PCID         = 1 << 17;
+        const DCA          = 1 << 18;
+        const SSE4_1       = 1 << 19;
+        const SSE4_2       = 1 << 20;
+        const X2APIC       = 1 << 21;
+        const MOVBE        = 1 << 22;
+        const POPCNT       = 1 << 23;
+        const TSC_DEADLINE = 1 << 24;
+        const AES          = 1 << 25;
+        const XSAVE        = 1 << 26;
+        const OSXSAVE      = 1 << 27;
+        const AVX          = 1 << 28;
+        const F16C         = 1 << 29;
+        const RDRAND       = 1 << 30;
+        const HYPERVISOR   = 1 << 31;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 0 EBX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Ebx: u32 {
+        const FSGSBASE             = 1 << 0;
+        const TSC_ADJUST           = 1 << 1;
+        const SGX                  = 1 << 2;
+        const BMI1                 = 1 << 3;
+        const HLE                  = 1 << 4;
+        const AVX2                 = 1 << 5;
+        const FDP_EXCPTN_ONLY      = 1 << 6;
+        const SMEP                 = 1 << 7;
+        const BMI2                 = 1 << 8;
+        const ERMS                 = 1 << 9;
+        const INVPCID              = 1 << 10;
+        const RTM                  = 1 << 11;
+        const PQM                  = 1 << 12;
+        const FPU_CS_DS_DEPRECATED = 1 << 13;
+        const MPX                  = 1 << 14;
+        const PQE                  = 1 << 15;
+        const AVX512F              = 1 << 16;
+        const AVX512DQ             = 1 << 17;
+        const RDSEED               = 1 << 18;
+        const ADX                  = 1 << 19;
+        const SMAP                 = 1 << 20;
+        const AVX512_IFMA          = 1 << 21;
+        const CLFLUSHOPT           = 1 << 23;
+        const CLWB                 = 1 << 24;
+        const INTEL_PT             = 1 << 25;
+        const AVX512PF             = 1 << 26;
+        const AVX512ER             = 1 << 27;
+        const AVX512CD             = 1 << 28;
+        const SHA                  = 1 << 29;
+        const AVX512BW             = 1 << 30;
+        const AVX512VL             = 1 << 31;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 0 ECX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Ecx: u32 {
+        const PREFETCHWT1      = 1 << 0;
+        const AVX512_VBMI      = 1 << 1;
+        const UMIP             = 1 << 2;
+        const PKU              = 1 << 3;
+        const OSPKE            = 1 << 4;
+        const WAITPKG          = 1 << 5;
+        const AVX512_VBMI2     = 1 << 6;
+        const CET_SS           = 1 << 7;
+        const GFNI             = 1 << 8;
+        const VAES             = 1 << 9;
+        const VPCLMULQDQ       = 1 << 10;
+        const AVX512_VNNI      = 1 << 11;
+        const AVX512_BITALG    = 1 << 12;
+        const TME_EN           = 1 << 13;
+        const AVX512_VPOPCNTDQ = 1 << 14;
+        const LA57             = 1 << 16;
+        const RDPID            = 1 << 22;
+        const KL               = 1 << 23;
+        const CLDEMOTE         = 1 << 25;
+        const MOVDIRI          = 1 << 27;
+        const MOVDIR64B        = 1 << 28;
+        const ENQCMD           = 1 << 29;
+        const SGX_LC           = 1 << 30;
+        const PKS              = 1 << 31;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 0 EDX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Edx: u32 {
+        const AVX512_4VNNIW          = 1 << 2;
+        const AVX512_4FMAPS          = 1 << 3;
+        const FSRM                   = 1 << 4;
+        const UINTR                  = 1 << 5;
+        const AVX512_VP2INTERSECT    = 1 << 8;
+        const SRBDS_CTRL             = 1 << 9;
+        const MD_CLEAR               = 1 << 10;
+        const RTM_ALWAYS_ABORT       = 1 << 11;
+        const TSX_FORCE_ABORT        = 1 << 13;
+        const SERIALIZE              = 1 << 14;
+        const HYBRID                 = 1 << 15;
+        const TSXLDTRK               = 1 << 16;
+        const PCONFIG                = 1 << 18;
+        const ARCHITECTURAL_LBR      = 1 << 19;
+        const CET_IBT                = 1 << 20;
+        const AMX_BF16               = 1 << 22;
+        const AVX512_FP16            = 1 << 23;
+        const AMX_TILE               = 1 << 24;
+        const AMX_INT8               = 1 << 25;
+        const IBRS_IBPB              = 1 << 26;
+        const STIBP                  = 1 << 27;
+        const L1D_FLUSH              = 1 << 28;
+        const IA32_ARCH_CAPABILITIES = 1 << 29;
+        const IA32_CORE_CAPABILITIES = 1 << 30;
+        const SSBD                   = 1 << 31;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 1 EAX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Sub1Eax: u32 {
+        const RAO_INT        = 1 << 3;
+        const AVX_VNNI       = 1 << 4;
+        const AVX512_BF16    = 1 << 5;
+        const LASS           = 1 << 6;
+        const CMPCCXADD      = 1 << 7;
+        const ARCHPERFMONEXT = 1 << 8;
+        const FZRM           = 1 << 10;
+        const FSRS           = 1 << 11;
+        const FSRC           = 1 << 12;
+        const FRED           = 1 << 17;
+        const LKGS           = 1 << 18;
+        const WRMSRNS        = 1 << 19;
+        const AMX_FP16       = 1 << 21;
+        const HRESET         = 1 << 22;
+        const AVX_IFMA       = 1 << 23;
+        const LAM            = 1 << 26;
+        const MSRLIST        = 1 << 27;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 1 EBX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Sub1Ebx: u32 {
+        const PPIN = 1 << 0;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 1 EDX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Sub1Edx: u32 {
+        const AVX_VNNI_INT8  = 1 << 4;
+        const AVX_NE_CONVERT = 1 << 5;
+        const AMX_COMPLEX    = 1 << 8;
+        const AVX_VNNI_INT16 = 1 << 10;
+        const PREFETCHITI    = 1 << 14;
+        const USER_MSR       = 1 << 15;
+        const CET_SSS        = 1 << 17;
+        const AVX10          = 1 << 18;
+        const APX_F          = 1 << 19;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 2 EDX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Sub2Edx: u32 {
+        const PSFD       = 1 << 0;
+        const IPRED_CTRL = 1 << 1;
+        const RRSBA_CTRL = 1 << 2;
+        const DDPD_U     = 1 << 3;
+        const BHI_CTRL   = 1 << 4;
+        const MCDT_NO    = 1 << 5;
+    }
+}
+
+bitflags! {
+    /// Leaf 7 subleaf 3 EDX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Leaf7Sub3Edx: u32 {
+        const AVX10_128 = 1 << 0;
+        const AVX10_256 = 1 << 1;
+        const AVX10_512 = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_0001 ECX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf1Ecx: u32 {
+        const LAHF_LM        = 1 << 0;
+        const CMP_LEGACY     = 1 << 1;
+        const SVM            = 1 << 2;
+        const EXTAPIC        = 1 << 3;
+        const CR8_LEGACY     = 1 << 4;
+        const ABM            = 1 << 5;
+        const SSE4A          = 1 << 6;
+        const MISALIGNSSE    = 1 << 7;
+        const PREFETCH_3DNOW = 1 << 8;
+        const OSVW           = 1 << 9;
+        const IBS            = 1 << 10;
+        const XOP            = 1 << 11;
+        const SKINIT         = 1 << 12;
+        const WDT            = 1 << 13;
+        const LWP            = 1 << 15;
+        const FMA4           = 1 << 16;
+        const TCE            = 1 << 17;
+        const NODEID_MSR     = 1 << 19;
+        const TBM            = 1 << 21;
+        const TOPOEXT        = 1 << 22;
+        const PERFCTR_CORE   = 1 << 23;
+        const PERFCTR_NB     = 1 << 24;
+        const DBX            = 1 << 26;
+        const PERFTSC        = 1 << 27;
+        const PCX_L2I        = 1 << 28;
+        const MONITORX       = 1 << 29;
+        const ADDR_MASK_EXT  = 1 << 30;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_0001 EDX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf1Edx: u32 {
+        const SYSCALL      = 1 << 11;
+        const MP           = 1 << 19;
+        const NX           = 1 << 20;
+        const MMXEXT       = 1 << 22;
+        const FXSR_OPT     = 1 << 25;
+        const PDPE1GB      = 1 << 26;
+        const RDTSCP       = 1 << 27;
+        const LM           = 1 << 29;
+        const AMD_3DNOWEXT = 1 << 30;
+        const AMD_3DNOW    = 1 << 31;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_0008 EBX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf8Ebx: u32 {
+        const CLZERO          = 1 << 0;
+        const IRPERF          = 1 << 1;
+        const XSAVEERPTR      = 1 << 2;
+        const RDPRU           = 1 << 4;
+        const MBE             = 1 << 6;
+        const MCOMMIT         = 1 << 8;
+        const WBNOINVD        = 1 << 9;
+        const IBPB            = 1 << 12;
+        const INT_WBINVD      = 1 << 13;
+        const IBRS            = 1 << 14;
+        const STIBP           = 1 << 15;
+        const IBRS_ALWAYS_ON  = 1 << 16;
+        const STIBP_ALWAYS_ON = 1 << 17;
+        const IBRS_PREFERRED  = 1 << 18;
+        const IBRS_SAME_MODE  = 1 << 19;
+        const NO_EFER_LMSLE   = 1 << 20;
+        const PPIN            = 1 << 23;
+        const SSBD            = 1 << 24;
+        const VIRT_SSBD       = 1 << 25;
+        const SSB_NO          = 1 << 26;
+        const PSFD            = 1 << 28;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_0008 ECX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf8Ecx: u32 {
+        const PERFCTR_CORE = 1 << 0;
+        const PERFCTR_NB   = 1 << 1;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_000A EDX (SVM).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeafAEdx: u32 {
+        const SVM_NPT               = 1 << 0;
+        const SVM_LBR_VIRT          = 1 << 1;
+        const SVM_LOCK              = 1 << 2;
+        const SVM_NRIP              = 1 << 3;
+        const SVM_TSC_RATE          = 1 << 4;
+        const SVM_VMCB_CLEAN        = 1 << 5;
+        const SVM_FLUSH_BY_ASID     = 1 << 6;
+        const SVM_DECODE_ASSISTS    = 1 << 7;
+        const SVM_PAUSE_FILTER      = 1 << 10;
+        const SVM_PAUSE_THRESHOLD   = 1 << 12;
+        const SVM_AVIC              = 1 << 13;
+        const SVM_V_VMSAVE_VMLOAD   = 1 << 15;
+        const SVM_VGIF              = 1 << 16;
+        const SVM_GMET              = 1 << 17;
+        const SVM_X2AVIC            = 1 << 18;
+        const SVM_SSSE_ERR          = 1 << 19;
+        const SVM_SPEC_CTRL         = 1 << 20;
+        const SVM_ROGPT             = 1 << 21;
+        const SVM_HOST_MCE_OVERRIDE = 1 << 23;
+        const SVM_INVLPGB           = 1 << 24;
+        const SVM_VNMI              = 1 << 25;
+        const SVM_IBS_VIRT          = 1 << 26;
+        const SVM_EXT_LVT           = 1 << 27;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_001A EAX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf1AEax: u32 {
+        const FP128 = 1 << 0;
+        const MOVU  = 1 << 1;
+        const FP256 = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_001F EAX (SME/SEV).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf1FEax: u32 {
+        const SME                = 1 << 0;
+        const SEV                = 1 << 1;
+        const PAGE_FLUSH_MSR     = 1 << 2;
+        const SEV_ES             = 1 << 3;
+        const SEV_SNP            = 1 << 4;
+        const VMPL               = 1 << 5;
+        const RMPQUERY           = 1 << 6;
+        const VMPL_SSS           = 1 << 7;
+        const SECURE_TSC         = 1 << 8;
+        const TSC_AUX_VIRT       = 1 << 9;
+        const HW_CACHE_COHERENCY = 1 << 10;
+        const HOST_64BIT         = 1 << 11;
+        const REST_INJ           = 1 << 12;
+        const ALT_INJ            = 1 << 13;
+        const DEBUG_SWAP         = 1 << 14;
+        const PREVENT_HOST_IBS   = 1 << 15;
+        const VTE                = 1 << 16;
+        const VMGEXIT_PARAM      = 1 << 17;
+        const VIRT_TOM_MSR       = 1 << 18;
+        const IBS_VIRT_GIF       = 1 << 19;
+        const VMSA_REG_PROT      = 1 << 24;
+        const SMT_PROTECTION     = 1 << 25;
+        const SECURE_AVIC        = 1 << 28;
+    }
+}
+
+bitflags! {
+    /// Leaf 0x8000_0021 EAX.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ExtLeaf21Eax: u32 {
+        const NO_NESTED_DATA_BP    = 1 << 0;
+        const FS_GS_NO_SERIALIZING = 1 << 1;
+        const LFENCE_SERIALIZING   = 1 << 2;
+        const SMM_PG_CFG_LOCK      = 1 << 3;
+        const NULL_SEL_CLEARS_BASE = 1 << 6;
+        const UAI                  = 1 << 7;
+        const AUTO_IBRS            = 1 << 8;
+        const NO_SMM_CTL_MSR       = 1 << 9;
+        const FSRS                 = 1 << 10;
+        const FSRC                 = 1 << 11;
+        const PREFETCH_CTL         = 1 << 13;
+        const CPUID_DIS            = 1 << 17;
+        const EPSF                 = 1 << 18;
+        const AGPR                 = 1 << 19;
+    }
+}
+
+bitflags! {
+    /// Leaf 0xC000_0001 EDX (Centaur/Zhaoxin PadLock).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct CentaurLeaf1Edx: u32 {
+        const PADLOCK_RNG     = 1 << 2;
+        const PADLOCK_RNG_EN  = 1 << 3;
+        const PADLOCK_ACE     = 1 << 6;
+        const PADLOCK_ACE_EN  = 1 << 7;
+        const PADLOCK_ACE2    = 1 << 8;
+        const PADLOCK_ACE2_EN = 1 << 9;
+        const PADLOCK_PHE     = 1 << 10;
+        const PADLOCK_PHE_EN  = 1 << 11;
+        const PADLOCK_PMM     = 1 << 12;
+        const PADLOCK_PMM_EN  = 1 << 13;
+    }
+}
+
+/// Every typed feature register; leaf 1 EDX is the existing `FeatureSet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FeatureFlags {
+    pub leaf1_edx: FeatureSet,
+    pub leaf1_ecx: Leaf1Ecx,
+    pub leaf7_ebx: Leaf7Ebx,
+    pub leaf7_ecx: Leaf7Ecx,
+    pub leaf7_edx: Leaf7Edx,
+    pub leaf7_sub1_eax: Leaf7Sub1Eax,
+    pub leaf7_sub1_ebx: Leaf7Sub1Ebx,
+    pub leaf7_sub1_edx: Leaf7Sub1Edx,
+    pub leaf7_sub2_edx: Leaf7Sub2Edx,
+    pub leaf7_sub3_edx: Leaf7Sub3Edx,
+    pub ext_leaf1_ecx: ExtLeaf1Ecx,
+    pub ext_leaf1_edx: ExtLeaf1Edx,
+    pub ext_leaf8_ebx: ExtLeaf8Ebx,
+    pub ext_leaf8_ecx: ExtLeaf8Ecx,
+    pub ext_leaf_a_edx: ExtLeafAEdx,
+    pub ext_leaf1a_eax: ExtLeaf1AEax,
+    pub ext_leaf1f_eax: ExtLeaf1FEax,
+    pub ext_leaf21_eax: ExtLeaf21Eax,
+    pub centaur_leaf1_edx: CentaurLeaf1Edx,
+}
+
+impl FeatureFlags {
+    /// Set one CPUID bit; bits in registers without a type here are ignored.
+    pub fn insert(&mut self, leaf: u32, subleaf: u32, register: Register, bit: u32) {
+        match (leaf, subleaf, register) {
+            (1, 0, Register::Edx) => self.leaf1_edx.insert(FeatureSet::from_bits_retain(1 << bit)),
+            (1, 0, Register::Ecx) => set(&mut self.leaf1_ecx, bit),
+            (7, 0, Register::Ebx) => set(&mut self.leaf7_ebx, bit),
+            (7, 0, Register::Ecx) => set(&mut self.leaf7_ecx, bit),
+            (7, 0, Register::Edx) => set(&mut self.leaf7_edx, bit),
+            (7, 1, Register::Eax) => set(&mut self.leaf7_sub1_eax, bit),
+            (7, 1, Register::Ebx) => set(&mut self.leaf7_sub1_ebx, bit),
+            (7, 1, Register::Edx) => set(&mut self.leaf7_sub1_edx, bit),
+            (7, 2, Register::Edx) => set(&mut self.leaf7_sub2_edx, bit),
+            (7, 3, Register::Edx) => set(&mut self.leaf7_sub3_edx, bit),
+            (0x8000_0001, 0, Register::Ecx) => set(&mut self.ext_leaf1_ecx, bit),
+            (0x8000_0001, 0, Register::Edx) => set(&mut self.ext_leaf1_edx, bit),
+            (0x8000_0008, 0, Register::Ebx) => set(&mut self.ext_leaf8_ebx, bit),
+            (0x8000_0008, 0, Register::Ecx) => set(&mut self.ext_leaf8_ecx, bit),
+            (0x8000_000A, 0, Register::Edx) => set(&mut self.ext_leaf_a_edx, bit),
+            (0x8000_001A, 0, Register::Eax) => set(&mut self.ext_leaf1a_eax, bit),
+            (0x8000_001F, 0, Register::Eax) => set(&mut self.ext_leaf1f_eax, bit),
+            (0x8000_0021, 0, Register::Eax) => set(&mut self.ext_leaf21_eax, bit),
+            (0xC000_0001, 0, Register::Edx) => set(&mut self.centaur_leaf1_edx, bit),
+            _ => {}
+        }
+    }
+}
+
+fn set<F: Flags<Bits = u32>>(flags: &mut F, bit: u32) {
+    flags.insert(F::from_bits_retain(1 << bit));
+}
@@ -0,0 +1,490 @@
+//! Typed Feature Identifiers
+//!
+//! `FeatureId` names every feature `CpuFeatures` decodes, and `FeatureBits`
+//! stores their support in a fixed bitset for constant-time lookups.
+
+use core::fmt;
+
+macro_rules! feature_ids {
+    ($($variant:ident => $name:literal,)*) => {
+        /// One variant per decoded feature name. Versioned names map to one
+        /// variant: `AVX10_V<n>` to `Avx10`, `PERFMON_V<n>` to `Perfmon`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum FeatureId {
+            $($variant,)*
+        }
+
+        impl FeatureId {
+            pub const ALL: &'static [FeatureId] = &[$(FeatureId::$variant,)*];
+
+            /// Name as used by `Feature::name` and `has_feature`.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(FeatureId::$variant => $name,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(FeatureId::$variant),)*
+                    _ if name.starts_with("AVX10_V") => Some(FeatureId::Avx10),
+                    _ if name.starts_with("PERFMON_V") => Some(FeatureId::Perfmon),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+feature_ids! {
+    Fpu                        => "FPU",
+    Vme                        => "VME",
+    De                         => "DE",
+    Pse                        => "PSE",
+    Tsc                        => "TSC",
+    Msr                        => "MSR",
+    Pae                        => "PAE",
+    Mce                        => "MCE",
+    Cx8                        => "CX8",
+    Apic                       => "APIC",
+    Sep                        => "SEP",
+    Mtrr                       => "MTRR",
+    Pge                        => "PGE",
+    Mca                        => "MCA",
+    Cmov                       => "CMOV",
+    Pat                        => "PAT",
+    Pse36                      => "PSE36",
+    Psn                        => "PSN",
+    Clfsh                      => "CLFSH",
+    Ds                         => "DS",
+    Acpi                       => "ACPI",
+    Mmx                        => "MMX",
+    Fxsr                       => "FXSR",
+    Sse                        => "SSE",
+    Sse2                       => "SSE2",
+    Ss                         => "SS",
+    Htt                        => "HTT",
+    Tm                         => "TM",
+    Pbe                        => "PBE",
+    Sse3                       => "SSE3",
+    Pclmulqdq                  => "PCLMULQDQ",
+    Dtes64                     => "DTES64",
+    Monitor                    => "MONITOR",
+    DsCpl                      => "DS-CPL",
+    Vmx                        => "VMX",
+    Smx                        => "SMX",
+    Eist                       => "EIST",
+    Tm2                        => "TM2",
+    Ssse3                      => "SSSE3",
+    CnxtId                     => "CNXT-ID",
+    Sdbg                       => "SDBG",
+    Fma                        => "FMA",
+    Cmpxchg16b                 => "CMPXCHG16B",
+    Xtpr                       => "xTPR",
+    Pdcm                       => "PDCM",
+    Pcid                       => "PCID",
+    Dca                        => "DCA",
+    Sse41                      => "SSE4.1",
+    Sse42                      => "SSE4.2",
+    X2apic                     => "x2APIC",
+    Movbe                      => "MOVBE",
+    Popcnt                     => "POPCNT",
+    TscDeadline                => "TSC-Deadline",
+    Aes                        => "AES",
+    Xsave                      => "XSAVE",
+    Osxsave                    => "OSXSAVE",
+    Avx                        => "AVX",
+    F16c                       => "F16C",
+    Rdrand                     => "RDRAND",
+    Hypervisor                 => "HYPERVISOR",
+    Fsgsbase                   => "FSGSBASE",
+    TscAdjust                  => "TSC_ADJUST",
+    Sgx                        => "SGX",
+    Bmi1                       => "BMI1",
+    Hle                        => "HLE",
+    Avx2                       => "AVX2",
+    FdpExcptnOnly              => "FDP_EXCPTN_ONLY",
+    Smep                       => "SMEP",
+    Bmi2                       => "BMI2",
+    Erms                       => "ERMS",
+    Invpcid                    => "INVPCID",
+    Rtm                        => "RTM",
+    Pqm                        => "PQM",
+    FpuCsDsDeprecated          => "FPU_CS_DS_DEPRECATED",
+    Mpx                        => "MPX",
+    Pqe                        => "PQE",
+    Avx512f                    => "AVX512F",
+    Avx512dq                   => "AVX512DQ",
+    Rdseed                     => "RDSEED",
+    Adx                        => "ADX",
+    Smap                       => "SMAP",
+    Avx512Ifma                 => "AVX512_IFMA",
+    Clflushopt                 => "CLFLUSHOPT",
+    Clwb                       => "CLWB",
+    IntelPt                    => "INTEL_PT",
+    Avx512pf                   => "AVX512PF",
+    Avx512er                   => "AVX512ER",
+    Avx512cd                   => "AVX512CD",
+    Sha                        => "SHA",
+    Avx512bw                   => "AVX512BW",
+    Avx512vl                   => "AVX512VL",
+    Prefetchwt1                => "PREFETCHWT1",
+    Avx512Vbmi                 => "AVX512_VBMI",
+    Umip                       => "UMIP",
+    Pku                        => "PKU",
+    Ospke                      => "OSPKE",
+    Waitpkg                    => "WAITPKG",
+    Avx512Vbmi2                => "AVX512_VBMI2",
+    CetSs                      => "CET_SS",
+    Gfni                       => "GFNI",
+    Vaes                       => "VAES",
+    Vpclmulqdq                 => "VPCLMULQDQ",
+    Avx512Vnni                 => "AVX512_VNNI",
+    Avx512Bitalg               => "AVX512_BITALG",
+    TmeEn                      => "TME_EN",
+    Avx512Vpopcntdq            => "AVX512_VPOPCNTDQ",
+    La57                       => "LA57",
+    Rdpid                      => "RDPID",
+    Kl                         => "KL",
+    Cldemote                   => "CLDEMOTE",
+    Movdiri                    => "MOVDIRI",
+    Movdir64b                  => "MOVDIR64B",
+    Enqcmd                     => "ENQCMD",
+    SgxLc                      => "SGX_LC",
+    Pks                        => "PKS",
+    Avx5124vnniw               => "AVX512_4VNNIW",
+    Avx5124fmaps               => "AVX512_4FMAPS",
+    Fsrm                       => "FSRM",
+    Uintr                      => "UINTR",
+    Avx512Vp2intersect         => "AVX512_VP2INTERSECT",
+    SrbdsCtrl                  => "SRBDS_CTRL",
+    MdClear                    => "MD_CLEAR",
+    RtmAlwaysAbort             => "RTM_ALWAYS_ABORT",
+    TsxForceAbort              => "TSX_FORCE_ABORT",
+    Serialize                  => "SERIALIZE",
+    Hybrid                     => "HYBRID",
+    Tsxldtrk                   => "TSXLDTRK",
+    Pconfig                    => "PCONFIG",
+    ArchitecturalLbr           => "ARCHITECTURAL_LBR",
+    CetIbt                     => "CET_IBT",
+    AmxBf16                    => "AMX_BF16",
+    Avx512Fp16                 => "AVX512_FP16",
+    AmxTile                    => "AMX_TILE",
+    AmxInt8                    => "AMX_INT8",
+    IbrsIbpb                   => "IBRS_IBPB",
+    Stibp                      => "STIBP",
+    L1dFlush                   => "L1D_FLUSH",
+    Ia32ArchCapabilities       => "IA32_ARCH_CAPABILITIES",
+    Ia32CoreCapabilities       => "IA32_CORE_CAPABILITIES",
+    Ssbd                       => "SSBD",
+    RaoInt                     => "RAO_INT",
+    AvxVnni                    => "AVX_VNNI",
+    Avx512Bf16                 => "AVX512_BF16",
+    Lass                       => "LASS",
+    Cmpccxadd                  => "CMPCCXADD",
+    Archperfmonext             => "ARCHPERFMONEXT",
+    Fzrm                       => "FZRM",
+    Fsrs                       => "FSRS",
+    Fsrc                       => "FSRC",
+    Fred                       => "FRED",
+    Lkgs                       => "LKGS",
+    Wrmsrns                    => "WRMSRNS",
+    AmxFp16                    => "AMX_FP16",
+    Hreset                     => "HRESET",
+    AvxIfma                    => "AVX_IFMA",
+    Lam                        => "LAM",
+    Msrlist                    => "MSRLIST",
+    Ppin                       => "PPIN",
+    AvxVnniInt8                => "AVX_VNNI_INT8",
+    AvxNeConvert               => "AVX_NE_CONVERT",
+    AmxComplex                 => "AMX_COMPLEX",
+    AvxVnniInt16               => "AVX_VNNI_INT16",
+    Prefetchiti                => "PREFETCHITI",
+    UserMsr                    => "USER_MSR",
+    CetSss                     => "CET_SSS",
+    Avx10                      => "AVX10",
+    ApxF                       => "APX_F",
+    Psfd                       => "PSFD",
+    IpredCtrl                  => "IPRED_CTRL",
+    RrsbaCtrl                  => "RRSBA_CTRL",
+    DdpdU                      => "DDPD_U",
+    BhiCtrl                    => "BHI_CTRL",
+    McdtNo                     => "MCDT_NO",
+    Syscall                    => "SYSCALL",
+    Mp                         => "MP",
+    Nx                         => "NX",
+    Mmxext                     => "MMXEXT",
+    FxsrOpt                    => "FXSR_OPT",
+    Pdpe1gb                    => "PDPE1GB",
+    Rdtscp                     => "RDTSCP",
+    Lm                         => "LM",
+    Amd3dNowExt                => "3DNOWEXT",
+    Amd3dNow                   => "3DNOW",
+    LahfLm                     => "LAHF_LM",
+    CmpLegacy                  => "CMP_LEGACY",
+    Svm                        => "SVM",
+    Extapic                    => "EXTAPIC",
+    Cr8Legacy                  => "CR8_LEGACY",
+    Abm                        => "ABM",
+    Sse4a                      => "SSE4A",
+    Misalignsse                => "MISALIGNSSE",
+    Prefetch3dNow              => "3DNOWPREFETCH",
+    Osvw                       => "OSVW",
+    Ibs                        => "IBS",
+    Xop                        => "XOP",
+    Skinit                     => "SKINIT",
+    Wdt                        => "WDT",
+    Lwp                        => "LWP",
+    Fma4                       => "FMA4",
+    Tce                        => "TCE",
+    NodeidMsr                  => "NODEID_MSR",
+    Tbm                        => "TBM",
+    Topoext                    => "TOPOEXT",
+    PerfctrCore                => "PERFCTR_CORE",
+    PerfctrNb                  => "PERFCTR_NB",
+    Dbx                        => "DBX",
+    Perftsc                    => "PERFTSC",
+    PcxL2i                     => "PCX_L2I",
+    Monitorx                   => "MONITORX",
+    AddrMaskExt                => "ADDR_MASK_EXT",
+    Clzero                     => "CLZERO",
+    Irperf                     => "IRPERF",
+    Xsaveerptr                 => "XSAVEERPTR",
+    Rdpru                      => "RDPRU",
+    Mbe                        => "MBE",
+    Mcommit                    => "MCOMMIT",
+    Wbnoinvd                   => "WBNOINVD",
+    Ibpb                       => "IBPB",
+    IntWbinvd                  => "INT_WBINVD",
+    Ibrs                       => "IBRS",
+    IbrsAlwaysOn               => "IBRS_ALWAYS_ON",
+    StibpAlwaysOn              => "STIBP_ALWAYS_ON",
+    IbrsPreferred              => "IBRS_PREFERRED",
+    IbrsSameMode               => "IBRS_SAME_MODE",
+    NoEferLmsle                => "NO_EFER_LMSLE",
+    VirtSsbd                   => "VIRT_SSBD",
+    SsbNo                      => "SSB_NO",
+    Xsaveopt                   => "XSAVEOPT",
+    Xsavec                     => "XSAVEC",
+    XgetbvEcx1                 => "XGETBV_ECX1",
+    Xsaves                     => "XSAVES",
+    Xfd                        => "XFD",
+    Avx10W128                  => "AVX10_128",
+    Avx10W256                  => "AVX10_256",
+    Avx10W512                  => "AVX10_512",
+    Dtherm                     => "DTHERM",
+    TurboBoost                 => "TURBO_BOOST",
+    Arat                       => "ARAT",
+    Pln                        => "PLN",
+    Ecmd                       => "ECMD",
+    Ptm                        => "PTM",
+    Hwp                        => "HWP",
+    HwpNotification            => "HWP_NOTIFICATION",
+    HwpActivityWindow          => "HWP_ACTIVITY_WINDOW",
+    HwpEnergyPerf              => "HWP_ENERGY_PERF",
+    HwpPackage                 => "HWP_PACKAGE",
+    Hdc                        => "HDC",
+    TurboBoost3                => "TURBO_BOOST_3",
+    HwpCapabilities            => "HWP_CAPABILITIES",
+    HwpPeci                    => "HWP_PECI",
+    HwpFlexible                => "HWP_FLEXIBLE",
+    HwpFastAccess              => "HWP_FAST_ACCESS",
+    HwFeedback                 => "HW_FEEDBACK",
+    IgnoreIdle                 => "IGNORE_IDLE",
+    ThreadDirector             => "THREAD_DIRECTOR",
+    ThermInterrupt             => "THERM_INTERRUPT",
+    HwFeedbackPerf             => "HW_FEEDBACK_PERF",
+    HwFeedbackSize             => "HW_FEEDBACK_SIZE",
+    PerfPref                   => "PERF_PREF",
+    Perfmon                    => "PERFMON",
+    PerfmonCoreCycles          => "PERFMON_CORE_CYCLES",
+    PerfmonInstrRetired        => "PERFMON_INSTR_RETIRED",
+    PerfmonRefCycles           => "PERFMON_REF_CYCLES",
+    PerfmonLlcRef              => "PERFMON_LLC_REF",
+    PerfmonLlcMisses           => "PERFMON_LLC_MISSES",
+    PerfmonBrInstr             => "PERFMON_BR_INSTR",
+    PerfmonBrMispredict        => "PERFMON_BR_MISPREDICT",
+    PerfmonFixedCtr0           => "PERFMON_FIXED_CTR0",
+    PerfmonFixedCtr1           => "PERFMON_FIXED_CTR1",
+    PerfmonFixedCtr2           => "PERFMON_FIXED_CTR2",
+    PerfmonAnythreadDeprecated => "PERFMON_ANYTHREAD_DEPRECATED",
+    RdtL3Monitoring            => "RDT_L3_MONITORING",
+    RdtL2Monitoring            => "RDT_L2_MONITORING",
+    RdtMba                     => "RDT_MBA",
+    Sgx1                       => "SGX1",
+    Sgx2                       => "SGX2",
+    Enclv                      => "ENCLV",
+    Encls                      => "ENCLS",
+    SvmNpt                     => "SVM_NPT",
+    SvmLbrVirt                 => "SVM_LBR_VIRT",
+    SvmLock                    => "SVM_LOCK",
+    SvmNrip                    => "SVM_NRIP",
+    SvmTscRate                 => "SVM_TSC_RATE",
+    SvmVmcbClean               => "SVM_VMCB_CLEAN",
+    SvmFlushByAsid             => "SVM_FLUSH_BY_ASID",
+    SvmDecodeAssists           => "SVM_DECODE_ASSISTS",
+    SvmPauseFilter             => "SVM_PAUSE_FILTER",
+    SvmPauseThreshold          => "SVM_PAUSE_THRESHOLD",
+    SvmAvic                    => "SVM_AVIC",
+    SvmVVmsaveVmload           => "SVM_V_VMSAVE_VMLOAD",
+    SvmVgif                    => "SVM_VGIF",
+    SvmGmet                    => "SVM_GMET",
+    SvmX2avic                  => "SVM_X2AVIC",
+    SvmSsseErr                 => "SVM_SSSE_ERR",
+    SvmSpecCtrl                => "SVM_SPEC_CTRL",
+    SvmRogpt                   => "SVM_ROGPT",
+    SvmHostMceOverride         => "SVM_HOST_MCE_OVERRIDE",
+    SvmInvlpgb                 => "SVM_INVLPGB",
+    SvmVnmi                    => "SVM_VNMI",
+    SvmIbsVirt                 => "SVM_IBS_VIRT",
+    SvmExtLvt                  => "SVM_EXT_LVT",
+    Sme                        => "SME",
+    Sev                        => "SEV",
+    PageFlushMsr               => "PAGE_FLUSH_MSR",
+    SevEs                      => "SEV_ES",
+    SevSnp                     => "SEV_SNP",
+    Vmpl                       => "VMPL",
+    Rmpquery                   => "RMPQUERY",
+    VmplSss                    => "VMPL_SSS",
+    SecureTsc                  => "SECURE_TSC",
+    TscAuxVirt                 => "TSC_AUX_VIRT",
+    HwCacheCoherency           => "HW_CACHE_COHERENCY",
+    Host64Bit                  => "64BIT_HOST",
+    RestInj                    => "REST_INJ",
+    AltInj                     => "ALT_INJ",
+    DebugSwap                  => "DEBUG_SWAP",
+    PreventHostIbs             => "PREVENT_HOST_IBS",
+    Vte                        => "VTE",
+    VmgexitParam               => "VMGEXIT_PARAM",
+    VirtTomMsr                 => "VIRT_TOM_MSR",
+    IbsVirtGif                 => "IBS_VIRT_GIF",
+    VmsaRegProt                => "VMSA_REG_PROT",
+    SmtProtection              => "SMT_PROTECTION",
+    SecureAvic                 => "SECURE_AVIC",
+    NoNestedDataBp             => "NO_NESTED_DATA_BP",
+    FsGsNoSerializing          => "FS_GS_NO_SERIALIZING",
+    LfenceSerializing          => "LFENCE_SERIALIZING",
+    SmmPgCfgLock               => "SMM_PG_CFG_LOCK",
+    NullSelClearsBase          => "NULL_SEL_CLEARS_BASE",
+    Uai                        => "UAI",
+    AutoIbrs                   => "AUTO_IBRS",
+    NoSmmCtlMsr                => "NO_SMM_CTL_MSR",
+    PrefetchCtl                => "PREFETCH_CTL",
+    CpuidDis                   => "CPUID_DIS",
+    Epsf                       => "EPSF",
+    Agpr                       => "AGPR",
+    Fp128                      => "FP128",
+    Movu                       => "MOVU",
+    Fp256                      => "FP256",
+    PadlockRng                 => "PADLOCK_RNG",
+    PadlockRngEn               => "PADLOCK_RNG_EN",
+    PadlockAce                 => "PADLOCK_ACE",
+    PadlockAceEn               => "PADLOCK_ACE_EN",
+    PadlockAce2                => "PADLOCK_ACE2",
+    PadlockAce2En              => "PADLOCK_ACE2_EN",
+    PadlockPhe                 => "PADLOCK_PHE",
+    PadlockPheEn               => "PADLOCK_PHE_EN",
+    PadlockPmm                 => "PADLOCK_PMM",
+    PadlockPmmEn               => "PADLOCK_PMM_EN",
+    TopologyV2                 => "TOPOLOGY_V2",
+    HybridInfo                 => "HYBRID_INFO",
+    PconfigEnum                => "PCONFIG_ENUM",
+    LbrInfo                    => "LBR_INFO",
+    TileInfo                   => "TILE_INFO",
+    TmulInfo                   => "TMUL_INFO",
+    RdtL3Cat                   => "RDT_L3_CAT",
+    RdtL3Cdp                   => "RDT_L3_CDP",
+    RdtL2Cat                   => "RDT_L2_CAT",
+    SgxMiscselect              => "SGX_MISCSELECT",
+    SgxAttributes              => "SGX_ATTRIBUTES",
+    DatEnum                    => "DAT_ENUM",
+}
+
+const WORDS: usize = FeatureId::ALL.len().div_ceil(64);
+
+/// Fixed-size set of `FeatureId`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FeatureBits([u64; WORDS]);
+
+impl FeatureBits {
+    pub const fn new() -> Self {
+        Self([0; WORDS])
+    }
+
+    pub const fn contains(&self, id: FeatureId) -> bool {
+        let bit = id as usize;
+        self.0[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    pub fn insert(&mut self, id: FeatureId) {
+        let bit = id as usize;
+        self.0[bit / 64] |= 1 << (bit % 64);
+    }
+
+    pub fn remove(&mut self, id: FeatureId) {
+        let bit = id as usize;
+        self.0[bit / 64] &= !(1 << (bit % 64));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    /// True if every feature in `other` is also in `self`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.0.iter().zip(other.0).all(|(a, b)| b & !a == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = FeatureId> + '_ {
+        FeatureId::ALL.iter().copied().filter(|id| self.contains(*id))
+    }
+}
+
+impl core::ops::BitAnd for FeatureBits {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self {
+        self &= rhs;
+        self
+    }
+}
+
+impl core::ops::BitAndAssign for FeatureBits {
+    fn bitand_assign(&mut self, rhs: Self) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a &= b;
+        }
+    }
+}
+
+impl core::ops::BitOr for FeatureBits {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a |= b;
+        }
+        self
+    }
+}
+
+impl FromIterator<FeatureId> for FeatureBits {
+    fn from_iter<I: IntoIterator<Item = FeatureId>>(iter: I) -> Self {
+        let mut bits = Self::new();
+        for id in iter {
+            bits.insert(id);
+        }
+        bits
+    }
+}
+
+impl fmt::Display for FeatureId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
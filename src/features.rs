@@ -2,12 +2,20 @@
 //!
 //! Comprehensive detection of x86_64 CPU features and instruction set extensions.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid, Register};
+use crate::feature_flags::FeatureFlags;
+use crate::feature_id::{FeatureBits, FeatureId};
+use crate::os_support::{self, OsSupport};
+use crate::vendor::CpuVendor;
 use bitflags::bitflags;
-use std::fmt;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
     pub struct FeatureSet: u128 {
         // Basic Features (Leaf 1, EDX)
         const FPU       = 1 << 0;
@@ -42,7 +50,28 @@ bitflags! {
     }
 }
 
+/// Serialized as the list of set flag names, e.g. `["FPU", "SSE", "SSE2"]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FeatureSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter_names().map(|(name, _)| name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FeatureSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        names.iter().try_fold(FeatureSet::empty(), |set, name| {
+            FeatureSet::from_name(name)
+                .map(|flag| set | flag)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown feature flag '{}'", name)))
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FeatureCategory {
     Simd,
     Security,
@@ -55,137 +84,1251 @@ pub enum FeatureCategory {
     System,
 }
 
+/// Finer groupings than `FeatureCategory` for UIs; a feature carries any
+/// number of tags, including none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeatureTag {
+    AtomicOps,
+    BitManip,
+    TransactionalMemory,
+    Prefetch,
+    CacheControl,
+    SpeculationControl,
+    /// Fast REP MOVS/STOS/CMPS variants.
+    StringOps,
+    Random,
+    /// Dot-product and reduced-precision instructions used for inference.
+    MachineLearning,
+    /// Low-power waits, serialization and user interrupts.
+    WaitAndSync,
+    Timing,
+}
+
+impl FeatureTag {
+    pub const ALL: [Self; 11] = [
+        Self::AtomicOps,
+        Self::BitManip,
+        Self::TransactionalMemory,
+        Self::Prefetch,
+        Self::CacheControl,
+        Self::SpeculationControl,
+        Self::StringOps,
+        Self::Random,
+        Self::MachineLearning,
+        Self::WaitAndSync,
+        Self::Timing,
+    ];
+
+    /// Names of the features carrying this tag.
+    pub fn members(self) -> &'static [&'static str] {
+        match self {
+            Self::AtomicOps => &[
+                "CMPXCHG16B", "MOVDIRI", "MOVDIR64B", "ENQCMD", "RAO_INT", "CMPCCXADD",
+            ],
+            Self::BitManip => &[
+                "POPCNT", "ABM", "BMI1", "BMI2", "TBM", "ADX", "AVX512_BITALG",
+                "AVX512_VPOPCNTDQ",
+            ],
+            Self::TransactionalMemory => &[
+                "HLE", "RTM", "TSXLDTRK", "RTM_ALWAYS_ABORT", "TSX_FORCE_ABORT",
+            ],
+            Self::Prefetch => &[
+                "3DNOWPREFETCH", "PREFETCHWT1", "PREFETCHITI", "AVX512PF", "PREFETCH_CTL",
+            ],
+            Self::CacheControl => &[
+                "CLFLUSHOPT", "CLWB", "CLDEMOTE", "WBNOINVD", "CLZERO", "INT_WBINVD",
+            ],
+            Self::SpeculationControl => &[
+                "IBRS_IBPB", "STIBP", "SSBD", "L1D_FLUSH", "MD_CLEAR", "IA32_ARCH_CAPABILITIES",
+                "IBPB", "IBRS", "IBRS_ALWAYS_ON", "STIBP_ALWAYS_ON", "IBRS_PREFERRED",
+                "IBRS_SAME_MODE", "VIRT_SSBD", "SSB_NO", "PSFD", "IPRED_CTRL", "RRSBA_CTRL",
+                "DDPD_U", "BHI_CTRL", "MCDT_NO", "SRBDS_CTRL", "AUTO_IBRS",
+            ],
+            Self::StringOps => &["ERMS", "FSRM", "FSRS", "FSRC", "FZRM"],
+            Self::Random => &["RDRAND", "RDSEED", "PADLOCK_RNG"],
+            Self::MachineLearning => &[
+                "AVX512_VNNI", "AVX_VNNI", "AVX512_BF16", "AVX512_FP16", "AMX_TILE", "AMX_INT8",
+                "AMX_BF16", "AMX_FP16", "AMX_COMPLEX", "AVX_VNNI_INT8", "AVX_VNNI_INT16",
+                "AVX_NE_CONVERT", "AVX512_4VNNIW", "AVX512_4FMAPS",
+            ],
+            Self::WaitAndSync => &["MONITOR", "MONITORX", "WAITPKG", "SERIALIZE", "HRESET", "UINTR"],
+            Self::Timing => &["RDTSCP", "TSC-Deadline", "TSC_ADJUST", "RDPID"],
+        }
+    }
+}
+
+/// Selects features for `CpuFeatures::features_by_category`; built from a
+/// `FeatureCategory`, a `FeatureTag` or a `(FeatureCategory, FeatureTag)` pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureFilter {
+    Category(FeatureCategory),
+    Tag(FeatureTag),
+    /// In the category and carrying the tag.
+    Both(FeatureCategory, FeatureTag),
+}
+
+impl FeatureFilter {
+    pub fn matches(&self, feature: &Feature) -> bool {
+        match *self {
+            Self::Category(category) => feature.category == category,
+            Self::Tag(tag) => feature.has_tag(tag),
+            Self::Both(category, tag) => feature.category == category && feature.has_tag(tag),
+        }
+    }
+}
+
+impl From<FeatureCategory> for FeatureFilter {
+    fn from(category: FeatureCategory) -> Self {
+        Self::Category(category)
+    }
+}
+
+impl From<FeatureTag> for FeatureFilter {
+    fn from(tag: FeatureTag) -> Self {
+        Self::Tag(tag)
+    }
+}
+
+impl From<(FeatureCategory, FeatureTag)> for FeatureFilter {
+    fn from((category, tag): (FeatureCategory, FeatureTag)) -> Self {
+        Self::Both(category, tag)
+    }
+}
+
+/// Where in CPUID a feature was decoded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureSource {
+    pub leaf: u32,
+    pub subleaf: u32,
+    /// `None` for features derived from a whole leaf or a multi-bit field
+    /// (leaf presence, version numbers).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub register: Option<Register>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bit: Option<u32>,
+}
+
+impl FeatureSource {
+    /// A feature derived from the leaf as a whole.
+    pub const fn new(leaf: u32, subleaf: u32) -> Self {
+        Self { leaf, subleaf, register: None, bit: None }
+    }
+
+    /// A feature reported by a single register bit.
+    pub const fn bit(leaf: u32, subleaf: u32, register: Register, bit: u32) -> Self {
+        Self { leaf, subleaf, register: Some(register), bit: Some(bit) }
+    }
+
+    /// The same leaf/subleaf without register and bit.
+    pub const fn leaf_only(&self) -> Self {
+        Self::new(self.leaf, self.subleaf)
+    }
+}
+
+impl fmt::Display for FeatureSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "leaf {:#x}, subleaf {}", self.leaf, self.subleaf)?;
+        if let (Some(register), Some(bit)) = (self.register, self.bit) {
+            write!(f, ", {}[{}]", register, bit)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Feature {
     pub name: String,
     pub category: FeatureCategory,
+    /// Static catalog text; not restored when deserializing.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
     pub description: &'static str,
     pub supported: bool,
+    pub source: FeatureSource,
+    /// Deprecated, removed or fused off on current products; see `deprecation_note`.
+    pub deprecated: bool,
+    /// The CPUID bit is set but microcode or firmware keeps the feature from
+    /// working, e.g. RTM with `RTM_ALWAYS_ABORT`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub disabled_by_platform: bool,
+    /// The OS enables the register state this feature needs (XCR0/IA32_XSS).
+    /// Independent of `supported`; true for features without such state.
+    #[cfg_attr(feature = "serde", serde(default = "os_enabled_default"))]
+    pub os_enabled: bool,
 }
 
-#[derive(Debug, Clone)]
+/// Data from before `os_enabled` existed carries no OS information.
+#[cfg(feature = "serde")]
+fn os_enabled_default() -> bool {
+    true
+}
+
+/// What a feature's CPUID bit means in practice; see `Feature::status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeatureStatus {
+    Supported,
+    /// Reported by CPUID but disabled by microcode or firmware.
+    DisabledByPlatform,
+    /// Works, but the vendor has abandoned it; see `Feature::deprecation_note`.
+    Deprecated,
+    Unsupported,
+}
+
+/// ISA extensions vendors have abandoned, with the reason shown to users.
+pub const DEPRECATED_FEATURES: &[(&str, &str)] = &[
+    ("MPX", "Removed from Intel CPUs since Ice Lake; dropped by GCC and Linux"),
+    ("SGX", "Deprecated on Intel client CPUs since 11th gen Core"),
+    ("SGX1", "Deprecated on Intel client CPUs since 11th gen Core"),
+    ("SGX2", "Deprecated on Intel client CPUs since 11th gen Core"),
+    ("SGX_LC", "Deprecated on Intel client CPUs since 11th gen Core"),
+    ("HLE", "TSX HLE is disabled by microcode on all Intel CPUs"),
+    ("RTM", "TSX is disabled by microcode or fused off on many Intel SKUs"),
+    ("3DNOW", "Removed from AMD CPUs since Bulldozer"),
+    ("3DNOWEXT", "Removed from AMD CPUs since Bulldozer"),
+    ("XOP", "Removed from AMD CPUs since Zen"),
+    ("FMA4", "Removed from AMD CPUs since Zen"),
+    ("TBM", "Removed from AMD CPUs since Zen"),
+    ("LWP", "Removed from AMD CPUs since Zen"),
+    ("AVX512PF", "Xeon Phi only; discontinued"),
+    ("AVX512ER", "Xeon Phi only; discontinued"),
+    ("AVX512_4VNNIW", "Xeon Phi only; discontinued"),
+    ("AVX512_4FMAPS", "Xeon Phi only; discontinued"),
+    ("AVX512_VP2INTERSECT", "Deprecated by Intel after Tiger Lake"),
+];
+
+const ALL_VENDORS: &[CpuVendor] = &[
+    CpuVendor::Intel,
+    CpuVendor::Amd,
+    CpuVendor::Hygon,
+    CpuVendor::Zhaoxin,
+    CpuVendor::Centaur,
+];
+const INTEL: &[CpuVendor] = &[CpuVendor::Intel];
+const AMD: &[CpuVendor] = &[CpuVendor::Amd, CpuVendor::Hygon];
+const CENTAUR: &[CpuVendor] = &[CpuVendor::Zhaoxin, CpuVendor::Centaur];
+
+/// Leaves only Intel defines: thermal/power, architectural perfmon, SGX,
+/// Processor Trace, Key Locker, hybrid, PCONFIG, LBR, AMX, V2 topology, AVX10.
+const INTEL_LEAVES: &[u32] = &[0x6, 0xA, 0x12, 0x14, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E, 0x1F, 0x24];
+
+/// Features in shared leaves that only Intel implements. AMD reports its own
+/// speculation controls in leaf 0x8000_0008, so the leaf 7 EDX ones are here.
+const INTEL_ONLY: &[&str] = &[
+    // Leaf 1 ECX
+    "DTES64", "DS-CPL", "VMX", "SMX", "EIST", "TM2", "CNXT-ID", "SDBG", "xTPR", "PDCM", "DCA",
+    "TSC-Deadline",
+    // Leaf 7
+    "SGX", "HLE", "RTM", "FDP_EXCPTN_ONLY", "MPX", "INTEL_PT", "AVX512PF", "AVX512ER",
+    "PREFETCHWT1", "WAITPKG", "TME_EN", "KL", "CLDEMOTE", "MOVDIRI", "MOVDIR64B", "ENQCMD",
+    "SGX_LC", "PKS", "AVX512_4VNNIW", "AVX512_4FMAPS", "UINTR", "SRBDS_CTRL", "MD_CLEAR",
+    "RTM_ALWAYS_ABORT", "TSX_FORCE_ABORT", "SERIALIZE", "HYBRID", "TSXLDTRK", "PCONFIG",
+    "ARCHITECTURAL_LBR", "CET_IBT", "AMX_BF16", "AVX512_FP16", "AMX_TILE", "AMX_INT8",
+    "IBRS_IBPB", "STIBP", "L1D_FLUSH", "IA32_ARCH_CAPABILITIES", "IA32_CORE_CAPABILITIES", "SSBD",
+    // Leaf 7 subleaf 1 and up
+    "RAO_INT", "LASS", "CMPCCXADD", "ARCHPERFMONEXT", "FZRM", "FSRS", "FSRC", "FRED", "LKGS",
+    "WRMSRNS", "AMX_FP16", "HRESET", "AVX_IFMA", "LAM", "MSRLIST", "PPIN", "AVX_VNNI_INT8",
+    "AVX_NE_CONVERT", "AMX_COMPLEX", "AVX_VNNI_INT16", "PREFETCHITI", "USER_MSR", "CET_SSS",
+    "AVX10", "APX_F", "PSFD", "IPRED_CTRL", "RRSBA_CTRL", "DDPD_U", "BHI_CTRL", "MCDT_NO",
+    "AVX10_128", "AVX10_256", "AVX10_512",
+    // Leaf 0xD
+    "XFD",
+];
+
+/// Features in AMD-defined extended leaves that Intel implements too.
+const EXTENDED_SHARED: &[&str] = &[
+    "SYSCALL", "NX", "PDPE1GB", "RDTSCP", "LM", "LAHF_LM", "ABM", "3DNOWPREFETCH", "WBNOINVD",
+];
+
+/// Feature names and their rustc `target_feature` / `is_x86_feature_detected!`
+/// spelling. Features without a rustc equivalent are absent.
+pub const RUST_TARGET_FEATURES: &[(&str, &str)] = &[
+    ("FXSR", "fxsr"),
+    ("SSE", "sse"),
+    ("SSE2", "sse2"),
+    ("SSE3", "sse3"),
+    ("SSSE3", "ssse3"),
+    ("SSE4.1", "sse4.1"),
+    ("SSE4.2", "sse4.2"),
+    ("SSE4A", "sse4a"),
+    ("PCLMULQDQ", "pclmulqdq"),
+    ("CMPXCHG16B", "cmpxchg16b"),
+    ("MOVBE", "movbe"),
+    ("POPCNT", "popcnt"),
+    ("ABM", "lzcnt"),
+    ("LAHF_LM", "lahfsahf"),
+    ("3DNOWPREFETCH", "prfchw"),
+    ("AES", "aes"),
+    ("SHA", "sha"),
+    ("GFNI", "gfni"),
+    ("VAES", "vaes"),
+    ("VPCLMULQDQ", "vpclmulqdq"),
+    ("KL", "kl"),
+    ("RDRAND", "rdrand"),
+    ("RDSEED", "rdseed"),
+    ("XSAVE", "xsave"),
+    ("XSAVEOPT", "xsaveopt"),
+    ("XSAVEC", "xsavec"),
+    ("XSAVES", "xsaves"),
+    ("BMI1", "bmi1"),
+    ("BMI2", "bmi2"),
+    ("ADX", "adx"),
+    ("TBM", "tbm"),
+    ("ERMS", "ermsb"),
+    ("RTM", "rtm"),
+    ("AVX", "avx"),
+    ("AVX2", "avx2"),
+    ("FMA", "fma"),
+    ("F16C", "f16c"),
+    ("AVX_VNNI", "avxvnni"),
+    ("AVX_IFMA", "avxifma"),
+    ("AVX_NE_CONVERT", "avxneconvert"),
+    ("AVX_VNNI_INT8", "avxvnniint8"),
+    ("AVX_VNNI_INT16", "avxvnniint16"),
+    ("AVX512F", "avx512f"),
+    ("AVX512DQ", "avx512dq"),
+    ("AVX512CD", "avx512cd"),
+    ("AVX512BW", "avx512bw"),
+    ("AVX512VL", "avx512vl"),
+    ("AVX512_IFMA", "avx512ifma"),
+    ("AVX512_VBMI", "avx512vbmi"),
+    ("AVX512_VBMI2", "avx512vbmi2"),
+    ("AVX512_VNNI", "avx512vnni"),
+    ("AVX512_BITALG", "avx512bitalg"),
+    ("AVX512_VPOPCNTDQ", "avx512vpopcntdq"),
+    ("AVX512_VP2INTERSECT", "avx512vp2intersect"),
+    ("AVX512_BF16", "avx512bf16"),
+    ("AVX512_FP16", "avx512fp16"),
+    ("AVX10_V1", "avx10.1"),
+    ("AVX10_V2", "avx10.2"),
+    ("AMX_TILE", "amx-tile"),
+    ("AMX_INT8", "amx-int8"),
+    ("AMX_BF16", "amx-bf16"),
+    ("AMX_FP16", "amx-fp16"),
+    ("AMX_COMPLEX", "amx-complex"),
+    ("APX_F", "apxf"),
+];
+
+/// Feature names and the flag Linux prints in `/proc/cpuinfo`. Features the
+/// kernel hides, or that this crate derives from enumeration leaves, are absent.
+pub const LINUX_FLAGS: &[(&str, &str)] = &[
+    // Leaf 1 EDX
+    ("FPU", "fpu"), ("VME", "vme"), ("DE", "de"), ("PSE", "pse"), ("TSC", "tsc"), ("MSR", "msr"),
+    ("PAE", "pae"), ("MCE", "mce"), ("CX8", "cx8"), ("APIC", "apic"), ("SEP", "sep"),
+    ("MTRR", "mtrr"), ("PGE", "pge"), ("MCA", "mca"), ("CMOV", "cmov"), ("PAT", "pat"),
+    ("PSE36", "pse36"), ("PSN", "pn"), ("CLFSH", "clflush"), ("DS", "dts"), ("ACPI", "acpi"),
+    ("MMX", "mmx"), ("FXSR", "fxsr"), ("SSE", "sse"), ("SSE2", "sse2"), ("SS", "ss"), ("HTT", "ht"),
+    ("TM", "tm"), ("PBE", "pbe"),
+    // Leaf 1 ECX
+    ("SSE3", "pni"), ("PCLMULQDQ", "pclmulqdq"), ("DTES64", "dtes64"), ("MONITOR", "monitor"),
+    ("DS-CPL", "ds_cpl"), ("VMX", "vmx"), ("SMX", "smx"), ("EIST", "est"), ("TM2", "tm2"),
+    ("SSSE3", "ssse3"), ("CNXT-ID", "cid"), ("SDBG", "sdbg"), ("FMA", "fma"),
+    ("CMPXCHG16B", "cx16"), ("xTPR", "xtpr"), ("PDCM", "pdcm"), ("PCID", "pcid"), ("DCA", "dca"),
+    ("SSE4.1", "sse4_1"), ("SSE4.2", "sse4_2"), ("x2APIC", "x2apic"), ("MOVBE", "movbe"),
+    ("POPCNT", "popcnt"), ("TSC-Deadline", "tsc_deadline_timer"), ("AES", "aes"),
+    ("XSAVE", "xsave"), ("AVX", "avx"), ("F16C", "f16c"), ("RDRAND", "rdrand"),
+    ("HYPERVISOR", "hypervisor"),
+    // Leaf 7
+    ("FSGSBASE", "fsgsbase"), ("TSC_ADJUST", "tsc_adjust"), ("SGX", "sgx"), ("BMI1", "bmi1"),
+    ("HLE", "hle"), ("AVX2", "avx2"), ("FDP_EXCPTN_ONLY", "fdp_excptn_only"), ("SMEP", "smep"),
+    ("BMI2", "bmi2"), ("ERMS", "erms"), ("INVPCID", "invpcid"), ("RTM", "rtm"), ("PQM", "cqm"),
+    ("MPX", "mpx"), ("PQE", "rdt_a"), ("AVX512F", "avx512f"), ("AVX512DQ", "avx512dq"),
+    ("RDSEED", "rdseed"), ("ADX", "adx"), ("SMAP", "smap"), ("AVX512_IFMA", "avx512ifma"),
+    ("CLFLUSHOPT", "clflushopt"), ("CLWB", "clwb"), ("INTEL_PT", "intel_pt"),
+    ("AVX512PF", "avx512pf"), ("AVX512ER", "avx512er"), ("AVX512CD", "avx512cd"), ("SHA", "sha_ni"),
+    ("AVX512BW", "avx512bw"), ("AVX512VL", "avx512vl"), ("AVX512_VBMI", "avx512vbmi"),
+    ("UMIP", "umip"), ("PKU", "pku"), ("OSPKE", "ospke"), ("WAITPKG", "waitpkg"),
+    ("AVX512_VBMI2", "avx512_vbmi2"), ("CET_SS", "user_shstk"), ("GFNI", "gfni"), ("VAES", "vaes"),
+    ("VPCLMULQDQ", "vpclmulqdq"), ("AVX512_VNNI", "avx512_vnni"),
+    ("AVX512_BITALG", "avx512_bitalg"), ("TME_EN", "tme"), ("AVX512_VPOPCNTDQ", "avx512_vpopcntdq"),
+    ("LA57", "la57"), ("RDPID", "rdpid"), ("CLDEMOTE", "cldemote"), ("MOVDIRI", "movdiri"),
+    ("MOVDIR64B", "movdir64b"), ("ENQCMD", "enqcmd"), ("SGX_LC", "sgx_lc"),
+    ("AVX512_4VNNIW", "avx512_4vnniw"), ("AVX512_4FMAPS", "avx512_4fmaps"), ("FSRM", "fsrm"),
+    ("AVX512_VP2INTERSECT", "avx512_vp2intersect"), ("SRBDS_CTRL", "srbds_ctrl"),
+    ("MD_CLEAR", "md_clear"), ("RTM_ALWAYS_ABORT", "rtm_always_abort"),
+    ("TSX_FORCE_ABORT", "tsx_force_abort"), ("SERIALIZE", "serialize"), ("HYBRID", "hybrid_cpu"),
+    ("TSXLDTRK", "tsxldtrk"), ("PCONFIG", "pconfig"), ("ARCHITECTURAL_LBR", "arch_lbr"),
+    ("CET_IBT", "ibt"), ("AMX_BF16", "amx_bf16"), ("AVX512_FP16", "avx512_fp16"),
+    ("AMX_TILE", "amx_tile"), ("AMX_INT8", "amx_int8"), ("L1D_FLUSH", "flush_l1d"),
+    ("IA32_ARCH_CAPABILITIES", "arch_capabilities"), ("AVX_VNNI", "avx_vnni"),
+    ("AVX512_BF16", "avx512_bf16"), ("LASS", "lass"), ("CMPCCXADD", "cmpccxadd"), ("FZRM", "fzrm"),
+    ("FSRS", "fsrs"), ("FSRC", "fsrc"), ("FRED", "fred"), ("LKGS", "lkgs"), ("WRMSRNS", "wrmsrns"),
+    ("AMX_FP16", "amx_fp16"), ("AVX_IFMA", "avx_ifma"), ("LAM", "lam"), ("PPIN", "intel_ppin"),
+    // Leaf 0xD subleaf 1
+    ("XSAVEOPT", "xsaveopt"), ("XSAVEC", "xsavec"), ("XGETBV_ECX1", "xgetbv1"),
+    ("XSAVES", "xsaves"), ("XFD", "xfd"),
+    // Leaf 6
+    ("DTHERM", "dtherm"), ("TURBO_BOOST", "ida"), ("ARAT", "arat"), ("PLN", "pln"), ("PTM", "pts"),
+    ("HWP", "hwp"), ("HWP_NOTIFICATION", "hwp_notify"), ("HWP_ACTIVITY_WINDOW", "hwp_act_window"),
+    ("HWP_ENERGY_PERF", "hwp_epp"), ("HWP_PACKAGE", "hwp_pkg_req"), ("HW_FEEDBACK", "hfi"),
+    // Leaf 0x8000_0001
+    ("SYSCALL", "syscall"), ("MP", "mp"), ("NX", "nx"), ("MMXEXT", "mmxext"),
+    ("FXSR_OPT", "fxsr_opt"), ("PDPE1GB", "pdpe1gb"), ("RDTSCP", "rdtscp"), ("LM", "lm"),
+    ("3DNOWEXT", "3dnowext"), ("3DNOW", "3dnow"), ("LAHF_LM", "lahf_lm"),
+    ("CMP_LEGACY", "cmp_legacy"), ("SVM", "svm"), ("EXTAPIC", "extapic"),
+    ("CR8_LEGACY", "cr8_legacy"), ("ABM", "abm"), ("SSE4A", "sse4a"),
+    ("MISALIGNSSE", "misalignsse"), ("3DNOWPREFETCH", "3dnowprefetch"), ("OSVW", "osvw"),
+    ("IBS", "ibs"), ("XOP", "xop"), ("SKINIT", "skinit"), ("WDT", "wdt"), ("LWP", "lwp"),
+    ("FMA4", "fma4"), ("TCE", "tce"), ("NODEID_MSR", "nodeid_msr"), ("TBM", "tbm"),
+    ("TOPOEXT", "topoext"), ("PERFCTR_CORE", "perfctr_core"), ("PERFCTR_NB", "perfctr_nb"),
+    ("DBX", "bpext"), ("PERFTSC", "ptsc"), ("PCX_L2I", "perfctr_llc"), ("MONITORX", "mwaitx"),
+    // Leaf 0x8000_0008
+    ("CLZERO", "clzero"), ("IRPERF", "irperf"), ("XSAVEERPTR", "xsaveerptr"), ("RDPRU", "rdpru"),
+    ("WBNOINVD", "wbnoinvd"), ("VIRT_SSBD", "virt_ssbd"),
+    // AMD SVM and memory encryption
+    ("SVM_NPT", "npt"), ("SVM_LBR_VIRT", "lbrv"), ("SVM_LOCK", "svm_lock"),
+    ("SVM_NRIP", "nrip_save"), ("SVM_TSC_RATE", "tsc_scale"), ("SVM_VMCB_CLEAN", "vmcb_clean"),
+    ("SVM_FLUSH_BY_ASID", "flushbyasid"), ("SVM_DECODE_ASSISTS", "decodeassists"),
+    ("SVM_PAUSE_FILTER", "pausefilter"), ("SVM_PAUSE_THRESHOLD", "pfthreshold"),
+    ("SVM_AVIC", "avic"), ("SVM_V_VMSAVE_VMLOAD", "v_vmsave_vmload"), ("SVM_VGIF", "vgif"),
+    ("SVM_X2AVIC", "x2avic"), ("SVM_SPEC_CTRL", "v_spec_ctrl"), ("SVM_VNMI", "vnmi"),
+    ("SME", "sme"), ("SEV", "sev"), ("SEV_ES", "sev_es"), ("SEV_SNP", "sev_snp"),
+    // Leaf 0xC000_0001
+    ("PADLOCK_RNG", "rng"), ("PADLOCK_RNG_EN", "rng_en"), ("PADLOCK_ACE", "ace"),
+    ("PADLOCK_ACE_EN", "ace_en"), ("PADLOCK_ACE2", "ace2"), ("PADLOCK_ACE2_EN", "ace2_en"),
+    ("PADLOCK_PHE", "phe"), ("PADLOCK_PHE_EN", "phe_en"), ("PADLOCK_PMM", "pmm"),
+    ("PADLOCK_PMM_EN", "pmm_en"),
+];
+
+/// Feature names and other spellings seen in GCC `-m` options, Windows
+/// `IsProcessorFeaturePresent` constants and vendor documentation. Linux flags
+/// and Rust target features resolve through their own tables.
+pub const FEATURE_ALIASES: &[(&str, &str)] = &[
+    // GCC
+    ("PCLMULQDQ", "pclmul"), ("CMPXCHG16B", "cx16"), ("LAHF_LM", "sahf"), ("RDRAND", "rdrnd"),
+    ("BMI1", "bmi"), ("CET_SS", "shstk"), ("KL", "keylocker"),
+    // Windows
+    ("MMX", "PF_MMX_INSTRUCTIONS_AVAILABLE"), ("SSE", "PF_XMMI_INSTRUCTIONS_AVAILABLE"),
+    ("SSE2", "PF_XMMI64_INSTRUCTIONS_AVAILABLE"), ("SSE3", "PF_SSE3_INSTRUCTIONS_AVAILABLE"),
+    ("SSSE3", "PF_SSSE3_INSTRUCTIONS_AVAILABLE"), ("SSE4.1", "PF_SSE4_1_INSTRUCTIONS_AVAILABLE"),
+    ("SSE4.2", "PF_SSE4_2_INSTRUCTIONS_AVAILABLE"), ("AVX", "PF_AVX_INSTRUCTIONS_AVAILABLE"),
+    ("AVX2", "PF_AVX2_INSTRUCTIONS_AVAILABLE"), ("AVX512F", "PF_AVX512F_INSTRUCTIONS_AVAILABLE"),
+    ("3DNOW", "PF_3DNOW_INSTRUCTIONS_AVAILABLE"), ("TSC", "PF_RDTSC_INSTRUCTION_AVAILABLE"),
+    ("RDTSCP", "PF_RDTSCP_INSTRUCTION_AVAILABLE"), ("RDRAND", "PF_RDRAND_INSTRUCTION_AVAILABLE"),
+    ("PAE", "PF_PAE_ENABLED"), ("NX", "PF_NX_ENABLED"), ("CMPXCHG16B", "PF_COMPARE_EXCHANGE128"),
+    ("OSXSAVE", "PF_XSAVE_ENABLED"),
+    // Vendor and marketing names
+    ("FPU", "x87"), ("AES", "AES-NI"), ("SHA", "SHA-NI"), ("ABM", "LZCNT"), ("CLFSH", "CLFLUSH"),
+    ("HTT", "Hyper-Threading"), ("EIST", "EST"), ("VMX", "VT-x"), ("SVM", "AMD-V"),
+    ("LM", "x86-64"), ("LM", "AMD64"), ("LM", "EM64T"), ("NX", "XD"), ("RTM", "TSX"),
+    ("PADLOCK_RNG", "XSTORE"), ("PADLOCK_ACE", "XCRYPT"),
+];
+
+impl Feature {
+    /// Flag name in the Linux `/proc/cpuinfo` `flags` line, if the kernel exports one.
+    pub fn linux_flag(&self) -> Option<&'static str> {
+        linux_flag(&self.name)
+    }
+
+    /// Crate name for `name` in any spelling `CpuFeatures::has_feature` accepts:
+    /// the crate's own, a Linux flag, a Rust target feature or a
+    /// `FEATURE_ALIASES` entry. Case, `_`, `-` and `.` are ignored, so
+    /// `"sse4_2"`, `"sse42"` and `"SSE4.2"` all give `"SSE4.2"`.
+    pub fn canonical_name(name: &str) -> Option<&'static str> {
+        let tables = [LINUX_FLAGS, RUST_TARGET_FEATURES, FEATURE_ALIASES];
+        let entries = || tables.into_iter().flatten();
+
+        // Crate names win over aliases that normalize the same way
+        FeatureId::ALL
+            .iter()
+            .map(|id| id.name())
+            .chain(entries().map(|(feature, _)| *feature))
+            .find(|feature| names_match(feature, name))
+            .or_else(|| {
+                entries()
+                    .find(|(_, alias)| names_match(alias, name))
+                    .map(|(feature, _)| *feature)
+            })
+    }
+
+    /// Name for `#[target_feature(enable = ...)]`, `-C target-feature` and
+    /// `is_x86_feature_detected!`, if rustc knows this feature.
+    pub fn rust_target_feature(&self) -> Option<&'static str> {
+        rust_target_feature(&self.name)
+    }
+
+    /// Vendors with CPUs that can report this feature, judged from where
+    /// CPUID reports it. Hygon follows AMD; Zhaoxin and VIA get the shared
+    /// features plus their own 0xC000_0000 range.
+    pub fn vendors(&self) -> &'static [CpuVendor] {
+        let name = self.name.as_str();
+        if self.source.leaf >= 0xC000_0000 {
+            CENTAUR
+        } else if self.source.leaf >= 0x8000_0000 {
+            if EXTENDED_SHARED.contains(&name) { ALL_VENDORS } else { AMD }
+        } else if (INTEL_LEAVES.contains(&self.source.leaf) && name != "ARAT")
+            || INTEL_ONLY.contains(&name)
+            || name.starts_with("AVX10_V")
+        {
+            INTEL
+        } else {
+            ALL_VENDORS
+        }
+    }
+
+    /// True if `vendor` implements this feature on some CPU; every feature
+    /// applies to vendors outside the tables, such as `Unknown`, `Other` and
+    /// the historic ones.
+    pub fn applies_to(&self, vendor: &CpuVendor) -> bool {
+        !ALL_VENDORS.contains(vendor) || self.vendors().contains(vendor)
+    }
+
+    pub fn tags(&self) -> Vec<FeatureTag> {
+        FeatureTag::ALL.into_iter().filter(|tag| self.has_tag(*tag)).collect()
+    }
+
+    pub fn has_tag(&self, tag: FeatureTag) -> bool {
+        tag.members().contains(&self.name.as_str())
+    }
+
+    /// Interpreted support: a set bit the platform disables is not `Supported`.
+    /// OS register state is separate; see `os_enabled`.
+    pub fn status(&self) -> FeatureStatus {
+        if !self.supported {
+            FeatureStatus::Unsupported
+        } else if self.disabled_by_platform {
+            FeatureStatus::DisabledByPlatform
+        } else if self.deprecated {
+            FeatureStatus::Deprecated
+        } else {
+            FeatureStatus::Supported
+        }
+    }
+
+    /// Why this feature should not be targeted by new code, if it is deprecated.
+    pub fn deprecation_note(&self) -> Option<&'static str> {
+        DEPRECATED_FEATURES
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map(|(_, note)| *note)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VectorWidth {
+    W128,
+    W256,
+    W512,
+}
+
+impl VectorWidth {
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::W128 => 128,
+            Self::W256 => 256,
+            Self::W512 => 512,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Avx10Info {
+    pub version: u32,
+    pub supports_128: bool,
+    pub supports_256: bool,
+    pub supports_512: bool,
+}
+
+impl Avx10Info {
+    pub fn supports_width(&self, width: VectorWidth) -> bool {
+        match width {
+            VectorWidth::W128 => self.supports_128,
+            VectorWidth::W256 => self.supports_256,
+            VectorWidth::W512 => self.supports_512,
+        }
+    }
+
+    pub fn max_width(&self) -> Option<VectorWidth> {
+        [VectorWidth::W512, VectorWidth::W256, VectorWidth::W128]
+            .into_iter()
+            .find(|w| self.supports_width(*w))
+    }
+
+    /// Widest supported vector length in bits; 0 if leaf 0x24 reports none.
+    pub fn max_vector_bits(&self) -> u32 {
+        self.max_width().map_or(0, |width| width.bits())
+    }
+}
+
+/// One AMX tile palette from CPUID leaf 0x1D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmxPalette {
+    /// Palette number for `TILECFG.palette_id`.
+    pub id: u32,
+    /// Bytes of tile storage across all tile registers.
+    pub total_tile_bytes: u32,
+    pub bytes_per_tile: u32,
+    pub bytes_per_row: u32,
+    /// Number of tile registers.
+    pub tile_registers: u32,
+    pub max_rows: u32,
+}
+
+/// AMX tile palettes (leaf 0x1D) and TMUL limits (leaf 0x1E), for sizing
+/// tile configurations.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmxInfo {
+    /// Palettes 1 and up; palette 0 is the initialized (tiles off) state.
+    pub palettes: Vec<AmxPalette>,
+    /// Maximum rows or columns (K) a TMUL instruction handles.
+    pub tmul_max_k: u32,
+    /// Maximum column bytes (N) a TMUL instruction handles.
+    pub tmul_max_n: u32,
+}
+
+impl AmxInfo {
+    pub fn palette(&self, id: u32) -> Option<&AmxPalette> {
+        self.palettes.iter().find(|p| p.id == id)
+    }
+}
+
+/// Intel Processor Trace capabilities from CPUID leaf 0x14.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProcessorTraceInfo {
+    pub cr3_filtering: bool,
+    /// Configurable PSB frequency and cycle-accurate mode.
+    pub configurable_psb: bool,
+    /// IP filtering, TraceStop and preserved trace state across warm reset.
+    pub ip_filtering: bool,
+    pub mtc: bool,
+    pub ptwrite: bool,
+    pub power_event_trace: bool,
+    /// Table of Physical Addresses output.
+    pub topa: bool,
+    /// ToPA tables may hold more than one output entry.
+    pub topa_multiple_entries: bool,
+    pub single_range_output: bool,
+    pub transport_output: bool,
+    /// IP payloads are linear addresses (CS base included) rather than RIP offsets.
+    pub lip: bool,
+    /// Configurable address ranges for IP filtering.
+    pub address_ranges: u32,
+    /// Bitmap of supported `IA32_RTIT_CTL.MTCFreq` encodings.
+    pub mtc_periods: u16,
+    /// Bitmap of supported `IA32_RTIT_CTL.CycThresh` encodings.
+    pub cycle_thresholds: u16,
+    /// Bitmap of supported `IA32_RTIT_CTL.PSBFreq` encodings.
+    pub psb_frequencies: u16,
+}
+
+/// AMD Instruction-Based Sampling capabilities from CPUID leaf 0x8000_001B.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IbsInfo {
+    pub fetch_sampling: bool,
+    pub op_sampling: bool,
+    /// Read/write of the op counter.
+    pub rd_wr_op_count: bool,
+    /// Op counting mode (dispatched ops instead of cycles).
+    pub op_counting: bool,
+    pub branch_target_address: bool,
+    /// 27-bit op counter and max count.
+    pub op_count_extended: bool,
+    pub rip_invalid_check: bool,
+    pub op_branch_fusion: bool,
+    pub fetch_control_extended: bool,
+    pub op_data4: bool,
+    pub l3_miss_filtering: bool,
+}
+
+/// Intel Key Locker capabilities from CPUID leaf 0x19.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyLockerInfo {
+    /// Handles may be restricted to CPL0.
+    pub cpl0_restriction: bool,
+    pub no_encrypt_restriction: bool,
+    pub no_decrypt_restriction: bool,
+    /// AESKLE: the OS enabled the AES Key Locker instructions.
+    pub aeskle: bool,
+    /// Wide (8-block) Key Locker instructions.
+    pub wide: bool,
+    /// IWKEY backup and restore MSRs.
+    pub iwkey_backup: bool,
+    /// LOADIWKEY accepts NoBackup.
+    pub no_backup: bool,
+    /// LOADIWKEY can randomize the IWKEY (KeySource 1).
+    pub random_iwkey: bool,
+}
+
+/// Highest SIMD instruction set level, ordered from oldest to newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimdLevel {
+    #[default]
+    None,
+    Sse,
+    Sse2,
+    Sse3,
+    Ssse3,
+    Sse41,
+    Sse42,
+    Avx,
+    Avx2,
+    Avx512,
+    Avx10 { version: u32, width: VectorWidth },
+}
+
+impl fmt::Display for SimdLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Sse => write!(f, "SSE"),
+            Self::Sse2 => write!(f, "SSE2"),
+            Self::Sse3 => write!(f, "SSE3"),
+            Self::Ssse3 => write!(f, "SSSE3"),
+            Self::Sse41 => write!(f, "SSE4.1"),
+            Self::Sse42 => write!(f, "SSE4.2"),
+            Self::Avx => write!(f, "AVX"),
+            Self::Avx2 => write!(f, "AVX2"),
+            Self::Avx512 => write!(f, "AVX-512"),
+            Self::Avx10 { version, width } => write!(f, "AVX10.{}/{}", version, width.bits()),
+        }
+    }
+}
+
+/// Vector capability with the legacy AVX-512 flags and AVX10 reconciled, as
+/// returned by `CpuFeatures::effective_simd_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EffectiveSimd {
+    pub level: SimdLevel,
+    /// Widest usable vector register in bits; 0 without SSE.
+    pub vector_bits: u32,
+    /// VNNI dot products at `vector_bits` (AVX512_VNNI, AVX_VNNI or AVX10).
+    pub vnni: bool,
+    /// BF16 arithmetic at `vector_bits` (AVX512_BF16 or AVX10).
+    pub bf16: bool,
+    /// FP16 arithmetic at `vector_bits` (AVX512_FP16 or AVX10).
+    pub fp16: bool,
+}
+
+impl fmt::Display for EffectiveSimd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.vector_bits == 0 {
+            return write!(f, "None");
+        }
+        write!(f, "{}-bit ({})", self.vector_bits, self.level)?;
+        let extensions: Vec<&str> = [(self.vnni, "VNNI"), (self.bf16, "BF16"), (self.fp16, "FP16")]
+            .into_iter()
+            .filter_map(|(on, name)| on.then_some(name))
+            .collect();
+        if !extensions.is_empty() {
+            write!(f, " with {}", extensions.join("/"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Widest vector at which each crypto extension is usable, OS register state
+/// included, as returned by `CpuFeatures::crypto_capabilities`. Narrower
+/// widths are usable too; `None` means not at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CryptoCaps {
+    /// AES rounds: 128-bit with AES-NI, 256/512-bit with VAES.
+    pub aes: Option<VectorWidth>,
+    /// Carry-less multiply: 128-bit with PCLMULQDQ, 256/512-bit with VPCLMULQDQ.
+    pub clmul: Option<VectorWidth>,
+    /// Galois field instructions: legacy SSE forms at 128 bits, VEX at 256, EVEX at 512.
+    pub gfni: Option<VectorWidth>,
+    /// SHA-1 and SHA-256 instructions, 128-bit only.
+    pub sha: bool,
+    /// EVEX encodings below 512 bits (AVX512VL or AVX10), giving 128/256-bit
+    /// code 32 vector registers and masking.
+    pub evex: bool,
+}
+
+impl fmt::Display for CryptoCaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        for (name, width) in [("AES", self.aes), ("CLMUL", self.clmul), ("GFNI", self.gfni)] {
+            if let Some(width) = width {
+                parts.push(format!("{} {}-bit", name, width.bits()));
+            }
+        }
+        if self.sha {
+            parts.push("SHA".to_string());
+        }
+        if parts.is_empty() {
+            return write!(f, "None");
+        }
+        write!(f, "{}", parts.join(", "))?;
+        if self.evex {
+            write!(f, " (EVEX)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "RawCpuFeatures"))]
 pub struct CpuFeatures {
     pub basic: FeatureSet,
     pub all_features: Vec<Feature>,
+    pub avx10: Option<Avx10Info>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub amx: Option<AmxInfo>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub processor_trace: Option<ProcessorTraceInfo>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ibs: Option<IbsInfo>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key_locker: Option<KeyLockerInfo>,
+    /// XCR0/IA32_XSS of the local OS; `None` for data that did not come from this machine.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub os_support: Option<OsSupport>,
+    /// Supported `basic` and `all_features` entries; call `reindex` after editing those.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ids: FeatureBits,
+}
+
+/// Serialized form of `CpuFeatures`; the index is rebuilt on load.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawCpuFeatures {
+    basic: FeatureSet,
+    all_features: Vec<Feature>,
+    avx10: Option<Avx10Info>,
+    #[serde(default)]
+    amx: Option<AmxInfo>,
+    #[serde(default)]
+    processor_trace: Option<ProcessorTraceInfo>,
+    #[serde(default)]
+    ibs: Option<IbsInfo>,
+    #[serde(default)]
+    key_locker: Option<KeyLockerInfo>,
+    #[serde(default)]
+    os_support: Option<OsSupport>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RawCpuFeatures> for CpuFeatures {
+    fn from(raw: RawCpuFeatures) -> Self {
+        Self {
+            amx: raw.amx,
+            processor_trace: raw.processor_trace,
+            ibs: raw.ibs,
+            key_locker: raw.key_locker,
+            os_support: raw.os_support,
+            ..Self::new(raw.basic, raw.all_features, raw.avx10)
+        }
+    }
 }
 
 impl CpuFeatures {
+    /// CPUID features annotated with the OS-enabled register state of this machine.
     pub fn detect() -> Self {
+        let mut features = Self::detect_with(&HardwareCpuid);
+        features.apply_os_support(OsSupport::detect());
+        features
+    }
+
+    /// CPUID-derived features only; `os_enabled` stays true and `os_support` empty.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "features", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
         let mut basic = FeatureSet::empty();
         let mut all_features = Vec::new();
+        let mut avx10 = None;
 
         // Leaf 1: Basic features
-        if is_leaf_supported(1) {
-            let result = cpuid(1, 0);
+        if src.is_leaf_supported(1) {
+            let result = src.cpuid(1, 0);
             detect_leaf1_edx(result.edx, &mut basic);
             detect_leaf1_ecx(result.ecx, &mut all_features);
         }
 
         // Leaf 7: Structured extended features
-        if is_leaf_supported(7) {
-            detect_leaf7(&mut all_features);
+        if src.is_leaf_supported(7) {
+            detect_leaf7(src, &mut all_features);
         }
 
         // Leaf 7 subleaf 1
-        if is_leaf_supported(7) {
-            detect_leaf7_sub1(&mut all_features);
+        if src.is_leaf_supported(7) {
+            detect_leaf7_sub1(src, &mut all_features);
         }
 
         // Leaf 7 subleaf 2
-        if is_leaf_supported(7) {
-            detect_leaf7_sub2(&mut all_features);
+        if src.is_leaf_supported(7) {
+            detect_leaf7_sub2(src, &mut all_features);
         }
 
         // Leaf 7 subleaf 3
-        if is_leaf_supported(7) {
-            detect_leaf7_sub3(&mut all_features);
+        if src.is_leaf_supported(7) {
+            detect_leaf7_sub3(src, &mut all_features);
         }
 
         // Leaf 6: Thermal and Power Management
-        if is_leaf_supported(6) {
-            detect_thermal_power(&mut all_features);
+        if src.is_leaf_supported(6) {
+            detect_thermal_power(src, &mut all_features);
         }
 
         // Leaf 0xA: Performance Monitoring
-        if is_leaf_supported(0xA) {
-            detect_perfmon(&mut all_features);
+        if src.is_leaf_supported(0xA) {
+            detect_perfmon(src, &mut all_features);
         }
 
         // Leaf 0x10: Resource Director Technology
-        if is_leaf_supported(0x10) {
-            detect_rdt(&mut all_features);
+        if src.is_leaf_supported(0x10) {
+            detect_rdt(src, &mut all_features);
         }
 
         // Leaf 0x12: SGX Extended
-        if is_leaf_supported(0x12) {
-            detect_sgx_extended(&mut all_features);
+        if src.is_leaf_supported(0x12) {
+            detect_sgx_extended(src, &mut all_features);
         }
 
         // Leaf 0x18: Deterministic Address Translation
-        if is_leaf_supported(0x18) {
-            detect_address_translation(&mut all_features);
+        if src.is_leaf_supported(0x18) {
+            detect_address_translation(src, &mut all_features);
         }
 
         // Leaf 0x24: AVX10
-        if is_leaf_supported(0x24) {
-            detect_avx10(&mut all_features);
+        if src.is_leaf_supported(0x24) {
+            avx10 = detect_avx10(src, &mut all_features);
         }
 
         // Extended leaves: Additional AMD/Intel features
-        if is_leaf_supported(0x8000_0001) {
-            detect_extended_features(&mut all_features);
+        if src.is_leaf_supported(0x8000_0001) {
+            detect_extended_features(src, &mut all_features);
         }
 
         // AMD Extended Features
-        if is_leaf_supported(0x8000_0008) {
-            detect_amd_extended(&mut all_features);
+        if src.is_leaf_supported(0x8000_0008) {
+            detect_amd_extended(src, &mut all_features);
         }
 
         // AMD SVM Extended
-        if is_leaf_supported(0x8000_000A) {
-            detect_amd_svm(&mut all_features);
+        if src.is_leaf_supported(0x8000_000A) {
+            detect_amd_svm(src, &mut all_features);
         }
 
         // AMD Performance Optimization
-        if is_leaf_supported(0x8000_001A) {
-            detect_amd_perf_optimization(&mut all_features);
+        if src.is_leaf_supported(0x8000_001A) {
+            detect_amd_perf_optimization(src, &mut all_features);
         }
 
         // AMD Memory Encryption
-        if is_leaf_supported(0x8000_001F) {
-            detect_amd_memory_encryption(&mut all_features);
+        if src.is_leaf_supported(0x8000_001F) {
+            detect_amd_memory_encryption(src, &mut all_features);
         }
 
         // AMD Extended Features 2
-        if is_leaf_supported(0x8000_0021) {
-            detect_amd_extended_features2(&mut all_features);
+        if src.is_leaf_supported(0x8000_0021) {
+            detect_amd_extended_features2(src, &mut all_features);
+        }
+
+        // Centaur/Zhaoxin PadLock
+        if src.is_leaf_supported(0xC000_0001) {
+            detect_centaur_features(src, &mut all_features);
         }
 
         // Intel specific leaves
-        detect_intel_specific(&mut all_features);
+        detect_intel_specific(src, &mut all_features);
+
+        for feature in &mut all_features {
+            feature.deprecated = feature.deprecation_note().is_some();
+        }
+        mark_disabled_by_platform(src, &mut all_features);
 
         Self {
+            amx: detect_amx(src),
+            processor_trace: detect_processor_trace(src),
+            ibs: detect_ibs(src),
+            key_locker: detect_key_locker(src),
+            ..Self::new(basic, all_features, avx10)
+        }
+    }
+
+    pub fn new(basic: FeatureSet, all_features: Vec<Feature>, avx10: Option<Avx10Info>) -> Self {
+        let mut features = Self {
             basic,
             all_features,
+            avx10,
+            amx: None,
+            processor_trace: None,
+            ibs: None,
+            key_locker: None,
+            os_support: None,
+            ids: FeatureBits::new(),
+        };
+        features.reindex();
+        features
+    }
+
+    /// Rebuild the `FeatureId` index after editing `basic` or `all_features`.
+    pub fn reindex(&mut self) {
+        self.ids = self
+            .basic
+            .iter_names()
+            .map(|(name, _)| name)
+            .chain(self.all_features.iter().filter(|f| f.supported).map(|f| f.name.as_str()))
+            .filter_map(FeatureId::from_name)
+            .collect();
+    }
+
+    /// Record the OS state and set every feature's `os_enabled` from it.
+    /// `None` means the OS has not enabled XSAVE, so no state-backed feature is usable.
+    pub fn apply_os_support(&mut self, os: Option<OsSupport>) {
+        self.os_support = os;
+        for feature in &mut self.all_features {
+            feature.os_enabled = match os {
+                Some(os) => os.enables(&feature.name),
+                None => !os_support::needs_os_state(&feature.name),
+            };
         }
     }
 
-    pub fn has_feature(&self, name: &str) -> bool {
+    /// Supported by the CPU, not disabled by microcode or firmware, and its
+    /// register state enabled by the OS; the question runtime dispatch should ask.
+    pub fn has_usable(&self, name: &str) -> bool {
+        let name = Feature::canonical_name(name).unwrap_or(name);
+        self.has_feature(name)
+            && !self
+                .all_features
+                .iter()
+                .any(|f| f.name == name && (!f.os_enabled || f.disabled_by_platform))
+    }
+
+    /// rustc target feature names of every usable feature (see `has_usable`),
+    /// e.g. to build `-C target-feature=+avx2,+bmi2` for a JIT or code generator.
+    pub fn as_target_features(&self) -> Vec<&'static str> {
+        let basic = self.basic.iter_names().map(|(name, _)| name);
+        let usable = self
+            .all_features
+            .iter()
+            .filter(|f| f.supported && f.os_enabled)
+            .map(|f| f.name.as_str());
+
+        let mut names = Vec::new();
+        for rust in basic.chain(usable).filter_map(rust_target_feature) {
+            if !names.contains(&rust) {
+                names.push(rust);
+            }
+        }
+        names
+    }
+
+    /// Linux `/proc/cpuinfo` flags of every supported feature, in CPUID order.
+    pub fn linux_flags(&self) -> Vec<&'static str> {
+        let basic = self.basic.iter_names().map(|(name, _)| name);
+        let supported = self.all_supported().into_iter().map(|f| f.name.as_str());
+
+        let mut flags = Vec::new();
+        for flag in basic.chain(supported).filter_map(linux_flag) {
+            if !flags.contains(&flag) {
+                flags.push(flag);
+            }
+        }
+        flags
+    }
+
+    /// Constant-time support check.
+    pub fn has(&self, id: FeatureId) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Every supported feature as a bitset, for cheap set comparisons.
+    pub fn ids(&self) -> FeatureBits {
+        self.ids
+    }
+
+    /// Supported features as per-register bitflags with CPUID bit positions.
+    pub fn flags(&self) -> FeatureFlags {
+        let mut flags = FeatureFlags { leaf1_edx: self.basic, ..FeatureFlags::default() };
+        for feature in self.all_features.iter().filter(|f| f.supported) {
+            let source = feature.source;
+            if let (Some(register), Some(bit)) = (source.register, source.bit) {
+                flags.insert(source.leaf, source.subleaf, register, bit);
+            }
+        }
+        flags
+    }
+
+    /// Features ordered by name, then source, independent of decoding order.
+    pub fn sorted(mut self) -> Self {
         self.all_features
+            .sort_by(|a, b| a.name.cmp(&b.name).then(a.source.cmp(&b.source)));
+        self
+    }
+
+    /// String lookup in any spelling `Feature::canonical_name` accepts. Crate
+    /// names with a `FeatureId` use the index, others scan `all_features`.
+    pub fn has_feature(&self, name: &str) -> bool {
+        // Versioned names share one id, so only an exact name match is authoritative
+        if let Some(id) = FeatureId::from_name(name).filter(|id| id.name() == name) {
+            return self.has(id);
+        }
+        if FeatureSet::from_name(name).is_some_and(|flag| self.basic.contains(flag))
+            || self.all_features.iter().any(|f| f.name == name && f.supported)
+        {
+            return true;
+        }
+
+        // Another spelling of a known name, or of a decoded one such as `avx10_v2`
+        match Feature::canonical_name(name) {
+            Some(canonical) if canonical != name => self.has_feature(canonical),
+            _ => self.all_features.iter().any(|f| f.supported && names_match(&f.name, name)),
+        }
+    }
+
+    /// Fails with every name in `names` this CPU does not support.
+    ///
+    /// Names match as in `has_feature`, so `"AVX512_VNNI"`, the
+    /// `target_feature` spelling `"avx512vnni"` and the Linux flag
+    /// `"avx512_vnni"` all work.
+    pub fn require(&self, names: &[&str]) -> Result<(), MissingFeatures> {
+        let missing: Vec<String> = names
             .iter()
-            .any(|f| f.name == name && f.supported)
+            .filter(|name| !self.has_feature(name))
+            .map(|name| name.to_string())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingFeatures { missing })
+        }
     }
 
-    pub fn features_by_category(&self, category: FeatureCategory) -> Vec<&Feature> {
+    /// Supported features in a category, with a tag, or both:
+    /// `features_by_category((FeatureCategory::Performance, FeatureTag::StringOps))`.
+    pub fn features_by_category(&self, filter: impl Into<FeatureFilter>) -> Vec<&Feature> {
+        let filter = filter.into();
         self.all_features
             .iter()
-            .filter(|f| f.category == category && f.supported)
+            .filter(|f| f.supported && filter.matches(f))
             .collect()
     }
 
     pub fn all_supported(&self) -> Vec<&Feature> {
         self.all_features.iter().filter(|f| f.supported).collect()
     }
+
+    /// Features `vendor` implements on some CPU but this one lacks, so Intel
+    /// parts do not list AMD SVM bits as missing and vice versa.
+    pub fn missing_for_vendor(&self, vendor: &CpuVendor) -> Vec<&Feature> {
+        self.all_features
+            .iter()
+            .filter(|f| !f.supported && f.applies_to(vendor))
+            .collect()
+    }
+
+    /// Features whose CPUID bit is set but which microcode or firmware disables.
+    pub fn disabled_by_platform(&self) -> Vec<&Feature> {
+        self.all_features
+            .iter()
+            .filter(|f| f.supported && f.disabled_by_platform)
+            .collect()
+    }
+
+    /// Supported features that vendors have deprecated or removed.
+    pub fn deprecated_supported(&self) -> Vec<&Feature> {
+        self.all_features
+            .iter()
+            .filter(|f| f.supported && f.deprecated)
+            .collect()
+    }
+
+    /// All decoded features (supported or not) grouped by their CPUID leaf/subleaf.
+    /// Keys carry no register or bit.
+    pub fn features_by_leaf(&self) -> BTreeMap<FeatureSource, Vec<&Feature>> {
+        let mut groups: BTreeMap<FeatureSource, Vec<&Feature>> = BTreeMap::new();
+        for feature in &self.all_features {
+            groups.entry(feature.source.leaf_only()).or_default().push(feature);
+        }
+        groups
+    }
+
+    /// True if AVX10 is at least `version` and usable at `width`.
+    pub fn supports_at_least_avx10(&self, version: u32, width: VectorWidth) -> bool {
+        self.avx10
+            .is_some_and(|avx10| avx10.version >= version && avx10.supports_width(width))
+    }
+
+    pub fn simd_level(&self) -> SimdLevel {
+        if let Some(width) = self.avx10.and_then(|avx10| avx10.max_width()) {
+            return SimdLevel::Avx10 {
+                version: self.avx10.map_or(0, |avx10| avx10.version),
+                width,
+            };
+        }
+
+        let levels = [
+            ("AVX512F", SimdLevel::Avx512),
+            ("AVX2", SimdLevel::Avx2),
+            ("AVX", SimdLevel::Avx),
+            ("SSE4.2", SimdLevel::Sse42),
+            ("SSE4.1", SimdLevel::Sse41),
+            ("SSSE3", SimdLevel::Ssse3),
+            ("SSE3", SimdLevel::Sse3),
+            ("SSE2", SimdLevel::Sse2),
+            ("SSE", SimdLevel::Sse),
+        ];
+        levels
+            .into_iter()
+            .find(|(name, _)| self.has_feature(name))
+            .map_or(SimdLevel::None, |(_, level)| level)
+    }
+
+    /// One answer for dispatch: vector width and the ML extensions usable at
+    /// it. AVX10 implies every AVX-512 extension below, so a CPU with AVX10
+    /// and no AVX-512 CPUID flags still reports VNNI/BF16/FP16.
+    pub fn effective_simd_level(&self) -> EffectiveSimd {
+        let level = self.simd_level();
+        let avx10 = matches!(level, SimdLevel::Avx10 { .. });
+        let vector_bits = match level {
+            SimdLevel::None => 0,
+            SimdLevel::Avx10 { width, .. } => width.bits(),
+            SimdLevel::Avx512 => 512,
+            SimdLevel::Avx | SimdLevel::Avx2 => 256,
+            _ => 128,
+        };
+        let avx512 = level == SimdLevel::Avx512;
+        let extension = |name| avx10 || (avx512 && self.has_feature(name));
+
+        EffectiveSimd {
+            level,
+            vector_bits,
+            vnni: extension("AVX512_VNNI") || (vector_bits == 256 && self.has_feature("AVX_VNNI")),
+            bf16: extension("AVX512_BF16"),
+            fp16: extension("AVX512_FP16"),
+        }
+    }
+
+    /// Vector widths usable by AES, CLMUL and GFNI code, combining the
+    /// extension flags with AVX, AVX-512 and AVX10 and the OS-enabled state.
+    pub fn crypto_capabilities(&self) -> CryptoCaps {
+        let avx10 = self.has_usable("AVX10");
+        let avx = self.has_usable("AVX");
+        let avx512 = self.has_usable("AVX512F")
+            || (avx10 && self.supports_at_least_avx10(1, VectorWidth::W512));
+        let width = |legacy: &str, vector: &str| {
+            if avx512 && self.has_usable(vector) {
+                Some(VectorWidth::W512)
+            } else if avx && self.has_usable(vector) {
+                Some(VectorWidth::W256)
+            } else {
+                self.has_usable(legacy).then_some(VectorWidth::W128)
+            }
+        };
+
+        CryptoCaps {
+            aes: width("AES", "VAES"),
+            clmul: width("PCLMULQDQ", "VPCLMULQDQ"),
+            gfni: width("GFNI", "GFNI"),
+            sha: self.has_usable("SHA"),
+            evex: avx10 || self.has_usable("AVX512VL"),
+        }
+    }
 }
 
 impl fmt::Display for CpuFeatures {
@@ -218,10 +1361,106 @@ impl fmt::Display for CpuFeatures {
             }
         }
 
+        if let Some(pt) = &self.processor_trace {
+            write!(f, "  Processor Trace: {} address ranges", pt.address_ranges)?;
+            for (set, name) in [
+                (pt.topa, "ToPA"),
+                (pt.single_range_output, "single-range"),
+                (pt.ptwrite, "PTWRITE"),
+                (pt.lip, "LIP"),
+            ] {
+                if set {
+                    write!(f, ", {}", name)?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(ibs) = &self.ibs {
+            write!(f, "  IBS:")?;
+            let modes = [
+                (ibs.fetch_sampling, "fetch sampling"),
+                (ibs.op_sampling, "op sampling"),
+                (ibs.op_counting, "op counting"),
+                (ibs.branch_target_address, "branch target"),
+                (ibs.l3_miss_filtering, "L3 miss filtering"),
+            ];
+            let names: Vec<&str> = modes.iter().filter(|(set, _)| *set).map(|(_, name)| *name).collect();
+            writeln!(f, " {}", names.join(", "))?;
+        }
+
+        if let Some(kl) = &self.key_locker {
+            writeln!(
+                f,
+                "  Key Locker: {}{}{}",
+                if kl.aeskle { "enabled" } else { "not enabled by the OS" },
+                if kl.wide { ", wide" } else { "" },
+                if kl.iwkey_backup { ", IWKEY backup" } else { "" }
+            )?;
+        }
+
+        if let Some(amx) = &self.amx
+            && let Some(palette) = amx.palette(1)
+        {
+            writeln!(
+                f,
+                "  AMX: {} tiles of {} rows x {} bytes, TMUL K={} N={}",
+                palette.tile_registers,
+                palette.max_rows,
+                palette.bytes_per_row,
+                amx.tmul_max_k,
+                amx.tmul_max_n
+            )?;
+        }
+
         Ok(())
     }
 }
 
+/// Required features the CPU lacks, as returned by `CpuFeatures::require`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFeatures {
+    /// Names as the caller spelled them.
+    pub missing: Vec<String>,
+}
+
+impl fmt::Display for MissingFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "this CPU lacks required instruction set extensions: {}",
+            self.missing.join(", ")
+        )
+    }
+}
+
+impl core::error::Error for MissingFeatures {}
+
+fn linux_flag(name: &str) -> Option<&'static str> {
+    LINUX_FLAGS
+        .iter()
+        .find(|(feature, _)| *feature == name)
+        .map(|(_, flag)| *flag)
+}
+
+fn rust_target_feature(name: &str) -> Option<&'static str> {
+    RUST_TARGET_FEATURES
+        .iter()
+        .find(|(feature, _)| *feature == name)
+        .map(|(_, rust)| *rust)
+}
+
+/// Equal ignoring ASCII case, `_`, `-` and `.`.
+fn names_match(a: &str, b: &str) -> bool {
+    name_key(a).eq(name_key(b))
+}
+
+fn name_key(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars()
+        .filter(|c| !matches!(c, '_' | '-' | '.'))
+        .map(|c| c.to_ascii_uppercase())
+}
+
 fn detect_leaf1_edx(edx: u32, features: &mut FeatureSet) {
     if edx & (1 << 0) != 0 {
         *features |= FeatureSet::FPU;
@@ -274,9 +1513,18 @@ fn detect_leaf1_edx(edx: u32, features: &mut FeatureSet) {
     if edx & (1 << 17) != 0 {
         *features |= FeatureSet::PSE36;
     }
+    if edx & (1 << 18) != 0 {
+        *features |= FeatureSet::PSN;
+    }
     if edx & (1 << 19) != 0 {
         *features |= FeatureSet::CLFSH;
     }
+    if edx & (1 << 21) != 0 {
+        *features |= FeatureSet::DS;
+    }
+    if edx & (1 << 22) != 0 {
+        *features |= FeatureSet::ACPI;
+    }
     if edx & (1 << 23) != 0 {
         *features |= FeatureSet::MMX;
     }
@@ -289,9 +1537,18 @@ fn detect_leaf1_edx(edx: u32, features: &mut FeatureSet) {
     if edx & (1 << 26) != 0 {
         *features |= FeatureSet::SSE2;
     }
+    if edx & (1 << 27) != 0 {
+        *features |= FeatureSet::SS;
+    }
     if edx & (1 << 28) != 0 {
         *features |= FeatureSet::HTT;
     }
+    if edx & (1 << 29) != 0 {
+        *features |= FeatureSet::TM;
+    }
+    if edx & (1 << 31) != 0 {
+        *features |= FeatureSet::PBE;
+    }
 }
 
 fn detect_leaf1_ecx(ecx: u32, features: &mut Vec<Feature>) {
@@ -430,12 +1687,16 @@ fn detect_leaf1_ecx(ecx: u32, features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (ecx & (1 << bit)) != 0,
+            source: FeatureSource::bit(1, 0, Register::Ecx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_leaf7(features: &mut Vec<Feature>) {
-    let result = cpuid(7, 0);
+fn detect_leaf7(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    let result = src.cpuid(7, 0);
 
     // EBX features
     let ebx_features = [
@@ -618,6 +1879,10 @@ fn detect_leaf7(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ebx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 0, Register::Ebx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -755,6 +2020,10 @@ fn detect_leaf7(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ecx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 0, Register::Ecx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -903,12 +2172,16 @@ fn detect_leaf7(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 0, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_leaf7_sub1(features: &mut Vec<Feature>) {
-    let result = cpuid(7, 1);
+fn detect_leaf7_sub1(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    let result = src.cpuid(7, 1);
 
     let eax_features = [
         (
@@ -1011,6 +2284,10 @@ fn detect_leaf7_sub1(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 1, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1027,6 +2304,10 @@ fn detect_leaf7_sub1(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ebx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 1, Register::Ebx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1093,12 +2374,16 @@ fn detect_leaf7_sub1(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 1, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_leaf7_sub2(features: &mut Vec<Feature>) {
-    let result = cpuid(7, 2);
+fn detect_leaf7_sub2(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    let result = src.cpuid(7, 2);
 
     let edx_features = [
         (
@@ -1130,12 +2415,16 @@ fn detect_leaf7_sub2(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 2, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_extended_features(features: &mut Vec<Feature>) {
-    let result = cpuid(0x8000_0001, 0);
+fn detect_extended_features(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    let result = src.cpuid(0x8000_0001, 0);
 
     // EDX extended features
     let edx_features = [
@@ -1172,6 +2461,10 @@ fn detect_extended_features(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_0001, 0, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1317,12 +2610,16 @@ fn detect_extended_features(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ecx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_0001, 0, Register::Ecx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_amd_extended(features: &mut Vec<Feature>) {
-    let result = cpuid(0x8000_0008, 0);
+fn detect_amd_extended(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    let result = src.cpuid(0x8000_0008, 0);
 
     let ebx_features = [
         (
@@ -1459,6 +2756,10 @@ fn detect_amd_extended(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ebx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_0008, 0, Register::Ebx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1483,14 +2784,38 @@ fn detect_amd_extended(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ecx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_0008, 0, Register::Ecx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_intel_specific(features: &mut Vec<Feature>) {
+/// Features whose bit survives even though the platform turned them off.
+fn mark_disabled_by_platform(src: &dyn CpuidSource, features: &mut [Feature]) {
+    let leaf7 = src.cpuid(7, 0);
+    // Microcode sets RTM_ALWAYS_ABORT when it disables TSX but keeps the RTM bit
+    let tsx_disabled = src.is_leaf_supported(7) && leaf7.edx & (1 << 11) != 0;
+    // Firmware that disables MPX leaves its XSAVE state components unreported
+    let mpx_state = src.is_leaf_supported(0xD) && src.cpuid(0xD, 0).eax & 0b11000 == 0b11000;
+    // Firmware that disables SGX may leave leaf 7 EBX[2] set but leaf 0x12 empty
+    let sgx_leaf = src.is_leaf_supported(0x12) && src.cpuid(0x12, 0).eax & 0b11 != 0;
+
+    for feature in features.iter_mut().filter(|f| f.supported) {
+        feature.disabled_by_platform = match feature.name.as_str() {
+            "RTM" | "HLE" => tsx_disabled,
+            "MPX" => !mpx_state,
+            "SGX" => !sgx_leaf,
+            _ => false,
+        };
+    }
+}
+
+fn detect_intel_specific(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
     // Intel leaf 0xD - Extended state enumeration
-    if is_leaf_supported(0xD) {
-        let result = cpuid(0xD, 1);
+    if src.is_leaf_supported(0xD) {
+        let result = src.cpuid(0xD, 1);
 
         let eax_features = [
             (
@@ -1531,97 +2856,105 @@ fn detect_intel_specific(features: &mut Vec<Feature>) {
                 category: *category,
                 description: desc,
                 supported: (result.eax & (1 << bit)) != 0,
-            });
-        }
-    }
-
-    // Intel leaf 0x14 - Processor Trace
-    if is_leaf_supported(0x14) {
-        let result = cpuid(0x14, 0);
-        let pt_features = [
-            (0, "PT_LIP", "Processor Trace LIP support"),
-            (1, "PT_MTC", "Processor Trace MTC support"),
-            (2, "PT_PTWRITE", "Processor Trace PTWRITE support"),
-            (3, "PT_POWER_EVENT", "Processor Trace Power Event support"),
-        ];
-
-        for (bit, name, desc) in pt_features.iter() {
-            features.push(Feature {
-                name: name.to_string(),
-                category: FeatureCategory::Debug,
-                description: desc,
-                supported: (result.ebx & (1 << bit)) != 0,
+                source: FeatureSource::bit(0xD, 1, Register::Eax, *bit),
+                deprecated: false,
+                disabled_by_platform: false,
+                os_enabled: true,
             });
         }
     }
 
     // Intel leaf 0x1F - V2 Extended Topology
-    if is_leaf_supported(0x1F) {
+    if src.is_leaf_supported(0x1F) {
         features.push(Feature {
             name: "TOPOLOGY_V2".to_string(),
             category: FeatureCategory::System,
             description: "V2 Extended Topology Enumeration",
             supported: true,
+            source: FeatureSource::new(0x1F, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     // Intel leaf 0x1A - Hybrid Information
-    if is_leaf_supported(0x1A) {
+    if src.is_leaf_supported(0x1A) {
         features.push(Feature {
             name: "HYBRID_INFO".to_string(),
             category: FeatureCategory::System,
             description: "Hybrid Core Information",
             supported: true,
+            source: FeatureSource::new(0x1A, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     // Intel leaf 0x1B - PCONFIG
-    if is_leaf_supported(0x1B) {
+    if src.is_leaf_supported(0x1B) {
         features.push(Feature {
             name: "PCONFIG_ENUM".to_string(),
             category: FeatureCategory::Security,
             description: "PCONFIG Enumeration",
             supported: true,
+            source: FeatureSource::new(0x1B, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     // Intel leaf 0x1C - Last Branch Records
-    if is_leaf_supported(0x1C) {
+    if src.is_leaf_supported(0x1C) {
         features.push(Feature {
             name: "LBR_INFO".to_string(),
             category: FeatureCategory::Debug,
             description: "Last Branch Record Information",
             supported: true,
+            source: FeatureSource::new(0x1C, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     // Intel leaf 0x1D - Tile Information
-    if is_leaf_supported(0x1D) {
+    if src.is_leaf_supported(0x1D) {
         features.push(Feature {
             name: "TILE_INFO".to_string(),
             category: FeatureCategory::Simd,
             description: "AMX Tile Information",
             supported: true,
+            source: FeatureSource::new(0x1D, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     // Intel leaf 0x1E - TMUL Information
-    if is_leaf_supported(0x1E) {
+    if src.is_leaf_supported(0x1E) {
         features.push(Feature {
             name: "TMUL_INFO".to_string(),
             category: FeatureCategory::Simd,
             description: "AMX TMUL Information",
             supported: true,
+            source: FeatureSource::new(0x1E, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_leaf7_sub3(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(7) {
+fn detect_leaf7_sub3(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(7) {
         return;
     }
 
-    let result = cpuid(7, 3);
+    let result = src.cpuid(7, 3);
 
     let edx_features = [
         (
@@ -1650,59 +2983,203 @@ fn detect_leaf7_sub3(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(7, 3, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_avx10(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x24) {
-        return;
+fn detect_avx10(src: &dyn CpuidSource, features: &mut Vec<Feature>) -> Option<Avx10Info> {
+    if !src.is_leaf_supported(0x24) {
+        return None;
     }
 
-    let result = cpuid(0x24, 0);
+    let result = src.cpuid(0x24, 0);
 
     let version = result.ebx & 0xFF;
-    if version > 0 {
-        features.push(Feature {
-            name: format!("AVX10_V{}", version),
-            category: FeatureCategory::Simd,
-            description: "AVX10 Version",
-            supported: true,
-        });
+    if version == 0 {
+        return None;
     }
 
-    if result.ebx & (1 << 16) != 0 {
+    let info = Avx10Info {
+        version,
+        supports_128: result.ebx & (1 << 16) != 0,
+        supports_256: result.ebx & (1 << 17) != 0,
+        supports_512: result.ebx & (1 << 18) != 0,
+    };
+
+    features.push(Feature {
+        name: format!("AVX10_V{}", version),
+        category: FeatureCategory::Simd,
+        description: "AVX10 Version",
+        supported: true,
+        source: FeatureSource::new(0x24, 0),
+        deprecated: false,
+        disabled_by_platform: false,
+        os_enabled: true,
+    });
+
+    if info.supports_128 {
         features.push(Feature {
             name: "AVX10_128".to_string(),
             category: FeatureCategory::Simd,
             description: "AVX10 128-bit vector support",
             supported: true,
+            source: FeatureSource::bit(0x24, 0, Register::Ebx, 16),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
-    if result.ebx & (1 << 17) != 0 {
+    if info.supports_256 {
         features.push(Feature {
             name: "AVX10_256".to_string(),
             category: FeatureCategory::Simd,
             description: "AVX10 256-bit vector support",
             supported: true,
+            source: FeatureSource::bit(0x24, 0, Register::Ebx, 17),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
-    if result.ebx & (1 << 18) != 0 {
+    if info.supports_512 {
         features.push(Feature {
             name: "AVX10_512".to_string(),
             category: FeatureCategory::Simd,
             description: "AVX10 512-bit vector support",
             supported: true,
+            source: FeatureSource::bit(0x24, 0, Register::Ebx, 18),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
+
+    Some(info)
+}
+
+fn detect_amx(src: &dyn CpuidSource) -> Option<AmxInfo> {
+    // Leaf 0x1D is only defined when leaf 7 reports AMX-TILE
+    if !src.is_leaf_supported(0x1D) || src.cpuid(7, 0).edx & (1 << 24) == 0 {
+        return None;
+    }
+
+    // Bounded in case a hypervisor returns garbage in EAX
+    let max_palette = src.cpuid(0x1D, 0).eax.min(63);
+    if max_palette == 0 {
+        return None;
+    }
+
+    let palettes = (1..=max_palette)
+        .map(|id| {
+            let result = src.cpuid(0x1D, id);
+            AmxPalette {
+                id,
+                total_tile_bytes: result.eax & 0xFFFF,
+                bytes_per_tile: result.eax >> 16,
+                bytes_per_row: result.ebx & 0xFFFF,
+                tile_registers: result.ebx >> 16,
+                max_rows: result.ecx & 0xFFFF,
+            }
+        })
+        .collect();
+
+    let mut info = AmxInfo { palettes, ..AmxInfo::default() };
+    if src.is_leaf_supported(0x1E) {
+        let result = src.cpuid(0x1E, 0);
+        info.tmul_max_k = result.ebx & 0xFF;
+        info.tmul_max_n = (result.ebx >> 8) & 0xFFFF;
+    }
+    Some(info)
+}
+
+fn detect_processor_trace(src: &dyn CpuidSource) -> Option<ProcessorTraceInfo> {
+    // Leaf 0x14 is only defined when leaf 7 reports Intel PT
+    if !src.is_leaf_supported(0x14) || src.cpuid(7, 0).ebx & (1 << 25) == 0 {
+        return None;
+    }
+
+    let main = src.cpuid(0x14, 0);
+    let mut info = ProcessorTraceInfo {
+        cr3_filtering: main.ebx & 1 != 0,
+        configurable_psb: main.ebx & (1 << 1) != 0,
+        ip_filtering: main.ebx & (1 << 2) != 0,
+        mtc: main.ebx & (1 << 3) != 0,
+        ptwrite: main.ebx & (1 << 4) != 0,
+        power_event_trace: main.ebx & (1 << 5) != 0,
+        topa: main.ecx & 1 != 0,
+        topa_multiple_entries: main.ecx & (1 << 1) != 0,
+        single_range_output: main.ecx & (1 << 2) != 0,
+        transport_output: main.ecx & (1 << 3) != 0,
+        lip: main.ecx & (1 << 31) != 0,
+        ..ProcessorTraceInfo::default()
+    };
+
+    if main.eax >= 1 {
+        let sub1 = src.cpuid(0x14, 1);
+        info.address_ranges = sub1.eax & 0x7;
+        info.mtc_periods = (sub1.eax >> 16) as u16;
+        info.cycle_thresholds = sub1.ebx as u16;
+        info.psb_frequencies = (sub1.ebx >> 16) as u16;
+    }
+    Some(info)
 }
 
-fn detect_thermal_power(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(6) {
+fn detect_ibs(src: &dyn CpuidSource) -> Option<IbsInfo> {
+    // Leaf 0x8000_001B is only defined with the IBS bit (0x8000_0001 ECX[10])
+    if !src.is_leaf_supported(0x8000_001B) || src.cpuid(0x8000_0001, 0).ecx & (1 << 10) == 0 {
+        return None;
+    }
+
+    let eax = src.cpuid(0x8000_001B, 0).eax;
+    // IBSFFV: the remaining bits are valid
+    if eax & 1 == 0 {
+        return None;
+    }
+
+    Some(IbsInfo {
+        fetch_sampling: eax & (1 << 1) != 0,
+        op_sampling: eax & (1 << 2) != 0,
+        rd_wr_op_count: eax & (1 << 3) != 0,
+        op_counting: eax & (1 << 4) != 0,
+        branch_target_address: eax & (1 << 5) != 0,
+        op_count_extended: eax & (1 << 6) != 0,
+        rip_invalid_check: eax & (1 << 7) != 0,
+        op_branch_fusion: eax & (1 << 8) != 0,
+        fetch_control_extended: eax & (1 << 9) != 0,
+        op_data4: eax & (1 << 10) != 0,
+        l3_miss_filtering: eax & (1 << 11) != 0,
+    })
+}
+
+fn detect_key_locker(src: &dyn CpuidSource) -> Option<KeyLockerInfo> {
+    // Leaf 0x19 is only defined with KL (leaf 7 ECX[23])
+    if !src.is_leaf_supported(0x19) || src.cpuid(7, 0).ecx & (1 << 23) == 0 {
+        return None;
+    }
+
+    let result = src.cpuid(0x19, 0);
+    Some(KeyLockerInfo {
+        cpl0_restriction: result.eax & 1 != 0,
+        no_encrypt_restriction: result.eax & (1 << 1) != 0,
+        no_decrypt_restriction: result.eax & (1 << 2) != 0,
+        aeskle: result.ebx & 1 != 0,
+        wide: result.ebx & (1 << 2) != 0,
+        iwkey_backup: result.ebx & (1 << 4) != 0,
+        no_backup: result.ecx & 1 != 0,
+        random_iwkey: result.ecx & (1 << 1) != 0,
+    })
+}
+
+fn detect_thermal_power(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(6) {
         return;
     }
 
-    let result = cpuid(6, 0);
+    let result = src.cpuid(6, 0);
 
     let eax_features = [
         (0, "DTHERM", FeatureCategory::Power, "Digital thermal sensor"),
@@ -1804,6 +3281,10 @@ fn detect_thermal_power(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(6, 0, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1834,16 +3315,20 @@ fn detect_thermal_power(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ecx & (1 << bit)) != 0,
+            source: FeatureSource::bit(6, 0, Register::Ecx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_perfmon(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0xA) {
+fn detect_perfmon(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0xA) {
         return;
     }
 
-    let result = cpuid(0xA, 0);
+    let result = src.cpuid(0xA, 0);
 
     let version = result.eax & 0xFF;
     if version > 0 {
@@ -1852,6 +3337,10 @@ fn detect_perfmon(features: &mut Vec<Feature>) {
             category: FeatureCategory::Performance,
             description: "Performance Monitoring version",
             supported: true,
+            source: FeatureSource::new(0xA, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1906,6 +3395,10 @@ fn detect_perfmon(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ebx & (1 << bit)) == 0,
+            source: FeatureSource::bit(0xA, 0, Register::Ebx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -1942,16 +3435,20 @@ fn detect_perfmon(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0xA, 0, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_rdt(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x10) {
+fn detect_rdt(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x10) {
         return;
     }
 
-    let result = cpuid(0x10, 0);
+    let result = src.cpuid(0x10, 0);
 
     let ebx_features = [
         (
@@ -1980,17 +3477,25 @@ fn detect_rdt(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.ebx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x10, 0, Register::Ebx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
     if result.ebx & (1 << 1) != 0 {
-        let l3_result = cpuid(0x10, 1);
+        let l3_result = src.cpuid(0x10, 1);
         if l3_result.eax != 0 {
             features.push(Feature {
                 name: "RDT_L3_CAT".to_string(),
                 category: FeatureCategory::Performance,
                 description: "L3 Cache Allocation Technology",
                 supported: true,
+                source: FeatureSource::new(0x10, 1),
+                deprecated: false,
+                disabled_by_platform: false,
+                os_enabled: true,
             });
         }
         if l3_result.ecx & (1 << 2) != 0 {
@@ -1999,29 +3504,37 @@ fn detect_rdt(features: &mut Vec<Feature>) {
                 category: FeatureCategory::Performance,
                 description: "L3 Code/Data Prioritization",
                 supported: true,
+                source: FeatureSource::bit(0x10, 1, Register::Ecx, 2),
+                deprecated: false,
+                disabled_by_platform: false,
+                os_enabled: true,
             });
         }
     }
 
     if result.ebx & (1 << 2) != 0 {
-        let l2_result = cpuid(0x10, 2);
+        let l2_result = src.cpuid(0x10, 2);
         if l2_result.eax != 0 {
             features.push(Feature {
                 name: "RDT_L2_CAT".to_string(),
                 category: FeatureCategory::Performance,
                 description: "L2 Cache Allocation Technology",
                 supported: true,
+                source: FeatureSource::new(0x10, 2),
+                deprecated: false,
+                disabled_by_platform: false,
+                os_enabled: true,
             });
         }
     }
 }
 
-fn detect_sgx_extended(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x12) {
+fn detect_sgx_extended(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x12) {
         return;
     }
 
-    let result = cpuid(0x12, 0);
+    let result = src.cpuid(0x12, 0);
 
     let eax_features = [
         (0, "SGX1", FeatureCategory::Security, "SGX1 leaf functions"),
@@ -2036,6 +3549,10 @@ fn detect_sgx_extended(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x12, 0, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
@@ -2045,26 +3562,34 @@ fn detect_sgx_extended(features: &mut Vec<Feature>) {
             category: FeatureCategory::Security,
             description: "SGX MISCSELECT support",
             supported: true,
+            source: FeatureSource::bit(0x12, 0, Register::Ebx, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 
-    let sub1 = cpuid(0x12, 1);
+    let sub1 = src.cpuid(0x12, 1);
     if sub1.eax != 0 || sub1.ebx != 0 || sub1.ecx != 0 || sub1.edx != 0 {
         features.push(Feature {
             name: "SGX_ATTRIBUTES".to_string(),
             category: FeatureCategory::Security,
             description: "SGX Attributes enumeration",
             supported: true,
+            source: FeatureSource::new(0x12, 1),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_address_translation(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x18) {
+fn detect_address_translation(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x18) {
         return;
     }
 
-    let result = cpuid(0x18, 0);
+    let result = src.cpuid(0x18, 0);
 
     if result.eax != 0 {
         features.push(Feature {
@@ -2072,16 +3597,20 @@ fn detect_address_translation(features: &mut Vec<Feature>) {
             category: FeatureCategory::Memory,
             description: "Deterministic Address Translation enumeration",
             supported: true,
+            source: FeatureSource::new(0x18, 0),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_amd_svm(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x8000_000A) {
+fn detect_amd_svm(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x8000_000A) {
         return;
     }
 
-    let result = cpuid(0x8000_000A, 0);
+    let result = src.cpuid(0x8000_000A, 0);
 
     let edx_features = [
         (
@@ -2215,16 +3744,20 @@ fn detect_amd_svm(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_000A, 0, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_amd_memory_encryption(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x8000_001F) {
+fn detect_amd_memory_encryption(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x8000_001F) {
         return;
     }
 
-    let result = cpuid(0x8000_001F, 0);
+    let result = src.cpuid(0x8000_001F, 0);
 
     let eax_features = [
         (
@@ -2353,16 +3886,20 @@ fn detect_amd_memory_encryption(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_001F, 0, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_amd_extended_features2(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x8000_0021) {
+fn detect_amd_extended_features2(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x8000_0021) {
         return;
     }
 
-    let result = cpuid(0x8000_0021, 0);
+    let result = src.cpuid(0x8000_0021, 0);
 
     let eax_features = [
         (
@@ -2447,16 +3984,20 @@ fn detect_amd_extended_features2(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_0021, 0, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
 
-fn detect_amd_perf_optimization(features: &mut Vec<Feature>) {
-    if !is_leaf_supported(0x8000_001A) {
+fn detect_amd_perf_optimization(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0x8000_001A) {
         return;
     }
 
-    let result = cpuid(0x8000_001A, 0);
+    let result = src.cpuid(0x8000_001A, 0);
 
     let eax_features = [
         (0, "FP128", FeatureCategory::Simd, "128-bit FP execution"),
@@ -2475,6 +4016,96 @@ fn detect_amd_perf_optimization(features: &mut Vec<Feature>) {
             category: *category,
             description: desc,
             supported: (result.eax & (1 << bit)) != 0,
+            source: FeatureSource::bit(0x8000_001A, 0, Register::Eax, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
+        });
+    }
+}
+
+fn detect_centaur_features(src: &dyn CpuidSource, features: &mut Vec<Feature>) {
+    if !src.is_leaf_supported(0xC000_0001) {
+        return;
+    }
+
+    let result = src.cpuid(0xC000_0001, 0);
+
+    // Each unit has a present bit and an enabled bit; firmware can switch
+    // a present unit off
+    let edx_features = [
+        (
+            2,
+            "PADLOCK_RNG",
+            FeatureCategory::Cryptography,
+            "PadLock random number generator (XSTORE)",
+        ),
+        (
+            3,
+            "PADLOCK_RNG_EN",
+            FeatureCategory::Cryptography,
+            "PadLock RNG enabled",
+        ),
+        (
+            6,
+            "PADLOCK_ACE",
+            FeatureCategory::Cryptography,
+            "PadLock Advanced Cryptography Engine (XCRYPT)",
+        ),
+        (
+            7,
+            "PADLOCK_ACE_EN",
+            FeatureCategory::Cryptography,
+            "PadLock ACE enabled",
+        ),
+        (
+            8,
+            "PADLOCK_ACE2",
+            FeatureCategory::Cryptography,
+            "PadLock ACE2 (AES-CTR)",
+        ),
+        (
+            9,
+            "PADLOCK_ACE2_EN",
+            FeatureCategory::Cryptography,
+            "PadLock ACE2 enabled",
+        ),
+        (
+            10,
+            "PADLOCK_PHE",
+            FeatureCategory::Cryptography,
+            "PadLock Hash Engine (SHA-1/SHA-256)",
+        ),
+        (
+            11,
+            "PADLOCK_PHE_EN",
+            FeatureCategory::Cryptography,
+            "PadLock PHE enabled",
+        ),
+        (
+            12,
+            "PADLOCK_PMM",
+            FeatureCategory::Cryptography,
+            "PadLock Montgomery Multiplier",
+        ),
+        (
+            13,
+            "PADLOCK_PMM_EN",
+            FeatureCategory::Cryptography,
+            "PadLock PMM enabled",
+        ),
+    ];
+
+    for (bit, name, category, desc) in edx_features.iter() {
+        features.push(Feature {
+            name: name.to_string(),
+            category: *category,
+            description: desc,
+            supported: (result.edx & (1 << bit)) != 0,
+            source: FeatureSource::bit(0xC000_0001, 0, Register::Edx, *bit),
+            deprecated: false,
+            disabled_by_platform: false,
+            os_enabled: true,
         });
     }
 }
@@ -1,53 +1,193 @@
 //! CPU Frequency Detection
 //!
-//! Detects CPU frequency information including base, max, and bus frequencies.
+//! Detects CPU frequency information including base, max, and bus frequencies,
+//! plus the OS frequency-scaling policies on Linux (cpufreq sysfs).
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::error::{self, DetectError};
+use crate::vendor_ops;
+use crate::vendor::{HypervisorInfo, HypervisorVendor};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencyInfo {
     pub base_mhz: Option<u32>,
     pub max_mhz: Option<u32>,
     pub bus_mhz: Option<u32>,
     pub tsc_mhz: Option<u32>,
+    pub policies: Vec<CpufreqPolicy>,
+}
+
+/// One cpufreq policy (`/sys/devices/system/cpu/cpufreq/policyN`).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpufreqPolicy {
+    pub id: u32,
+    pub cpus: Vec<usize>,
+    pub driver: String,
+    pub governor: String,
+    pub scaling_min_mhz: Option<u32>,
+    pub scaling_max_mhz: Option<u32>,
+    pub hardware_min_mhz: Option<u32>,
+    pub hardware_max_mhz: Option<u32>,
+    pub energy_performance_preference: Option<String>,
+}
+
+impl CpufreqPolicy {
+    /// True if the OS caps this policy below what the hardware allows.
+    pub fn is_capped(&self) -> bool {
+        matches!(
+            (self.scaling_max_mhz, self.hardware_max_mhz),
+            (Some(scaling), Some(hardware)) if scaling < hardware
+        )
+    }
 }
 
 impl FrequencyInfo {
     pub fn detect() -> Self {
-        let mut info = Self {
-            base_mhz: None,
-            max_mhz: None,
-            bus_mhz: None,
-            tsc_mhz: None,
-        };
-
-        if is_leaf_supported(0x16) {
-            let result = cpuid(0x16, 0);
-            if result.eax != 0 {
-                info.base_mhz = Some(result.eax & 0xFFFF);
-            }
-            if result.ebx != 0 {
-                info.max_mhz = Some(result.ebx & 0xFFFF);
-            }
-            if result.ecx != 0 {
-                info.bus_mhz = Some(result.ecx & 0xFFFF);
-            }
+        let mut info = Self::detect_with(&HardwareCpuid);
+        info.policies = detect_policies();
+
+        info
+    }
+
+    /// CPUID-derived frequencies only; cpufreq policies describe the local
+    /// machine and are left empty.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "frequency", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        vendor_ops::detect(src).frequency(src)
+    }
+
+    /// Like `detect`, but fails when no CPUID or hypervisor leaf reports a frequency.
+    pub fn try_detect() -> Result<Self, DetectError> {
+        let mut info = Self::try_detect_with(&HardwareCpuid)?;
+        info.policies = detect_policies();
+
+        Ok(info)
+    }
+
+    pub fn try_detect_with(src: &dyn CpuidSource) -> Result<Self, DetectError> {
+        let info = Self::detect_with(src);
+        if info.base_mhz.is_none() && info.max_mhz.is_none() && info.tsc_mhz.is_none() {
+            return Err(error::empty_leaf(src, 0x16, 0));
+        }
+        Ok(info)
+    }
+
+    /// Scaling driver of the first policy (e.g. `intel_pstate`, `amd-pstate`, `acpi-cpufreq`).
+    pub fn driver(&self) -> Option<&str> {
+        self.policies.first().map(|p| p.driver.as_str())
+    }
+
+    /// Governor of the first policy (e.g. `powersave`, `performance`, `schedutil`).
+    pub fn governor(&self) -> Option<&str> {
+        self.policies.first().map(|p| p.governor.as_str())
+    }
+}
+
+/// Nominal frequencies from leaves 0x16 and 0x15 (Intel, Zhaoxin).
+pub(crate) fn detect_cpuid(src: &dyn CpuidSource) -> FrequencyInfo {
+    let mut info = FrequencyInfo::default();
+
+    if src.is_leaf_supported(0x16) {
+        let result = src.cpuid(0x16, 0);
+        if result.eax != 0 {
+            info.base_mhz = Some(result.eax & 0xFFFF);
+        }
+        if result.ebx != 0 {
+            info.max_mhz = Some(result.ebx & 0xFFFF);
         }
+        if result.ecx != 0 {
+            info.bus_mhz = Some(result.ecx & 0xFFFF);
+        }
+    }
 
-        if is_leaf_supported(0x15) {
-            let result = cpuid(0x15, 0);
-            if result.ebx != 0 && result.eax != 0 {
-                let crystal_hz = if result.ecx != 0 {
-                    result.ecx
-                } else {
-                    24_000_000
-                };
-                info.tsc_mhz = Some(
-                    (crystal_hz as u64 * result.ebx as u64 / result.eax as u64 / 1_000_000) as u32,
-                );
-            }
+    if src.is_leaf_supported(0x15) {
+        let result = src.cpuid(0x15, 0);
+        if result.ebx != 0 && result.eax != 0 {
+            let crystal_hz = if result.ecx != 0 {
+                result.ecx
+            } else {
+                24_000_000
+            };
+            info.tsc_mhz = Some(
+                (crystal_hz as u64 * result.ebx as u64 / result.eax as u64 / 1_000_000) as u32,
+            );
         }
+    }
 
-        info
+    info
+}
+
+/// Fill TSC and bus frequency from the VMware/KVM timing leaf 0x4000_0010
+/// when the CPU leaves did not provide them.
+pub(crate) fn apply_hypervisor_timing(src: &dyn CpuidSource, info: &mut FrequencyInfo) {
+    let Some(hypervisor) = HypervisorInfo::detect_with(src) else {
+        return;
+    };
+
+    // Other hypervisors use base + 0x10 differently
+    let timing_leaf = hypervisor.base_leaf + 0x10;
+    if !matches!(hypervisor.vendor, HypervisorVendor::VMware | HypervisorVendor::Kvm)
+        || hypervisor.max_leaf < timing_leaf
+    {
+        return;
     }
+
+    // EAX: TSC frequency in kHz, EBX: bus (local APIC timer) frequency in kHz
+    let result = src.cpuid(timing_leaf, 0);
+    if info.tsc_mhz.is_none() && result.eax != 0 {
+        info.tsc_mhz = Some(result.eax / 1000);
+    }
+    if info.bus_mhz.is_none() && result.ebx != 0 {
+        info.bus_mhz = Some(result.ebx / 1000);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(feature = "tracing", tracing::instrument(name = "cpufreq", level = "debug"))]
+pub(crate) fn detect_policies() -> Vec<CpufreqPolicy> {
+    use std::path::Path;
+
+    let Ok(entries) = std::fs::read_dir("/sys/devices/system/cpu/cpufreq") else {
+        return Vec::new();
+    };
+
+    let read = |dir: &Path, file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    let read_mhz = |dir: &Path, file: &str| {
+        read(dir, file)?.parse::<u32>().ok().map(|khz| khz / 1000)
+    };
+
+    let mut policies: Vec<CpufreqPolicy> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let id = entry.file_name().to_str()?.strip_prefix("policy")?.parse().ok()?;
+            let dir = entry.path();
+            Some(CpufreqPolicy {
+                id,
+                cpus: read(&dir, "affected_cpus")
+                    .map(|list| list.split_whitespace().filter_map(|cpu| cpu.parse().ok()).collect())
+                    .unwrap_or_default(),
+                driver: read(&dir, "scaling_driver").unwrap_or_default(),
+                governor: read(&dir, "scaling_governor").unwrap_or_default(),
+                scaling_min_mhz: read_mhz(&dir, "scaling_min_freq"),
+                scaling_max_mhz: read_mhz(&dir, "scaling_max_freq"),
+                hardware_min_mhz: read_mhz(&dir, "cpuinfo_min_freq"),
+                hardware_max_mhz: read_mhz(&dir, "cpuinfo_max_freq"),
+                energy_performance_preference: read(&dir, "energy_performance_preference"),
+            })
+        })
+        .collect();
+
+    policies.sort_by_key(|p| p.id);
+    policies
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn detect_policies() -> Vec<CpufreqPolicy> {
+    Vec::new()
 }
@@ -0,0 +1,167 @@
+//! Marketing Identification
+//!
+//! An embedded table mapping family/model/stepping signatures to the product
+//! codename, approximate process node and the series the part was sold as.
+//! Built with the `identify` feature; the values are for reports, not for
+//! tuning, which should follow `Microarchitecture`.
+
+use crate::microarch::Microarchitecture;
+use crate::vendor::{CpuVendor, VendorInfo};
+use core::fmt;
+
+/// Human-friendly identity of a CPU model, e.g. "Raphael, Zen 4, TSMC 5nm".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CpuIdentity {
+    pub codename: &'static str,
+    pub microarchitecture: Microarchitecture,
+    /// Foundry and node as marketed; the I/O die of chiplet parts is not listed.
+    pub process: &'static str,
+    /// Product series, e.g. "Ryzen 7000" or "Xeon Scalable (4th gen)".
+    pub generation: &'static str,
+}
+
+impl fmt::Display for CpuIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {} ({})",
+            self.codename, self.microarchitecture, self.process, self.generation
+        )
+    }
+}
+
+/// Intel family 6: model, highest stepping, codename, process, series. The
+/// first row whose stepping limit is not exceeded wins.
+const INTEL: &[(u32, u32, &str, &str, &str)] = &[
+    (0x1A, 0xF, "Bloomfield", "Intel 45nm", "Core i7 (1st gen)"),
+    (0x1E, 0xF, "Lynnfield", "Intel 45nm", "Core (1st gen)"),
+    (0x2E, 0xF, "Beckton", "Intel 45nm", "Xeon 7500"),
+    (0x25, 0xF, "Clarkdale", "Intel 32nm", "Core (1st gen)"),
+    (0x2C, 0xF, "Gulftown", "Intel 32nm", "Core i7-980X / Xeon 5600"),
+    (0x2F, 0xF, "Westmere-EX", "Intel 32nm", "Xeon E7"),
+    (0x2A, 0xF, "Sandy Bridge", "Intel 32nm", "Core (2nd gen)"),
+    (0x2D, 0xF, "Sandy Bridge-EP", "Intel 32nm", "Xeon E5"),
+    (0x3A, 0xF, "Ivy Bridge", "Intel 22nm", "Core (3rd gen)"),
+    (0x3E, 0xF, "Ivy Bridge-EP", "Intel 22nm", "Xeon E5 v2"),
+    (0x3C, 0xF, "Haswell", "Intel 22nm", "Core (4th gen)"),
+    (0x45, 0xF, "Haswell-ULT", "Intel 22nm", "Core (4th gen)"),
+    (0x46, 0xF, "Crystal Well", "Intel 22nm", "Core (4th gen)"),
+    (0x3F, 0xF, "Haswell-EP", "Intel 22nm", "Xeon E5 v3"),
+    (0x3D, 0xF, "Broadwell", "Intel 14nm", "Core (5th gen)"),
+    (0x47, 0xF, "Broadwell-H", "Intel 14nm", "Core (5th gen)"),
+    (0x4F, 0xF, "Broadwell-EP", "Intel 14nm", "Xeon E5 v4"),
+    (0x56, 0xF, "Broadwell-DE", "Intel 14nm", "Xeon D-1500"),
+    (0x4E, 0xF, "Skylake-U", "Intel 14nm", "Core (6th gen)"),
+    (0x5E, 0xF, "Skylake-S", "Intel 14nm", "Core (6th gen)"),
+    (0x55, 0x4, "Skylake-SP", "Intel 14nm", "Xeon Scalable (1st gen)"),
+    (0x55, 0x7, "Cascade Lake", "Intel 14nm", "Xeon Scalable (2nd gen)"),
+    (0x55, 0xF, "Cooper Lake", "Intel 14nm", "Xeon Scalable (3rd gen)"),
+    (0x8E, 0x9, "Kaby Lake-U", "Intel 14nm", "Core (7th gen)"),
+    (0x8E, 0xB, "Whiskey Lake", "Intel 14nm", "Core (8th gen)"),
+    (0x8E, 0xF, "Comet Lake-U", "Intel 14nm", "Core (10th gen)"),
+    (0x9E, 0x9, "Kaby Lake-S", "Intel 14nm", "Core (7th gen)"),
+    (0x9E, 0xF, "Coffee Lake", "Intel 14nm", "Core (8th/9th gen)"),
+    (0xA5, 0xF, "Comet Lake-S", "Intel 14nm", "Core (10th gen)"),
+    (0xA6, 0xF, "Comet Lake-U", "Intel 14nm", "Core (10th gen)"),
+    (0x7D, 0xF, "Ice Lake-Y", "Intel 10nm", "Core (10th gen)"),
+    (0x7E, 0xF, "Ice Lake-U", "Intel 10nm", "Core (10th gen)"),
+    (0x6A, 0xF, "Ice Lake-SP", "Intel 10nm", "Xeon Scalable (3rd gen)"),
+    (0x6C, 0xF, "Ice Lake-D", "Intel 10nm", "Xeon D-2700"),
+    (0x8C, 0xF, "Tiger Lake-U", "Intel 10nm SuperFin", "Core (11th gen)"),
+    (0x8D, 0xF, "Tiger Lake-H", "Intel 10nm SuperFin", "Core (11th gen)"),
+    (0xA7, 0xF, "Rocket Lake", "Intel 14nm", "Core (11th gen)"),
+    (0x97, 0xF, "Alder Lake-S", "Intel 7", "Core (12th gen)"),
+    (0x9A, 0xF, "Alder Lake-P", "Intel 7", "Core (12th gen)"),
+    (0xB7, 0xF, "Raptor Lake-S", "Intel 7", "Core (13th/14th gen)"),
+    (0xBA, 0xF, "Raptor Lake-P", "Intel 7", "Core (13th gen)"),
+    (0xBF, 0xF, "Raptor Lake-S", "Intel 7", "Core (13th/14th gen)"),
+    (0xAA, 0xF, "Meteor Lake", "Intel 4", "Core Ultra (Series 1)"),
+    (0xAC, 0xF, "Meteor Lake", "Intel 4", "Core Ultra (Series 1)"),
+    (0xBD, 0xF, "Lunar Lake", "TSMC N3B", "Core Ultra (Series 2)"),
+    (0xC5, 0xF, "Arrow Lake-H", "TSMC N3B", "Core Ultra (Series 2)"),
+    (0xC6, 0xF, "Arrow Lake-S", "TSMC N3B", "Core Ultra (Series 2)"),
+    (0x8F, 0xF, "Sapphire Rapids", "Intel 7", "Xeon Scalable (4th gen)"),
+    (0xCF, 0xF, "Emerald Rapids", "Intel 7", "Xeon Scalable (5th gen)"),
+    (0xAD, 0xF, "Granite Rapids", "Intel 3", "Xeon 6 (P-core)"),
+    (0xAE, 0xF, "Granite Rapids-D", "Intel 3", "Xeon 6 SoC"),
+    (0x5C, 0xF, "Apollo Lake", "Intel 14nm", "Pentium / Celeron (Apollo Lake)"),
+    (0x5F, 0xF, "Denverton", "Intel 14nm", "Atom C3000"),
+    (0x7A, 0xF, "Gemini Lake", "Intel 14nm", "Pentium Silver / Celeron"),
+    (0x86, 0xF, "Snow Ridge", "Intel 10nm", "Atom P5900 / C5000"),
+    (0x96, 0xF, "Elkhart Lake", "Intel 10nm", "Atom x6000E"),
+    (0x9C, 0xF, "Jasper Lake", "Intel 10nm", "Pentium Silver / Celeron N"),
+    (0xBE, 0xF, "Alder Lake-N", "Intel 7", "Intel N-series / Core i3-N"),
+    (0xAF, 0xF, "Sierra Forest", "Intel 3", "Xeon 6 (E-core)"),
+];
+
+/// AMD: family, model, codename, process, series.
+const AMD: &[(u32, u32, &str, &str, &str)] = &[
+    (0x15, 0x01, "Zambezi", "GlobalFoundries 32nm SOI", "FX / Opteron 6200"),
+    (0x15, 0x02, "Vishera", "GlobalFoundries 32nm SOI", "FX / Opteron 6300"),
+    (0x15, 0x10, "Trinity", "GlobalFoundries 32nm SOI", "A-Series (Trinity)"),
+    (0x15, 0x13, "Richland", "GlobalFoundries 32nm SOI", "A-Series (Richland)"),
+    (0x15, 0x30, "Kaveri", "GlobalFoundries 28nm", "A-Series (Kaveri)"),
+    (0x15, 0x38, "Godavari", "GlobalFoundries 28nm", "A-Series (Godavari)"),
+    (0x15, 0x60, "Carrizo", "GlobalFoundries 28nm", "A-Series (Carrizo)"),
+    (0x15, 0x65, "Bristol Ridge", "GlobalFoundries 28nm", "A-Series (Bristol Ridge)"),
+    (0x15, 0x70, "Stoney Ridge", "GlobalFoundries 28nm", "A-Series (Stoney Ridge)"),
+    (0x16, 0x00, "Kabini", "TSMC 28nm", "Athlon / Sempron (Kabini)"),
+    (0x16, 0x30, "Beema", "GlobalFoundries 28nm", "A-Series (Beema)"),
+    (0x17, 0x01, "Summit Ridge / Naples", "GlobalFoundries 14nm", "Ryzen 1000 / EPYC 7001"),
+    (0x17, 0x08, "Pinnacle Ridge", "GlobalFoundries 12nm", "Ryzen 2000"),
+    (0x17, 0x11, "Raven Ridge", "GlobalFoundries 14nm", "Ryzen 2000G"),
+    (0x17, 0x18, "Picasso", "GlobalFoundries 12nm", "Ryzen 3000G"),
+    (0x17, 0x20, "Dali", "GlobalFoundries 14nm", "Athlon 3000"),
+    (0x17, 0x31, "Rome", "TSMC 7nm", "EPYC 7002 / Threadripper 3000"),
+    (0x17, 0x60, "Renoir", "TSMC 7nm", "Ryzen 4000"),
+    (0x17, 0x68, "Lucienne", "TSMC 7nm", "Ryzen 5000U"),
+    (0x17, 0x71, "Matisse", "TSMC 7nm", "Ryzen 3000"),
+    (0x17, 0x90, "Van Gogh", "TSMC 7nm", "Steam Deck APU"),
+    (0x17, 0xA0, "Mendocino", "TSMC 6nm", "Ryzen 7020"),
+    (0x19, 0x01, "Milan", "TSMC 7nm", "EPYC 7003"),
+    (0x19, 0x08, "Chagall", "TSMC 7nm", "Threadripper PRO 5000"),
+    (0x19, 0x11, "Genoa", "TSMC 5nm", "EPYC 9004"),
+    (0x19, 0x18, "Storm Peak", "TSMC 5nm", "Threadripper 7000"),
+    (0x19, 0x21, "Vermeer", "TSMC 7nm", "Ryzen 5000"),
+    (0x19, 0x44, "Rembrandt", "TSMC 6nm", "Ryzen 6000"),
+    (0x19, 0x50, "Cezanne", "TSMC 7nm", "Ryzen 5000G"),
+    (0x19, 0x61, "Raphael", "TSMC 5nm", "Ryzen 7000"),
+    (0x19, 0x74, "Phoenix", "TSMC 4nm", "Ryzen 7040"),
+    (0x19, 0x78, "Phoenix 2", "TSMC 4nm", "Ryzen 8040"),
+    (0x19, 0xA0, "Bergamo / Siena", "TSMC 5nm", "EPYC 97x4 / 8004"),
+    (0x1A, 0x02, "Turin", "TSMC N4P", "EPYC 9005"),
+    (0x1A, 0x11, "Turin Dense", "TSMC N3", "EPYC 9005 (Zen 5c)"),
+    (0x1A, 0x24, "Strix Point", "TSMC N4P", "Ryzen AI 300"),
+    (0x1A, 0x44, "Granite Ridge", "TSMC N4P", "Ryzen 9000"),
+    (0x1A, 0x70, "Strix Halo", "TSMC N4P", "Ryzen AI Max"),
+];
+
+impl VendorInfo {
+    /// Codename, process node and series from the embedded table; `None` for
+    /// signatures it does not list.
+    pub fn identify(&self) -> Option<CpuIdentity> {
+        let (codename, process, generation) = match self.vendor {
+            CpuVendor::Intel if self.family == 6 => INTEL
+                .iter()
+                .find(|&&(model, max_stepping, ..)| {
+                    model == self.model && self.stepping <= max_stepping
+                })
+                .map(|&(_, _, codename, process, generation)| (codename, process, generation))?,
+            CpuVendor::Amd => AMD
+                .iter()
+                .find(|&&(family, model, ..)| family == self.family && model == self.model)
+                .map(|&(_, _, codename, process, generation)| (codename, process, generation))?,
+            CpuVendor::Hygon if self.family == 0x18 => {
+                ("Dhyana", "GlobalFoundries 14nm", "Hygon C86")
+            }
+            _ => return None,
+        };
+        Some(CpuIdentity {
+            codename,
+            microarchitecture: self.microarchitecture(),
+            process,
+            generation,
+        })
+    }
+}
@@ -4,36 +4,211 @@
 //! Follows Unix philosophy: each module does one thing well.
 
 #![cfg(target_arch = "x86_64")]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 pub mod address;
+#[cfg(feature = "std")]
+pub mod baseline;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "std")]
 pub mod cache;
 pub mod cpuid;
+#[cfg(feature = "std")]
+mod descriptors;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod dump;
+pub mod error;
+pub mod feature_flags;
+pub mod feature_id;
 pub mod features;
+#[cfg(feature = "std")]
 pub mod frequency;
+#[cfg(feature = "identify")]
+pub mod identify;
+#[cfg(feature = "std")]
+pub mod memory_encryption;
+pub mod microarch;
+#[cfg(feature = "std")]
+pub mod microcode;
+#[cfg(feature = "std")]
 pub mod msr;
+pub mod os_support;
+#[cfg(feature = "std")]
+mod parallel;
+#[cfg(feature = "std")]
+pub mod numa;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod percpu;
+#[cfg(feature = "std")]
 pub mod platform;
+#[cfg(feature = "std")]
+pub mod prefetch;
+#[cfg(feature = "std")]
 pub mod power;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod proc_cpuinfo;
+#[cfg(feature = "std")]
+pub mod quality;
+#[cfg(feature = "std")]
+pub mod rdt;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod sampler;
+#[cfg(feature = "std")]
+pub mod security;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub mod schema;
+pub mod sgx;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
 pub mod thermal;
+#[cfg(feature = "std")]
 pub mod tlb;
+#[cfg(feature = "std")]
 pub mod topology;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod topology_map;
+#[cfg(feature = "std")]
+pub mod validate;
 pub mod vendor;
+#[cfg(feature = "std")]
+pub mod vendor_ops;
+pub mod xsave;
 
 pub use address::AddressInfo;
-pub use cache::{CacheInfo, CacheLevel, CacheType};
-pub use features::{CpuFeatures, Feature, FeatureCategory, FeatureSet};
-pub use frequency::FrequencyInfo;
+#[cfg(feature = "std")]
+pub use baseline::{Baseline, BaselineReport};
+#[cfg(feature = "std")]
+pub use bench::{BenchResult, LevelResult};
+#[cfg(feature = "std")]
+pub use cache::{
+    line_size, prefetch_size, CacheConflict, CacheDetection, CacheHierarchy, CacheId, CacheInfo,
+    CacheLevel, CacheScope, CacheSource, CacheType,
+};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use cpuid::DevCpuid;
+pub use cpuid::{CpuidSource, HardwareCpuid, RecordedCpuid};
+#[cfg(feature = "std")]
+pub use diff::{CpuInfoDiff, FieldChange, FrequencyDelta};
+#[cfg(feature = "std")]
+pub use dump::CpuidDump;
+pub use error::DetectError;
+pub use feature_flags::{
+    CentaurLeaf1Edx, ExtLeaf1AEax, ExtLeaf1Ecx, ExtLeaf1Edx, ExtLeaf1FEax, ExtLeaf21Eax,
+    ExtLeaf8Ebx, ExtLeaf8Ecx, ExtLeafAEdx, FeatureFlags, Leaf1Ecx, Leaf7Ebx, Leaf7Ecx, Leaf7Edx,
+    Leaf7Sub1Eax, Leaf7Sub1Ebx, Leaf7Sub1Edx, Leaf7Sub2Edx, Leaf7Sub3Edx,
+};
+pub use feature_id::{FeatureBits, FeatureId};
+pub use features::{
+    AmxInfo, AmxPalette, Avx10Info, CpuFeatures, CryptoCaps, EffectiveSimd, Feature,
+    FeatureCategory, FeatureFilter, FeatureSet, FeatureSource, FeatureStatus, FeatureTag,
+    FEATURE_ALIASES, IbsInfo, KeyLockerInfo, MissingFeatures, LINUX_FLAGS, ProcessorTraceInfo,
+    RUST_TARGET_FEATURES, SimdLevel, VectorWidth,
+};
+#[cfg(feature = "std")]
+pub use frequency::{CpufreqPolicy, FrequencyInfo};
+#[cfg(feature = "identify")]
+pub use identify::CpuIdentity;
+#[cfg(feature = "std")]
+pub use memory_encryption::{
+    MemoryEncryptionInfo, SevInfo, TmeActivation, TmeAlgorithm, TmeCapability, TmeInfo,
+};
+pub use microarch::Microarchitecture;
+#[cfg(feature = "std")]
 pub use msr::MsrInfo;
+#[cfg(feature = "std")]
+pub use numa::{NumaInfo, NumaNode};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use percpu::{
+    CoreFeatures, CoreTypeTlb, FeatureAsymmetry, PerCoreFeatures, PerCoreTlb, PerCpuInfo,
+    TlbAsymmetry,
+};
+pub use os_support::OsSupport;
+#[cfg(feature = "std")]
 pub use platform::PlatformInfo;
-pub use power::PowerInfo;
-pub use tlb::{TlbEntry, TlbInfo};
-pub use topology::{CoreType, CpuTopology};
-pub use vendor::{CpuVendor, VendorInfo};
+#[cfg(feature = "std")]
+pub use prefetch::{Prefetcher, PrefetcherInfo};
+#[cfg(feature = "std")]
+pub use power::{MonitorMwaitInfo, PowerInfo, ThreadDirectorInfo};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use proc_cpuinfo::CpuinfoDiff;
+#[cfg(feature = "std")]
+pub use profile::{DetectionStats, StageStats};
+#[cfg(feature = "std")]
+pub use quality::{DetectionQuality, QualityIssue};
+#[cfg(feature = "std")]
+pub use rdt::{BandwidthAllocation, CacheAllocation, RdtInfo, RdtMonitoring};
+#[cfg(feature = "std")]
+pub use report::{ReportFormatter, Verbosity};
+#[cfg(all(feature = "std", feature = "serde"))]
+pub use schema::SchemaError;
+#[cfg(feature = "std")]
+pub use security::{
+    MitigationReport, SpeculationControls, Vulnerability, VulnerabilityAssessment,
+    VulnerabilityStatus,
+};
+pub use sgx::{EpcSection, SgxInfo};
+#[cfg(feature = "std")]
+pub use summary::CpuSummary;
+#[cfg(feature = "std")]
+pub use thermal::ThermalInfo;
+#[cfg(feature = "std")]
+pub use tlb::{
+    Associativity, HugepageSupport, PageSizes, TlbEntry, TlbInfo, TlbKind, TlbLevel,
+    TlbManagement, TlbReach,
+};
+#[cfg(feature = "std")]
+pub use topology::{
+    AmdTopology, ApicIdLayout, ApicIdParts, CoreCounts, CoreIdentity, CoreType, CountConfidence,
+    CountReconciliation, CpuTopology, OsCpuCounts, PackageInfo, TopologyLevel, TopologyLevelType,
+    TopologyLevels,
+};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use topology_map::{CoreNode, DieNode, LogicalCpu, PackageNode, TopologyMap};
+#[cfg(feature = "std")]
+pub use validate::ConsistencyWarning;
+pub use vendor::{
+    CpuVendor, HypervInfo, HypervisorInfo, HypervisorVendor, KvmFeatures, Signature, VendorInfo,
+};
+#[cfg(feature = "std")]
+pub use vendor_ops::VendorOps;
+pub use xsave::{XsaveComponent, XsaveInfo};
 
+#[cfg(feature = "std")]
+use profile::Profiler;
+#[cfg(feature = "std")]
 use std::fmt;
 
-#[derive(Debug, Clone)]
+/// Layout version of serialized `CpuInfo`.
+///
+/// Within one version fields are only ever added. Renaming, removing or
+/// retyping a field bumps the version and adds a migration in `schema`.
+/// Version 0 is the unversioned layout from before the field existed.
+#[cfg(feature = "std")]
+pub const SCHEMA_VERSION: u32 = 5;
+
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpuInfo {
+    /// `SCHEMA_VERSION` for detected values; 0 when built by hand or read from an old dump.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schema_version: u32,
     pub vendor: VendorInfo,
+    /// Derived from `vendor`; `Unknown` when vendor detection is skipped.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub microarchitecture: Microarchitecture,
     pub features: CpuFeatures,
     pub topology: CpuTopology,
     pub cache: Vec<CacheInfo>,
@@ -43,44 +218,347 @@ pub struct CpuInfo {
     pub tlb: TlbInfo,
     pub platform: PlatformInfo,
     pub msr: MsrInfo,
+    pub rdt: RdtInfo,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub xsave: XsaveInfo,
+    /// `None` without SGX.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sgx: Option<SgxInfo>,
+    /// Intel TME-MK and AMD SME/SEV parameters.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory_encryption: MemoryEncryptionInfo,
+    /// Whether the CPUID data looked genuine; always assessed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quality: DetectionQuality,
+}
+
+/// Selects which subsystems `CpuInfo` detects. Skipped subsystems keep their
+/// `Default` value.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DetectOptions {
+    pub vendor: bool,
+    pub features: bool,
+    pub topology: bool,
+    pub cache: bool,
+    pub power: bool,
+    pub frequency: bool,
+    pub address: bool,
+    pub tlb: bool,
+    pub platform: bool,
+    pub msr: bool,
+    pub rdt: bool,
+    pub xsave: bool,
+    pub sgx: bool,
+    pub memory_encryption: bool,
+}
+
+#[cfg(feature = "std")]
+impl DetectOptions {
+    pub fn all() -> Self {
+        Self {
+            vendor: true,
+            features: true,
+            topology: true,
+            cache: true,
+            power: true,
+            frequency: true,
+            address: true,
+            tlb: true,
+            platform: true,
+            msr: true,
+            rdt: true,
+            xsave: true,
+            sgx: true,
+            memory_encryption: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            vendor: false,
+            features: false,
+            topology: false,
+            cache: false,
+            power: false,
+            frequency: false,
+            address: false,
+            tlb: false,
+            platform: false,
+            msr: false,
+            rdt: false,
+            xsave: false,
+            sgx: false,
+            memory_encryption: false,
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl Default for DetectOptions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Chooses subsystems one by one; everything not requested keeps its
+/// `Default` value in the resulting `CpuInfo`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+pub struct CpuInfoBuilder<'a> {
+    options: DetectOptions,
+    source: Option<&'a dyn CpuidSource>,
+}
+
+#[cfg(feature = "std")]
+impl<'a> CpuInfoBuilder<'a> {
+    pub fn vendor(mut self) -> Self {
+        self.options.vendor = true;
+        self
+    }
+
+    pub fn features(mut self) -> Self {
+        self.options.features = true;
+        self
+    }
+
+    pub fn topology(mut self) -> Self {
+        self.options.topology = true;
+        self
+    }
+
+    pub fn cache(mut self) -> Self {
+        self.options.cache = true;
+        self
+    }
+
+    pub fn power(mut self) -> Self {
+        self.options.power = true;
+        self
+    }
+
+    pub fn frequency(mut self) -> Self {
+        self.options.frequency = true;
+        self
+    }
+
+    pub fn address(mut self) -> Self {
+        self.options.address = true;
+        self
+    }
+
+    pub fn tlb(mut self) -> Self {
+        self.options.tlb = true;
+        self
+    }
+
+    pub fn platform(mut self) -> Self {
+        self.options.platform = true;
+        self
+    }
+
+    pub fn msr(mut self) -> Self {
+        self.options.msr = true;
+        self
+    }
+
+    pub fn rdt(mut self) -> Self {
+        self.options.rdt = true;
+        self
+    }
+
+    pub fn xsave(mut self) -> Self {
+        self.options.xsave = true;
+        self
+    }
+
+    pub fn sgx(mut self) -> Self {
+        self.options.sgx = true;
+        self
+    }
+
+    pub fn memory_encryption(mut self) -> Self {
+        self.options.memory_encryption = true;
+        self
+    }
+
+    /// Read CPUID from `source` instead of the executing CPU.
+    pub fn source(mut self, source: &'a dyn CpuidSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn options(&self) -> DetectOptions {
+        self.options
+    }
+
+    pub fn detect(self) -> CpuInfo {
+        self.detect_profiled().0
+    }
+
+    pub fn detect_profiled(self) -> (CpuInfo, DetectionStats) {
+        match self.source {
+            Some(source) => CpuInfo::detect_profiled_with(&self.options, source),
+            None => CpuInfo::detect_profiled(&self.options),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl CpuInfo {
+    /// Start a detection that only runs the subsystems selected on the builder.
+    pub fn builder<'a>() -> CpuInfoBuilder<'a> {
+        CpuInfoBuilder {
+            options: DetectOptions::none(),
+            source: None,
+        }
+    }
+
     pub fn detect() -> Self {
-        Self {
-            vendor: VendorInfo::detect(),
-            features: CpuFeatures::detect(),
-            topology: CpuTopology::detect(),
-            cache: CacheInfo::detect_all(),
-            power: PowerInfo::detect(),
-            frequency: FrequencyInfo::detect(),
-            address: AddressInfo::detect(),
-            tlb: TlbInfo::detect(),
-            platform: PlatformInfo::detect(),
-            msr: MsrInfo::detect(),
+        Self::detect_with_options(&DetectOptions::all())
+    }
+
+    /// `detect` with every list in a stable order, for golden-file comparisons.
+    pub fn detect_canonical() -> Self {
+        Self::detect().canonical()
+    }
+
+    /// Sort features, caches, TLB entries and cpufreq policies so output does
+    /// not depend on detection order. Cache `instance_id`s are cleared, since
+    /// they depend on which CPU ran the detection.
+    pub fn canonical(mut self) -> Self {
+        self.features = self.features.sorted();
+        for cache in &mut self.cache {
+            cache.instance_id = 0;
         }
+        CacheInfo::sort(&mut self.cache);
+        self.tlb = self.tlb.sorted();
+        self.frequency.policies.sort_by_key(|p| p.id);
+        self
+    }
+
+    pub fn detect_with_options(options: &DetectOptions) -> Self {
+        Self::detect_profiled(options).0
+    }
+
+    /// Detect everything from `src` instead of the executing CPU.
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self::detect_profiled_with(&DetectOptions::all(), src).0
+    }
+
+    /// Detect the selected subsystems, recording time and CPUID/MSR reads per stage.
+    pub fn detect_profiled(options: &DetectOptions) -> (Self, DetectionStats) {
+        let (mut info, stats) = Self::detect_profiled_with(options, &HardwareCpuid);
+        info.apply_local_sources(options);
+        (info, stats)
+    }
+
+    /// Fill in what the executing machine knows beyond CPUID: XCR0, OS CPU
+    /// counts, cpufreq policies, TME and Hyper-V MSRs. Shared by the
+    /// sequential and parallel paths.
+    pub(crate) fn apply_local_sources(&mut self, options: &DetectOptions) {
+        if options.features {
+            self.features.apply_os_support(OsSupport::detect());
+        }
+        if options.topology {
+            self.topology.reconcile(OsCpuCounts::detect());
+        }
+        if options.frequency {
+            self.frequency.policies = frequency::detect_policies();
+        }
+        if options.memory_encryption {
+            self.memory_encryption.read_msrs();
+        }
+        if options.vendor
+            && let Some(hyperv) = self.vendor.hypervisor.as_mut().and_then(|h| h.hyperv.as_mut())
+        {
+            hyperv.read_msrs();
+        }
+    }
+
+    /// Like `detect_profiled`, reading CPUID from `src`. Sources outside CPUID
+    /// (XCR0, OS CPU counts, sysfs cpufreq policies, TME and Hyper-V MSRs) are not
+    /// consulted.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "detect", level = "debug", skip_all))]
+    pub fn detect_profiled_with(
+        options: &DetectOptions,
+        src: &dyn CpuidSource,
+    ) -> (Self, DetectionStats) {
+        let mut profiler = Profiler::new();
+        let mut info = Self {
+            schema_version: SCHEMA_VERSION,
+            ..Self::default()
+        };
+
+        info.quality = profiler.stage("quality", || DetectionQuality::assess_with(src));
+
+        if options.vendor {
+            info.vendor = profiler.stage("vendor", || VendorInfo::detect_with(src));
+            info.microarchitecture = info.vendor.microarchitecture();
+        }
+        if options.features {
+            info.features = profiler.stage("features", || CpuFeatures::detect_with(src));
+        }
+        if options.topology {
+            info.topology = profiler.stage("topology", || CpuTopology::detect_with(src));
+        }
+        if options.cache {
+            info.cache = profiler.stage("cache", || CacheInfo::detect_all_with(src));
+        }
+        if options.power {
+            info.power = profiler.stage("power", || PowerInfo::detect_with(src));
+        }
+        if options.frequency {
+            info.frequency = profiler.stage("frequency", || FrequencyInfo::detect_with(src));
+        }
+        if options.address {
+            info.address = profiler.stage("address", || AddressInfo::detect_with(src));
+        }
+        if options.tlb {
+            info.tlb = profiler.stage("tlb", || TlbInfo::detect_with(src));
+        }
+        if options.platform {
+            info.platform = profiler.stage("platform", || PlatformInfo::detect_with(src));
+        }
+        if options.msr {
+            info.msr = profiler.stage("msr", || MsrInfo::detect_with(src));
+        }
+        if options.rdt {
+            info.rdt = profiler.stage("rdt", || RdtInfo::detect_with(src));
+        }
+        if options.xsave {
+            info.xsave = profiler.stage("xsave", || XsaveInfo::detect_with(src));
+        }
+        if options.sgx {
+            info.sgx = profiler.stage("sgx", || SgxInfo::detect_with(src));
+        }
+        if options.memory_encryption {
+            info.memory_encryption =
+                profiler.stage("memory_encryption", || MemoryEncryptionInfo::detect_with(src));
+        }
+
+        profiler.stage("quirks", || vendor_ops::detect(src).apply_quirks(src, &mut info, options));
+
+        (info, profiler.finish())
     }
 }
 
+#[cfg(feature = "std")]
 impl fmt::Display for CpuInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{}", self.vendor)?;
-        writeln!(f, "\n{}", self.topology)?;
-        writeln!(f, "\n{}", self.features)?;
-        writeln!(f, "\nCache Information:")?;
-        for cache in &self.cache {
-            writeln!(f, "  {}", cache)?;
-        }
-        writeln!(
-            f,
-            "\nFrequency: Base={:?} MHz, Max={:?} MHz",
-            self.frequency.base_mhz, self.frequency.max_mhz
-        )?;
-        writeln!(
-            f,
-            "\nAddress Sizes: Physical={} bits, Virtual={} bits",
-            self.address.physical_bits, self.address.virtual_bits
-        )?;
-        Ok(())
+        write!(f, "{}", self.report(Verbosity::Normal))
     }
 }
+
+/// Check the shared `CpuInfo` for a fixed list of features at startup.
+///
+/// Expands to `Result<(), MissingFeatures>` listing every name the CPU lacks;
+/// names follow `CpuFeatures::require` matching.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! require_features {
+    ($($name:literal),+ $(,)?) => {
+        $crate::CpuInfo::get().features.require(&[$($name),+])
+    };
+}
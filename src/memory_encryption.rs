@@ -0,0 +1,203 @@
+//! Memory Encryption Detection
+//!
+//! Intel Total Memory Encryption (TME and multi-key TME) and AMD Secure
+//! Memory Encryption / Secure Encrypted Virtualization parameters. CPUID only
+//! says TME exists; key counts and algorithms live in IA32_TME_CAPABILITY and
+//! IA32_TME_ACTIVATE, which `detect` reads when the msr driver allows it.
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::msr;
+
+const IA32_TME_CAPABILITY: u32 = 0x981;
+const IA32_TME_ACTIVATE: u32 = 0x982;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TmeAlgorithm {
+    AesXts128,
+    AesXts128Integrity,
+    AesXts256,
+}
+
+impl TmeAlgorithm {
+    /// Algorithms in bit order of the capability and activation bitmaps.
+    const ALL: [Self; 3] = [Self::AesXts128, Self::AesXts128Integrity, Self::AesXts256];
+
+    fn from_bitmap(bitmap: u64) -> Vec<Self> {
+        Self::ALL
+            .into_iter()
+            .enumerate()
+            .filter(|(bit, _)| bitmap & (1 << bit) != 0)
+            .map(|(_, algorithm)| algorithm)
+            .collect()
+    }
+}
+
+/// IA32_TME_CAPABILITY.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TmeCapability {
+    pub algorithms: Vec<TmeAlgorithm>,
+    /// Physical address bits available for multi-key TME key IDs.
+    pub max_keyid_bits: u32,
+    /// Multi-key TME keys the CPU supports.
+    pub max_keys: u32,
+}
+
+impl TmeCapability {
+    pub fn from_msr(value: u64) -> Self {
+        Self {
+            algorithms: TmeAlgorithm::from_bitmap(value & 0xF),
+            max_keyid_bits: ((value >> 32) & 0xF) as u32,
+            max_keys: ((value >> 36) & 0x7FFF) as u32,
+        }
+    }
+}
+
+/// IA32_TME_ACTIVATE, as configured by the BIOS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TmeActivation {
+    pub locked: bool,
+    pub enabled: bool,
+    /// Algorithm encrypting memory without a multi-key TME key ID.
+    pub algorithm: Option<TmeAlgorithm>,
+    /// Physical address bits the BIOS assigned to key IDs; 0 disables multi-key TME.
+    pub keyid_bits: u32,
+    /// Algorithms PCONFIG may program into multi-key TME keys.
+    pub mktme_algorithms: Vec<TmeAlgorithm>,
+}
+
+impl TmeActivation {
+    pub fn from_msr(value: u64) -> Self {
+        Self {
+            locked: value & 1 != 0,
+            enabled: value & (1 << 1) != 0,
+            algorithm: TmeAlgorithm::ALL.get(((value >> 4) & 0xF) as usize).copied(),
+            keyid_bits: ((value >> 32) & 0xF) as u32,
+            mktme_algorithms: TmeAlgorithm::from_bitmap(value >> 48),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TmeInfo {
+    /// PCONFIG can program multi-key TME keys (leaf 0x1B target 1).
+    pub pconfig_mktme: bool,
+    /// `None` without MSR access (root, Linux `msr` driver) or from `detect_with`.
+    pub capability: Option<TmeCapability>,
+    pub activation: Option<TmeActivation>,
+}
+
+/// AMD SME/SEV parameters from CPUID leaf 0x8000_001F.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SevInfo {
+    pub sme: bool,
+    pub sev: bool,
+    pub sev_es: bool,
+    pub sev_snp: bool,
+    /// Page table bit that marks a page encrypted.
+    pub c_bit: u32,
+    /// Physical address bits lost when encryption is enabled.
+    pub physical_address_reduction: u32,
+    /// Virtual machine privilege levels under SEV-SNP.
+    pub vmpl_count: u32,
+    /// Encrypted guests supported at once; ASIDs run from 1 to this value.
+    pub max_encrypted_guests: u32,
+    /// Lowest ASID usable by an SEV guest without SEV-ES; the ASIDs below it
+    /// are reserved for SEV-ES and SEV-SNP guests.
+    pub min_sev_asid: u32,
+}
+
+impl SevInfo {
+    /// ASIDs available to SEV-ES and SEV-SNP guests.
+    pub fn sev_es_asids(&self) -> u32 {
+        self.min_sev_asid.saturating_sub(1).min(self.max_encrypted_guests)
+    }
+
+    /// ASIDs available to plain SEV guests.
+    pub fn sev_asids(&self) -> u32 {
+        self.max_encrypted_guests - self.sev_es_asids()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryEncryptionInfo {
+    /// Intel TME; `None` without TME_EN (leaf 7 ECX[13]).
+    pub tme: Option<TmeInfo>,
+    /// AMD SME/SEV; `None` without leaf 0x8000_001F.
+    pub sev: Option<SevInfo>,
+}
+
+impl MemoryEncryptionInfo {
+    /// CPUID plus the TME MSRs of the executing CPU.
+    pub fn detect() -> Self {
+        let mut info = Self::detect_with(&HardwareCpuid);
+        info.read_msrs();
+        info
+    }
+
+    /// CPUID only; TME `capability` and `activation` stay `None`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "memory_encryption", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self {
+            tme: detect_tme(src),
+            sev: detect_sev(src),
+        }
+    }
+
+    /// Fill TME `capability` and `activation` from this CPU's MSRs.
+    pub fn read_msrs(&mut self) {
+        if let Some(tme) = &mut self.tme {
+            tme.capability = msr::read_current(IA32_TME_CAPABILITY)
+                .ok()
+                .map(TmeCapability::from_msr);
+            tme.activation = msr::read_current(IA32_TME_ACTIVATE)
+                .ok()
+                .map(TmeActivation::from_msr);
+        }
+    }
+}
+
+fn detect_tme(src: &dyn CpuidSource) -> Option<TmeInfo> {
+    if !src.is_leaf_supported(7) || src.cpuid(7, 0).ecx & (1 << 13) == 0 {
+        return None;
+    }
+
+    let mut info = TmeInfo::default();
+    if src.is_leaf_supported(0x1B) {
+        // Subleaves of type 1 list PCONFIG target IDs; type 0 ends the list
+        for subleaf in 0..16 {
+            let result = src.cpuid(0x1B, subleaf);
+            if result.eax & 0xFFF != 1 {
+                break;
+            }
+            if [result.ebx, result.ecx, result.edx].contains(&1) {
+                info.pconfig_mktme = true;
+            }
+        }
+    }
+    Some(info)
+}
+
+fn detect_sev(src: &dyn CpuidSource) -> Option<SevInfo> {
+    if !src.is_leaf_supported(0x8000_001F) {
+        return None;
+    }
+
+    let result = src.cpuid(0x8000_001F, 0);
+    Some(SevInfo {
+        sme: result.eax & 1 != 0,
+        sev: result.eax & (1 << 1) != 0,
+        sev_es: result.eax & (1 << 3) != 0,
+        sev_snp: result.eax & (1 << 4) != 0,
+        c_bit: result.ebx & 0x3F,
+        physical_address_reduction: (result.ebx >> 6) & 0x3F,
+        vmpl_count: (result.ebx >> 12) & 0xF,
+        max_encrypted_guests: result.ecx,
+        min_sev_asid: result.edx,
+    })
+}
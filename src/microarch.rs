@@ -0,0 +1,205 @@
+//! Microarchitecture Identification
+//!
+//! Maps the vendor and family/model/stepping signature to the core design.
+//! Tuning decisions (gather throughput, AVX-512 frequency licences, cache
+//! policies) follow the core, not the feature bits.
+
+use crate::vendor::{CpuVendor, VendorInfo};
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Microarchitecture {
+    // Intel big cores
+    Nehalem,
+    Westmere,
+    SandyBridge,
+    IvyBridge,
+    Haswell,
+    Broadwell,
+    Skylake,
+    SkylakeServer,
+    CascadeLake,
+    CooperLake,
+    KabyLake,
+    CoffeeLake,
+    CometLake,
+    IceLake,
+    IceLakeServer,
+    TigerLake,
+    RocketLake,
+    AlderLake,
+    RaptorLake,
+    MeteorLake,
+    LunarLake,
+    ArrowLake,
+    SapphireRapids,
+    EmeraldRapids,
+    GraniteRapids,
+    // Intel Atom cores
+    Goldmont,
+    GoldmontPlus,
+    Tremont,
+    Gracemont,
+    SierraForest,
+    // AMD
+    Bulldozer,
+    Piledriver,
+    Steamroller,
+    Excavator,
+    Jaguar,
+    Puma,
+    Zen,
+    ZenPlus,
+    Zen2,
+    Zen3,
+    Zen4,
+    Zen5,
+    // Hygon
+    Dhyana,
+    #[default]
+    Unknown,
+}
+
+impl Microarchitecture {
+    /// `family` and `model` are the display values with the extended fields folded in.
+    pub fn from_signature(vendor: &CpuVendor, family: u32, model: u32, stepping: u32) -> Self {
+        match vendor {
+            CpuVendor::Intel if family == 6 => intel(model, stepping),
+            CpuVendor::Amd => amd(family, model),
+            CpuVendor::Hygon if family == 0x18 => Self::Dhyana,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Nehalem => "Nehalem",
+            Self::Westmere => "Westmere",
+            Self::SandyBridge => "Sandy Bridge",
+            Self::IvyBridge => "Ivy Bridge",
+            Self::Haswell => "Haswell",
+            Self::Broadwell => "Broadwell",
+            Self::Skylake => "Skylake",
+            Self::SkylakeServer => "Skylake-SP",
+            Self::CascadeLake => "Cascade Lake",
+            Self::CooperLake => "Cooper Lake",
+            Self::KabyLake => "Kaby Lake",
+            Self::CoffeeLake => "Coffee Lake",
+            Self::CometLake => "Comet Lake",
+            Self::IceLake => "Ice Lake",
+            Self::IceLakeServer => "Ice Lake-SP",
+            Self::TigerLake => "Tiger Lake",
+            Self::RocketLake => "Rocket Lake",
+            Self::AlderLake => "Alder Lake",
+            Self::RaptorLake => "Raptor Lake",
+            Self::MeteorLake => "Meteor Lake",
+            Self::LunarLake => "Lunar Lake",
+            Self::ArrowLake => "Arrow Lake",
+            Self::SapphireRapids => "Sapphire Rapids",
+            Self::EmeraldRapids => "Emerald Rapids",
+            Self::GraniteRapids => "Granite Rapids",
+            Self::Goldmont => "Goldmont",
+            Self::GoldmontPlus => "Goldmont Plus",
+            Self::Tremont => "Tremont",
+            Self::Gracemont => "Gracemont",
+            Self::SierraForest => "Sierra Forest",
+            Self::Bulldozer => "Bulldozer",
+            Self::Piledriver => "Piledriver",
+            Self::Steamroller => "Steamroller",
+            Self::Excavator => "Excavator",
+            Self::Jaguar => "Jaguar",
+            Self::Puma => "Puma",
+            Self::Zen => "Zen",
+            Self::ZenPlus => "Zen+",
+            Self::Zen2 => "Zen 2",
+            Self::Zen3 => "Zen 3",
+            Self::Zen4 => "Zen 4",
+            Self::Zen5 => "Zen 5",
+            Self::Dhyana => "Dhyana",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// Hybrid parts mixing performance and efficiency cores.
+    pub fn is_hybrid(&self) -> bool {
+        matches!(
+            self,
+            Self::AlderLake | Self::RaptorLake | Self::MeteorLake | Self::LunarLake | Self::ArrowLake
+        )
+    }
+}
+
+fn intel(model: u32, stepping: u32) -> Microarchitecture {
+    use Microarchitecture::*;
+
+    match model {
+        0x1A | 0x1E | 0x1F | 0x2E => Nehalem,
+        0x25 | 0x2C | 0x2F => Westmere,
+        0x2A | 0x2D => SandyBridge,
+        0x3A | 0x3E => IvyBridge,
+        0x3C | 0x3F | 0x45 | 0x46 => Haswell,
+        0x3D | 0x47 | 0x4F | 0x56 => Broadwell,
+        0x4E | 0x5E => Skylake,
+        // One model number covers three server generations, told apart by stepping
+        0x55 => match stepping {
+            0..=4 => SkylakeServer,
+            5..=7 => CascadeLake,
+            _ => CooperLake,
+        },
+        0x8E => KabyLake,
+        0x9E if stepping >= 0xA => CoffeeLake,
+        0x9E => KabyLake,
+        0xA5 | 0xA6 => CometLake,
+        0x7D | 0x7E => IceLake,
+        0x6A | 0x6C => IceLakeServer,
+        0x8C | 0x8D => TigerLake,
+        0xA7 => RocketLake,
+        0x97 | 0x9A => AlderLake,
+        0xB7 | 0xBA | 0xBF => RaptorLake,
+        0xAA | 0xAC => MeteorLake,
+        0xBD => LunarLake,
+        0xC5 | 0xC6 => ArrowLake,
+        0x8F => SapphireRapids,
+        0xCF => EmeraldRapids,
+        0xAD | 0xAE => GraniteRapids,
+        0x5C | 0x5F => Goldmont,
+        0x7A => GoldmontPlus,
+        0x86 | 0x96 | 0x9C => Tremont,
+        0xBE => Gracemont,
+        0xAF => SierraForest,
+        _ => Unknown,
+    }
+}
+
+fn amd(family: u32, model: u32) -> Microarchitecture {
+    use Microarchitecture::*;
+
+    match (family, model) {
+        (0x15, 0x00..=0x0F) => Bulldozer,
+        (0x15, 0x10..=0x1F) => Piledriver,
+        (0x15, 0x30..=0x3F) => Steamroller,
+        (0x15, 0x60..=0x7F) => Excavator,
+        (0x16, 0x00..=0x0F) => Jaguar,
+        (0x16, 0x30..=0x3F) => Puma,
+        (0x17, 0x08 | 0x18) => ZenPlus,
+        (0x17, 0x00..=0x2F) => Zen,
+        (0x17, _) => Zen2,
+        (0x19, 0x10..=0x1F | 0x60..=0x7F | 0xA0..=0xAF) => Zen4,
+        (0x19, _) => Zen3,
+        (0x1A, _) => Zen5,
+        _ => Unknown,
+    }
+}
+
+impl VendorInfo {
+    pub fn microarchitecture(&self) -> Microarchitecture {
+        Microarchitecture::from_signature(&self.vendor, self.family, self.model, self.stepping)
+    }
+}
+
+impl fmt::Display for Microarchitecture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,51 @@
+//! Microcode Revision
+//!
+//! The loaded microcode patch level, which CPUID does not report. Linux
+//! exposes it in sysfs and `/proc/cpuinfo`; elsewhere only the MSR is left,
+//! and reading it needs root.
+
+use crate::msr;
+use crate::vendor::{CpuVendor, VendorInfo};
+
+/// `IA32_BIOS_SIGN_ID` on Intel, `MSR_AMD64_PATCH_LEVEL` on AMD.
+const MSR_MICROCODE_REVISION: u32 = 0x8B;
+
+impl VendorInfo {
+    /// Microcode revision of CPU 0, from sysfs, then `/proc/cpuinfo`, then
+    /// MSR 0x8B. `None` when every source is unavailable.
+    pub fn microcode_revision(&self) -> Option<u32> {
+        read_sysfs()
+            .or_else(read_proc_cpuinfo)
+            .or_else(|| self.read_msr())
+    }
+
+    /// The MSR holds the revision that was current at the last CPUID leaf 1
+    /// after a write of 0; the kernel does that whenever it loads an update.
+    fn read_msr(&self) -> Option<u32> {
+        let value = msr::read(0, MSR_MICROCODE_REVISION).ok()?;
+        match self.vendor {
+            CpuVendor::Intel | CpuVendor::Zhaoxin => Some((value >> 32) as u32),
+            CpuVendor::Amd | CpuVendor::Hygon => Some(value as u32),
+            _ => None,
+        }
+    }
+}
+
+fn read_sysfs() -> Option<u32> {
+    let version = std::fs::read_to_string("/sys/devices/system/cpu/cpu0/microcode/version").ok()?;
+    parse_hex(&version)
+}
+
+/// `microcode\t: 0x2b000590` in the first processor entry.
+fn read_proc_cpuinfo() -> Option<u32> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        if key.trim() == "microcode" { parse_hex(value) } else { None }
+    })
+}
+
+fn parse_hex(value: &str) -> Option<u32> {
+    let value = value.trim();
+    u32::from_str_radix(value.strip_prefix("0x").unwrap_or(value), 16).ok()
+}
@@ -1,11 +1,19 @@
 //! MSR (Model-Specific Register) Information
 //!
-//! Provides information about MSR support (read-only, no actual MSR access).
-//! Cross-platform safe - only reports capabilities, doesn't access MSRs.
+//! Provides information about MSR support. `MsrInfo` only reports capabilities
+//! and never touches MSRs; `read` offers optional raw access through the Linux
+//! msr driver (`/dev/cpu/N/msr`, requires root and the `msr` module).
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use std::cell::Cell;
+use std::io;
 
-#[derive(Debug, Clone)]
+thread_local! {
+    static MSR_READS: Cell<u64> = const { Cell::new(0) };
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MsrInfo {
     pub msr_support: bool,
     pub rdmsr_wrmsr: bool,
@@ -20,33 +28,28 @@ pub struct MsrInfo {
 
 impl MsrInfo {
     pub fn detect() -> Self {
-        let mut info = Self {
-            msr_support: false,
-            rdmsr_wrmsr: false,
-            msr_platform_info: false,
-            msr_temperature: false,
-            msr_perf_status: false,
-            msr_perf_ctl: false,
-            msr_misc_enable: false,
-            msr_energy_perf_bias: false,
-            msr_turbo_ratio_limit: false,
-        };
-
-        if is_leaf_supported(1) {
-            let result = cpuid(1, 0);
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "msr", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let mut info = Self::default();
+
+        if src.is_leaf_supported(1) {
+            let result = src.cpuid(1, 0);
             info.msr_support = (result.edx & (1 << 5)) != 0;
             info.rdmsr_wrmsr = info.msr_support;
         }
 
-        if is_leaf_supported(6) {
-            let result = cpuid(6, 0);
+        if src.is_leaf_supported(6) {
+            let result = src.cpuid(6, 0);
             info.msr_temperature = (result.eax & (1 << 0)) != 0;
             info.msr_turbo_ratio_limit = (result.eax & (1 << 1)) != 0;
             info.msr_energy_perf_bias = (result.ecx & (1 << 3)) != 0;
         }
 
-        if is_leaf_supported(7) {
-            let result = cpuid(7, 0);
+        if src.is_leaf_supported(7) {
+            let result = src.cpuid(7, 0);
             info.msr_platform_info = (result.ecx & (1 << 15)) != 0;
         }
 
@@ -57,3 +60,52 @@ impl MsrInfo {
         info
     }
 }
+
+/// Read an MSR on the given logical CPU.
+#[cfg(target_os = "linux")]
+pub fn read(cpu: usize, register: u32) -> io::Result<u64> {
+    read_from(&open(cpu)?, register)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read(_cpu: usize, _register: u32) -> io::Result<u64> {
+    MSR_READS.with(|reads| reads.set(reads.get() + 1));
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "MSR access is only supported on Linux",
+    ))
+}
+
+/// Read an MSR on the logical CPU the calling thread currently runs on. Pin
+/// the thread first if the value may differ between cores.
+#[cfg(target_os = "linux")]
+pub fn read_current(register: u32) -> io::Result<u64> {
+    // SAFETY: sched_getcpu has no preconditions
+    let cpu = unsafe { libc::sched_getcpu() };
+    read(usize::try_from(cpu).map_err(|_| io::Error::last_os_error())?, register)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_current(register: u32) -> io::Result<u64> {
+    read(0, register)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn open(cpu: usize) -> io::Result<std::fs::File> {
+    std::fs::File::open(format!("/dev/cpu/{}/msr", cpu))
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn read_from(file: &std::fs::File, register: u32) -> io::Result<u64> {
+    use std::os::unix::fs::FileExt;
+
+    MSR_READS.with(|reads| reads.set(reads.get() + 1));
+    let mut buf = [0u8; 8];
+    file.read_exact_at(&mut buf, register as u64)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Number of MSR reads issued so far on the calling thread.
+pub fn msr_read_count() -> u64 {
+    MSR_READS.with(Cell::get)
+}
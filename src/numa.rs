@@ -0,0 +1,135 @@
+//! NUMA Topology (Linux)
+//!
+//! Nodes, their CPUs and memory, and the SLIT distances between them, as the
+//! kernel lists them under `/sys/devices/system/node`. `link_packages` ties
+//! each node to the APIC-derived packages of `CpuTopology`.
+
+use crate::topology::CpuTopology;
+use std::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumaNode {
+    pub id: u32,
+    /// Online logical CPUs; empty for memory-only nodes such as CXL or HBM.
+    pub cpus: Vec<usize>,
+    /// `MemTotal` of the node; `None` when `meminfo` is unreadable.
+    pub memory_bytes: Option<u64>,
+    /// Distance to every node in `NumaInfo::nodes` order; 10 is local.
+    pub distances: Vec<u32>,
+    /// IDs of the packages whose CPUs sit in this node; empty until
+    /// `NumaInfo::link_packages`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub packages: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NumaInfo {
+    /// Online nodes in ID order.
+    pub nodes: Vec<NumaNode>,
+}
+
+impl NumaInfo {
+    /// `None` where the kernel exposes no node directory (not Linux, or a
+    /// kernel without NUMA support).
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "numa", level = "debug"))]
+    pub fn detect() -> Option<Self> {
+        detect_sysfs()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Node holding logical CPU `cpu`.
+    pub fn node_of_cpu(&self, cpu: usize) -> Option<&NumaNode> {
+        self.nodes.iter().find(|node| node.cpus.contains(&cpu))
+    }
+
+    /// Distance from node `from` to node `to`, by node ID.
+    pub fn distance(&self, from: u32, to: u32) -> Option<u32> {
+        let to = self.nodes.iter().position(|node| node.id == to)?;
+        let from = self.nodes.iter().find(|node| node.id == from)?;
+        from.distances.get(to).copied()
+    }
+
+    /// Total memory across all nodes.
+    pub fn memory_bytes(&self) -> u64 {
+        self.nodes.iter().filter_map(|node| node.memory_bytes).sum()
+    }
+
+    /// Fill every node's `packages` from `topology.packages`, which needs
+    /// `CpuTopology::detect_packages` to have run.
+    pub fn link_packages(&mut self, topology: &CpuTopology) {
+        for node in &mut self.nodes {
+            node.packages = topology
+                .packages
+                .iter()
+                .filter(|package| package.cpus.iter().any(|cpu| node.cpus.contains(cpu)))
+                .map(|package| package.id)
+                .collect();
+        }
+    }
+}
+
+impl fmt::Display for NumaNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {}: {} CPUs", self.id, self.cpus.len())?;
+        if let Some(bytes) = self.memory_bytes {
+            write!(f, ", {} MiB", bytes >> 20)?;
+        }
+        if !self.packages.is_empty() {
+            let packages: Vec<String> = self.packages.iter().map(|id| id.to_string()).collect();
+            write!(f, ", package {}", packages.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_sysfs() -> Option<NumaInfo> {
+    use crate::percpu;
+    use std::path::Path;
+
+    let entries = std::fs::read_dir("/sys/devices/system/node").ok()?;
+
+    let read = |dir: &Path, file: &str| {
+        std::fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    let mut nodes: Vec<NumaNode> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let id = entry.file_name().to_str()?.strip_prefix("node")?.parse().ok()?;
+            let dir = entry.path();
+            // "Node 0 MemTotal:  6158152 kB"
+            let memory_bytes = read(&dir, "meminfo").and_then(|meminfo| {
+                let line = meminfo.lines().find(|line| line.contains("MemTotal:"))?;
+                let kb = line.split_whitespace().rev().nth(1)?.parse::<u64>().ok()?;
+                Some(kb << 10)
+            });
+            Some(NumaNode {
+                id,
+                cpus: read(&dir, "cpulist")
+                    .and_then(|list| percpu::parse_cpu_list(&list))
+                    .unwrap_or_default(),
+                memory_bytes,
+                distances: read(&dir, "distance")
+                    .map(|list| list.split_whitespace().filter_map(|d| d.parse().ok()).collect())
+                    .unwrap_or_default(),
+                packages: Vec::new(),
+            })
+        })
+        .collect();
+
+    nodes.sort_by_key(|node| node.id);
+    Some(NumaInfo { nodes })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_sysfs() -> Option<NumaInfo> {
+    None
+}
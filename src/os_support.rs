@@ -0,0 +1,121 @@
+//! OS-Enabled Register State
+//!
+//! CPUID reports what the CPU implements; XCR0 (and IA32_XSS for supervisor
+//! state) reports which register state the OS saves across context switches.
+//! An instruction is only usable when both agree.
+
+use crate::cpuid;
+
+/// XCR0 SSE state (XMM registers).
+pub const XCR0_SSE: u64 = 1 << 1;
+/// XCR0 AVX state (upper halves of YMM registers).
+pub const XCR0_AVX: u64 = 1 << 2;
+/// XCR0 MPX bound registers and BNDCSR.
+pub const XCR0_MPX: u64 = 0b11 << 3;
+/// XCR0 AVX-512 opmask, upper ZMM0-15 and ZMM16-31 state.
+pub const XCR0_AVX512: u64 = 0b111 << 5;
+/// XCR0 protection keys (PKRU).
+pub const XCR0_PKRU: u64 = 1 << 9;
+/// XCR0 AMX tile configuration and tile data.
+pub const XCR0_AMX: u64 = 0b11 << 17;
+/// XCR0 APX extended general-purpose registers.
+pub const XCR0_APX: u64 = 1 << 19;
+/// IA32_XSS Processor Trace state.
+pub const XSS_PT: u64 = 1 << 8;
+/// IA32_XSS user-mode CET state.
+pub const XSS_CET_U: u64 = 1 << 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OsSupport {
+    pub xcr0: u64,
+    /// IA32_XSS; `None` without XSAVES or without MSR access (root, Linux `msr` driver).
+    pub xss: Option<u64>,
+}
+
+impl OsSupport {
+    /// State enabled on the executing CPU, or `None` when the OS has not
+    /// enabled XSAVE at all (then no AVX-class feature is usable).
+    pub fn detect() -> Option<Self> {
+        let xcr0 = cpuid::xcr0()?;
+        Some(Self { xcr0, xss: read_xss() })
+    }
+
+    pub fn avx_enabled(&self) -> bool {
+        self.xcr0_has(XCR0_SSE | XCR0_AVX)
+    }
+
+    pub fn avx512_enabled(&self) -> bool {
+        self.xcr0_has(XCR0_SSE | XCR0_AVX | XCR0_AVX512)
+    }
+
+    pub fn amx_enabled(&self) -> bool {
+        self.xcr0_has(XCR0_AMX)
+    }
+
+    pub fn apx_enabled(&self) -> bool {
+        self.xcr0_has(XCR0_APX)
+    }
+
+    /// Whether the OS enables the state feature `name` needs. Features without
+    /// XSAVE-managed state, and supervisor state when IA32_XSS is unreadable,
+    /// count as enabled.
+    pub fn enables(&self, name: &str) -> bool {
+        match required_state(name) {
+            Some(State::Xcr0(bits)) => self.xcr0_has(bits),
+            Some(State::Xss(bits)) => self.xss.is_none_or(|xss| xss & bits == bits),
+            None => true,
+        }
+    }
+
+    fn xcr0_has(&self, bits: u64) -> bool {
+        self.xcr0 & bits == bits
+    }
+}
+
+enum State {
+    Xcr0(u64),
+    Xss(u64),
+}
+
+/// True if `name` needs XSAVE-managed state the OS must enable.
+pub(crate) fn needs_os_state(name: &str) -> bool {
+    required_state(name).is_some()
+}
+
+fn required_state(name: &str) -> Option<State> {
+    let avx = XCR0_SSE | XCR0_AVX;
+    let state = match name {
+        _ if name.starts_with("AVX512") || name.starts_with("AVX10") => {
+            State::Xcr0(avx | XCR0_AVX512)
+        }
+        _ if name.starts_with("AMX_") => State::Xcr0(XCR0_AMX),
+        "AVX" | "AVX2" | "FMA" | "F16C" | "FMA4" | "XOP" | "VAES" | "VPCLMULQDQ" | "AVX_VNNI"
+        | "AVX_IFMA" | "AVX_NE_CONVERT" | "AVX_VNNI_INT8" | "AVX_VNNI_INT16" => State::Xcr0(avx),
+        "APX_F" => State::Xcr0(XCR0_APX),
+        "MPX" => State::Xcr0(XCR0_MPX),
+        "PKU" => State::Xcr0(XCR0_PKRU),
+        "CET_SS" | "CET_IBT" => State::Xss(XSS_CET_U),
+        "INTEL_PT" => State::Xss(XSS_PT),
+        _ => return None,
+    };
+    Some(state)
+}
+
+/// IA32_XSS of the executing CPU; only present with XSAVES (leaf 0xD.1 EAX[3]).
+#[cfg(all(feature = "std", target_os = "linux"))]
+fn read_xss() -> Option<u64> {
+    const IA32_XSS: u32 = 0xDA0;
+
+    if cpuid::max_cpuid_leaf() < 0xD || cpuid::cpuid(0xD, 1).eax & (1 << 3) == 0 {
+        return None;
+    }
+    // SAFETY: sched_getcpu has no preconditions
+    let cpu = usize::try_from(unsafe { libc::sched_getcpu() }).ok()?;
+    crate::msr::read(cpu, IA32_XSS).ok()
+}
+
+#[cfg(not(all(feature = "std", target_os = "linux")))]
+fn read_xss() -> Option<u64> {
+    None
+}
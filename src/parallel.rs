@@ -0,0 +1,122 @@
+//! Parallel Detection
+//!
+//! Runs independent subsystems on scoped threads, for hypervisors that trap
+//! every CPUID leaf. On Linux the helper threads are pinned to the caller's
+//! CPU so hybrid parts report the same core as sequential detection.
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::vendor_ops;
+use crate::{
+    AddressInfo, CacheInfo, CpuFeatures, CpuInfo, CpuTopology, DetectOptions, DetectionQuality,
+    FrequencyInfo, MemoryEncryptionInfo, MsrInfo, PlatformInfo, PowerInfo, RdtInfo, SCHEMA_VERSION,
+    SgxInfo, TlbInfo, VendorInfo, XsaveInfo,
+};
+use std::thread::{self, Scope, ScopedJoinHandle};
+
+impl CpuInfo {
+    /// `detect` with subsystems running concurrently; the result is identical.
+    pub fn detect_parallel() -> Self {
+        let options = DetectOptions::all();
+        let mut info = Self::detect_parallel_with(&options, &HardwareCpuid);
+        info.apply_local_sources(&options);
+        info
+    }
+
+    /// Parallel `detect_profiled_with` without the per-stage statistics.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "detect_parallel", level = "debug", skip_all))]
+    pub fn detect_parallel_with(options: &DetectOptions, src: &(dyn CpuidSource + Sync)) -> Self {
+        let cpu = current_cpu();
+
+        thread::scope(|scope| {
+            let quality = spawn(scope, true, cpu, || DetectionQuality::assess_with(src));
+            let vendor = spawn(scope, options.vendor, cpu, || VendorInfo::detect_with(src));
+            let features = spawn(scope, options.features, cpu, || CpuFeatures::detect_with(src));
+            let topology = spawn(scope, options.topology, cpu, || CpuTopology::detect_with(src));
+            let cache = spawn(scope, options.cache, cpu, || CacheInfo::detect_all_with(src));
+            let power = spawn(scope, options.power, cpu, || PowerInfo::detect_with(src));
+            let frequency = spawn(scope, options.frequency, cpu, || FrequencyInfo::detect_with(src));
+            let address = spawn(scope, options.address, cpu, || AddressInfo::detect_with(src));
+            let tlb = spawn(scope, options.tlb, cpu, || TlbInfo::detect_with(src));
+            let platform = spawn(scope, options.platform, cpu, || PlatformInfo::detect_with(src));
+            let msr = spawn(scope, options.msr, cpu, || MsrInfo::detect_with(src));
+            let rdt = spawn(scope, options.rdt, cpu, || RdtInfo::detect_with(src));
+            let xsave = spawn(scope, options.xsave, cpu, || XsaveInfo::detect_with(src));
+            let sgx = spawn(scope, options.sgx, cpu, || SgxInfo::detect_with(src));
+            let memory_encryption = spawn(scope, options.memory_encryption, cpu, || {
+                MemoryEncryptionInfo::detect_with(src)
+            });
+
+            let mut info = Self {
+                schema_version: SCHEMA_VERSION,
+                ..Self::default()
+            };
+            join(quality, &mut info.quality);
+            join(vendor, &mut info.vendor);
+            info.microarchitecture = info.vendor.microarchitecture();
+            join(features, &mut info.features);
+            join(topology, &mut info.topology);
+            join(cache, &mut info.cache);
+            join(power, &mut info.power);
+            join(frequency, &mut info.frequency);
+            join(address, &mut info.address);
+            join(tlb, &mut info.tlb);
+            join(platform, &mut info.platform);
+            join(msr, &mut info.msr);
+            join(rdt, &mut info.rdt);
+            join(xsave, &mut info.xsave);
+            join(sgx, &mut info.sgx);
+            join(memory_encryption, &mut info.memory_encryption);
+
+            // Quirks patch several subsystems, so they run once everything is in
+            vendor_ops::detect(src).apply_quirks(src, &mut info, options);
+            info
+        })
+    }
+}
+
+fn spawn<'scope, T: Send + 'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    enabled: bool,
+    cpu: Option<usize>,
+    detect: impl FnOnce() -> T + Send + 'scope,
+) -> Option<ScopedJoinHandle<'scope, T>> {
+    enabled.then(|| {
+        #[cfg(feature = "tracing")]
+        let span = tracing::Span::current();
+        scope.spawn(move || {
+            #[cfg(feature = "tracing")]
+            let _span = span.enter();
+            pin(cpu);
+            detect()
+        })
+    })
+}
+
+fn join<T>(handle: Option<ScopedJoinHandle<'_, T>>, slot: &mut T) {
+    if let Some(handle) = handle {
+        *slot = handle
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_cpu() -> Option<usize> {
+    // SAFETY: sched_getcpu has no preconditions
+    usize::try_from(unsafe { libc::sched_getcpu() }).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_cpu() -> Option<usize> {
+    None
+}
+
+/// Best effort: an unpinned thread still detects correctly on symmetric CPUs.
+fn pin(cpu: Option<usize>) {
+    #[cfg(target_os = "linux")]
+    if let Some(cpu) = cpu {
+        let _ = crate::percpu::pin_current_thread(cpu);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = cpu;
+}
@@ -0,0 +1,435 @@
+//! Per-CPU Detection (Linux)
+//!
+//! Runs detection pinned to each online CPU to find cores whose ISA, caches or
+//! frequencies differ (hybrid parts, mixed steppings, multi-socket systems,
+//! partial hypervisor pinning) and the feature set that is safe to dispatch on
+//! regardless of scheduling.
+
+use crate::cache::CacheInfo;
+use crate::cpuid::HardwareCpuid;
+use crate::features::{Avx10Info, CpuFeatures};
+use crate::frequency::FrequencyInfo;
+use crate::tlb::{TlbEntry, TlbInfo};
+use crate::topology::{self, CoreType, CpuTopology, PackageInfo};
+use crate::CpuInfo;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::thread;
+
+/// Online logical CPUs as listed in `/sys/devices/system/cpu/online`.
+pub fn online_cpus() -> io::Result<Vec<usize>> {
+    let list = std::fs::read_to_string("/sys/devices/system/cpu/online")?;
+    parse_cpu_list(list.trim())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed CPU list"))
+}
+
+/// Parse a kernel CPU list such as `0-3,8,10-11`.
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// Run `f` on a helper thread pinned to `cpu` and return its result.
+pub fn run_on_cpu<T: Send>(cpu: usize, f: impl FnOnce() -> T + Send) -> io::Result<T> {
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                pin_current_thread(cpu)?;
+                Ok(f())
+            })
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("per-CPU detection thread panicked")))
+    })
+}
+
+pub(crate) fn pin_current_thread(cpu: usize) -> io::Result<()> {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "CPU index out of range"));
+    }
+
+    // SAFETY: cpu_set_t is plain data; CPU_SET is bounds-checked above and
+    // sched_setaffinity only reads the set we pass in.
+    let result = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Per-core parameters of one logical CPU.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerCpuInfo {
+    pub cpu: usize,
+    pub apic_id: u32,
+    pub core_type: CoreType,
+    pub caches: Vec<CacheInfo>,
+    /// CPUID frequencies as seen on this CPU, plus the cpufreq policy covering it.
+    pub frequency: FrequencyInfo,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tlb: TlbInfo,
+    /// Leaf 0x1A native model ID; `None` on non-hybrid parts.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub native_model_id: Option<u32>,
+}
+
+impl CpuInfo {
+    /// Detect caches, TLBs, core type, APIC ID and frequency pinned to every online CPU.
+    pub fn detect_per_cpu() -> io::Result<Vec<PerCpuInfo>> {
+        Self::detect_per_cpu_on(&online_cpus()?)
+    }
+
+    pub fn detect_per_cpu_on(cpus: &[usize]) -> io::Result<Vec<PerCpuInfo>> {
+        cpus.iter()
+            .map(|&cpu| {
+                let mut info = run_on_cpu(cpu, || {
+                    let apic_id = topology::apic_id();
+                    let mut caches = CacheInfo::detect_all();
+                    for cache in &mut caches {
+                        cache.assign_instance(apic_id);
+                    }
+                    PerCpuInfo {
+                        cpu,
+                        apic_id,
+                        core_type: CoreType::detect(),
+                        caches,
+                        frequency: FrequencyInfo::detect(),
+                        tlb: TlbInfo::detect().sorted(),
+                        native_model_id: CoreType::native_model_id_with(&HardwareCpuid),
+                    }
+                })?;
+                info.frequency.policies.retain(|policy| policy.cpus.contains(&cpu));
+                Ok(info)
+            })
+            .collect()
+    }
+}
+
+impl CpuTopology {
+    /// Fill `performance_cores`, `efficient_cores` and `smt_cpus` by pinning
+    /// to every online CPU.
+    pub fn detect_core_types(&mut self) -> io::Result<()> {
+        self.apply_core_types(&pinned_core_types()?);
+        Ok(())
+    }
+
+    /// Fill `packages` from the APIC ID of every online CPU, so multi-socket
+    /// systems report all sockets rather than the detecting CPU's.
+    pub fn detect_packages(&mut self) -> io::Result<()> {
+        self.apply_packages(&pinned_core_types()?);
+        Ok(())
+    }
+
+    /// `detect_core_types` and `detect_packages` from per-CPU results
+    /// already at hand.
+    pub fn apply_per_cpu(&mut self, per_cpu: &[PerCpuInfo]) {
+        let cpus: Vec<_> =
+            per_cpu.iter().map(|info| (info.cpu, info.apic_id, info.core_type)).collect();
+        self.apply_core_types(&cpus);
+        self.apply_packages(&cpus);
+    }
+
+    /// SMT siblings of every online CPU, the CPU itself included, keyed by
+    /// CPU number. Read from sysfs `thread_siblings_list`, or derived by
+    /// pinning to each CPU and grouping APIC IDs when sysfs lacks it.
+    pub fn smt_siblings(&self) -> io::Result<BTreeMap<usize, Vec<usize>>> {
+        let cpus = online_cpus()?;
+        let sysfs: Option<BTreeMap<usize, Vec<usize>>> = cpus
+            .iter()
+            .map(|&cpu| {
+                let path =
+                    format!("/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list", cpu);
+                let list = std::fs::read_to_string(path).ok()?;
+                Some((cpu, parse_cpu_list(list.trim())?))
+            })
+            .collect();
+        match sysfs {
+            Some(siblings) => Ok(siblings),
+            None => Ok(self.siblings_by_apic_id(&pinned_core_types()?)),
+        }
+    }
+
+    /// Group logical CPUs into cores by the APIC ID above the SMT field.
+    fn siblings_by_apic_id(&self, cpus: &[(usize, u32, CoreType)]) -> BTreeMap<usize, Vec<usize>> {
+        let core_of = |apic_id: u32| {
+            let parts = self.apic_layout.decompose(apic_id);
+            (parts.package, parts.core)
+        };
+        cpus.iter()
+            .map(|&(cpu, apic_id, _)| {
+                let core = core_of(apic_id);
+                let siblings = cpus
+                    .iter()
+                    .filter(|&&(_, other_id, _)| core_of(other_id) == core)
+                    .map(|&(other, _, _)| other)
+                    .collect();
+                (cpu, siblings)
+            })
+            .collect()
+    }
+
+    fn apply_packages(&mut self, cpus: &[(usize, u32, CoreType)]) {
+        let mut packages: Vec<(PackageInfo, BTreeSet<u32>)> = Vec::new();
+        for &(cpu, apic_id, _) in cpus {
+            let parts = self.apic_layout.decompose(apic_id);
+            let index = match packages.iter().position(|(package, _)| package.id == parts.package) {
+                Some(index) => index,
+                None => {
+                    let package = PackageInfo { id: parts.package, ..Default::default() };
+                    packages.push((package, BTreeSet::new()));
+                    packages.len() - 1
+                }
+            };
+            let (package, cores) = &mut packages[index];
+            package.logical_processors += 1;
+            package.cpus.push(cpu);
+            cores.insert(parts.core);
+        }
+        self.packages = packages
+            .into_iter()
+            .map(|(package, cores)| PackageInfo { cores: cores.len() as u32, ..package })
+            .collect();
+        self.packages.sort_by_key(|package| package.id);
+    }
+
+    fn apply_core_types(&mut self, cpus: &[(usize, u32, CoreType)]) {
+        let core_of = |apic_id: u32| {
+            let parts = self.apic_layout.decompose(apic_id);
+            (parts.package, parts.core)
+        };
+        let cores_of_type = |core_type: CoreType| {
+            let cores: BTreeSet<_> = cpus
+                .iter()
+                .filter(|(_, _, kind)| *kind == core_type)
+                .map(|&(_, apic_id, _)| core_of(apic_id))
+                .collect();
+            cores.len() as u32
+        };
+
+        let hybrid = cpus.iter().any(|(_, _, kind)| *kind != CoreType::Unknown);
+        self.performance_cores = hybrid.then(|| cores_of_type(CoreType::Performance));
+        self.efficient_cores = hybrid.then(|| cores_of_type(CoreType::Efficient));
+        self.smt_cpus = self
+            .siblings_by_apic_id(cpus)
+            .into_iter()
+            .filter(|(_, siblings)| siblings.len() > 1)
+            .map(|(cpu, _)| cpu)
+            .collect();
+    }
+}
+
+/// CPU index, APIC ID and core type of every online CPU.
+fn pinned_core_types() -> io::Result<Vec<(usize, u32, CoreType)>> {
+    online_cpus()?
+        .into_iter()
+        .map(|cpu| run_on_cpu(cpu, || (cpu, topology::apic_id(), CoreType::detect())))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreFeatures {
+    pub cpu: usize,
+    pub core_type: CoreType,
+    pub features: CpuFeatures,
+}
+
+/// A feature that is supported on some CPUs but not on others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureAsymmetry {
+    pub name: String,
+    pub supported_on: Vec<usize>,
+    pub missing_on: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerCoreFeatures {
+    pub cores: Vec<CoreFeatures>,
+}
+
+impl PerCoreFeatures {
+    /// Detect features on every online CPU.
+    pub fn detect() -> io::Result<Self> {
+        Self::detect_on(&online_cpus()?)
+    }
+
+    pub fn detect_on(cpus: &[usize]) -> io::Result<Self> {
+        let cores = cpus
+            .iter()
+            .map(|&cpu| {
+                run_on_cpu(cpu, || CoreFeatures {
+                    cpu,
+                    core_type: CoreType::detect(),
+                    features: CpuFeatures::detect(),
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self { cores })
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.asymmetries().is_empty()
+    }
+
+    /// Features whose support differs across the detected CPUs.
+    pub fn asymmetries(&self) -> Vec<FeatureAsymmetry> {
+        let mut names: BTreeSet<&str> = BTreeSet::new();
+        for core in &self.cores {
+            names.extend(core.features.basic.iter_names().map(|(name, _)| name));
+            names.extend(core.features.all_supported().iter().map(|f| f.name.as_str()));
+        }
+
+        let mut asymmetries = Vec::new();
+        for name in names {
+            let (supported, missing): (Vec<&CoreFeatures>, Vec<&CoreFeatures>) = self
+                .cores
+                .iter()
+                .partition(|core| core.features.has_feature(name));
+
+            if !missing.is_empty() {
+                asymmetries.push(FeatureAsymmetry {
+                    name: name.to_string(),
+                    supported_on: supported.iter().map(|core| core.cpu).collect(),
+                    missing_on: missing.iter().map(|core| core.cpu).collect(),
+                });
+            }
+        }
+        asymmetries
+    }
+
+    /// Features supported on every detected CPU, safe for runtime dispatch.
+    pub fn intersection(&self) -> CpuFeatures {
+        let Some((first, rest)) = self.cores.split_first() else {
+            return CpuFeatures::default();
+        };
+
+        let mut features = first.features.clone();
+        for core in rest {
+            features.basic &= core.features.basic;
+            features.avx10 = match (features.avx10, core.features.avx10) {
+                (Some(a), Some(b)) => Some(Avx10Info {
+                    version: a.version.min(b.version),
+                    supports_128: a.supports_128 && b.supports_128,
+                    supports_256: a.supports_256 && b.supports_256,
+                    supports_512: a.supports_512 && b.supports_512,
+                }),
+                _ => None,
+            };
+            if features.amx != core.features.amx {
+                features.amx = None;
+            }
+            if features.processor_trace != core.features.processor_trace {
+                features.processor_trace = None;
+            }
+            if features.ibs != core.features.ibs {
+                features.ibs = None;
+            }
+            if features.key_locker != core.features.key_locker {
+                features.key_locker = None;
+            }
+        }
+
+        for feature in &mut features.all_features {
+            feature.supported = feature.supported
+                && rest.iter().all(|core| {
+                    core.features.all_features.iter().any(|f| {
+                        f.name == feature.name && f.source == feature.source && f.supported
+                    })
+                });
+        }
+        features.reindex();
+
+        features
+    }
+}
+
+/// TLBs of one core type, taken from the first CPU of that type.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreTypeTlb {
+    pub core_type: CoreType,
+    pub cpus: Vec<usize>,
+    pub tlb: TlbInfo,
+}
+
+/// A TLB that some core types have and others do not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlbAsymmetry {
+    pub entry: TlbEntry,
+    pub present_on: Vec<CoreType>,
+    pub missing_on: Vec<CoreType>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerCoreTlb {
+    pub core_types: Vec<CoreTypeTlb>,
+}
+
+impl PerCoreTlb {
+    /// Detect TLBs on every online CPU and group them by core type.
+    pub fn detect() -> io::Result<Self> {
+        Ok(Self::from_per_cpu(&CpuInfo::detect_per_cpu()?))
+    }
+
+    /// Group already detected CPUs by core type, in order of first appearance.
+    pub fn from_per_cpu(per_cpu: &[PerCpuInfo]) -> Self {
+        let mut core_types: Vec<CoreTypeTlb> = Vec::new();
+        for info in per_cpu {
+            match core_types.iter_mut().find(|group| group.core_type == info.core_type) {
+                Some(group) => group.cpus.push(info.cpu),
+                None => core_types.push(CoreTypeTlb {
+                    core_type: info.core_type,
+                    cpus: vec![info.cpu],
+                    tlb: info.tlb.clone(),
+                }),
+            }
+        }
+        Self { core_types }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.asymmetries().is_empty()
+    }
+
+    /// TLB entries not identical on every core type, e.g. a P-core STLB the
+    /// E-cores size differently. Empty on non-hybrid parts.
+    pub fn asymmetries(&self) -> Vec<TlbAsymmetry> {
+        let entries: BTreeSet<TlbEntry> = self
+            .core_types
+            .iter()
+            .flat_map(|group| group.tlb.entries.iter().copied())
+            .collect();
+
+        let mut asymmetries = Vec::new();
+        for entry in entries {
+            let (present, missing): (Vec<&CoreTypeTlb>, Vec<&CoreTypeTlb>) = self
+                .core_types
+                .iter()
+                .partition(|group| group.tlb.entries.contains(&entry));
+
+            if !missing.is_empty() {
+                asymmetries.push(TlbAsymmetry {
+                    entry,
+                    present_on: present.iter().map(|group| group.core_type).collect(),
+                    missing_on: missing.iter().map(|group| group.core_type).collect(),
+                });
+            }
+        }
+        asymmetries
+    }
+}
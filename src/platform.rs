@@ -2,9 +2,10 @@
 //!
 //! Detects platform-specific information and capabilities.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlatformInfo {
     pub max_cpuid_leaf: u32,
     pub max_extended_leaf: u32,
@@ -25,29 +26,22 @@ pub struct PlatformInfo {
 
 impl PlatformInfo {
     pub fn detect() -> Self {
-        let max_cpuid = cpuid(0, 0).eax;
-        let max_extended = cpuid(0x8000_0000, 0).eax;
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "platform", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let max_cpuid = src.cpuid(0, 0).eax;
+        let max_extended = src.cpuid(0x8000_0000, 0).eax;
 
         let mut info = Self {
             max_cpuid_leaf: max_cpuid,
             max_extended_leaf: max_extended,
-            microcode_update: false,
-            machine_check: false,
-            mtrr: false,
-            page_attribute_table: false,
-            page_size_extension: false,
-            time_stamp_counter: false,
-            model_specific_registers: false,
-            apic_on_chip: false,
-            x2apic: false,
-            local_apic_timer_always_running: false,
-            tsc_invariant: false,
-            tsc_deadline: false,
-            xapic: false,
+            ..Self::default()
         };
 
-        if is_leaf_supported(1) {
-            let result = cpuid(1, 0);
+        if src.is_leaf_supported(1) {
+            let result = src.cpuid(1, 0);
 
             info.time_stamp_counter = (result.edx & (1 << 4)) != 0;
             info.model_specific_registers = (result.edx & (1 << 5)) != 0;
@@ -62,13 +56,13 @@ impl PlatformInfo {
             info.xapic = (result.ecx & (1 << 21)) != 0;
         }
 
-        if is_leaf_supported(6) {
-            let result = cpuid(6, 0);
+        if src.is_leaf_supported(6) {
+            let result = src.cpuid(6, 0);
             info.local_apic_timer_always_running = (result.eax & (1 << 2)) != 0;
         }
 
-        if is_leaf_supported(0x8000_0007) {
-            let result = cpuid(0x8000_0007, 0);
+        if src.is_leaf_supported(0x8000_0007) {
+            let result = src.cpuid(0x8000_0007, 0);
             info.tsc_invariant = (result.edx & (1 << 8)) != 0;
         }
 
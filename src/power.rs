@@ -2,9 +2,10 @@
 //!
 //! Comprehensive power management and thermal feature detection.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PowerInfo {
     pub digital_thermal_sensor: bool,
     pub turbo_boost: bool,
@@ -29,45 +30,96 @@ pub struct PowerInfo {
     pub therm_status: bool,
     pub tm2: bool,
     pub num_interrupt_thresholds: u32,
+    /// `None` without MONITOR/MWAIT or leaf 5.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub monitor_mwait: Option<MonitorMwaitInfo>,
+    /// Hardware Feedback Interface table layout; `None` without HFI.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hfi: Option<ThreadDirectorInfo>,
+}
+
+/// Hardware Feedback Interface and Thread Director enumeration (leaf 6 ECX
+/// and EDX), enough to size and index the HFI table the OS allocates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThreadDirectorInfo {
+    /// Rows carry a performance capability (EDX[0]).
+    pub performance_capability: bool,
+    /// Rows carry an energy efficiency capability (EDX[1]).
+    pub efficiency_capability: bool,
+    /// Table size in bytes, a whole number of 4 KiB pages (EDX[11:8]).
+    pub table_size: u32,
+    /// Row of the detecting logical CPU (EDX[31:16]); differs per CPU.
+    pub table_index: u32,
+    /// Thread Director classes (ECX[15:8]); 0 with plain HFI.
+    pub classes: u32,
+}
+
+impl ThreadDirectorInfo {
+    /// Classes the table has columns for; plain HFI has one.
+    pub fn table_classes(&self) -> u32 {
+        self.classes.max(1)
+    }
+
+    /// Capabilities per class.
+    pub fn capabilities(&self) -> u32 {
+        self.performance_capability as u32 + self.efficiency_capability as u32
+    }
+
+    /// Bytes of the header and of each row: one byte per capability and
+    /// class, rounded up to 8. The header follows an 8-byte timestamp.
+    pub fn row_size(&self) -> u32 {
+        (self.capabilities() * self.table_classes()).div_ceil(8) * 8
+    }
+
+    /// Byte offset of the detecting CPU's row in the table.
+    pub fn row_offset(&self) -> u32 {
+        8 + self.row_size() * (1 + self.table_index)
+    }
+}
+
+/// MONITOR/MWAIT parameters from CPUID leaf 5.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonitorMwaitInfo {
+    /// Smallest monitor-line size in bytes.
+    pub min_line_size: u32,
+    /// Largest monitor-line size in bytes.
+    pub max_line_size: u32,
+    /// MWAIT extensions are enumerated; without them the fields below are unset.
+    pub extensions: bool,
+    /// MWAIT wakes on interrupts even when they are masked (ECX[1]).
+    pub interrupt_break_event: bool,
+    /// MWAIT sub-states per C-state, C0 first; which C-state an MWAIT hint
+    /// reaches is model specific.
+    pub c_substates: [u32; 8],
+}
+
+impl MonitorMwaitInfo {
+    /// Total MWAIT hints across all C-states.
+    pub fn total_substates(&self) -> u32 {
+        self.c_substates.iter().sum()
+    }
 }
 
 impl PowerInfo {
     pub fn detect() -> Self {
-        let mut info = Self {
-            digital_thermal_sensor: false,
-            turbo_boost: false,
-            arat: false,
-            pln: false,
-            pts: false,
-            hwp: false,
-            hwp_notification: false,
-            hwp_activity_window: false,
-            hwp_epp: false,
-            hwp_package: false,
-            hdc: false,
-            turbo_boost_max_3: false,
-            hwp_capabilities: false,
-            hwp_peci: false,
-            flexible_hwp: false,
-            hwp_fast_access: false,
-            hw_feedback: false,
-            ignore_idle_hwp: false,
-            thread_director: false,
-            therm_interrupt: false,
-            therm_status: false,
-            tm2: false,
-            num_interrupt_thresholds: 0,
-        };
-
-        if is_leaf_supported(1) {
-            let result = cpuid(1, 0);
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "power", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let mut info = Self::default();
+
+        if src.is_leaf_supported(1) {
+            let result = src.cpuid(1, 0);
             info.therm_status = (result.ecx & (1 << 3)) != 0;
             info.tm2 = (result.ecx & (1 << 8)) != 0;
             info.therm_interrupt = (result.edx & (1 << 22)) != 0;
         }
 
-        if is_leaf_supported(6) {
-            let result = cpuid(6, 0);
+        if src.is_leaf_supported(6) {
+            let result = src.cpuid(6, 0);
 
             info.digital_thermal_sensor = (result.eax & (1 << 0)) != 0;
             info.turbo_boost = (result.eax & (1 << 1)) != 0;
@@ -89,7 +141,34 @@ impl PowerInfo {
             info.ignore_idle_hwp = (result.eax & (1 << 20)) != 0;
             info.thread_director = (result.eax & (1 << 23)) != 0;
 
-            info.num_interrupt_thresholds = (result.ebx & 0xF) as u32;
+            info.num_interrupt_thresholds = result.ebx & 0xF;
+
+            if info.hw_feedback {
+                info.hfi = Some(ThreadDirectorInfo {
+                    performance_capability: result.edx & 1 != 0,
+                    efficiency_capability: result.edx & (1 << 1) != 0,
+                    table_size: (((result.edx >> 8) & 0xF) + 1) * 4096,
+                    table_index: result.edx >> 16,
+                    classes: if info.thread_director { (result.ecx >> 8) & 0xFF } else { 0 },
+                });
+            }
+        }
+
+        // Leaf 5 is only meaningful with MONITOR (leaf 1 ECX[3])
+        if src.is_leaf_supported(5) && src.cpuid(1, 0).ecx & (1 << 3) != 0 {
+            let result = src.cpuid(5, 0);
+            let extensions = result.ecx & 1 != 0;
+            info.monitor_mwait = Some(MonitorMwaitInfo {
+                min_line_size: result.eax & 0xFFFF,
+                max_line_size: result.ebx & 0xFFFF,
+                extensions,
+                interrupt_break_event: extensions && result.ecx & (1 << 1) != 0,
+                c_substates: if extensions {
+                    core::array::from_fn(|state| (result.edx >> (state * 4)) & 0xF)
+                } else {
+                    [0; 8]
+                },
+            });
         }
 
         info
@@ -0,0 +1,103 @@
+//! Hardware Prefetcher Configuration
+//!
+//! Decodes the prefetcher control MSRs (Intel `MSR_MISC_FEATURE_CONTROL` 0x1A4,
+//! AMD `PrefetchControl` 0xC000_0108). Reading them requires MSR access.
+
+use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::msr;
+use crate::vendor::{CpuVendor, VendorInfo};
+use std::io;
+
+pub const MSR_MISC_FEATURE_CONTROL: u32 = 0x1A4;
+pub const MSR_AMD_PREFETCH_CONTROL: u32 = 0xC000_0108;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Prefetcher {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PrefetcherInfo {
+    pub msr: u32,
+    pub raw: u64,
+    pub prefetchers: Vec<Prefetcher>,
+}
+
+impl PrefetcherInfo {
+    /// Read and decode the prefetcher control MSR on `cpu`.
+    pub fn read(cpu: usize) -> io::Result<Self> {
+        let register = Self::control_register().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no known prefetcher control MSR on this CPU",
+            )
+        })?;
+        Ok(Self::decode(register, msr::read(cpu, register)?))
+    }
+
+    /// Decode a raw control MSR value. Every defined bit is a *disable* bit.
+    pub fn decode(register: u32, raw: u64) -> Self {
+        let bits: &[(u32, &str, &str)] = match register {
+            MSR_MISC_FEATURE_CONTROL => &[
+                (0, "L2_STREAM", "L2 hardware (streamer) prefetcher"),
+                (1, "L2_ADJACENT", "L2 adjacent cache line prefetcher"),
+                (2, "DCU_STREAM", "L1 data (DCU next-line) prefetcher"),
+                (3, "DCU_IP", "L1 data IP-stride prefetcher"),
+            ],
+            MSR_AMD_PREFETCH_CONTROL => &[
+                (0, "L1_STREAM", "L1 stream prefetcher"),
+                (1, "L1_STRIDE", "L1 stride prefetcher"),
+                (2, "L1_REGION", "L1 region prefetcher"),
+                (3, "L2_STREAM", "L2 stream prefetcher"),
+                (5, "L2_UP_DOWN", "L2 up/down prefetcher"),
+            ],
+            _ => &[],
+        };
+
+        let prefetchers = bits
+            .iter()
+            .map(|&(bit, name, description)| Prefetcher {
+                name,
+                description,
+                enabled: (raw & (1 << bit)) == 0,
+            })
+            .collect();
+
+        Self {
+            msr: register,
+            raw,
+            prefetchers,
+        }
+    }
+
+    pub fn all_enabled(&self) -> bool {
+        self.prefetchers.iter().all(|p| p.enabled)
+    }
+
+    /// Prefetcher control MSR of the executing CPU, without reading it: 0x1A4
+    /// on Intel, 0xC000_0108 on AMD parts reporting PREFETCH_CTL
+    /// (leaf 0x8000_0021 EAX[13]).
+    pub fn control_register() -> Option<u32> {
+        control_msr(VendorInfo::detect().vendor)
+    }
+}
+
+/// Prefetcher control MSR for this vendor, if the CPU has one.
+fn control_msr(vendor: CpuVendor) -> Option<u32> {
+    match vendor {
+        CpuVendor::Intel => Some(MSR_MISC_FEATURE_CONTROL),
+        CpuVendor::Amd | CpuVendor::Hygon => {
+            // Leaf 0x8000_0021 EAX bit 13: PREFETCH_CTL
+            if is_leaf_supported(0x8000_0021) && (cpuid(0x8000_0021, 0).eax & (1 << 13)) != 0 {
+                Some(MSR_AMD_PREFETCH_CONTROL)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,84 @@
+//! /proc/cpuinfo Comparison (Linux)
+//!
+//! The kernel masks CPUID bits it will not let user space rely on (TSX after
+//! the TAA mitigation, SGX without the driver, AVX-512 when XCR0 lacks ZMM
+//! state, `clearcpuid=`) and adds flags of its own. Comparing its `flags` line
+//! with CPUID answers "why doesn't my program see this feature".
+
+use crate::features::{CpuFeatures, LINUX_FLAGS};
+use std::fmt;
+use std::io;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuinfoDiff {
+    /// Features CPUID reports whose flag the kernel does not export, by crate
+    /// name: features the kernel disabled, and on recent kernels also flags it
+    /// tracks without printing (`fzrm`, `lkgs`).
+    pub hidden_by_kernel: Vec<String>,
+    /// Kernel flags without a CPUID-reported counterpart: synthesized by the
+    /// kernel (`constant_tsc`, `ibrs_enhanced`) or decoded by it but not by this crate.
+    pub kernel_only: Vec<String>,
+}
+
+impl CpuinfoDiff {
+    pub fn is_empty(&self) -> bool {
+        self.hidden_by_kernel.is_empty() && self.kernel_only.is_empty()
+    }
+}
+
+impl fmt::Display for CpuinfoDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Kernel flags match CPUID");
+        }
+        if !self.hidden_by_kernel.is_empty() {
+            writeln!(f, "Hidden by the kernel: {}", self.hidden_by_kernel.join(", "))?;
+        }
+        if !self.kernel_only.is_empty() {
+            writeln!(f, "Kernel only: {}", self.kernel_only.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl CpuFeatures {
+    /// Compare with the `flags` line of `/proc/cpuinfo`. Detect on the CPU the
+    /// first `processor` entry describes (usually CPU 0) on hybrid parts.
+    pub fn diff_against_proc_cpuinfo(&self) -> io::Result<CpuinfoDiff> {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo")?;
+        let flags = flags_line(&cpuinfo).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "no flags line in /proc/cpuinfo")
+        })?;
+        Ok(self.diff_against_cpuinfo_flags(flags))
+    }
+
+    /// Compare with a space-separated kernel `flags` value, e.g. from another
+    /// machine's `/proc/cpuinfo`.
+    pub fn diff_against_cpuinfo_flags(&self, flags: &str) -> CpuinfoDiff {
+        let kernel: Vec<&str> = flags.split_whitespace().collect();
+        let ours = self.linux_flags();
+
+        let hidden_by_kernel = ours
+            .iter()
+            .filter(|flag| !kernel.contains(flag))
+            .filter_map(|flag| LINUX_FLAGS.iter().find(|(_, f)| f == flag))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let kernel_only = kernel
+            .iter()
+            .filter(|flag| !ours.contains(flag))
+            .map(|flag| flag.to_string())
+            .collect();
+
+        CpuinfoDiff { hidden_by_kernel, kernel_only }
+    }
+}
+
+/// Value of the first `flags` line.
+fn flags_line(cpuinfo: &str) -> Option<&str> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "flags").then_some(value)
+    })
+}
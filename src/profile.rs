@@ -0,0 +1,96 @@
+//! Detection Profiling
+//!
+//! Measures how long each detection stage takes and how many CPUID/MSR reads it issues.
+
+use crate::cpuid::cpuid_read_count;
+use crate::msr::msr_read_count;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StageStats {
+    pub name: &'static str,
+    pub duration: Duration,
+    pub cpuid_reads: u64,
+    pub msr_reads: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DetectionStats {
+    pub stages: Vec<StageStats>,
+    pub total: Duration,
+}
+
+impl DetectionStats {
+    pub fn cpuid_reads(&self) -> u64 {
+        self.stages.iter().map(|s| s.cpuid_reads).sum()
+    }
+
+    pub fn msr_reads(&self) -> u64 {
+        self.stages.iter().map(|s| s.msr_reads).sum()
+    }
+
+    pub fn stage(&self, name: &str) -> Option<&StageStats> {
+        self.stages.iter().find(|s| s.name == name)
+    }
+}
+
+impl fmt::Display for DetectionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Detection Statistics:")?;
+        for stage in &self.stages {
+            writeln!(
+                f,
+                "  {:<10} {:>10.3?}  {:>4} CPUID  {:>4} MSR",
+                stage.name, stage.duration, stage.cpuid_reads, stage.msr_reads
+            )?;
+        }
+        write!(
+            f,
+            "  {:<10} {:>10.3?}  {:>4} CPUID  {:>4} MSR",
+            "total",
+            self.total,
+            self.cpuid_reads(),
+            self.msr_reads()
+        )
+    }
+}
+
+pub(crate) struct Profiler {
+    start: Instant,
+    stages: Vec<StageStats>,
+}
+
+impl Profiler {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            stages: Vec::new(),
+        }
+    }
+
+    pub(crate) fn stage<T>(&mut self, name: &'static str, detect: impl FnOnce() -> T) -> T {
+        let cpuid_before = cpuid_read_count();
+        let msr_before = msr_read_count();
+        let start = Instant::now();
+
+        let value = detect();
+
+        self.stages.push(StageStats {
+            name,
+            duration: start.elapsed(),
+            cpuid_reads: cpuid_read_count() - cpuid_before,
+            msr_reads: msr_read_count() - msr_before,
+        });
+        value
+    }
+
+    pub(crate) fn finish(self) -> DetectionStats {
+        DetectionStats {
+            stages: self.stages,
+            total: self.start.elapsed(),
+        }
+    }
+}
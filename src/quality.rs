@@ -0,0 +1,145 @@
+//! Detection Quality
+//!
+//! Flags CPUID data that is zeroed, filtered or contradictory (sandboxes,
+//! trapping hypervisors, broken emulators) so consumers do not mistake the
+//! resulting defaults for real hardware properties.
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use std::fmt;
+
+/// Highest plausible standard or extended leaf offset; larger values are garbage.
+const MAX_PLAUSIBLE_LEAVES: u32 = 0x100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QualityIssue {
+    /// Leaf 0 reports no leaves beyond itself.
+    MaxLeafTooLow(u32),
+    /// Leaf 0 reports an implausible number of leaves.
+    MaxLeafImplausible(u32),
+    /// Leaf 0 vendor string is zeros or not printable ASCII.
+    InvalidVendorString,
+    /// Leaf 1 EAX family/model/stepping signature is zero.
+    ZeroSignature,
+    /// 0x8000_0000 does not report the extended range every x86_64 CPU has.
+    ExtendedLeavesMissing(u32),
+    /// SSE or SSE2 reported absent, which no x86_64 CPU can be.
+    MissingArchitecturalFeatures,
+    /// HTT is set but leaf 1 EBX reports zero logical processors.
+    ZeroLogicalProcessors,
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxLeafTooLow(max) => write!(f, "maximum CPUID leaf is {:#x}", max),
+            Self::MaxLeafImplausible(max) => {
+                write!(f, "maximum CPUID leaf {:#x} is implausible", max)
+            }
+            Self::InvalidVendorString => write!(f, "vendor string is empty or garbled"),
+            Self::ZeroSignature => write!(f, "family/model/stepping signature is zero"),
+            Self::ExtendedLeavesMissing(max) => {
+                write!(f, "maximum extended leaf is {:#x}", max)
+            }
+            Self::MissingArchitecturalFeatures => {
+                write!(f, "SSE/SSE2 reported absent on an x86_64 CPU")
+            }
+            Self::ZeroLogicalProcessors => write!(f, "HTT set with zero logical processors"),
+        }
+    }
+}
+
+/// How far the detected values can be trusted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DetectionQuality {
+    /// CPUID looks like real hardware.
+    #[default]
+    Trusted,
+    /// Some leaves are zeroed or contradictory; affected values fall back to defaults.
+    Degraded(Vec<QualityIssue>),
+    /// CPUID is effectively unavailable; nearly every value is a default.
+    Faulted(Vec<QualityIssue>),
+}
+
+impl DetectionQuality {
+    pub fn assess() -> Self {
+        Self::assess_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "quality", level = "debug", skip_all))]
+    pub fn assess_with(src: &dyn CpuidSource) -> Self {
+        let mut issues = Vec::new();
+
+        let leaf0 = src.cpuid(0, 0);
+        if leaf0.eax < 1 {
+            issues.push(QualityIssue::MaxLeafTooLow(leaf0.eax));
+        } else if leaf0.eax >= MAX_PLAUSIBLE_LEAVES {
+            issues.push(QualityIssue::MaxLeafImplausible(leaf0.eax));
+        }
+
+        let vendor: Vec<u8> = [leaf0.ebx, leaf0.edx, leaf0.ecx]
+            .iter()
+            .flat_map(|r| r.to_le_bytes())
+            .collect();
+        let vendor_valid = vendor.iter().all(|b| b.is_ascii_graphic() || *b == b' ')
+            && vendor.iter().any(|b| *b != b' ');
+        if !vendor_valid {
+            issues.push(QualityIssue::InvalidVendorString);
+        }
+
+        // Leaf 1 is architectural; read it even when leaf 0 under-reports
+        let leaf1 = src.cpuid(1, 0);
+        if leaf1.eax == 0 {
+            issues.push(QualityIssue::ZeroSignature);
+        }
+        if (leaf1.edx & (1 << 25)) == 0 || (leaf1.edx & (1 << 26)) == 0 {
+            issues.push(QualityIssue::MissingArchitecturalFeatures);
+        }
+        if (leaf1.edx & (1 << 28)) != 0 && (leaf1.ebx >> 16) & 0xFF == 0 {
+            issues.push(QualityIssue::ZeroLogicalProcessors);
+        }
+
+        // Long mode itself is reported in 0x8000_0001, so x86_64 implies at least that leaf
+        let max_extended = src.cpuid(0x8000_0000, 0).eax;
+        if !(0x8000_0001..0x8000_0000 + MAX_PLAUSIBLE_LEAVES).contains(&max_extended) {
+            issues.push(QualityIssue::ExtendedLeavesMissing(max_extended));
+        }
+
+        let faulted = issues.iter().any(|issue| {
+            matches!(
+                issue,
+                QualityIssue::MaxLeafTooLow(_) | QualityIssue::ZeroSignature
+            )
+        }) && !vendor_valid;
+
+        match (issues.is_empty(), faulted) {
+            (true, _) => Self::Trusted,
+            (false, true) => Self::Faulted(issues),
+            (false, false) => Self::Degraded(issues),
+        }
+    }
+
+    pub fn is_trusted(&self) -> bool {
+        matches!(self, Self::Trusted)
+    }
+
+    pub fn issues(&self) -> &[QualityIssue] {
+        match self {
+            Self::Trusted => &[],
+            Self::Degraded(issues) | Self::Faulted(issues) => issues,
+        }
+    }
+}
+
+impl fmt::Display for DetectionQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Trusted => return write!(f, "trusted"),
+            Self::Degraded(_) => "degraded",
+            Self::Faulted(_) => "faulted",
+        };
+        let issues: Vec<String> = self.issues().iter().map(ToString::to_string).collect();
+        write!(f, "{} ({})", label, issues.join("; "))
+    }
+}
@@ -0,0 +1,132 @@
+//! Resource Director Technology Detection
+//!
+//! Decodes cache/memory-bandwidth monitoring capabilities (CMT/MBM, leaf 0xF)
+//! and cache/memory-bandwidth allocation (CAT/MBA, leaf 0x10).
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RdtMonitoring {
+    /// Highest RMID of any resource (leaf 0xF EBX); RMIDs are zero-based.
+    pub max_rmid: u32,
+    /// Highest RMID for L3 monitoring (leaf 0xF subleaf 1 ECX).
+    pub l3_max_rmid: u32,
+    pub l3_occupancy: bool,
+    pub mbm_total: bool,
+    pub mbm_local: bool,
+    pub scaling_factor: u32,
+    pub counter_width: u32,
+    pub overflow_bit: bool,
+}
+
+/// Cache allocation (CAT) for one cache level, from leaf 0x10 subleaf 1 (L3)
+/// or 2 (L2).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheAllocation {
+    /// Classes of service, CLOS 0 included.
+    pub clos_count: u32,
+    /// Bits in a capacity bitmask (CBM).
+    pub cbm_length: u32,
+    /// CBM bits whose ways other agents, such as I/O, may also fill.
+    pub shareable_mask: u32,
+    /// Code and data prioritization: separate masks for code and data.
+    pub cdp: bool,
+    /// CBMs may have gaps.
+    pub noncontiguous_cbm: bool,
+}
+
+/// Memory bandwidth allocation (MBA), from leaf 0x10 subleaf 3.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandwidthAllocation {
+    pub clos_count: u32,
+    /// Highest throttling value, in percent of delay, a class may be given.
+    pub max_delay: u32,
+    /// Throttling values map linearly to bandwidth.
+    pub linear: bool,
+}
+
+impl BandwidthAllocation {
+    /// Step between valid throttling values in percent; `None` when the
+    /// response is not linear and the values are only an ordering.
+    pub fn granularity(&self) -> Option<u32> {
+        self.linear.then(|| 100u32.saturating_sub(self.max_delay).max(1))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RdtInfo {
+    pub monitoring: Option<RdtMonitoring>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub l3_allocation: Option<CacheAllocation>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub l2_allocation: Option<CacheAllocation>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bandwidth_allocation: Option<BandwidthAllocation>,
+}
+
+impl RdtInfo {
+    pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "rdt", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let allocation = if src.is_leaf_supported(0x10) { src.cpuid(0x10, 0).ebx } else { 0 };
+        Self {
+            monitoring: detect_monitoring(src),
+            l3_allocation: (allocation & (1 << 1) != 0).then(|| detect_cache_allocation(src, 1)),
+            l2_allocation: (allocation & (1 << 2) != 0).then(|| detect_cache_allocation(src, 2)),
+            bandwidth_allocation: (allocation & (1 << 3) != 0)
+                .then(|| detect_bandwidth_allocation(src)),
+        }
+    }
+}
+
+fn detect_monitoring(src: &dyn CpuidSource) -> Option<RdtMonitoring> {
+    if !src.is_leaf_supported(0xF) {
+        return None;
+    }
+
+    let result = src.cpuid(0xF, 0);
+
+    // EDX bit 1: L3 cache monitoring, the only resource type defined so far
+    if result.edx & (1 << 1) == 0 {
+        return None;
+    }
+
+    let l3 = src.cpuid(0xF, 1);
+    Some(RdtMonitoring {
+        max_rmid: result.ebx,
+        l3_max_rmid: l3.ecx,
+        l3_occupancy: (l3.edx & (1 << 0)) != 0,
+        mbm_total: (l3.edx & (1 << 1)) != 0,
+        mbm_local: (l3.edx & (1 << 2)) != 0,
+        scaling_factor: l3.ebx,
+        counter_width: 24 + (l3.eax & 0xFF),
+        overflow_bit: (l3.eax & (1 << 8)) != 0,
+    })
+}
+
+fn detect_cache_allocation(src: &dyn CpuidSource, resource: u32) -> CacheAllocation {
+    let result = src.cpuid(0x10, resource);
+    CacheAllocation {
+        clos_count: (result.edx & 0xFFFF) + 1,
+        cbm_length: (result.eax & 0x1F) + 1,
+        shareable_mask: result.ebx,
+        cdp: (result.ecx & (1 << 2)) != 0,
+        noncontiguous_cbm: (result.ecx & (1 << 3)) != 0,
+    }
+}
+
+fn detect_bandwidth_allocation(src: &dyn CpuidSource) -> BandwidthAllocation {
+    let result = src.cpuid(0x10, 3);
+    BandwidthAllocation {
+        clos_count: (result.edx & 0xFFFF) + 1,
+        max_delay: (result.eax & 0xFFF) + 1,
+        linear: (result.ecx & (1 << 2)) != 0,
+    }
+}
@@ -0,0 +1,405 @@
+//! Text Reports
+//!
+//! Human-readable rendering of a whole `CpuInfo` at a chosen level of detail.
+//! Every subsystem appears at `Normal` and `Full`; `Brief` is a single line.
+
+use crate::CpuInfo;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// One-line `CpuSummary`.
+    Brief,
+    /// Every subsystem, with flag groups collapsed to the supported names.
+    #[default]
+    Normal,
+    /// Every detected value, including unsupported flags and cpufreq policies.
+    Full,
+}
+
+/// `Display` adapter returned by `CpuInfo::report`.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportFormatter<'a> {
+    info: &'a CpuInfo,
+    verbosity: Verbosity,
+}
+
+impl<'a> ReportFormatter<'a> {
+    pub fn new(info: &'a CpuInfo, verbosity: Verbosity) -> Self {
+        Self { info, verbosity }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+}
+
+impl CpuInfo {
+    pub fn report(&self, verbosity: Verbosity) -> ReportFormatter<'_> {
+        ReportFormatter::new(self, verbosity)
+    }
+}
+
+impl fmt::Display for ReportFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = self.info;
+        let full = self.verbosity == Verbosity::Full;
+
+        if !info.quality.is_trusted() {
+            writeln!(f, "Warning: CPUID data is {}\n", info.quality)?;
+        }
+        if self.verbosity == Verbosity::Brief {
+            return writeln!(f, "{}", info.summary());
+        }
+
+        writeln!(f, "{}", info.vendor)?;
+        #[cfg(feature = "identify")]
+        if let Some(identity) = info.vendor.identify() {
+            writeln!(f, "Identified as: {}", identity)?;
+        }
+        if let Some(kvm) = info.vendor.hypervisor.as_ref().and_then(|h| h.kvm.as_ref()) {
+            writeln!(f, "KVM paravirt: {}", kvm.names().join(", "))?;
+        }
+        if let Some(hyperv) = info.vendor.hypervisor.as_ref().and_then(|h| h.hyperv.as_ref()) {
+            writeln!(f, "{}: {}", hyperv, hyperv.names().join(", "))?;
+        }
+        writeln!(f, "\n{}", info.topology)?;
+        // CpuFeatures' Display already ends its last line
+        write!(f, "\n{}", info.features)?;
+        if full {
+            writeln!(f, "  All:")?;
+            for feature in &info.features.all_features {
+                write!(f, "    {:<16} {:?} ({})", feature.name, feature.category, feature.source)?;
+                if feature.supported && !feature.os_enabled {
+                    write!(f, " [state not enabled by the OS]")?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        writeln!(f, "\nCache Information:")?;
+        for cache in &info.cache {
+            writeln!(f, "  {}", cache)?;
+        }
+
+        let tlb = &info.tlb.entries;
+        write!(f, "\nTLB Information:")?;
+        if !full {
+            writeln!(f, " {} entries", tlb.len())?;
+        } else {
+            writeln!(f)?;
+            if tlb.is_empty() {
+                writeln!(f, "  None")?;
+            }
+            for entry in tlb {
+                writeln!(f, "  {}", entry)?;
+            }
+            writeln!(f, "  Management: {}", info.tlb.management)?;
+            writeln!(f, "  Page sizes: {}", info.tlb.page_sizes)?;
+        }
+
+        let freq = &info.frequency;
+        writeln!(f, "\nFrequency: Base={}, Max={}", mhz(freq.base_mhz), mhz(freq.max_mhz))?;
+        if full {
+            writeln!(f, "  Bus: {}", mhz(freq.bus_mhz))?;
+            writeln!(f, "  TSC: {}", mhz(freq.tsc_mhz))?;
+            for policy in &freq.policies {
+                writeln!(
+                    f,
+                    "  Policy {} (CPUs {:?}): {}/{}, {} - {}{}",
+                    policy.id,
+                    policy.cpus,
+                    policy.driver,
+                    policy.governor,
+                    mhz(policy.scaling_min_mhz),
+                    mhz(policy.scaling_max_mhz),
+                    if policy.is_capped() { " (capped)" } else { "" }
+                )?;
+            }
+        }
+
+        let address = &info.address;
+        write!(
+            f,
+            "\nAddress Sizes: Physical={} bits, Virtual={} bits",
+            address.physical_bits, address.virtual_bits
+        )?;
+        match address.guest_physical_bits {
+            Some(bits) if full => writeln!(f, ", Guest Physical={} bits", bits)?,
+            _ => writeln!(f)?,
+        }
+
+        let xsave = &info.xsave;
+        if xsave.user_mask != 0 {
+            writeln!(
+                f,
+                "\nXSAVE Area: XSAVE={} bytes (enabled {}), XSAVEC={} bytes, XSAVES={} bytes (enabled {})",
+                xsave.xsave_size,
+                xsave.xsave_enabled_size,
+                xsave.xsavec_size,
+                xsave.xsaves_size,
+                xsave.xsaves_enabled_size
+            )?;
+            if full {
+                for c in &xsave.components {
+                    writeln!(
+                        f,
+                        "  {:>2} {:<26} {:>5} bytes at {:>5}{}{}{}",
+                        c.index,
+                        c.name(),
+                        c.size,
+                        c.offset,
+                        if c.supervisor { ", supervisor" } else { "" },
+                        if c.aligned { ", 64-byte aligned" } else { "" },
+                        if c.xfd { ", XFD" } else { "" }
+                    )?;
+                }
+            }
+        }
+
+        match &info.sgx {
+            None => writeln!(f, "\nSGX: Not supported")?,
+            Some(sgx) => {
+                writeln!(
+                    f,
+                    "\nSGX: {}, EPC {} MiB in {} section(s), max enclave {} (64-bit)",
+                    if sgx.sgx2 { "SGX2" } else if sgx.sgx1 { "SGX1" } else { "no leaf functions" },
+                    sgx.epc_size() >> 20,
+                    sgx.epc_sections.len(),
+                    bytes(sgx.max_enclave_size_64)
+                )?;
+                if full {
+                    writeln!(f, "  Max enclave (32-bit): {}", bytes(sgx.max_enclave_size_32))?;
+                    writeln!(f, "  MISCSELECT: {:#x}", sgx.misc_select)?;
+                    writeln!(f, "  Attributes: {:#x}, XFRM: {:#x}", sgx.attributes, sgx.xfrm)?;
+                    for section in &sgx.epc_sections {
+                        writeln!(
+                            f,
+                            "  EPC {:#x}: {} MiB{}",
+                            section.base,
+                            section.size >> 20,
+                            if section.integrity_protected { "" } else { " (confidentiality only)" }
+                        )?;
+                    }
+                }
+            }
+        }
+
+        let encryption = &info.memory_encryption;
+        if let Some(tme) = &encryption.tme {
+            write!(f, "\nTME: {}", if tme.pconfig_mktme { "multi-key via PCONFIG" } else { "single key" })?;
+            match &tme.capability {
+                Some(cap) => writeln!(f, ", {} MKTME keys, {:?}", cap.max_keys, cap.algorithms)?,
+                None => writeln!(f, " (MSRs not readable)")?,
+            }
+            if full && let Some(act) = &tme.activation {
+                writeln!(
+                    f,
+                    "  Activation: {}{}, {} key ID bits, algorithm {:?}, MKTME algorithms {:?}",
+                    if act.enabled { "enabled" } else { "disabled" },
+                    if act.locked { " (locked)" } else { "" },
+                    act.keyid_bits,
+                    act.algorithm,
+                    act.mktme_algorithms
+                )?;
+            }
+        }
+        if let Some(sev) = &encryption.sev {
+            flag_section(f, "AMD SME/SEV", full, &[
+                (sev.sme, "SME"),
+                (sev.sev, "SEV"),
+                (sev.sev_es, "SEV-ES"),
+                (sev.sev_snp, "SEV-SNP"),
+            ])?;
+            if full {
+                writeln!(
+                    f,
+                    "  C-bit {}, {} address bits reserved, {} VMPLs",
+                    sev.c_bit,
+                    sev.physical_address_reduction,
+                    sev.vmpl_count
+                )?;
+                writeln!(
+                    f,
+                    "  ASIDs: {} encrypted guests, {} SEV-ES/SNP, {} SEV (from ASID {})",
+                    sev.max_encrypted_guests,
+                    sev.sev_es_asids(),
+                    sev.sev_asids(),
+                    sev.min_sev_asid
+                )?;
+            }
+        }
+
+        let power = &info.power;
+        flag_section(f, "Power Management", full, &[
+            (power.digital_thermal_sensor, "Digital Thermal Sensor"),
+            (power.turbo_boost, "Turbo Boost"),
+            (power.turbo_boost_max_3, "Turbo Boost Max 3.0"),
+            (power.arat, "Always Running APIC Timer"),
+            (power.pln, "Power Limit Notification"),
+            (power.pts, "Package Thermal Status"),
+            (power.hwp, "Hardware P-States"),
+            (power.hwp_notification, "HWP Notification"),
+            (power.hwp_activity_window, "HWP Activity Window"),
+            (power.hwp_epp, "HWP Energy Performance Preference"),
+            (power.hwp_package, "HWP Package Level Control"),
+            (power.hwp_capabilities, "HWP Capabilities"),
+            (power.hwp_peci, "HWP PECI Override"),
+            (power.flexible_hwp, "Flexible HWP"),
+            (power.hwp_fast_access, "HWP Fast Access"),
+            (power.ignore_idle_hwp, "Ignore Idle Logical Processor HWP"),
+            (power.hdc, "Hardware Duty Cycling"),
+            (power.hw_feedback, "Hardware Feedback Interface"),
+            (power.thread_director, "Thread Director"),
+            (power.therm_interrupt, "Thermal Interrupt"),
+            (power.therm_status, "Thermal Status"),
+            (power.tm2, "Thermal Monitor 2"),
+        ])?;
+        if full {
+            writeln!(f, "  Interrupt Thresholds: {}", power.num_interrupt_thresholds)?;
+        }
+        if let Some(hfi) = &power.hfi {
+            writeln!(
+                f,
+                "  HFI: {} KiB table, {} class(es), {}-byte rows, this CPU at row {}",
+                hfi.table_size / 1024,
+                hfi.table_classes(),
+                hfi.row_size(),
+                hfi.table_index
+            )?;
+        }
+        if let Some(mwait) = &power.monitor_mwait {
+            writeln!(
+                f,
+                "  MONITOR/MWAIT: {}-{} byte lines, {} sub-states{}",
+                mwait.min_line_size,
+                mwait.max_line_size,
+                mwait.total_substates(),
+                if mwait.interrupt_break_event { ", interrupt break events" } else { "" }
+            )?;
+            if full {
+                for (state, count) in mwait.c_substates.iter().enumerate().filter(|(_, n)| **n > 0) {
+                    writeln!(f, "    C{}: {} sub-state(s)", state, count)?;
+                }
+            }
+        }
+
+        let platform = &info.platform;
+        writeln!(
+            f,
+            "\nCPUID Range: Standard={:#x}, Extended={:#x}",
+            platform.max_cpuid_leaf, platform.max_extended_leaf
+        )?;
+        flag_section(f, "Platform", full, &[
+            (platform.microcode_update, "Microcode Update"),
+            (platform.machine_check, "Machine Check"),
+            (platform.mtrr, "MTRR"),
+            (platform.page_attribute_table, "Page Attribute Table"),
+            (platform.page_size_extension, "Page Size Extension"),
+            (platform.time_stamp_counter, "Time Stamp Counter"),
+            (platform.tsc_invariant, "Invariant TSC"),
+            (platform.tsc_deadline, "TSC Deadline"),
+            (platform.model_specific_registers, "Model Specific Registers"),
+            (platform.apic_on_chip, "On-Chip APIC"),
+            (platform.xapic, "xAPIC"),
+            (platform.x2apic, "x2APIC"),
+            (platform.local_apic_timer_always_running, "APIC Timer Always Running"),
+        ])?;
+
+        let msr = &info.msr;
+        flag_section(f, "MSR Access", full, &[
+            (msr.msr_support, "MSR Support"),
+            (msr.rdmsr_wrmsr, "RDMSR/WRMSR"),
+            (msr.msr_platform_info, "Platform Info"),
+            (msr.msr_temperature, "Temperature"),
+            (msr.msr_perf_status, "Performance Status"),
+            (msr.msr_perf_ctl, "Performance Control"),
+            (msr.msr_misc_enable, "Misc Enable"),
+            (msr.msr_energy_perf_bias, "Energy Performance Bias"),
+            (msr.msr_turbo_ratio_limit, "Turbo Ratio Limit"),
+        ])?;
+
+        match &info.rdt.monitoring {
+            None => writeln!(f, "\nResource Monitoring: Not supported")?,
+            Some(mon) => {
+                flag_section(f, "Resource Monitoring", full, &[
+                    (mon.l3_occupancy, "L3 Occupancy"),
+                    (mon.mbm_total, "Total Memory Bandwidth"),
+                    (mon.mbm_local, "Local Memory Bandwidth"),
+                    (mon.overflow_bit, "Counter Overflow Bit"),
+                ])?;
+                writeln!(f, "  Max RMID: {} (L3: {})", mon.max_rmid, mon.l3_max_rmid)?;
+                if full {
+                    writeln!(f, "  Scaling Factor: {}", mon.scaling_factor)?;
+                    writeln!(f, "  Counter Width: {} bits", mon.counter_width)?;
+                }
+            }
+        }
+
+        let rdt = &info.rdt;
+        if rdt.l3_allocation.is_some()
+            || rdt.l2_allocation.is_some()
+            || rdt.bandwidth_allocation.is_some()
+        {
+            writeln!(f, "\nResource Allocation:")?;
+            for (name, cat) in [("L3 CAT", rdt.l3_allocation), ("L2 CAT", rdt.l2_allocation)] {
+                if let Some(cat) = cat {
+                    write!(f, "  {}: {} CLOS, {}-bit CBM", name, cat.clos_count, cat.cbm_length)?;
+                    if cat.cdp {
+                        write!(f, ", CDP")?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            if let Some(mba) = rdt.bandwidth_allocation {
+                write!(f, "  MBA: {} CLOS, max delay {}%", mba.clos_count, mba.max_delay)?;
+                if let Some(granularity) = mba.granularity() {
+                    write!(f, ", {}% steps", granularity)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `Normal` lists only what is supported; `Full` lists every flag.
+fn flag_section(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    full: bool,
+    flags: &[(bool, &str)],
+) -> fmt::Result {
+    write!(f, "\n{}:", title)?;
+    if full {
+        writeln!(f)?;
+        for (enabled, name) in flags {
+            writeln!(f, "  {}: {}", name, if *enabled { "Yes" } else { "No" })?;
+        }
+        return Ok(());
+    }
+
+    let enabled: Vec<&str> = flags.iter().filter(|(on, _)| *on).map(|(_, name)| *name).collect();
+    if enabled.is_empty() {
+        writeln!(f, " None")
+    } else {
+        writeln!(f, " {}", enabled.join(", "))
+    }
+}
+
+/// Power-of-two sizes such as enclave limits, in the largest whole unit.
+fn bytes(value: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KiB", "MiB", "GiB", "TiB"];
+    let mut unit = 0;
+    let mut value = value;
+    while value >= 1024 && value.is_multiple_of(1024) && unit + 1 < UNITS.len() {
+        value /= 1024;
+        unit += 1;
+    }
+    format!("{} {}", value, UNITS[unit])
+}
+
+fn mhz(value: Option<u32>) -> String {
+    value.map_or_else(|| "unknown".to_string(), |mhz| format!("{} MHz", mhz))
+}
@@ -0,0 +1,167 @@
+//! Live MSR Sampling (Linux)
+//!
+//! Batched per-core reads of MSR-backed live counters (APERF/MPERF, thermal
+//! status, C-state residencies). Each CPU's `/dev/cpu/N/msr` handle is opened
+//! once and all CPUs are read in a single pass per interval, split across a few
+//! worker threads on large machines, so watch-style sampling stays cheap.
+
+use crate::msr;
+use std::fs::File;
+use std::io;
+use std::thread;
+use std::time::Instant;
+
+pub const IA32_TIME_STAMP_COUNTER: u32 = 0x10;
+pub const IA32_MPERF: u32 = 0xE7;
+pub const IA32_APERF: u32 = 0xE8;
+pub const IA32_THERM_STATUS: u32 = 0x19C;
+pub const MSR_TEMPERATURE_TARGET: u32 = 0x1A2;
+pub const IA32_PACKAGE_THERM_STATUS: u32 = 0x1B1;
+pub const MSR_PKG_C6_RESIDENCY: u32 = 0x3F9;
+pub const MSR_CORE_C6_RESIDENCY: u32 = 0x3FD;
+
+/// CPUs handled per worker thread before a pass is split across threads.
+const CPUS_PER_WORKER: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuSample {
+    pub cpu: usize,
+    pub values: Vec<(u32, Option<u64>)>,
+}
+
+impl CpuSample {
+    pub fn value(&self, register: u32) -> Option<u64> {
+        self.values
+            .iter()
+            .find(|(r, _)| *r == register)
+            .and_then(|(_, v)| *v)
+    }
+
+    fn delta(&self, earlier: &CpuSample, register: u32) -> Option<u64> {
+        Some(self.value(register)?.wrapping_sub(earlier.value(register)?))
+    }
+
+    /// Average effective frequency since `earlier`, from the APERF/MPERF ratio.
+    pub fn effective_mhz(&self, earlier: &CpuSample, base_mhz: u32) -> Option<f64> {
+        let aperf = self.delta(earlier, IA32_APERF)?;
+        let mperf = self.delta(earlier, IA32_MPERF)?;
+        if mperf == 0 {
+            return None;
+        }
+        Some(base_mhz as f64 * aperf as f64 / mperf as f64)
+    }
+
+    /// Percentage of TSC time spent in the state counted by `register` since `earlier`.
+    pub fn residency_percent(&self, earlier: &CpuSample, register: u32) -> Option<f64> {
+        let residency = self.delta(earlier, register)?;
+        let tsc = self.delta(earlier, IA32_TIME_STAMP_COUNTER)?;
+        if tsc == 0 {
+            return None;
+        }
+        Some(100.0 * residency as f64 / tsc as f64)
+    }
+
+    /// Core temperature in degrees Celsius, given the TjMax from `MSR_TEMPERATURE_TARGET`.
+    pub fn temperature_c(&self, tj_max: u32) -> Option<u32> {
+        decode_temperature(self.value(IA32_THERM_STATUS)?, tj_max)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MsrSample {
+    pub timestamp: Instant,
+    pub cpus: Vec<CpuSample>,
+}
+
+pub struct MsrSampler {
+    handles: Vec<(usize, File)>,
+    registers: Vec<u32>,
+}
+
+impl MsrSampler {
+    pub fn new(cpus: &[usize], registers: &[u32]) -> io::Result<Self> {
+        let handles = cpus
+            .iter()
+            .map(|&cpu| Ok((cpu, msr::open(cpu)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            handles,
+            registers: registers.to_vec(),
+        })
+    }
+
+    /// Sampler over every CPU exposing `/dev/cpu/N/msr`.
+    pub fn for_all_cpus(registers: &[u32]) -> io::Result<Self> {
+        let mut cpus: Vec<usize> = std::fs::read_dir("/dev/cpu")?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        cpus.sort_unstable();
+        Self::new(&cpus, registers)
+    }
+
+    pub fn registers(&self) -> &[u32] {
+        &self.registers
+    }
+
+    /// Read every register on every CPU in one pass. A panic in a worker
+    /// thread is re-raised rather than dropping its CPUs from the sample.
+    pub fn sample(&self) -> MsrSample {
+        let timestamp = Instant::now();
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = self
+            .handles
+            .len()
+            .div_ceil(workers)
+            .max(CPUS_PER_WORKER);
+
+        let cpus = if self.handles.len() <= chunk {
+            self.read_chunk(&self.handles)
+        } else {
+            thread::scope(|scope| {
+                let jobs: Vec<_> = self
+                    .handles
+                    .chunks(chunk)
+                    .map(|handles| scope.spawn(move || self.read_chunk(handles)))
+                    .collect();
+                jobs.into_iter()
+                    .flat_map(|job| {
+                        job.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect()
+            })
+        };
+
+        MsrSample { timestamp, cpus }
+    }
+
+    fn read_chunk(&self, handles: &[(usize, File)]) -> Vec<CpuSample> {
+        handles
+            .iter()
+            .map(|(cpu, file)| CpuSample {
+                cpu: *cpu,
+                values: self
+                    .registers
+                    .iter()
+                    .map(|&register| (register, msr::read_from(file, register).ok()))
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// TjMax in degrees Celsius from `MSR_TEMPERATURE_TARGET` bits 23:16.
+pub fn decode_tj_max(temperature_target: u64) -> u32 {
+    ((temperature_target >> 16) & 0xFF) as u32
+}
+
+/// Temperature from an `IA32_(PACKAGE_)THERM_STATUS` value; `None` if the reading is invalid.
+pub fn decode_temperature(therm_status: u64, tj_max: u32) -> Option<u32> {
+    // Bit 31: reading valid, bits 22:16: degrees below TjMax
+    if therm_status & (1 << 31) == 0 {
+        return None;
+    }
+    let below = ((therm_status >> 16) & 0x7F) as u32;
+    Some(tj_max.saturating_sub(below))
+}
@@ -0,0 +1,177 @@
+//! Serialized Schema Versioning
+//!
+//! Versioned JSON for long-term storage of `CpuInfo`, with migrations that
+//! upgrade documents written by older crate versions to the current layout.
+
+use crate::{CpuInfo, CpuVendor, SCHEMA_VERSION};
+use serde_json::Value;
+use std::fmt;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+const MIGRATIONS: &[fn(&mut Value)] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+];
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Json(serde_json::Error),
+    /// Written by a newer crate version than this one understands.
+    UnsupportedVersion(u64),
+    NotAnObject,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid CpuInfo JSON: {}", err),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "schema version {} is newer than the supported version {}",
+                version, SCHEMA_VERSION
+            ),
+            Self::NotAnObject => write!(f, "CpuInfo JSON must be an object"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl CpuInfo {
+    /// Serialize with the current `schema_version`.
+    pub fn to_json(&self) -> Result<String, SchemaError> {
+        let mut value = serde_json::to_value(self)?;
+        value["schema_version"] = SCHEMA_VERSION.into();
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Parse JSON written by any crate version, migrating older layouts first.
+    pub fn from_json_any_version(json: &str) -> Result<Self, SchemaError> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let object = value.as_object().ok_or(SchemaError::NotAnObject)?;
+
+        let version = object
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        if version > SCHEMA_VERSION as u64 {
+            return Err(SchemaError::UnsupportedVersion(version));
+        }
+
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(&mut value);
+        }
+        value["schema_version"] = SCHEMA_VERSION.into();
+
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Version 1 only introduced `schema_version` itself.
+fn migrate_v0_to_v1(_value: &mut Value) {}
+
+/// Version 2 replaced the strings of `TlbEntry` with typed fields:
+/// `tlb_type` "L1 Data" became `level` and `kind`, `page_size` "2M/4M" became
+/// the `page_sizes` list `["M2", "M4"]`, and `associativity` "8-way" or
+/// "Fully" became `{"Ways": 8}` or `"Full"`.
+fn migrate_v1_to_v2(value: &mut Value) {
+    let Some(entries) = value.pointer_mut("/tlb/entries").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for entry in entries {
+        let Some(entry) = entry.as_object_mut() else {
+            continue;
+        };
+        let text = |field: &str| entry.get(field).and_then(Value::as_str).unwrap_or("").to_string();
+        let (tlb_type, page_size, associativity) =
+            (text("tlb_type"), text("page_size"), text("associativity"));
+
+        let (level, kind) = tlb_type.split_once(' ').unwrap_or(("L1", "Unified"));
+        let page_sizes: Vec<Value> = page_size
+            .split('/')
+            .filter_map(|size| match size {
+                "4K" => Some("K4"),
+                "2M" => Some("M2"),
+                "4M" => Some("M4"),
+                "1G" => Some("G1"),
+                _ => None,
+            })
+            .map(Value::from)
+            .collect();
+        let associativity = match associativity.as_str() {
+            "Fully" => Value::from("Full"),
+            "Disabled" => Value::from("Disabled"),
+            ways => match ways.strip_suffix("-way").and_then(|n| n.parse::<u32>().ok()) {
+                Some(ways) => serde_json::json!({ "Ways": ways }),
+                None => Value::from("Reserved"),
+            },
+        };
+
+        entry.insert("level".into(), level.into());
+        entry.insert("kind".into(), kind.into());
+        entry.insert("page_sizes".into(), page_sizes.into());
+        entry.insert("associativity".into(), associativity);
+        entry.remove("tlb_type");
+        entry.remove("page_size");
+    }
+}
+
+/// Version 3 gave `CpuVendor` the historic vendors and `Other(String)`, so a
+/// `vendor` recorded as "Unknown" is re-derived from `vendor_string`.
+fn migrate_v2_to_v3(value: &mut Value) {
+    let Some(vendor) = value.get_mut("vendor").and_then(Value::as_object_mut) else {
+        return;
+    };
+    if vendor.get("vendor").and_then(Value::as_str) != Some("Unknown") {
+        return;
+    }
+    let vendor_string = vendor.get("vendor_string").and_then(Value::as_str).unwrap_or("");
+    if let Ok(derived) = serde_json::to_value(CpuVendor::from_vendor_string(vendor_string)) {
+        vendor.insert("vendor".into(), derived);
+    }
+}
+
+/// Version 4 renamed `memory_encryption.amd` (`AmdEncryptionInfo`) to
+/// `memory_encryption.sev` (`SevInfo`); the inner fields kept their names.
+/// The rename shipped before this version existed, so any older document
+/// may carry either key.
+fn migrate_v3_to_v4(value: &mut Value) {
+    let Some(encryption) = value.get_mut("memory_encryption").and_then(Value::as_object_mut) else {
+        return;
+    };
+    if let Some(amd) = encryption.remove("amd")
+        && !encryption.contains_key("sev")
+    {
+        encryption.insert("sev".into(), amd);
+    }
+}
+
+/// Version 5 stores `rdt.monitoring.max_rmid` and `l3_max_rmid` as the
+/// highest RMID CPUID reports rather than the RMID count (one more).
+fn migrate_v4_to_v5(value: &mut Value) {
+    let Some(monitoring) = value.pointer_mut("/rdt/monitoring").and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    for key in ["max_rmid", "l3_max_rmid"] {
+        if let Some(count) = monitoring.get(key).and_then(Value::as_u64) {
+            monitoring.insert(key.into(), count.saturating_sub(1).into());
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! Speculative Execution Mitigations
+//!
+//! Collects the speculation controls the CPU enumerates and the immunity it
+//! declares: IA32_ARCH_CAPABILITIES `*_NO` bits on Intel, CPUID leaves
+//! 0x8000_0008 and 0x8000_0021 on AMD. A vulnerability is only reported as
+//! not affected when the hardware says so; the OS mitigation state in
+//! `/sys/devices/system/cpu/vulnerabilities` is out of scope.
+
+use crate::cpuid::{CpuidResult, CpuidSource, HardwareCpuid};
+use crate::msr;
+use crate::vendor::CpuVendor;
+use std::fmt;
+
+const IA32_ARCH_CAPABILITIES: u32 = 0x10A;
+
+const RDCL_NO: u64 = 1 << 0;
+const IBRS_ALL: u64 = 1 << 1;
+const SSB_NO: u64 = 1 << 4;
+const MDS_NO: u64 = 1 << 5;
+const TAA_NO: u64 = 1 << 8;
+const SBDR_SSDP_NO: u64 = 1 << 13;
+const FBSDP_NO: u64 = 1 << 14;
+const PSDP_NO: u64 = 1 << 15;
+const FB_CLEAR: u64 = 1 << 17;
+const BHI_NO: u64 = 1 << 20;
+const GDS_CTRL: u64 = 1 << 25;
+const GDS_NO: u64 = 1 << 26;
+const RFDS_NO: u64 = 1 << 27;
+const RFDS_CLEAR: u64 = 1 << 28;
+
+/// Speculation controls the CPU enumerates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeculationControls {
+    pub ibrs: bool,
+    /// IBRS_ALL: IBRS can stay set across privilege changes. Only known
+    /// when IA32_ARCH_CAPABILITIES was read.
+    pub enhanced_ibrs: bool,
+    /// AMD automatic IBRS (leaf 0x8000_0021 EAX[8]).
+    pub auto_ibrs: bool,
+    pub ibpb: bool,
+    pub stibp: bool,
+    pub ssbd: bool,
+    /// VERW clears microarchitectural buffers.
+    pub md_clear: bool,
+    pub l1d_flush: bool,
+    pub bhi_ctrl: bool,
+    /// Predictive store forwarding disable.
+    pub psfd: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Vulnerability {
+    Meltdown,
+    SpectreV2,
+    SpeculativeStoreBypass,
+    L1tf,
+    Mds,
+    Taa,
+    MmioStaleData,
+    Rfds,
+    Gds,
+    Bhi,
+    Srso,
+    Tsa,
+}
+
+impl Vulnerability {
+    /// Bug name in the `bugs` line of Linux's /proc/cpuinfo.
+    pub fn linux_name(self) -> &'static str {
+        match self {
+            Self::Meltdown => "cpu_meltdown",
+            Self::SpectreV2 => "spectre_v2",
+            Self::SpeculativeStoreBypass => "spec_store_bypass",
+            Self::L1tf => "l1tf",
+            Self::Mds => "mds",
+            Self::Taa => "taa",
+            Self::MmioStaleData => "mmio_stale_data",
+            Self::Rfds => "rfds",
+            Self::Gds => "gds",
+            Self::Bhi => "bhi",
+            Self::Srso => "srso",
+            Self::Tsa => "tsa",
+        }
+    }
+}
+
+impl fmt::Display for Vulnerability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Meltdown => "Meltdown",
+            Self::SpectreV2 => "Spectre v2",
+            Self::SpeculativeStoreBypass => "Speculative Store Bypass",
+            Self::L1tf => "L1TF",
+            Self::Mds => "MDS",
+            Self::Taa => "TSX Async Abort",
+            Self::MmioStaleData => "MMIO Stale Data",
+            Self::Rfds => "Register File Data Sampling",
+            Self::Gds => "Gather Data Sampling",
+            Self::Bhi => "Branch History Injection",
+            Self::Srso => "Speculative Return Stack Overflow",
+            Self::Tsa => "Transient Scheduler Attacks",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VulnerabilityStatus {
+    /// The CPU declares itself not affected.
+    NotAffected,
+    /// Not declared immune; the CPU has the control the OS mitigates with.
+    Mitigable,
+    /// Not declared immune and no hardware control is enumerated.
+    Vulnerable,
+    /// Immunity would be declared in IA32_ARCH_CAPABILITIES or a CPUID leaf
+    /// that could not be read.
+    Unknown,
+}
+
+impl fmt::Display for VulnerabilityStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAffected => write!(f, "Not affected"),
+            Self::Mitigable => write!(f, "Mitigable"),
+            Self::Vulnerable => write!(f, "Vulnerable"),
+            Self::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VulnerabilityAssessment {
+    pub vulnerability: Vulnerability,
+    pub status: VulnerabilityStatus,
+    /// The bit or control the status rests on.
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MitigationReport {
+    pub controls: SpeculationControls,
+    /// Raw IA32_ARCH_CAPABILITIES; `None` when the CPU lacks it or it could not be read.
+    pub arch_capabilities: Option<u64>,
+    pub vulnerabilities: Vec<VulnerabilityAssessment>,
+}
+
+impl MitigationReport {
+    /// CPUID plus IA32_ARCH_CAPABILITIES of the executing CPU when the msr
+    /// driver allows it.
+    pub fn detect() -> Self {
+        let arch_capabilities = has_arch_capabilities(&HardwareCpuid)
+            .then(|| msr::read_current(IA32_ARCH_CAPABILITIES).ok())
+            .flatten();
+        Self::assess(&HardwareCpuid, arch_capabilities)
+    }
+
+    /// CPUID only; declarations in IA32_ARCH_CAPABILITIES are `Unknown`.
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self::assess(src, None)
+    }
+
+    /// Assess `src` with an IA32_ARCH_CAPABILITIES value read elsewhere,
+    /// e.g. `msr::read` on a specific core.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "security", level = "debug", skip_all))]
+    pub fn assess(src: &dyn CpuidSource, arch_capabilities: Option<u64>) -> Self {
+        let leaf7 = read_leaf(src, 7, 0);
+        let leaf7_2 = if leaf7.eax >= 2 { read_leaf(src, 7, 2) } else { CpuidResult::default() };
+        let ext8 = read_leaf(src, 0x8000_0008, 0);
+        let ext21 = read_leaf(src, 0x8000_0021, 0);
+
+        let controls = SpeculationControls {
+            ibrs: leaf7.edx & (1 << 26) != 0 || ext8.ebx & (1 << 14) != 0,
+            enhanced_ibrs: arch_capabilities.is_some_and(|caps| caps & IBRS_ALL != 0),
+            auto_ibrs: ext21.eax & (1 << 8) != 0,
+            ibpb: leaf7.edx & (1 << 26) != 0 || ext8.ebx & (1 << 12) != 0,
+            stibp: leaf7.edx & (1 << 27) != 0 || ext8.ebx & (1 << 15) != 0,
+            ssbd: leaf7.edx & (1 << 31) != 0 || ext8.ebx & ((1 << 24) | (1 << 25)) != 0,
+            md_clear: leaf7.edx & (1 << 10) != 0,
+            l1d_flush: leaf7.edx & (1 << 28) != 0,
+            bhi_ctrl: leaf7_2.edx & (1 << 4) != 0,
+            psfd: leaf7_2.edx & 1 != 0 || ext8.ebx & (1 << 28) != 0,
+        };
+
+        let ctx = Context {
+            vendor: CpuVendor::detect_with(src),
+            arch_capabilities,
+            has_arch_capabilities: has_arch_capabilities(src),
+            ext21: src.is_leaf_supported(0x8000_0021).then_some(ext21),
+        };
+        let c = &controls;
+        let caps = arch_capabilities.unwrap_or(0);
+
+        let mut vulnerabilities = vec![
+            ctx.intel(Vulnerability::Meltdown, RDCL_NO, "RDCL_NO", None),
+            ctx.spectre_v2(c),
+            ctx.store_bypass(c, ext8),
+            ctx.intel(Vulnerability::L1tf, RDCL_NO, "RDCL_NO", c.l1d_flush.then_some("L1D_FLUSH")),
+            ctx.intel(Vulnerability::Mds, MDS_NO, "MDS_NO", c.md_clear.then_some("MD_CLEAR")),
+        ];
+        vulnerabilities.push(if leaf7.ebx & (1 << 11) == 0 && !ctx.amd_like() {
+            assessment(Vulnerability::Taa, VulnerabilityStatus::NotAffected, "RTM not supported")
+        } else {
+            ctx.intel(Vulnerability::Taa, TAA_NO, "TAA_NO", c.md_clear.then_some("MD_CLEAR"))
+        });
+        let fb_clear = (caps & FB_CLEAR != 0).then_some("FB_CLEAR");
+        vulnerabilities.extend([
+            ctx.intel(
+                Vulnerability::MmioStaleData,
+                SBDR_SSDP_NO | FBSDP_NO | PSDP_NO,
+                "SBDR_SSDP_NO, FBSDP_NO and PSDP_NO",
+                fb_clear.or(c.md_clear.then_some("MD_CLEAR")),
+            ),
+            ctx.intel(
+                Vulnerability::Rfds,
+                RFDS_NO,
+                "RFDS_NO",
+                (caps & RFDS_CLEAR != 0).then_some("RFDS_CLEAR"),
+            ),
+            ctx.intel(
+                Vulnerability::Gds,
+                GDS_NO,
+                "GDS_NO",
+                (caps & GDS_CTRL != 0).then_some("GDS_CTRL"),
+            ),
+            ctx.intel(Vulnerability::Bhi, BHI_NO, "BHI_NO", c.bhi_ctrl.then_some("BHI_CTRL")),
+            ctx.amd(Vulnerability::Srso, 0, 1 << 29, "SRSO_NO", |ext21| {
+                if ext21.eax & (1 << 28) != 0 {
+                    Some("IBPB_BRTYPE")
+                } else {
+                    (ext21.eax & (1 << 27) != 0).then_some("SBPB")
+                }
+            }),
+            ctx.amd(Vulnerability::Tsa, 2, (1 << 1) | (1 << 2), "TSA_SQ_NO and TSA_L1_NO", |ext21| {
+                (ext21.eax & (1 << 5) != 0).then_some("VERW_CLEAR")
+            }),
+        ]);
+
+        Self {
+            controls,
+            arch_capabilities,
+            vulnerabilities,
+        }
+    }
+
+    pub fn status(&self, vulnerability: Vulnerability) -> Option<VulnerabilityStatus> {
+        self.vulnerabilities
+            .iter()
+            .find(|v| v.vulnerability == vulnerability)
+            .map(|v| v.status)
+    }
+
+    /// Vulnerabilities neither declared fixed nor backed by a hardware control.
+    pub fn vulnerable(&self) -> Vec<Vulnerability> {
+        self.vulnerabilities
+            .iter()
+            .filter(|v| v.status == VulnerabilityStatus::Vulnerable)
+            .map(|v| v.vulnerability)
+            .collect()
+    }
+}
+
+impl fmt::Display for MitigationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = &self.controls;
+        let controls: Vec<&str> = [
+            (c.ibrs, "IBRS"),
+            (c.enhanced_ibrs, "Enhanced IBRS"),
+            (c.auto_ibrs, "AutoIBRS"),
+            (c.ibpb, "IBPB"),
+            (c.stibp, "STIBP"),
+            (c.ssbd, "SSBD"),
+            (c.md_clear, "MD_CLEAR"),
+            (c.l1d_flush, "L1D_FLUSH"),
+            (c.bhi_ctrl, "BHI_CTRL"),
+            (c.psfd, "PSFD"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+
+        if controls.is_empty() {
+            writeln!(f, "Speculation Controls: None")?;
+        } else {
+            writeln!(f, "Speculation Controls: {}", controls.join(", "))?;
+        }
+        match self.arch_capabilities {
+            Some(caps) => writeln!(f, "IA32_ARCH_CAPABILITIES: {:#x}", caps)?,
+            None => writeln!(f, "IA32_ARCH_CAPABILITIES: not available")?,
+        }
+        writeln!(f, "Vulnerabilities:")?;
+        for v in &self.vulnerabilities {
+            writeln!(f, "  {:<34} {} ({})", v.vulnerability, v.status, v.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the declarations can be read from.
+struct Context {
+    vendor: CpuVendor,
+    arch_capabilities: Option<u64>,
+    has_arch_capabilities: bool,
+    /// `None` without leaf 0x8000_0021.
+    ext21: Option<CpuidResult>,
+}
+
+impl Context {
+    fn amd_like(&self) -> bool {
+        matches!(self.vendor, CpuVendor::Amd | CpuVendor::Hygon)
+    }
+
+    /// An issue Intel parts declare immunity to through IA32_ARCH_CAPABILITIES.
+    fn intel(
+        &self,
+        vulnerability: Vulnerability,
+        bits: u64,
+        names: &str,
+        control: Option<&str>,
+    ) -> VulnerabilityAssessment {
+        if self.amd_like() {
+            return assessment(vulnerability, VulnerabilityStatus::NotAffected, "Intel-specific");
+        }
+        match self.arch_capabilities {
+            Some(caps) if caps & bits == bits => assessment(
+                vulnerability,
+                VulnerabilityStatus::NotAffected,
+                &format!("{} in IA32_ARCH_CAPABILITIES", names),
+            ),
+            None if self.has_arch_capabilities => assessment(
+                vulnerability,
+                VulnerabilityStatus::Unknown,
+                "IA32_ARCH_CAPABILITIES not readable",
+            ),
+            _ => undeclared(vulnerability, control),
+        }
+    }
+
+    /// An issue AMD parts declare immunity to in leaf 0x8000_0021 `register`
+    /// (0 = EAX, 2 = ECX).
+    fn amd(
+        &self,
+        vulnerability: Vulnerability,
+        register: usize,
+        bits: u32,
+        names: &str,
+        control: impl Fn(CpuidResult) -> Option<&'static str>,
+    ) -> VulnerabilityAssessment {
+        if !self.amd_like() {
+            return assessment(vulnerability, VulnerabilityStatus::NotAffected, "AMD-specific");
+        }
+        let Some(ext21) = self.ext21 else {
+            return assessment(
+                vulnerability,
+                VulnerabilityStatus::Unknown,
+                "leaf 0x8000_0021 not reported",
+            );
+        };
+        let value = [ext21.eax, ext21.ebx, ext21.ecx, ext21.edx][register];
+        if value & bits == bits {
+            assessment(
+                vulnerability,
+                VulnerabilityStatus::NotAffected,
+                &format!("{} in CPUID 0x8000_0021", names),
+            )
+        } else {
+            undeclared(vulnerability, control(ext21))
+        }
+    }
+
+    fn spectre_v2(&self, c: &SpeculationControls) -> VulnerabilityAssessment {
+        let vulnerability = Vulnerability::SpectreV2;
+        let control = if c.enhanced_ibrs {
+            Some("Enhanced IBRS")
+        } else if c.auto_ibrs {
+            Some("AutoIBRS")
+        } else {
+            (c.ibrs || c.ibpb || c.stibp).then_some("IBRS/IBPB/STIBP")
+        };
+        undeclared(vulnerability, control)
+    }
+
+    fn store_bypass(&self, c: &SpeculationControls, ext8: CpuidResult) -> VulnerabilityAssessment {
+        let vulnerability = Vulnerability::SpeculativeStoreBypass;
+        let control = c.ssbd.then_some("SSBD");
+        if self.amd_like() {
+            return if ext8.ebx & (1 << 26) != 0 {
+                let reason = "SSB_NO in CPUID 0x8000_0008";
+                assessment(vulnerability, VulnerabilityStatus::NotAffected, reason)
+            } else {
+                undeclared(vulnerability, control)
+            };
+        }
+        self.intel(vulnerability, SSB_NO, "SSB_NO", control)
+    }
+}
+
+fn undeclared(vulnerability: Vulnerability, control: Option<&str>) -> VulnerabilityAssessment {
+    match control {
+        Some(control) => assessment(
+            vulnerability,
+            VulnerabilityStatus::Mitigable,
+            &format!("not declared immune; {} available", control),
+        ),
+        None => assessment(
+            vulnerability,
+            VulnerabilityStatus::Vulnerable,
+            "not declared immune and no hardware control",
+        ),
+    }
+}
+
+fn assessment(
+    vulnerability: Vulnerability,
+    status: VulnerabilityStatus,
+    reason: &str,
+) -> VulnerabilityAssessment {
+    VulnerabilityAssessment {
+        vulnerability,
+        status,
+        reason: reason.to_string(),
+    }
+}
+
+fn has_arch_capabilities(src: &dyn CpuidSource) -> bool {
+    read_leaf(src, 7, 0).edx & (1 << 29) != 0
+}
+
+fn read_leaf(src: &dyn CpuidSource, leaf: u32, subleaf: u32) -> CpuidResult {
+    if src.is_leaf_supported(leaf) {
+        src.cpuid(leaf, subleaf)
+    } else {
+        CpuidResult::default()
+    }
+}
@@ -0,0 +1,92 @@
+//! Intel SGX Capabilities
+//!
+//! Decodes CPUID leaf 0x12: enclave size limits, the MISCSELECT and
+//! SECS.ATTRIBUTES bits an enclave may set, and the Enclave Page Cache (EPC)
+//! sections reserved by the BIOS.
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use alloc::vec::Vec;
+
+/// One EPC section (leaf 0x12 subleaf 2 and up).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EpcSection {
+    /// Physical base address.
+    pub base: u64,
+    pub size: u64,
+    /// Integrity and replay protected; false for confidentiality-only EPC.
+    pub integrity_protected: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SgxInfo {
+    pub sgx1: bool,
+    pub sgx2: bool,
+    /// MISCSELECT bits an enclave may request.
+    pub misc_select: u32,
+    /// Largest enclave in bytes outside 64-bit mode.
+    pub max_enclave_size_32: u64,
+    /// Largest enclave in bytes in 64-bit mode.
+    pub max_enclave_size_64: u64,
+    /// SECS.ATTRIBUTES bits an enclave may set.
+    pub attributes: u64,
+    /// SECS.ATTRIBUTES.XFRM bits an enclave may set.
+    pub xfrm: u64,
+    pub epc_sections: Vec<EpcSection>,
+}
+
+impl SgxInfo {
+    /// `None` without SGX (leaf 7 EBX[2]) or leaf 0x12.
+    pub fn detect() -> Option<Self> {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "sgx", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Option<Self> {
+        if !src.is_leaf_supported(0x12) || src.cpuid(7, 0).ebx & (1 << 2) == 0 {
+            return None;
+        }
+
+        let caps = src.cpuid(0x12, 0);
+        let attrs = src.cpuid(0x12, 1);
+        let mut info = Self {
+            sgx1: caps.eax & 1 != 0,
+            sgx2: caps.eax & (1 << 1) != 0,
+            misc_select: caps.ebx,
+            max_enclave_size_32: size_from_bits(caps.edx & 0xFF),
+            max_enclave_size_64: size_from_bits((caps.edx >> 8) & 0xFF),
+            attributes: attrs.eax as u64 | (attrs.ebx as u64) << 32,
+            xfrm: attrs.ecx as u64 | (attrs.edx as u64) << 32,
+            epc_sections: Vec::new(),
+        };
+
+        // Sections are listed until the first invalid subleaf
+        for subleaf in 2..64 {
+            let result = src.cpuid(0x12, subleaf);
+            if result.eax & 0xF != 1 {
+                break;
+            }
+            info.epc_sections.push(EpcSection {
+                base: (result.eax & 0xFFFF_F000) as u64 | ((result.ebx & 0xF_FFFF) as u64) << 32,
+                size: (result.ecx & 0xFFFF_F000) as u64 | ((result.edx & 0xF_FFFF) as u64) << 32,
+                integrity_protected: result.ecx & 0xF == 1,
+            });
+        }
+
+        Some(info)
+    }
+
+    /// Total EPC across all sections.
+    pub fn epc_size(&self) -> u64 {
+        self.epc_sections.iter().map(|s| s.size).sum()
+    }
+}
+
+/// Leaf 0x12 reports enclave limits as a power of two; 0 means not reported.
+fn size_from_bits(bits: u32) -> u64 {
+    match bits {
+        0 => 0,
+        _ => 1u64.checked_shl(bits).unwrap_or(u64::MAX),
+    }
+}
@@ -0,0 +1,64 @@
+//! Shared Detection Result
+//!
+//! A process-wide, lazily detected `CpuInfo` that long-running programs can
+//! re-detect after microcode updates, CPU hotplug or VM live migration.
+
+use crate::CpuInfo;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+
+type RefreshHook = Arc<dyn Fn(&CpuInfo) + Send + Sync>;
+
+static CURRENT: OnceLock<RwLock<Option<Arc<CpuInfo>>>> = OnceLock::new();
+static HOOKS: Mutex<Vec<RefreshHook>> = Mutex::new(Vec::new());
+/// Held across detect, store and notify so hooks see refreshes in order.
+static REFRESH: Mutex<()> = Mutex::new(());
+
+fn current() -> &'static RwLock<Option<Arc<CpuInfo>>> {
+    CURRENT.get_or_init(|| RwLock::new(None))
+}
+
+impl CpuInfo {
+    /// The shared instance, detected on first use.
+    pub fn get() -> Arc<CpuInfo> {
+        if let Some(info) = current().read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+            return Arc::clone(info);
+        }
+
+        let mut slot = current().write().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(slot.get_or_insert_with(|| Arc::new(CpuInfo::detect())))
+    }
+
+    /// Re-run detection and atomically replace the shared instance.
+    ///
+    /// Readers holding the previous `Arc` keep a consistent snapshot; hooks
+    /// registered with `on_refresh` run afterwards with the new result.
+    /// Concurrent refreshes are serialized, so the last hook call always
+    /// carries the stored instance.
+    pub fn refresh() -> Arc<CpuInfo> {
+        let _refresh = REFRESH.lock().unwrap_or_else(PoisonError::into_inner);
+        let info = Arc::new(CpuInfo::detect());
+        *current().write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::clone(&info));
+
+        // Run the hooks unlocked so they may register further hooks
+        let hooks = HOOKS.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        for hook in &hooks {
+            hook(&info);
+        }
+        info
+    }
+
+    /// Drop the shared instance; the next `get` detects again.
+    pub fn invalidate() {
+        *current().write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Register a callback run after every `refresh`. Hooks may call `get`
+    /// and `on_refresh`, but calling `refresh` from a hook deadlocks.
+    /// A hook registered from a hook first runs on the next refresh.
+    pub fn on_refresh(hook: impl Fn(&CpuInfo) + Send + Sync + 'static) {
+        HOOKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::new(hook));
+    }
+}
@@ -0,0 +1,82 @@
+//! Capability Summary
+//!
+//! A handful of high-signal fields for dashboards and inventory listings that
+//! do not want to walk the full feature list.
+
+use crate::features::SimdLevel;
+use crate::microarch::Microarchitecture;
+use crate::vendor::CpuVendor;
+use crate::CpuInfo;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuSummary {
+    pub vendor: CpuVendor,
+    pub brand: String,
+    pub microarchitecture: Microarchitecture,
+    pub logical_processors: u32,
+    pub physical_cores: u32,
+    pub simd_level: SimdLevel,
+    /// Every cache instance in the package, not just the ones one core sees.
+    pub total_cache_bytes: u64,
+    /// Highest of the CPUID maximum and the cpufreq hardware maximum.
+    pub max_frequency_mhz: Option<u32>,
+    /// VT-x or AMD-V is available to this OS.
+    pub virtualization: bool,
+    /// This OS itself runs under a hypervisor.
+    pub hypervisor: bool,
+}
+
+impl CpuInfo {
+    pub fn summary(&self) -> CpuSummary {
+        let total_cache_bytes = self.cache_hierarchy().per_package_bytes(&self.topology);
+
+        let max_frequency_mhz = self
+            .frequency
+            .policies
+            .iter()
+            .filter_map(|p| p.hardware_max_mhz)
+            .chain(self.frequency.max_mhz)
+            .max();
+
+        CpuSummary {
+            vendor: self.vendor.vendor.clone(),
+            brand: self.vendor.brand_or_signature(),
+            microarchitecture: self.microarchitecture,
+            logical_processors: self.topology.logical_processors,
+            physical_cores: self.topology.physical_cores,
+            simd_level: self.features.simd_level(),
+            total_cache_bytes,
+            max_frequency_mhz,
+            virtualization: self.features.has_feature("VMX") || self.features.has_feature("SVM"),
+            hypervisor: self.features.has_feature("HYPERVISOR"),
+        }
+    }
+}
+
+impl fmt::Display for CpuSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}), {}C/{}T, {}, {} KB cache",
+            self.brand,
+            self.vendor.as_str(),
+            self.microarchitecture,
+            self.physical_cores,
+            self.logical_processors,
+            self.simd_level,
+            self.total_cache_bytes / 1024
+        )?;
+        if let Some(mhz) = self.max_frequency_mhz {
+            write!(f, ", up to {} MHz", mhz)?;
+        }
+        if self.virtualization {
+            write!(f, ", virtualization")?;
+        }
+        if self.hypervisor {
+            write!(f, ", under hypervisor")?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
 //! CPU Thermal and Power Management Detection
 //!
-//! Detects thermal monitoring and power management features.
+//! Detects thermal monitoring and power management features. The leaf 6 EAX
+//! subset of `PowerInfo`; `CpuInfo::thermal` derives it without extra CPUID reads.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::power::PowerInfo;
+use crate::CpuInfo;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThermalInfo {
     pub digital_thermal_sensor: bool,
     pub turbo_boost: bool,
@@ -21,22 +26,15 @@ pub struct ThermalInfo {
 
 impl ThermalInfo {
     pub fn detect() -> Self {
-        let mut info = Self {
-            digital_thermal_sensor: false,
-            turbo_boost: false,
-            arat: false,
-            pln: false,
-            pts: false,
-            hwp: false,
-            hwp_notification: false,
-            hwp_activity_window: false,
-            hwp_epp: false,
-            hwp_package: false,
-            hdc: false,
-        };
-
-        if is_leaf_supported(6) {
-            let result = cpuid(6, 0);
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "thermal", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let mut info = Self::default();
+
+        if src.is_leaf_supported(6) {
+            let result = src.cpuid(6, 0);
 
             info.digital_thermal_sensor = (result.eax & (1 << 0)) != 0;
             info.turbo_boost = (result.eax & (1 << 1)) != 0;
@@ -54,3 +52,51 @@ impl ThermalInfo {
         info
     }
 }
+
+impl From<&PowerInfo> for ThermalInfo {
+    fn from(power: &PowerInfo) -> Self {
+        Self {
+            digital_thermal_sensor: power.digital_thermal_sensor,
+            turbo_boost: power.turbo_boost,
+            arat: power.arat,
+            pln: power.pln,
+            pts: power.pts,
+            hwp: power.hwp,
+            hwp_notification: power.hwp_notification,
+            hwp_activity_window: power.hwp_activity_window,
+            hwp_epp: power.hwp_epp,
+            hwp_package: power.hwp_package,
+            hdc: power.hdc,
+        }
+    }
+}
+
+impl CpuInfo {
+    /// Thermal and HWP capabilities from the already detected `power` section.
+    pub fn thermal(&self) -> ThermalInfo {
+        ThermalInfo::from(&self.power)
+    }
+}
+
+impl fmt::Display for ThermalInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Thermal Management:")?;
+        let flags = [
+            (self.digital_thermal_sensor, "Digital Thermal Sensor"),
+            (self.turbo_boost, "Turbo Boost"),
+            (self.arat, "Always Running APIC Timer"),
+            (self.pln, "Power Limit Notification"),
+            (self.pts, "Package Thermal Status"),
+            (self.hwp, "Hardware P-States"),
+            (self.hwp_notification, "HWP Notification"),
+            (self.hwp_activity_window, "HWP Activity Window"),
+            (self.hwp_epp, "HWP Energy Performance Preference"),
+            (self.hwp_package, "HWP Package Level Control"),
+            (self.hdc, "Hardware Duty Cycling"),
+        ];
+        for (enabled, name) in flags {
+            write!(f, "\n  {}: {}", name, if enabled { "Yes" } else { "No" })?;
+        }
+        Ok(())
+    }
+}
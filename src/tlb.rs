@@ -2,193 +2,645 @@
 //!
 //! Detects TLB sizes and configurations.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cache::human_bytes;
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::descriptors;
+use crate::error::{self, DetectError};
+use crate::vendor::CpuVendor;
+use crate::vendor_ops;
+use bitflags::bitflags;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TlbLevel {
+    #[default]
+    L1,
+    L2,
+    L3,
+}
+
+impl TlbLevel {
+    fn from_number(level: u32) -> Option<Self> {
+        match level {
+            1 => Some(Self::L1),
+            2 => Some(Self::L2),
+            3 => Some(Self::L3),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TlbLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TlbKind {
+    Data,
+    Instruction,
+    #[default]
+    Unified,
+    /// Data TLB that only serves loads (leaf 0x18 type 4).
+    LoadOnly,
+    /// Data TLB that only serves stores (leaf 0x18 type 5).
+    StoreOnly,
+}
+
+impl fmt::Display for TlbKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Data => "Data",
+            Self::Instruction => "Instruction",
+            Self::Unified => "Unified",
+            Self::LoadOnly => "Load-only",
+            Self::StoreOnly => "Store-only",
+        })
+    }
+}
+
+bitflags! {
+    /// Page sizes one TLB holds translations for; many hold several.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct PageSizes: u8 {
+        const K4 = 1 << 0;
+        const M2 = 1 << 1;
+        const M4 = 1 << 2;
+        const G1 = 1 << 3;
+    }
+}
+
+/// Serialized as the list of set size names, e.g. `["K4", "M2"]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PageSizes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter_names().map(|(name, _)| name))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PageSizes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        names.iter().try_fold(PageSizes::empty(), |set, name| {
+            PageSizes::from_name(name)
+                .map(|size| set | size)
+                .ok_or_else(|| serde::de::Error::custom(format!("unknown page size '{}'", name)))
+        })
+    }
+}
+
+impl PageSizes {
+    /// Bytes in one page of each size, smallest first.
+    pub const SIZES: [(Self, u64); 4] = [
+        (Self::K4, 4 << 10),
+        (Self::M2, 2 << 20),
+        (Self::M4, 4 << 20),
+        (Self::G1, 1 << 30),
+    ];
+
+    /// Bytes per page for a single size; 0 for an empty or multi-size set.
+    pub fn page_bytes(&self) -> u64 {
+        Self::SIZES.iter().find(|(size, _)| size == self).map_or(0, |(_, bytes)| *bytes)
+    }
+}
+
+/// Human form, e.g. `4K/2M/4M`.
+impl fmt::Display for PageSizes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = [(Self::K4, "4K"), (Self::M2, "2M"), (Self::M4, "4M"), (Self::G1, "1G")];
+        let sizes: Vec<&str> = labels
+            .iter()
+            .filter(|(size, _)| self.contains(*size))
+            .map(|(_, label)| *label)
+            .collect();
+        f.write_str(&sizes.join("/"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Associativity {
+    Ways(u32),
+    Full,
+    /// The TLB exists but is turned off (AMD associativity code 0).
+    Disabled,
+    /// An encoding the vendor documents as reserved.
+    Reserved,
+    /// The source gives no associativity (leaf 2 descriptor 0x4F).
+    Unknown,
+}
+
+impl Default for Associativity {
+    fn default() -> Self {
+        Self::Ways(1)
+    }
+}
+
+impl fmt::Display for Associativity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ways(ways) => write!(f, "{}-way", ways),
+            Self::Full => f.write_str("Fully"),
+            Self::Disabled => f.write_str("Disabled"),
+            Self::Reserved => f.write_str("Reserved"),
+            Self::Unknown => f.write_str("unknown associativity"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TlbEntry {
-    pub page_size: String,
+    pub level: TlbLevel,
+    pub kind: TlbKind,
+    pub page_sizes: PageSizes,
     pub entries: u32,
-    pub associativity: String,
-    pub tlb_type: String,
+    pub associativity: Associativity,
+    /// Logical processors sharing this TLB (leaf 0x18 EDX[25:14] + 1); 0 when
+    /// the source does not say.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shared_by: u32,
+    /// Leaf 0x18 partitioning scheme; 0 is soft partitioning between the
+    /// sharing logical processors, other values are reserved.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub partitioning: u32,
+}
+
+impl TlbEntry {
+    /// Level and kind, e.g. `L2 Unified`.
+    pub fn name(&self) -> String {
+        format!("{} {}", self.level, self.kind)
+    }
+
+    /// Memory this TLB maps when filled with `page_size` pages; 0 if it does
+    /// not hold that size.
+    pub fn reach(&self, page_size: PageSizes) -> u64 {
+        if self.page_sizes.contains(page_size) {
+            self.entries as u64 * page_size.page_bytes()
+        } else {
+            0
+        }
+    }
+}
+
+/// Memory one TLB level maps for one page size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlbReach {
+    pub level: TlbLevel,
+    pub kind: TlbKind,
+    pub page_size: PageSizes,
+    pub bytes: u64,
+}
+
+impl fmt::Display for TlbReach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} covers {} with {} pages",
+            self.level,
+            self.kind,
+            human_bytes(self.bytes),
+            self.page_size
+        )
+    }
+}
+
+impl fmt::Display for TlbEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {} entries, {}",
+            self.name(),
+            self.page_sizes,
+            self.entries,
+            self.associativity
+        )?;
+        if self.shared_by > 1 {
+            write!(f, ", shared by {} threads", self.shared_by)?;
+        }
+        Ok(())
+    }
+}
+
+/// Instructions and modes for managing TLB contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TlbManagement {
+    /// Process-context identifiers tag entries by address space (leaf 1 ECX[17]).
+    pub pcid: bool,
+    /// INVPCID flushes by PCID (leaf 7 EBX[10]).
+    pub invpcid: bool,
+    /// Global pages survive CR3 writes (leaf 1 EDX[13]).
+    pub global_pages: bool,
+    /// AMD INVLPGB/TLBSYNC broadcast invalidation (0x8000_0008 EBX[3]).
+    pub invlpgb: bool,
+    /// Most pages one INVLPGB may invalidate (0x8000_0008 EDX[15:0] + 1);
+    /// 0 without INVLPGB.
+    pub invlpgb_max_pages: u32,
+    /// AMD translation cache extension: INVLPG leaves unrelated upper-level
+    /// page table entries cached (0x8000_0001 ECX[17]).
+    pub translation_cache_extension: bool,
+    /// SVM can flush only the current guest's ASID (0x8000_000A EDX[6]).
+    pub flush_by_asid: bool,
+}
+
+impl TlbManagement {
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let leaf1 = src.cpuid(1, 0);
+        let mut info = Self {
+            pcid: leaf1.ecx & (1 << 17) != 0,
+            global_pages: leaf1.edx & (1 << 13) != 0,
+            ..Default::default()
+        };
+        if src.is_leaf_supported(7) {
+            info.invpcid = src.cpuid(7, 0).ebx & (1 << 10) != 0;
+        }
+        if src.is_leaf_supported(0x8000_0001) {
+            info.translation_cache_extension = src.cpuid(0x8000_0001, 0).ecx & (1 << 17) != 0;
+        }
+        if src.is_leaf_supported(0x8000_0008) {
+            let result = src.cpuid(0x8000_0008, 0);
+            info.invlpgb = result.ebx & (1 << 3) != 0;
+            if info.invlpgb {
+                info.invlpgb_max_pages = (result.edx & 0xFFFF) + 1;
+            }
+        }
+        if src.is_leaf_supported(0x8000_000A) {
+            info.flush_by_asid = src.cpuid(0x8000_000A, 0).edx & (1 << 6) != 0;
+        }
+        info
+    }
 }
 
-#[derive(Debug, Clone)]
+/// Comma-separated list of what is supported, or "none".
+impl fmt::Display for TlbManagement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.pcid {
+            parts.push("PCID".to_string());
+        }
+        if self.invpcid {
+            parts.push("INVPCID".to_string());
+        }
+        if self.global_pages {
+            parts.push("global pages".to_string());
+        }
+        if self.invlpgb {
+            parts.push(format!("INVLPGB/TLBSYNC (up to {} pages)", self.invlpgb_max_pages));
+        }
+        if self.translation_cache_extension {
+            parts.push("TCE".to_string());
+        }
+        if self.flush_by_asid {
+            parts.push("flush by ASID".to_string());
+        }
+        if parts.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&parts.join(", "))
+        }
+    }
+}
+
+/// What the CPU, its TLBs and optionally the kernel offer for one page size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HugepageSupport {
+    pub page_size: PageSizes,
+    /// The paging architecture can map pages of this size.
+    pub architectural: bool,
+    /// TLB entries that hold this size, summed over every TLB.
+    pub tlb_entries: u32,
+    /// Linux has a hugetlb pool for this size (`/sys/kernel/mm/hugepages`);
+    /// `None` unless read with `hugepage_support_with_kernel` on Linux.
+    pub kernel_enabled: Option<bool>,
+}
+
+impl fmt::Display for HugepageSupport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}, {} TLB entries",
+            self.page_size,
+            if self.architectural { "supported" } else { "not supported" },
+            self.tlb_entries
+        )?;
+        match self.kernel_enabled {
+            Some(true) => write!(f, ", enabled in the kernel"),
+            Some(false) => write!(f, ", not enabled in the kernel"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TlbInfo {
     pub entries: Vec<TlbEntry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub management: TlbManagement,
+    /// Page sizes the paging architecture supports: 4K always, 2M with PAE,
+    /// 4M with PSE (32-bit paging only) and 1G with PDPE1GB.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub page_sizes: PageSizes,
 }
 
 impl TlbInfo {
     pub fn detect() -> Self {
-        let mut entries = Vec::new();
+        Self::detect_with(&HardwareCpuid)
+    }
 
-        if is_leaf_supported(0x8000_0005) {
-            detect_amd_l1_tlb(&mut entries);
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "tlb", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self {
+            entries: vendor_ops::detect(src).tlbs(src),
+            management: TlbManagement::detect_with(src),
+            page_sizes: detect_page_sizes(src),
         }
+    }
+
+    /// Entries ordered by level, kind, page sizes, entry count and associativity.
+    pub fn sorted(mut self) -> Self {
+        self.entries.sort();
+        self
+    }
+
+    /// Entries covering `size` pages, e.g. every 1G TLB.
+    pub fn for_page_size(&self, size: PageSizes) -> impl Iterator<Item = &TlbEntry> {
+        self.entries.iter().filter(move |e| e.page_sizes.contains(size))
+    }
+
+    /// Data memory reachable without a page walk when mapped with `page_size`
+    /// pages: the largest reach of any level's data, load-only or unified
+    /// TLBs. Levels are not added up, since the second level mostly repeats
+    /// the first.
+    pub fn coverage(&self, page_size: PageSizes) -> u64 {
+        self.reach()
+            .iter()
+            .filter(|r| r.page_size == page_size)
+            .filter(|r| !matches!(r.kind, TlbKind::Instruction | TlbKind::StoreOnly))
+            .map(|r| r.bytes)
+            .max()
+            .unwrap_or(0)
+    }
 
-        if is_leaf_supported(0x8000_0006) {
-            detect_amd_l2_tlb(&mut entries);
+    /// Reach per level, kind and page size, with split TLBs of the same kind
+    /// (e.g. a 2M/4M and a 1G L1 DTLB) added together.
+    pub fn reach(&self) -> Vec<TlbReach> {
+        let mut reach: Vec<TlbReach> = Vec::new();
+        for entry in &self.entries {
+            for (page_size, _) in PageSizes::SIZES {
+                let bytes = entry.reach(page_size);
+                if bytes == 0 {
+                    continue;
+                }
+                let (level, kind) = (entry.level, entry.kind);
+                match reach
+                    .iter_mut()
+                    .find(|r| r.level == level && r.kind == kind && r.page_size == page_size)
+                {
+                    Some(existing) => existing.bytes += bytes,
+                    None => reach.push(TlbReach { level, kind, page_size, bytes }),
+                }
+            }
         }
+        reach.sort_by_key(|r| (r.level, r.kind, r.page_size));
+        reach
+    }
+
+    /// Architectural and TLB support for each page size, smallest first.
+    pub fn hugepage_support(&self) -> Vec<HugepageSupport> {
+        PageSizes::SIZES
+            .iter()
+            .map(|&(page_size, _)| HugepageSupport {
+                page_size,
+                architectural: self.page_sizes.contains(page_size),
+                tlb_entries: self.for_page_size(page_size).map(|e| e.entries).sum(),
+                kernel_enabled: None,
+            })
+            .collect()
+    }
 
-        if is_leaf_supported(0x18) {
-            detect_intel_tlb(&mut entries);
+    /// `hugepage_support` plus the hugetlb page sizes the running Linux kernel
+    /// offers; on other systems `kernel_enabled` stays `None`.
+    pub fn hugepage_support_with_kernel(&self) -> Vec<HugepageSupport> {
+        let mut support = self.hugepage_support();
+        #[cfg(target_os = "linux")]
+        for entry in &mut support {
+            let kb = entry.page_size.page_bytes() / 1024;
+            let pool = format!("/sys/kernel/mm/hugepages/hugepages-{}kB", kb);
+            // 4K pages are the base page size, never a hugetlb pool
+            entry.kernel_enabled =
+                Some(entry.page_size == PageSizes::K4 || std::path::Path::new(&pool).exists());
         }
+        support
+    }
 
-        Self { entries }
+    /// Like `detect`, but fails instead of returning no TLB entries.
+    pub fn try_detect() -> Result<Self, DetectError> {
+        Self::try_detect_with(&HardwareCpuid)
+    }
+
+    pub fn try_detect_with(src: &dyn CpuidSource) -> Result<Self, DetectError> {
+        let info = Self::detect_with(src);
+        if info.entries.is_empty() {
+            let leaf = match CpuVendor::detect_with(src) {
+                CpuVendor::Amd | CpuVendor::Hygon => 0x8000_0005,
+                _ => 0x18,
+            };
+            return Err(error::empty_leaf(src, leaf, 0));
+        }
+        Ok(info)
     }
 }
 
-fn detect_amd_l1_tlb(entries: &mut Vec<TlbEntry>) {
-    let result = cpuid(0x8000_0005, 0);
+/// AMD L1/L2 TLB descriptors from leaves 0x8000_0005 and 0x8000_0006, plus
+/// the 1G-page TLBs from leaf 0x8000_0019.
+pub(crate) fn detect_amd(src: &dyn CpuidSource) -> Vec<TlbEntry> {
+    let mut entries = Vec::new();
 
-    let l1_dtlb_2m4m = (result.eax >> 16) & 0xFFFF;
-    if l1_dtlb_2m4m != 0 {
-        entries.push(TlbEntry {
-            page_size: "2M/4M".to_string(),
-            entries: (l1_dtlb_2m4m & 0xFF) as u32,
-            associativity: decode_assoc((l1_dtlb_2m4m >> 8) & 0xFF),
-            tlb_type: "L1 Data".to_string(),
-        });
+    if src.is_leaf_supported(0x8000_0005) {
+        detect_amd_l1_tlb(src, &mut entries);
     }
 
-    let l1_itlb_2m4m = result.eax & 0xFFFF;
-    if l1_itlb_2m4m != 0 {
-        entries.push(TlbEntry {
-            page_size: "2M/4M".to_string(),
-            entries: (l1_itlb_2m4m & 0xFF) as u32,
-            associativity: decode_assoc((l1_itlb_2m4m >> 8) & 0xFF),
-            tlb_type: "L1 Instruction".to_string(),
-        });
+    if src.is_leaf_supported(0x8000_0006) {
+        detect_amd_l2_tlb(src, &mut entries);
     }
 
-    let l1_dtlb_4k = (result.ebx >> 16) & 0xFFFF;
-    if l1_dtlb_4k != 0 {
-        entries.push(TlbEntry {
-            page_size: "4K".to_string(),
-            entries: (l1_dtlb_4k & 0xFF) as u32,
-            associativity: decode_assoc((l1_dtlb_4k >> 8) & 0xFF),
-            tlb_type: "L1 Data".to_string(),
-        });
+    if src.is_leaf_supported(0x8000_0019) {
+        detect_amd_1g_tlb(src, &mut entries);
     }
 
-    let l1_itlb_4k = result.ebx & 0xFFFF;
-    if l1_itlb_4k != 0 {
-        entries.push(TlbEntry {
-            page_size: "4K".to_string(),
-            entries: (l1_itlb_4k & 0xFF) as u32,
-            associativity: decode_assoc((l1_itlb_4k >> 8) & 0xFF),
-            tlb_type: "L1 Instruction".to_string(),
-        });
-    }
+    entries
 }
 
-fn detect_amd_l2_tlb(entries: &mut Vec<TlbEntry>) {
-    let result = cpuid(0x8000_0006, 0);
+/// Deterministic address translation parameters from leaf 0x18.
+pub(crate) fn detect_intel(src: &dyn CpuidSource) -> Vec<TlbEntry> {
+    let mut entries = Vec::new();
 
-    let l2_dtlb_2m4m = (result.eax >> 16) & 0xFFFF;
-    if l2_dtlb_2m4m != 0 {
-        entries.push(TlbEntry {
-            page_size: "2M/4M".to_string(),
-            entries: (l2_dtlb_2m4m & 0xFFF) as u32,
-            associativity: decode_assoc_l2((l2_dtlb_2m4m >> 12) & 0xF),
-            tlb_type: "L2 Data".to_string(),
-        });
+    if src.is_leaf_supported(0x18) {
+        detect_intel_tlb(src, &mut entries);
     }
 
-    let l2_itlb_2m4m = result.eax & 0xFFFF;
-    if l2_itlb_2m4m != 0 {
-        entries.push(TlbEntry {
-            page_size: "2M/4M".to_string(),
-            entries: (l2_itlb_2m4m & 0xFFF) as u32,
-            associativity: decode_assoc_l2((l2_itlb_2m4m >> 12) & 0xF),
-            tlb_type: "L2 Instruction".to_string(),
-        });
+    // Leaf 2 descriptors on CPUs without leaf 0x18, or with an empty one
+    if entries.is_empty() {
+        entries = descriptors::tlbs(src);
     }
 
-    let l2_dtlb_4k = (result.ebx >> 16) & 0xFFFF;
-    if l2_dtlb_4k != 0 {
-        entries.push(TlbEntry {
-            page_size: "4K".to_string(),
-            entries: (l2_dtlb_4k & 0xFFF) as u32,
-            associativity: decode_assoc_l2((l2_dtlb_4k >> 12) & 0xF),
-            tlb_type: "L2 Data".to_string(),
-        });
+    entries
+}
+
+fn detect_amd_l1_tlb(src: &dyn CpuidSource, entries: &mut Vec<TlbEntry>) {
+    let result = src.cpuid(0x8000_0005, 0);
+
+    let large = PageSizes::M2 | PageSizes::M4;
+    let tlbs = [
+        ((result.eax >> 16) & 0xFFFF, TlbKind::Data, large),
+        (result.eax & 0xFFFF, TlbKind::Instruction, large),
+        ((result.ebx >> 16) & 0xFFFF, TlbKind::Data, PageSizes::K4),
+        (result.ebx & 0xFFFF, TlbKind::Instruction, PageSizes::K4),
+    ];
+    for (tlb, kind, page_sizes) in tlbs {
+        if tlb != 0 {
+            entries.push(TlbEntry {
+                level: TlbLevel::L1,
+                kind,
+                page_sizes,
+                entries: tlb & 0xFF,
+                associativity: decode_assoc((tlb >> 8) & 0xFF),
+                ..Default::default()
+            });
+        }
     }
+}
 
-    let l2_itlb_4k = result.ebx & 0xFFFF;
-    if l2_itlb_4k != 0 {
-        entries.push(TlbEntry {
-            page_size: "4K".to_string(),
-            entries: (l2_itlb_4k & 0xFFF) as u32,
-            associativity: decode_assoc_l2((l2_itlb_4k >> 12) & 0xF),
-            tlb_type: "L2 Instruction".to_string(),
-        });
+fn detect_amd_l2_tlb(src: &dyn CpuidSource, entries: &mut Vec<TlbEntry>) {
+    let result = src.cpuid(0x8000_0006, 0);
+
+    let large = PageSizes::M2 | PageSizes::M4;
+    let tlbs = [
+        ((result.eax >> 16) & 0xFFFF, TlbKind::Data, large),
+        (result.eax & 0xFFFF, TlbKind::Instruction, large),
+        ((result.ebx >> 16) & 0xFFFF, TlbKind::Data, PageSizes::K4),
+        (result.ebx & 0xFFFF, TlbKind::Instruction, PageSizes::K4),
+    ];
+    for (tlb, kind, page_sizes) in tlbs {
+        if tlb != 0 {
+            entries.push(TlbEntry {
+                level: TlbLevel::L2,
+                kind,
+                page_sizes,
+                entries: tlb & 0xFFF,
+                associativity: decode_assoc_l2((tlb >> 12) & 0xF),
+                ..Default::default()
+            });
+        }
     }
 }
 
-fn detect_intel_tlb(entries: &mut Vec<TlbEntry>) {
-    for subleaf in 0..10 {
-        let result = cpuid(0x18, subleaf);
-        if result.eax == 0 {
-            break;
+/// Same layout as the L2 fields of 0x8000_0006, for both levels.
+fn detect_amd_1g_tlb(src: &dyn CpuidSource, entries: &mut Vec<TlbEntry>) {
+    let result = src.cpuid(0x8000_0019, 0);
+
+    let tlbs = [
+        ((result.eax >> 16) & 0xFFFF, TlbLevel::L1, TlbKind::Data),
+        (result.eax & 0xFFFF, TlbLevel::L1, TlbKind::Instruction),
+        ((result.ebx >> 16) & 0xFFFF, TlbLevel::L2, TlbKind::Data),
+        (result.ebx & 0xFFFF, TlbLevel::L2, TlbKind::Instruction),
+    ];
+    for (tlb, level, kind) in tlbs {
+        if tlb != 0 {
+            entries.push(TlbEntry {
+                level,
+                kind,
+                page_sizes: PageSizes::G1,
+                entries: tlb & 0xFFF,
+                associativity: decode_assoc_l2((tlb >> 12) & 0xF),
+                ..Default::default()
+            });
         }
+    }
+}
 
-        let tlb_type = match result.edx & 0x1F {
-            0 => continue,
-            1 => "Data",
-            2 => "Instruction",
-            3 => "Unified",
+fn detect_page_sizes(src: &dyn CpuidSource) -> PageSizes {
+    let leaf1 = src.cpuid(1, 0);
+    let mut sizes = PageSizes::K4;
+    sizes.set(PageSizes::M2, leaf1.edx & (1 << 6) != 0);
+    sizes.set(PageSizes::M4, leaf1.edx & (1 << 3) != 0);
+    if src.is_leaf_supported(0x8000_0001) {
+        sizes.set(PageSizes::G1, src.cpuid(0x8000_0001, 0).edx & (1 << 26) != 0);
+    }
+    sizes
+}
+
+/// Subleaf 0 EAX gives the last subleaf; EAX is reserved in the others, so
+/// it cannot end the walk. Subleaves of type 0 are invalid and skipped.
+fn detect_intel_tlb(src: &dyn CpuidSource, entries: &mut Vec<TlbEntry>) {
+    // Bounded in case a hypervisor returns garbage in EAX
+    let last = src.cpuid(0x18, 0).eax.min(63);
+    for subleaf in 0..=last {
+        let result = src.cpuid(0x18, subleaf);
+
+        let kind = match result.edx & 0x1F {
+            1 => TlbKind::Data,
+            2 => TlbKind::Instruction,
+            3 => TlbKind::Unified,
+            4 => TlbKind::LoadOnly,
+            5 => TlbKind::StoreOnly,
             _ => continue,
         };
-
-        let level = ((result.edx >> 5) & 0x7) as u32;
-        let page_size = match (result.ebx >> 0) & 0x3 {
-            0 => "4K",
-            1 => "2M",
-            2 => "4M",
-            3 => "1G",
-            _ => "Unknown",
+        let Some(level) = TlbLevel::from_number((result.edx >> 5) & 0x7) else {
+            continue;
         };
 
-        let ways = ((result.ebx >> 16) & 0xFFFF) as u32;
+        // EBX[3:0] is a bitmap of 4K, 2M, 4M and 1G support
+        let page_sizes = PageSizes::from_bits_truncate((result.ebx & 0xF) as u8);
+        let ways = (result.ebx >> 16) & 0xFFFF;
         let sets = result.ecx;
 
         entries.push(TlbEntry {
-            page_size: page_size.to_string(),
+            level,
+            kind,
+            page_sizes,
             entries: ways * sets,
-            associativity: if ways == 0xFFFF {
-                "Fully".to_string()
+            associativity: if result.edx & (1 << 8) != 0 || ways == 0xFFFF {
+                Associativity::Full
             } else {
-                format!("{}-way", ways)
+                Associativity::Ways(ways)
             },
-            tlb_type: format!("L{} {}", level, tlb_type),
+            shared_by: ((result.edx >> 14) & 0xFFF) + 1,
+            partitioning: (result.ebx >> 8) & 0x7,
         });
     }
 }
 
-fn decode_assoc(val: u32) -> String {
+fn decode_assoc(val: u32) -> Associativity {
     match val {
-        0x00 => "Reserved".to_string(),
-        0x01 => "1-way".to_string(),
-        0x02 => "2-way".to_string(),
-        0xFF => "Fully".to_string(),
-        _ => format!("{}-way", val),
+        0x00 => Associativity::Reserved,
+        0xFF => Associativity::Full,
+        _ => Associativity::Ways(val),
     }
 }
 
-fn decode_assoc_l2(val: u32) -> String {
+fn decode_assoc_l2(val: u32) -> Associativity {
     match val {
-        0x0 => "Disabled".to_string(),
-        0x1 => "1-way".to_string(),
-        0x2 => "2-way".to_string(),
-        0x4 => "4-way".to_string(),
-        0x6 => "8-way".to_string(),
-        0x8 => "16-way".to_string(),
-        0xA => "32-way".to_string(),
-        0xB => "48-way".to_string(),
-        0xC => "64-way".to_string(),
-        0xD => "96-way".to_string(),
-        0xE => "128-way".to_string(),
-        0xF => "Fully".to_string(),
-        _ => format!("{}-way", val),
+        0x0 => Associativity::Disabled,
+        0x1 => Associativity::Ways(1),
+        0x2 => Associativity::Ways(2),
+        0x4 => Associativity::Ways(4),
+        0x6 => Associativity::Ways(8),
+        0x8 => Associativity::Ways(16),
+        0xA => Associativity::Ways(32),
+        0xB => Associativity::Ways(48),
+        0xC => Associativity::Ways(64),
+        0xD => Associativity::Ways(96),
+        0xE => Associativity::Ways(128),
+        0xF => Associativity::Full,
+        _ => Associativity::Reserved,
     }
 }
@@ -2,87 +2,703 @@
 //! 
 //! Detects CPU core count, threading, and topology information.
 
-use crate::cpuid::{cpuid, is_leaf_supported};
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use crate::vendor::Signature;
+use crate::vendor_ops;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CoreType {
     Performance,
     Efficient,
+    #[default]
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+impl CoreType {
+    /// Core type of the CPU this thread is currently running on (hybrid parts only).
+    pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        if !src.is_leaf_supported(0x1A) {
+            return Self::Unknown;
+        }
+
+        // Leaf 0x1A EAX[31:24]: 0x20 = Atom (E-core), 0x40 = Core (P-core)
+        match src.cpuid(0x1A, 0).eax >> 24 {
+            0x20 => Self::Efficient,
+            0x40 => Self::Performance,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Native model ID of the CPU this thread is running on (leaf 0x1A
+    /// EAX[23:0]), telling core generations of one type apart.
+    pub fn native_model_id_with(src: &dyn CpuidSource) -> Option<u32> {
+        if !src.is_leaf_supported(0x1A) {
+            return None;
+        }
+        Some(src.cpuid(0x1A, 0).eax & 0xFF_FFFF).filter(|&id| id != 0)
+    }
+}
+
+/// Core type and native model ID of one logical CPU (leaf 0x1A).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreIdentity {
+    pub core_type: CoreType,
+    /// `None` on non-hybrid parts.
+    pub native_model_id: Option<u32>,
+}
+
+impl CoreIdentity {
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self {
+            core_type: CoreType::detect_with(src),
+            native_model_id: CoreType::native_model_id_with(src),
+        }
+    }
+
+    pub fn is_efficient(&self) -> bool {
+        self.core_type == CoreType::Efficient
+    }
+}
+
+impl fmt::Display for CoreIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.core_type {
+            CoreType::Performance => write!(f, "P-core")?,
+            CoreType::Efficient => write!(f, "E-core")?,
+            CoreType::Unknown => write!(f, "unknown core type")?,
+        }
+        match self.native_model_id {
+            Some(id) => write!(f, " (native model {:#x})", id),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Domain types of leaf 0x1F (0xB has only `Smt` and `Core`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TopologyLevelType {
+    Smt,
+    Core,
+    Module,
+    Tile,
+    Die,
+    DieGroup,
+    /// A type this crate does not know yet.
+    Other(u32),
+}
+
+impl TopologyLevelType {
+    fn from_raw(level_type: u32) -> Self {
+        match level_type {
+            1 => Self::Smt,
+            2 => Self::Core,
+            3 => Self::Module,
+            4 => Self::Tile,
+            5 => Self::Die,
+            6 => Self::DieGroup,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for TopologyLevelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Smt => write!(f, "SMT"),
+            Self::Core => write!(f, "Core"),
+            Self::Module => write!(f, "Module"),
+            Self::Tile => write!(f, "Tile"),
+            Self::Die => write!(f, "Die"),
+            Self::DieGroup => write!(f, "Die group"),
+            Self::Other(level_type) => write!(f, "Type {}", level_type),
+        }
+    }
+}
+
+/// One subleaf of leaf 0x1F/0xB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopologyLevel {
+    pub level_type: TopologyLevelType,
+    /// Right shift of an x2APIC ID that yields the ID of the next level up.
+    pub shift: u32,
+    /// Logical processors in one domain of the next level up, as shipped;
+    /// for the last level, in the whole package.
+    pub logical_processors: u32,
+}
+
+/// Every level of leaf 0x1F, or of 0xB on CPUs without it, lowest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopologyLevels {
+    /// 0x1F or 0xB.
+    pub leaf: u32,
+    pub levels: Vec<TopologyLevel>,
+}
+
+impl TopologyLevels {
+    /// `None` without leaf 0x1F or 0xB.
+    pub fn detect_with(src: &dyn CpuidSource) -> Option<Self> {
+        let leaf = extended_leaf(src)?;
+        let mut levels = Vec::new();
+        for subleaf in 0..16 {
+            let result = src.cpuid(leaf, subleaf);
+            let level_type = (result.ecx >> 8) & 0xFF;
+            if level_type == 0 {
+                break;
+            }
+            levels.push(TopologyLevel {
+                level_type: TopologyLevelType::from_raw(level_type),
+                shift: result.eax & 0x1F,
+                logical_processors: result.ebx & 0xFFFF,
+            });
+        }
+        Some(Self { leaf, levels })
+    }
+
+    pub fn get(&self, level_type: TopologyLevelType) -> Option<&TopologyLevel> {
+        self.levels.iter().find(|level| level.level_type == level_type)
+    }
+
+    /// Logical processors in the package.
+    pub fn package_logical_processors(&self) -> u32 {
+        self.levels.last().map_or(1, |level| level.logical_processors.max(1))
+    }
+
+    /// Logical processors in one domain of `level_type`: 1 for an SMT thread,
+    /// the SMT width for a core, and so on.
+    pub fn logical_per_domain(&self, level_type: TopologyLevelType) -> Option<u32> {
+        let index = self.levels.iter().position(|level| level.level_type == level_type)?;
+        Some(match index {
+            0 => 1,
+            _ => self.levels[index - 1].logical_processors.max(1),
+        })
+    }
+
+    /// Domains of `level_type` in the package, e.g. dies per package.
+    pub fn domains_per_package(&self, level_type: TopologyLevelType) -> Option<u32> {
+        let per_domain = self.logical_per_domain(level_type)?;
+        Some((self.package_logical_processors() / per_domain).max(1))
+    }
+
+    /// Bits of the x2APIC ID that number domains of `level_type` within the
+    /// next level up.
+    pub fn width(&self, level_type: TopologyLevelType) -> Option<u32> {
+        let index = self.levels.iter().position(|level| level.level_type == level_type)?;
+        let below = if index == 0 { 0 } else { self.levels[index - 1].shift };
+        Some(self.levels[index].shift.saturating_sub(below))
+    }
+
+    /// ID of the `level_type` domain holding `x2apic_id`, within the next
+    /// level up: the die within its package, the module within its die.
+    pub fn domain_id(&self, level_type: TopologyLevelType, x2apic_id: u32) -> Option<u32> {
+        let index = self.levels.iter().position(|level| level.level_type == level_type)?;
+        let below = if index == 0 { 0 } else { self.levels[index - 1].shift };
+        let width = self.width(level_type)?;
+        Some(x2apic_id.checked_shr(below).unwrap_or(0) & mask(width))
+    }
+}
+
+impl fmt::Display for TopologyLevels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let levels: Vec<String> = self
+            .levels
+            .iter()
+            .map(|level| {
+                let count = self.domains_per_package(level.level_type).unwrap_or(1);
+                format!("{} {}", count, level.level_type)
+            })
+            .collect();
+        write!(f, "{} per package (leaf {:#x})", levels.join(", "), self.leaf)
+    }
+}
+
+/// How an APIC ID splits into SMT thread, core and package fields. Module,
+/// tile and die levels of leaf 0x1F are folded into the core field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApicIdLayout {
+    /// Low bits selecting the thread within a core.
+    pub smt_bits: u32,
+    /// Bits above those selecting the core within the package.
+    pub core_bits: u32,
+}
+
+impl ApicIdLayout {
+    /// Right shift that leaves the package ID.
+    pub fn package_shift(&self) -> u32 {
+        self.smt_bits + self.core_bits
+    }
+
+    pub fn decompose(&self, apic_id: u32) -> ApicIdParts {
+        let field = |shift: u32, bits: u32| apic_id.checked_shr(shift).unwrap_or(0) & mask(bits);
+        ApicIdParts {
+            package: apic_id.checked_shr(self.package_shift()).unwrap_or(0),
+            core: field(self.smt_bits, self.core_bits),
+            thread: field(0, self.smt_bits),
+        }
+    }
+}
+
+/// Package, core and thread numbers of one APIC ID. Core numbers are IDs,
+/// not indices: they can have gaps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApicIdParts {
+    pub package: u32,
+    pub core: u32,
+    pub thread: u32,
+}
+
+impl fmt::Display for ApicIdParts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "package {}, core {}, thread {}", self.package, self.core, self.thread)
+    }
+}
+
+/// AMD and Hygon node and core complex structure from leaf 0x8000_001E
+/// (TOPOEXT) and the L3 sharing of leaf 0x8000_001D.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmdTopology {
+    /// 32-bit extended APIC ID (EAX).
+    pub extended_apic_id: u32,
+    /// Core ID, a compute unit ID on family 0x15 (EBX[7:0]).
+    pub compute_unit_id: u32,
+    pub threads_per_compute_unit: u32,
+    /// NUMA node ID (ECX[7:0]).
+    pub node_id: u32,
+    pub nodes_per_processor: u32,
+    /// Core complex (CCX): the group of cores sharing one L3.
+    pub ccx_id: Option<u32>,
+    pub threads_per_ccx: Option<u32>,
+    /// Core complex die (CCD). Zen 3 and later put one CCX on each CCD, Zen,
+    /// Zen 2 and the dense Zen 4c and Zen 5c server parts two.
+    pub ccd_id: Option<u32>,
+}
+
+impl AmdTopology {
+    /// `None` without TOPOEXT (0x8000_0001 ECX[22]) or leaf 0x8000_001E.
+    pub fn detect_with(src: &dyn CpuidSource) -> Option<Self> {
+        let topoext =
+            src.is_leaf_supported(0x8000_0001) && src.cpuid(0x8000_0001, 0).ecx & (1 << 22) != 0;
+        if !topoext || !src.is_leaf_supported(0x8000_001E) {
+            return None;
+        }
+
+        let result = src.cpuid(0x8000_001E, 0);
+        let mut info = Self {
+            extended_apic_id: result.eax,
+            compute_unit_id: result.ebx & 0xFF,
+            threads_per_compute_unit: ((result.ebx >> 8) & 0xFF) + 1,
+            node_id: result.ecx & 0xFF,
+            nodes_per_processor: ((result.ecx >> 8) & 0x7) + 1,
+            ..Default::default()
+        };
+
+        if let Some(l3) = amd_l3_sharing(src) {
+            let ccx_id = result.eax >> bits_for(l3);
+            let signature = Signature::from_raw(src.cpuid(1, 0).eax);
+            info.ccx_id = Some(ccx_id);
+            info.threads_per_ccx = Some(l3);
+            info.ccd_id = Some(if ccxs_per_ccd(signature) == 2 { ccx_id >> 1 } else { ccx_id });
+        }
+        Some(info)
+    }
+}
+
+impl fmt::Display for AmdTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {} of {}", self.node_id, self.nodes_per_processor)?;
+        if let (Some(ccd), Some(ccx), Some(threads)) =
+            (self.ccd_id, self.ccx_id, self.threads_per_ccx)
+        {
+            write!(f, ", CCD {}, CCX {} ({} threads)", ccd, ccx, threads)?;
+        }
+        write!(f, ", core {}", self.compute_unit_id)
+    }
+}
+
+/// CCXs on each CCD: two on Zen, Zen 2 and the dense Zen 4c (Bergamo,
+/// Siena) and Zen 5c (Turin Dense) server parts, one on other Zen 3 and later.
+fn ccxs_per_ccd(signature: Signature) -> u32 {
+    match (signature.family, signature.model) {
+        (0x19, 0xA0..=0xAF) | (0x1A, 0x10..=0x1F) => 2,
+        (family, _) if family >= 0x19 => 1,
+        _ => 2,
+    }
+}
+
+/// Threads sharing the L3, from leaf 0x8000_001D.
+fn amd_l3_sharing(src: &dyn CpuidSource) -> Option<u32> {
+    if !src.is_leaf_supported(0x8000_001D) {
+        return None;
+    }
+    (0..8)
+        .map(|subleaf| src.cpuid(0x8000_001D, subleaf).eax)
+        .take_while(|eax| eax & 0x1F != 0)
+        .find(|eax| (eax >> 5) & 0x7 == 3)
+        .map(|eax| ((eax >> 14) & 0xFFF) + 1)
+}
+
+/// One physical package (socket) as seen from its online logical CPUs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackageInfo {
+    /// Package field of the APIC ID.
+    pub id: u32,
+    pub cores: u32,
+    pub logical_processors: u32,
+    /// Online logical CPUs in the package.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cpus: Vec<usize>,
+}
+
+/// Per-package counts, from CPUID or from the OS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreCounts {
+    pub logical_processors: u32,
+    pub physical_cores: u32,
+    pub threads_per_core: u32,
+}
+
+impl CoreCounts {
+    /// No zero counts, no more cores than threads, and cores times threads
+    /// adding up except on hybrid parts, where only P-cores have SMT.
+    pub fn is_plausible(&self, hybrid: bool) -> bool {
+        self.logical_processors > 0
+            && self.physical_cores > 0
+            && self.threads_per_core > 0
+            && self.physical_cores <= self.logical_processors
+            && (hybrid || self.physical_cores * self.threads_per_core == self.logical_processors)
+    }
+}
+
+impl fmt::Display for CoreCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} logical, {} cores, {} threads per core",
+            self.logical_processors, self.physical_cores, self.threads_per_core
+        )
+    }
+}
+
+/// System-wide CPU counts reported by the OS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OsCpuCounts {
+    /// CPUs this process may run on: `sched_getaffinity` on Linux,
+    /// `std::thread::available_parallelism` elsewhere.
+    pub available: u32,
+    /// Online logical CPUs; `None` outside Linux.
+    pub online: Option<u32>,
+    /// Distinct package and core ID pairs in sysfs; `None` outside Linux.
+    pub physical_cores: Option<u32>,
+    pub packages: Option<u32>,
+}
+
+impl OsCpuCounts {
+    pub fn detect() -> Self {
+        detect_os_counts()
+    }
+}
+
+/// How far `CpuTopology`'s counts can be trusted after `reconcile`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CountConfidence {
+    /// CPUID and the OS agree.
+    Confirmed,
+    /// CPUID counts kept, but the OS could not confirm them (only the
+    /// process affinity was known, and it differs).
+    #[default]
+    Unverified,
+    /// CPUID counts were implausible or contradicted by the OS, typically
+    /// under a hypervisor that passes host leaves through, and were replaced.
+    Corrected,
+}
+
+impl fmt::Display for CountConfidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Confirmed => write!(f, "confirmed by the OS"),
+            Self::Unverified => write!(f, "not confirmed by the OS"),
+            Self::Corrected => write!(f, "corrected from the OS"),
+        }
+    }
+}
+
+/// Both sides of `CpuTopology::reconcile`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CountReconciliation {
+    /// Counts as CPUID reported them.
+    pub cpuid: CoreCounts,
+    pub os: OsCpuCounts,
+    pub confidence: CountConfidence,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpuTopology {
     pub logical_processors: u32,
     pub physical_cores: u32,
     pub threads_per_core: u32,
     pub has_hyperthreading: bool,
     pub hybrid: bool,
+    /// 8-bit initial APIC ID of the detecting CPU (leaf 1 EBX[31:24]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub initial_apic_id: u32,
+    /// 32-bit x2APIC ID of the detecting CPU (leaf 0x1F/0xB EDX).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub x2apic_id: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub apic_layout: ApicIdLayout,
+    /// Leaf 0x1F/0xB levels; `None` on CPUs with neither.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub levels: Option<TopologyLevels>,
+    /// Node, CCD and CCX structure on AMD and Hygon; `None` elsewhere.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub amd: Option<AmdTopology>,
+    /// P-cores (not threads); `None` until per-CPU detection has run, and on
+    /// parts that do not report core types.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub performance_cores: Option<u32>,
+    /// E-cores, filled like `performance_cores`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub efficient_cores: Option<u32>,
+    /// Logical CPUs whose core runs more than one thread; P-cores only on
+    /// hybrid parts. Empty until per-CPU detection has run.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub smt_cpus: Vec<usize>,
+    /// Packages in ID order; empty until per-CPU detection has run. The
+    /// counts above describe one package.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub packages: Vec<PackageInfo>,
+    /// CPUID and OS counts side by side; `None` from `detect_with`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reconciliation: Option<CountReconciliation>,
 }
 
 impl CpuTopology {
+    /// CPUID counts checked against the OS's; see `reconcile`.
     pub fn detect() -> Self {
-        let mut logical_processors = 1;
-        let mut physical_cores = 1;
-        let threads_per_core ;
-        let mut has_hyperthreading = false;
-        let mut hybrid = false;
-
-        // Get Hyper-Threading status from leaf 1
-        if is_leaf_supported(1) {
-            let result = cpuid(1, 0);
-            has_hyperthreading = (result.edx & (1 << 28)) != 0;
-        }
-
-        // Prioritize leaf 0xB for topology information
-        if is_leaf_supported(0xB) {
-            threads_per_core = detect_threads_per_core_leaf_b();
-            logical_processors = detect_logical_processors_leaf_b();
-            if logical_processors > 0 && threads_per_core > 0 {
-                physical_cores = logical_processors / threads_per_core;
-            }
-        } else {
-            // Fallback if leaf 0xB is not supported
-            if is_leaf_supported(1) {
-                let result = cpuid(1, 0);
-                // For older CPUs, EBX[23:16] might give logical processors
-                logical_processors = ((result.ebx >> 16) & 0xFF) as u32;
-            }
-            if is_leaf_supported(4) {
-                let result = cpuid(4, 0);
-                physical_cores = ((result.eax >> 26) & 0x3F) as u32 + 1;
-            }
+        let mut topology = Self::detect_with(&HardwareCpuid);
+        topology.reconcile(OsCpuCounts::detect());
+        topology
+    }
 
-            // If logical_processors is still 1 (and hyperthreading is off), set it to physical_cores
-            if logical_processors == 1 && !has_hyperthreading {
-                logical_processors = physical_cores;
-            }
-            
-            // Final check for threads_per_core in fallback
-            if physical_cores > 0 {
-                threads_per_core = logical_processors / physical_cores;
-            } else {
-                threads_per_core = 1;
-            }
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "topology", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let mut topology = vendor_ops::detect(src).topology(src);
+        topology.initial_apic_id = src.cpuid(1, 0).ebx >> 24;
+        topology.x2apic_id = extended_leaf(src).map(|leaf| src.cpuid(leaf, 0).edx);
+        topology.levels = TopologyLevels::detect_with(src);
+        topology.apic_layout = detect_apic_layout(src, topology.levels.as_ref());
+        topology.amd = AmdTopology::detect_with(src);
+        topology
+    }
+
+    /// Core type of the CPU the calling thread runs on right now. The
+    /// scheduler may move the thread at any time; pin it for a stable answer.
+    pub fn current_core_type() -> CoreIdentity {
+        Self::current_core_type_with(&HardwareCpuid)
+    }
+
+    pub fn current_core_type_with(src: &dyn CpuidSource) -> CoreIdentity {
+        CoreIdentity::detect_with(src)
+    }
+
+    /// The x2APIC ID when there is one, otherwise the initial APIC ID.
+    pub fn apic_id(&self) -> u32 {
+        self.x2apic_id.unwrap_or(self.initial_apic_id)
+    }
+
+    /// Package, core and thread of the detecting CPU.
+    pub fn location(&self) -> ApicIdParts {
+        self.apic_layout.decompose(self.apic_id())
+    }
+
+    /// The counts as CPUID reported them.
+    pub fn cpuid_counts(&self) -> CoreCounts {
+        self.reconciliation.map_or(
+            CoreCounts {
+                logical_processors: self.logical_processors,
+                physical_cores: self.physical_cores,
+                threads_per_core: self.threads_per_core,
+            },
+            |reconciliation| reconciliation.cpuid,
+        )
+    }
+
+    /// Check the per-package counts against `os` and replace them when they
+    /// are implausible or the OS's online CPUs contradict them. The process
+    /// affinity alone never overrides plausible CPUID counts.
+    pub fn reconcile(&mut self, os: OsCpuCounts) {
+        let cpuid = self.cpuid_counts();
+        let packages = os.packages.unwrap_or(1).max(1);
+        let os_logical = os.online.unwrap_or(os.available).div_ceil(packages);
+        let os_cores = os.physical_cores.map(|cores| cores.div_ceil(packages));
+
+        let plausible = cpuid.is_plausible(self.hybrid);
+        let agrees = cpuid.logical_processors == os_logical
+            && os_cores.is_none_or(|cores| cores == cpuid.physical_cores);
+        let confidence = match (plausible, agrees) {
+            (true, true) => CountConfidence::Confirmed,
+            (true, false) if os.online.is_none() => CountConfidence::Unverified,
+            _ if os_logical == 0 => CountConfidence::Unverified,
+            _ => CountConfidence::Corrected,
+        };
+
+        if confidence == CountConfidence::Corrected {
+            let threads = if plausible { cpuid.threads_per_core } else { 1 };
+            self.logical_processors = os_logical;
+            self.physical_cores = os_cores.unwrap_or(os_logical / threads).clamp(1, os_logical);
+            self.threads_per_core = (os_logical / self.physical_cores).max(1);
+            self.has_hyperthreading = self.threads_per_core > 1;
+        }
+        self.reconciliation = Some(CountReconciliation { cpuid, os, confidence });
+    }
+
+    /// Packages in the system; 1 until per-CPU detection has run.
+    pub fn package_count(&self) -> u32 {
+        (self.packages.len() as u32).max(1)
+    }
+
+    /// Cores across all packages.
+    pub fn total_cores(&self) -> u32 {
+        if self.packages.is_empty() {
+            return self.physical_cores;
         }
+        self.packages.iter().map(|package| package.cores).sum()
+    }
 
-        // Check for hybrid architecture (Intel 12th gen+)
-        if is_leaf_supported(7) {
-            let result = cpuid(7, 0);
-            hybrid = (result.edx & (1 << 15)) != 0;
+    /// Online logical processors across all packages.
+    pub fn total_logical_processors(&self) -> u32 {
+        if self.packages.is_empty() {
+            return self.logical_processors;
         }
+        self.packages.iter().map(|package| package.logical_processors).sum()
+    }
+}
 
-        Self {
-            logical_processors,
-            physical_cores,
-            threads_per_core,
-            has_hyperthreading,
-            hybrid,
+#[cfg(target_os = "linux")]
+fn detect_os_counts() -> OsCpuCounts {
+    use std::collections::BTreeSet;
+
+    // SAFETY: cpu_set_t is plain data that sched_getaffinity fills in
+    let available = unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        match libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) {
+            0 => libc::CPU_COUNT(&set) as u32,
+            _ => 0,
         }
+    };
+
+    let online = crate::percpu::online_cpus().ok();
+    let read_id = |cpu: usize, file: &str| {
+        let path = format!("/sys/devices/system/cpu/cpu{}/topology/{}", cpu, file);
+        std::fs::read_to_string(path).ok()?.trim().parse::<i64>().ok()
+    };
+    let cores: Option<BTreeSet<(i64, i64)>> = online.as_ref().and_then(|cpus| {
+        cpus.iter()
+            .map(|&cpu| Some((read_id(cpu, "physical_package_id")?, read_id(cpu, "core_id")?)))
+            .collect()
+    });
+
+    OsCpuCounts {
+        available,
+        online: online.as_ref().map(|cpus| cpus.len() as u32),
+        physical_cores: cores.as_ref().map(|cores| cores.len() as u32),
+        packages: cores.map(|cores| {
+            cores.iter().map(|(package, _)| package).collect::<BTreeSet<_>>().len() as u32
+        }),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_os_counts() -> OsCpuCounts {
+    OsCpuCounts {
+        available: std::thread::available_parallelism().map_or(0, |n| n.get() as u32),
+        ..Default::default()
+    }
+}
+
+/// APIC ID of the CPU this thread is currently running on: the 32-bit x2APIC ID
+/// from leaf 0x1F/0xB when available, otherwise the 8-bit ID from leaf 1.
+pub fn apic_id() -> u32 {
+    apic_id_with(&HardwareCpuid)
+}
+
+pub fn apic_id_with(src: &dyn CpuidSource) -> u32 {
+    match extended_leaf(src) {
+        Some(leaf) => src.cpuid(leaf, 0).edx,
+        None => src.cpuid(1, 0).ebx >> 24,
     }
 }
 
+/// Leaf 0x1F, or 0xB, if implemented.
+pub(crate) fn extended_leaf(src: &dyn CpuidSource) -> Option<u32> {
+    // EBX == 0 at subleaf 0 means the leaf is not implemented
+    [0x1F, 0xB]
+        .into_iter()
+        .find(|&leaf| src.is_leaf_supported(leaf) && src.cpuid(leaf, 0).ebx != 0)
+}
+
+fn mask(bits: u32) -> u32 {
+    1u32.checked_shl(bits).map_or(u32::MAX, |bit| bit - 1)
+}
+
+/// Bits needed to number `count` items.
+fn bits_for(count: u32) -> u32 {
+    count.max(1).next_power_of_two().trailing_zeros()
+}
+
+/// From leaf 0x1F/0xB shifts when present, then AMD's 0x8000_0008 ApicIdSize,
+/// then the legacy leaf 1 and leaf 4 maximum counts.
+fn detect_apic_layout(src: &dyn CpuidSource, levels: Option<&TopologyLevels>) -> ApicIdLayout {
+    if let Some(levels) = levels {
+        // The last level's shift reaches the package ID
+        let smt_bits = levels.get(TopologyLevelType::Smt).map_or(0, |level| level.shift);
+        let package_shift = levels.levels.last().map_or(0, |level| level.shift);
+        return ApicIdLayout { smt_bits, core_bits: package_shift.saturating_sub(smt_bits) };
+    }
+
+    if src.is_leaf_supported(0x8000_0008) {
+        // ECX[15:12]: APIC ID bits for the cores and threads of the package
+        let ecx = src.cpuid(0x8000_0008, 0).ecx;
+        let size = match (ecx >> 12) & 0xF {
+            0 => bits_for((ecx & 0xFF) + 1),
+            size => size,
+        };
+        if size != 0 {
+            let threads = if src.is_leaf_supported(0x8000_001E) {
+                ((src.cpuid(0x8000_001E, 0).ebx >> 8) & 0xFF) + 1
+            } else {
+                1
+            };
+            let smt_bits = bits_for(threads).min(size);
+            return ApicIdLayout { smt_bits, core_bits: size - smt_bits };
+        }
+    }
+
+    let leaf1 = src.cpuid(1, 0);
+    let logical = if leaf1.edx & (1 << 28) != 0 { (leaf1.ebx >> 16) & 0xFF } else { 1 };
+    let cores = if src.is_leaf_supported(4) { ((src.cpuid(4, 0).eax >> 26) & 0x3F) + 1 } else { 1 };
+    let smt_bits = bits_for(logical.max(1) / cores);
+    ApicIdLayout { smt_bits, core_bits: bits_for(cores) }
+}
+
 impl fmt::Display for CpuTopology {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "CPU Topology:")?;
@@ -90,34 +706,121 @@ impl fmt::Display for CpuTopology {
         writeln!(f, "  Physical Cores: {}", self.physical_cores)?;
         writeln!(f, "  Threads per Core: {}", self.threads_per_core)?;
         writeln!(f, "  Hyper-Threading: {}", if self.has_hyperthreading { "Yes" } else { "No" })?;
-        write!(f, "  Hybrid Architecture: {}", if self.hybrid { "Yes" } else { "No" })
+        writeln!(f, "  Hybrid Architecture: {}", if self.hybrid { "Yes" } else { "No" })?;
+        if let Some(levels) = &self.levels {
+            writeln!(f, "  Levels: {}", levels)?;
+        }
+        if let Some(amd) = &self.amd {
+            writeln!(f, "  AMD Topology: {}", amd)?;
+        }
+        if self.packages.len() > 1 {
+            let cores: Vec<String> =
+                self.packages.iter().map(|package| package.cores.to_string()).collect();
+            writeln!(
+                f,
+                "  Packages: {} ({} cores, {} in total)",
+                self.packages.len(),
+                cores.join("/"),
+                self.total_cores()
+            )?;
+        }
+        if let Some(reconciliation) = &self.reconciliation {
+            write!(f, "  Counts: {}", reconciliation.confidence)?;
+            match reconciliation.confidence {
+                CountConfidence::Corrected => writeln!(f, " (CPUID: {})", reconciliation.cpuid)?,
+                _ => writeln!(f)?,
+            }
+        }
+        if let (Some(p_cores), Some(e_cores)) = (self.performance_cores, self.efficient_cores)
+        {
+            writeln!(f, "  Core Types: {} P-cores, {} E-cores", p_cores, e_cores)?;
+        }
+        write!(f, "  APIC ID: {:#x} ({})", self.apic_id(), self.location())
     }
 }
 
-fn detect_threads_per_core_leaf_b() -> u32 {
-    for subleaf in 0..10 {
-        let result = cpuid(0xB, subleaf);
-        let level_type = (result.ecx >> 8) & 0xFF;
-        if level_type == 1 { // SMT level
-            return result.ebx & 0xFFFF;
+/// Leaf 0xB enumeration with a leaf 1/4 fallback (Intel, Zhaoxin, unknown vendors).
+pub(crate) fn detect_standard(src: &dyn CpuidSource) -> CpuTopology {
+    let mut logical_processors = 1;
+    let mut physical_cores = 1;
+    let threads_per_core ;
+    let mut has_hyperthreading = false;
+    let mut hybrid = false;
+
+    // Get Hyper-Threading status from leaf 1
+    if src.is_leaf_supported(1) {
+        let result = src.cpuid(1, 0);
+        has_hyperthreading = (result.edx & (1 << 28)) != 0;
+    }
+
+    // Prioritize leaf 0x1F/0xB for topology information. With die or module
+    // levels the package count sits on the last level, not on the core level.
+    if let Some(levels) = TopologyLevels::detect_with(src) {
+        threads_per_core = levels
+            .get(TopologyLevelType::Smt)
+            .map_or(1, |level| level.logical_processors.max(1));
+        logical_processors = levels.package_logical_processors();
+        physical_cores = (logical_processors / threads_per_core).max(1);
+    } else {
+        // Fallback if leaf 0xB is not supported
+        if src.is_leaf_supported(1) {
+            let result = src.cpuid(1, 0);
+            // For older CPUs, EBX[23:16] might give logical processors
+            logical_processors = (result.ebx >> 16) & 0xFF;
+        }
+        if src.is_leaf_supported(4) {
+            let result = src.cpuid(4, 0);
+            physical_cores = ((result.eax >> 26) & 0x3F) + 1;
         }
-        if level_type == 0 {
-            break;
+
+        // If logical_processors is still 1 (and hyperthreading is off), set it to physical_cores
+        if logical_processors == 1 && !has_hyperthreading {
+            logical_processors = physical_cores;
         }
+        
+        // Final check for threads_per_core in fallback
+        threads_per_core = logical_processors.checked_div(physical_cores).unwrap_or(1);
+    }
+
+    // Check for hybrid architecture (Intel 12th gen+)
+    if src.is_leaf_supported(7) {
+        let result = src.cpuid(7, 0);
+        hybrid = (result.edx & (1 << 15)) != 0;
+    }
+
+    CpuTopology {
+        logical_processors,
+        physical_cores,
+        threads_per_core,
+        has_hyperthreading,
+        hybrid,
+        ..Default::default()
     }
-    1
 }
 
-fn detect_logical_processors_leaf_b() -> u32 {
-    for subleaf in 0..10 {
-        let result = cpuid(0xB, subleaf);
-        let level_type = (result.ecx >> 8) & 0xFF;
-        if level_type == 2 { // Core level
-            return result.ebx & 0xFFFF;
-        }
-        if level_type == 0 {
-            break;
-        }
+/// AMD extended topology from leaves 0x8000_0008 and 0x8000_001E, for parts
+/// without leaf 0xB.
+pub(crate) fn detect_amd_extended(src: &dyn CpuidSource) -> Option<CpuTopology> {
+    if !src.is_leaf_supported(0x8000_0008) {
+        return None;
     }
-    1
+
+    // ECX[7:0]: number of logical processors minus one
+    let logical_processors = (src.cpuid(0x8000_0008, 0).ecx & 0xFF) + 1;
+
+    // 0x8000_001E EBX[15:8]: threads per compute unit minus one
+    let threads_per_core = if src.is_leaf_supported(0x8000_001E) {
+        ((src.cpuid(0x8000_001E, 0).ebx >> 8) & 0xFF) + 1
+    } else {
+        1
+    };
+
+    Some(CpuTopology {
+        logical_processors,
+        physical_cores: logical_processors / threads_per_core,
+        threads_per_core,
+        has_hyperthreading: threads_per_core > 1,
+        hybrid: false,
+        ..Default::default()
+    })
 }
@@ -0,0 +1,164 @@
+//! Topology Map (Linux)
+//!
+//! Every online logical CPU placed in a package → die → core → thread tree,
+//! with its APIC ID, core type and the cache instances it uses. Built from
+//! per-CPU detection, so it needs affinity pinning like `percpu`.
+
+use crate::cache::CacheId;
+use crate::percpu::PerCpuInfo;
+use crate::topology::{ApicIdParts, CoreType, CpuTopology, TopologyLevelType};
+use crate::CpuInfo;
+use std::fmt;
+use std::io;
+
+/// One logical CPU (a thread) in the map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogicalCpu {
+    /// OS CPU number.
+    pub cpu: usize,
+    pub apic_id: u32,
+    /// Package, core and thread fields of `apic_id`.
+    pub location: ApicIdParts,
+    /// Die within the package; 0 without a leaf 0x1F die level.
+    pub die: u32,
+    pub core_type: CoreType,
+    /// Cache instances this CPU uses, lowest level first.
+    pub caches: Vec<CacheId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreNode {
+    /// Core field of the APIC ID; unique within the package, not the die.
+    pub id: u32,
+    pub core_type: CoreType,
+    pub threads: Vec<LogicalCpu>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DieNode {
+    pub id: u32,
+    pub cores: Vec<CoreNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackageNode {
+    pub id: u32,
+    pub dies: Vec<DieNode>,
+}
+
+/// Packages, dies, cores and threads in APIC ID order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopologyMap {
+    pub packages: Vec<PackageNode>,
+}
+
+impl TopologyMap {
+    /// Pin to every online CPU and build the map.
+    pub fn detect() -> io::Result<Self> {
+        Ok(Self::from_per_cpu(&CpuInfo::detect_per_cpu()?, &CpuTopology::detect()))
+    }
+
+    /// `topology` supplies the APIC ID layout and the leaf 0x1F levels.
+    pub fn from_per_cpu(per_cpu: &[PerCpuInfo], topology: &CpuTopology) -> Self {
+        let mut cpus: Vec<LogicalCpu> = per_cpu
+            .iter()
+            .map(|info| LogicalCpu {
+                cpu: info.cpu,
+                apic_id: info.apic_id,
+                location: topology.apic_layout.decompose(info.apic_id),
+                die: topology
+                    .levels
+                    .as_ref()
+                    .and_then(|levels| levels.domain_id(TopologyLevelType::Die, info.apic_id))
+                    .unwrap_or(0),
+                core_type: info.core_type,
+                caches: info.caches.iter().map(|cache| cache.id()).collect(),
+            })
+            .collect();
+        cpus.sort_by_key(|cpu| (cpu.location, cpu.cpu));
+
+        // Die bits sit above the core bits, so each die's cores are contiguous
+        let packages = cpus
+            .chunk_by(|a, b| a.location.package == b.location.package)
+            .map(|package| PackageNode {
+                id: package[0].location.package,
+                dies: package
+                    .chunk_by(|a, b| a.die == b.die)
+                    .map(|die| DieNode {
+                        id: die[0].die,
+                        cores: die
+                            .chunk_by(|a, b| a.location.core == b.location.core)
+                            .map(|core| CoreNode {
+                                id: core[0].location.core,
+                                core_type: core[0].core_type,
+                                threads: core.to_vec(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self { packages }
+    }
+
+    /// Every logical CPU, in tree order.
+    pub fn cpus(&self) -> impl Iterator<Item = &LogicalCpu> {
+        self.packages
+            .iter()
+            .flat_map(|package| &package.dies)
+            .flat_map(|die| &die.cores)
+            .flat_map(|core| &core.threads)
+    }
+
+    /// Logical CPU with OS number `cpu`.
+    pub fn cpu(&self, cpu: usize) -> Option<&LogicalCpu> {
+        self.cpus().find(|logical| logical.cpu == cpu)
+    }
+
+    /// OS numbers of the threads on `cpu`'s core, `cpu` included.
+    pub fn siblings(&self, cpu: usize) -> Vec<usize> {
+        self.packages
+            .iter()
+            .flat_map(|package| &package.dies)
+            .flat_map(|die| &die.cores)
+            .find(|core| core.threads.iter().any(|thread| thread.cpu == cpu))
+            .map_or_else(Vec::new, |core| core.threads.iter().map(|thread| thread.cpu).collect())
+    }
+
+    /// OS numbers of the CPUs sharing cache instance `cache`.
+    pub fn sharing(&self, cache: CacheId) -> Vec<usize> {
+        self.cpus()
+            .filter(|logical| logical.caches.contains(&cache))
+            .map(|logical| logical.cpu)
+            .collect()
+    }
+}
+
+impl fmt::Display for TopologyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for package in &self.packages {
+            writeln!(f, "Package {}", package.id)?;
+            for die in &package.dies {
+                writeln!(f, "  Die {}", die.id)?;
+                for core in &die.cores {
+                    let threads: Vec<String> = core
+                        .threads
+                        .iter()
+                        .map(|thread| format!("CPU {} ({:#x})", thread.cpu, thread.apic_id))
+                        .collect();
+                    write!(f, "    Core {}", core.id)?;
+                    if core.core_type != CoreType::Unknown {
+                        write!(f, " {:?}", core.core_type)?;
+                    }
+                    writeln!(f, ": {}", threads.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
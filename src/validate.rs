@@ -0,0 +1,248 @@
+//! Consistency Checks
+//!
+//! Cross-checks detected values against each other and against the OS-enabled
+//! XSAVE state. A warning means either the CPU/hypervisor reports something
+//! contradictory or one of the decoders misread a leaf.
+
+use crate::cache::{CacheLevel, CacheType};
+use crate::cpuid;
+use crate::os_support::{XCR0_AVX, XCR0_AVX512, XCR0_SSE};
+use crate::CpuInfo;
+use std::fmt;
+
+/// Features that cannot be present without another one.
+const DEPENDENCIES: &[(&str, &str)] = &[
+    ("SSSE3", "SSE3"),
+    ("SSE4.1", "SSSE3"),
+    ("SSE4.2", "SSE4.1"),
+    ("AVX", "XSAVE"),
+    ("AVX2", "AVX"),
+    ("FMA", "AVX"),
+    ("F16C", "AVX"),
+    ("AVX512F", "AVX2"),
+    ("VAES", "AES"),
+    ("VPCLMULQDQ", "PCLMULQDQ"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsistencyWarning {
+    /// `feature` is reported but the feature it builds on is not.
+    MissingDependency { feature: String, requires: String },
+    /// An AVX-512 extension is reported without AVX512F.
+    Avx512WithoutFoundation(String),
+    /// AVX or AVX-512 is reported while the OS has not enabled XSAVE.
+    XsaveNotEnabled(String),
+    /// AVX is reported but XCR0 does not enable YMM state.
+    YmmStateDisabled { xcr0: u64 },
+    /// AVX-512 is reported but XCR0 does not enable opmask/ZMM state.
+    ZmmStateDisabled { xcr0: u64 },
+    ZeroLogicalProcessors,
+    ZeroThreadsPerCore,
+    /// More physical cores than logical processors.
+    CoresExceedThreads { cores: u32, logical: u32 },
+    /// Cores times threads per core does not add up on a non-hybrid part.
+    TopologyMismatch { cores: u32, threads_per_core: u32, logical: u32 },
+    /// The L3 is reported as private to one thread on a multi-core part.
+    UnsharedL3 { cores: u32 },
+    /// `ways * line_size * sets` does not give the reported size.
+    CacheGeometryMismatch { level: CacheLevel, cache_type: CacheType, size: u64, computed: u64 },
+    /// An outer cache level is smaller than the inner one.
+    CacheHierarchyInverted { inner: CacheLevel, outer: CacheLevel },
+    /// The base frequency is above the maximum frequency.
+    BaseAboveMax { base_mhz: u32, max_mhz: u32 },
+    /// Physical or virtual address width outside what x86_64 defines.
+    ImplausibleAddressBits { physical: u32, virtual_bits: u32 },
+}
+
+impl fmt::Display for ConsistencyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingDependency { feature, requires } => {
+                write!(f, "{} is set but {} is not", feature, requires)
+            }
+            Self::Avx512WithoutFoundation(feature) => {
+                write!(f, "{} is set but AVX512F is not", feature)
+            }
+            Self::XsaveNotEnabled(feature) => {
+                write!(f, "{} is set but the OS has not enabled XSAVE (OSXSAVE clear)", feature)
+            }
+            Self::YmmStateDisabled { xcr0 } => {
+                write!(f, "AVX is set but XCR0 ({:#x}) does not enable YMM state", xcr0)
+            }
+            Self::ZmmStateDisabled { xcr0 } => {
+                write!(f, "AVX512F is set but XCR0 ({:#x}) does not enable ZMM state", xcr0)
+            }
+            Self::ZeroLogicalProcessors => write!(f, "zero logical processors"),
+            Self::ZeroThreadsPerCore => write!(f, "zero threads per core"),
+            Self::CoresExceedThreads { cores, logical } => write!(
+                f,
+                "{} physical cores but only {} logical processors",
+                cores, logical
+            ),
+            Self::TopologyMismatch { cores, threads_per_core, logical } => write!(
+                f,
+                "{} cores x {} threads per core != {} logical processors",
+                cores, threads_per_core, logical
+            ),
+            Self::UnsharedL3 { cores } => {
+                write!(f, "L3 shared by 1 thread on a {}-core CPU", cores)
+            }
+            Self::CacheGeometryMismatch { level, cache_type, size, computed } => write!(
+                f,
+                "{:?} {:?} cache is {} bytes but its geometry gives {}",
+                level, cache_type, size, computed
+            ),
+            Self::CacheHierarchyInverted { inner, outer } => {
+                write!(f, "{:?} cache is smaller than {:?}", outer, inner)
+            }
+            Self::BaseAboveMax { base_mhz, max_mhz } => {
+                write!(f, "base frequency {} MHz exceeds max {} MHz", base_mhz, max_mhz)
+            }
+            Self::ImplausibleAddressBits { physical, virtual_bits } => write!(
+                f,
+                "implausible address sizes: {} bits physical, {} bits virtual",
+                physical, virtual_bits
+            ),
+        }
+    }
+}
+
+impl CpuInfo {
+    /// Consistency checks, including XCR0 read from the executing CPU.
+    /// Use `validate_with_xcr0(None)` for data that came from another machine.
+    pub fn validate(&self) -> Vec<ConsistencyWarning> {
+        self.validate_with_xcr0(cpuid::xcr0())
+    }
+
+    /// Consistency checks; the XSAVE state checks run only when `xcr0` is given.
+    pub fn validate_with_xcr0(&self, xcr0: Option<u64>) -> Vec<ConsistencyWarning> {
+        let mut warnings = Vec::new();
+        self.check_features(xcr0, &mut warnings);
+        self.check_topology(&mut warnings);
+        self.check_caches(&mut warnings);
+
+        if let (Some(base_mhz), Some(max_mhz)) = (self.frequency.base_mhz, self.frequency.max_mhz)
+            && base_mhz > max_mhz
+        {
+            warnings.push(ConsistencyWarning::BaseAboveMax { base_mhz, max_mhz });
+        }
+
+        let address = &self.address;
+        if !(32..=52).contains(&address.physical_bits) || !matches!(address.virtual_bits, 48 | 57) {
+            warnings.push(ConsistencyWarning::ImplausibleAddressBits {
+                physical: address.physical_bits,
+                virtual_bits: address.virtual_bits,
+            });
+        }
+
+        warnings
+    }
+
+    fn check_features(&self, xcr0: Option<u64>, warnings: &mut Vec<ConsistencyWarning>) {
+        let features = &self.features;
+
+        for (feature, requires) in DEPENDENCIES {
+            if features.has_feature(feature) && !features.has_feature(requires) {
+                warnings.push(ConsistencyWarning::MissingDependency {
+                    feature: feature.to_string(),
+                    requires: requires.to_string(),
+                });
+            }
+        }
+
+        if !features.has_feature("AVX512F") {
+            for feature in features.all_supported() {
+                if feature.name.starts_with("AVX512") {
+                    warnings.push(ConsistencyWarning::Avx512WithoutFoundation(feature.name.clone()));
+                }
+            }
+        }
+
+        let avx = features.has_feature("AVX");
+        let avx512 = features.has_feature("AVX512F");
+        if !features.has_feature("OSXSAVE") {
+            for (set, name) in [(avx, "AVX"), (avx512, "AVX512F")] {
+                if set {
+                    warnings.push(ConsistencyWarning::XsaveNotEnabled(name.to_string()));
+                }
+            }
+            return;
+        }
+
+        if let Some(xcr0) = xcr0 {
+            if avx && xcr0 & (XCR0_SSE | XCR0_AVX) != XCR0_SSE | XCR0_AVX {
+                warnings.push(ConsistencyWarning::YmmStateDisabled { xcr0 });
+            }
+            if avx512 && xcr0 & XCR0_AVX512 != XCR0_AVX512 {
+                warnings.push(ConsistencyWarning::ZmmStateDisabled { xcr0 });
+            }
+        }
+    }
+
+    fn check_topology(&self, warnings: &mut Vec<ConsistencyWarning>) {
+        let topo = &self.topology;
+        if topo.logical_processors == 0 {
+            warnings.push(ConsistencyWarning::ZeroLogicalProcessors);
+        }
+        if topo.threads_per_core == 0 {
+            warnings.push(ConsistencyWarning::ZeroThreadsPerCore);
+        }
+        if topo.physical_cores > topo.logical_processors {
+            warnings.push(ConsistencyWarning::CoresExceedThreads {
+                cores: topo.physical_cores,
+                logical: topo.logical_processors,
+            });
+        } else if !topo.hybrid
+            && topo.threads_per_core != 0
+            && topo.physical_cores * topo.threads_per_core != topo.logical_processors
+        {
+            // Hybrid parts mix SMT P-cores with single-thread E-cores
+            warnings.push(ConsistencyWarning::TopologyMismatch {
+                cores: topo.physical_cores,
+                threads_per_core: topo.threads_per_core,
+                logical: topo.logical_processors,
+            });
+        }
+    }
+
+    fn check_caches(&self, warnings: &mut Vec<ConsistencyWarning>) {
+        for cache in &self.cache {
+            let computed = cache.ways as u64 * cache.line_size as u64 * cache.sets as u64;
+            if computed != 0 && computed != cache.size {
+                warnings.push(ConsistencyWarning::CacheGeometryMismatch {
+                    level: cache.level,
+                    cache_type: cache.cache_type,
+                    size: cache.size,
+                    computed,
+                });
+            }
+        }
+
+        let cores = self.topology.physical_cores;
+        let l3 = self.cache.iter().find(|c| c.level == CacheLevel::L3);
+        if cores > 1 && l3.is_some_and(|c| c.shared_by == 1) {
+            warnings.push(ConsistencyWarning::UnsharedL3 { cores });
+        }
+
+        // Compare each level's data/unified cache with the next level out
+        let size_of = |level: CacheLevel| {
+            self.cache
+                .iter()
+                .filter(|c| c.level == level && c.cache_type != CacheType::Instruction)
+                .map(|c| c.size)
+                .max()
+        };
+        let levels = [CacheLevel::L1, CacheLevel::L2, CacheLevel::L3, CacheLevel::L4];
+        for pair in levels.windows(2) {
+            if let (Some(inner), Some(outer)) = (size_of(pair[0]), size_of(pair[1]))
+                && outer < inner
+            {
+                warnings.push(ConsistencyWarning::CacheHierarchyInverted {
+                    inner: pair[0],
+                    outer: pair[1],
+                });
+            }
+        }
+    }
+}
@@ -2,31 +2,118 @@
 //!
 //! Identifies CPU manufacturer and provides vendor-specific information.
 
-use crate::cpuid::{cpuid, CpuidResult};
-use std::fmt;
+use crate::cpuid::{CpuidResult, CpuidSource, HardwareCpuid};
+use crate::error::{self, DetectError};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CpuVendor {
     Intel,
     Amd,
     Hygon,
     Zhaoxin,
+    /// VIA and the first Zhaoxin parts, which kept the VIA vendor string.
+    Centaur,
+    Transmeta,
+    Cyrix,
+    NexGen,
+    Sis,
+    Umc,
+    /// DM&P Vortex86 SoCs.
+    Vortex,
+    /// National Semiconductor Geode.
+    Nsc,
+    Rdc,
+    /// MCST Elbrus running x86 code through binary translation.
+    Elbrus,
+    /// The ao486 FPGA core.
+    Ao486,
+    /// Microsoft's x86 emulation on Arm64 Windows.
+    MicrosoftXta,
+    /// Apple Rosetta 2 on Arm64 macOS.
+    AppleRosetta,
+    /// Any other non-blank vendor string, verbatim.
+    Other(String),
+    /// Blank vendor string, or nothing detected yet.
+    #[default]
     Unknown,
 }
 
+/// Leaf 0 vendor strings and their vendors; several vendors used more than one.
+const VENDOR_STRINGS: &[(&str, CpuVendor)] = &[
+    ("GenuineIntel", CpuVendor::Intel),
+    ("GenuineIotel", CpuVendor::Intel),
+    ("AuthenticAMD", CpuVendor::Amd),
+    ("AMDisbetter!", CpuVendor::Amd),
+    ("HygonGenuine", CpuVendor::Hygon),
+    ("  Shanghai  ", CpuVendor::Zhaoxin),
+    ("CentaurHauls", CpuVendor::Centaur),
+    ("VIA VIA VIA ", CpuVendor::Centaur),
+    ("GenuineTMx86", CpuVendor::Transmeta),
+    ("TransmetaCPU", CpuVendor::Transmeta),
+    ("CyrixInstead", CpuVendor::Cyrix),
+    ("NexGenDriven", CpuVendor::NexGen),
+    ("SiS SiS SiS ", CpuVendor::Sis),
+    ("UMC UMC UMC ", CpuVendor::Umc),
+    ("Vortex86 SoC", CpuVendor::Vortex),
+    ("Geode by NSC", CpuVendor::Nsc),
+    ("Genuine  RDC", CpuVendor::Rdc),
+    ("E2K MACHINE", CpuVendor::Elbrus),
+    ("GenuineAO486", CpuVendor::Ao486),
+    ("MiSTer AO486", CpuVendor::Ao486),
+    ("Virtual CPU ", CpuVendor::MicrosoftXta),
+    ("VirtualApple", CpuVendor::AppleRosetta),
+];
+
 impl CpuVendor {
-    pub fn as_str(&self) -> &'static str {
+    pub fn from_vendor_string(vendor_string: &str) -> Self {
+        // Elbrus pads with a NUL rather than a space
+        let trimmed = vendor_string.trim_end_matches('\0');
+        if let Some((_, vendor)) = VENDOR_STRINGS.iter().find(|(s, _)| *s == trimmed) {
+            return vendor.clone();
+        }
+        if trimmed.trim().is_empty() {
+            Self::Unknown
+        } else {
+            Self::Other(trimmed.to_string())
+        }
+    }
+
+    /// Vendor of the executing CPU (leaf 0 only).
+    pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        Self::from_vendor_string(&read_vendor_string(&src.cpuid(0, 0)))
+    }
+
+    /// Primary vendor string; `Other` gives back the string it was built from.
+    pub fn as_str(&self) -> &str {
         match self {
-            Self::Intel => "GenuineIntel",
-            Self::Amd => "AuthenticAMD",
-            Self::Hygon => "HygonGenuine",
-            Self::Zhaoxin => "  Shanghai  ",
+            Self::Other(vendor_string) => vendor_string,
             Self::Unknown => "Unknown",
+            vendor => VENDOR_STRINGS
+                .iter()
+                .find(|(_, v)| v == vendor)
+                .map_or("Unknown", |(s, _)| *s),
         }
     }
+
+    /// True when the vendor string is one this crate recognizes. Only Intel,
+    /// AMD, Hygon and Zhaoxin get dedicated detection; the rest, historic
+    /// vendors included, fall back to the generic CPUID paths.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Other(_) | Self::Unknown)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VendorInfo {
     pub vendor: CpuVendor,
     pub vendor_string: String,
@@ -34,35 +121,145 @@ pub struct VendorInfo {
     pub family: u32,
     pub model: u32,
     pub stepping: u32,
+    /// `None` on bare metal (leaf 1 ECX bit 31 clear).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hypervisor: Option<HypervisorInfo>,
 }
 
 impl VendorInfo {
     pub fn detect() -> Self {
-        let vendor_result = cpuid(0, 0);
-        let vendor_string = read_vendor_string(&vendor_result);
-        let vendor = match vendor_string.as_str() {
-            "GenuineIntel" => CpuVendor::Intel,
-            "AuthenticAMD" => CpuVendor::Amd,
-            "HygonGenuine" => CpuVendor::Hygon,
-            "  Shanghai  " => CpuVendor::Zhaoxin,
-            _ => CpuVendor::Unknown,
-        };
+        Self::detect_with(&HardwareCpuid)
+    }
 
-        let signature = cpuid(1, 0);
-        let family = extract_family(signature.eax);
-        let model = extract_model(signature.eax);
-        let stepping = signature.eax & 0xF;
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "vendor", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        let vendor_result = src.cpuid(0, 0);
+        let vendor_string = read_vendor_string(&vendor_result);
+        let vendor = CpuVendor::from_vendor_string(&vendor_string);
 
-        let brand_string = read_brand_string();
+        let signature = Signature::from_raw(src.cpuid(1, 0).eax);
+        let brand_string = read_brand_string(src);
 
         Self {
             vendor,
             vendor_string,
             brand_string,
-            family,
-            model,
-            stepping,
+            family: signature.family,
+            model: signature.model,
+            stepping: signature.stepping,
+            hypervisor: HypervisorInfo::detect_with(src),
+        }
+    }
+
+    /// Like `detect`, but fails when the brand string leaves are missing or blank.
+    pub fn try_detect() -> Result<Self, DetectError> {
+        Self::try_detect_with(&HardwareCpuid)
+    }
+
+    pub fn try_detect_with(src: &dyn CpuidSource) -> Result<Self, DetectError> {
+        error::require_leaf(src, 1, 0)?;
+        error::require_leaf(src, 0x8000_0004, 0)?;
+
+        let info = Self::detect_with(src);
+        if info.brand_string.is_empty() {
+            return Err(error::empty_leaf(src, 0x8000_0002, 0));
+        }
+        Ok(info)
+    }
+
+    /// Family, model and stepping as one value; `signature().to_raw()` is the
+    /// leaf 1 EAX that microcode release notes and errata lists quote.
+    pub fn signature(&self) -> Signature {
+        Signature::new(self.family, self.model, self.stepping)
+    }
+
+    /// Brand string, or the vendor and signature when the CPU has none,
+    /// e.g. "GenuineIntel Family 0x6 Model 0x8F Stepping 8".
+    pub fn brand_or_signature(&self) -> String {
+        if !self.brand_string.is_empty() {
+            return self.brand_string.clone();
+        }
+        format!(
+            "{} Family {:#X} Model {:#X} Stepping {}",
+            self.vendor_string.trim(),
+            self.family,
+            self.model,
+            self.stepping
+        )
+    }
+
+    /// Brand string without "(R)", "(TM)" and their symbols, whitespace collapsed.
+    pub fn brand_without_trademarks(&self) -> String {
+        let mut brand = self.brand_string.clone();
+        for mark in ["(R)", "(r)", "(TM)", "(tm)", "®", "™"] {
+            brand = brand.replace(mark, "");
         }
+        brand.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Marketing model number, e.g. "i7-13700K", "Ryzen 9 7950X", "Gold 6338"
+    /// or "E5-2680 v4". `None` for generic strings such as "Intel(R) Xeon(R)
+    /// Processor" under a hypervisor.
+    pub fn brand_model_number(&self) -> Option<String> {
+        let brand = self.brand_without_trademarks();
+        let brand = brand.split('@').next().unwrap_or_default();
+        let words: Vec<&str> = brand
+            .split_whitespace()
+            .take_while(|word| !BRAND_STOP_WORDS.iter().any(|stop| stop.eq_ignore_ascii_case(word)))
+            .filter(|word| !BRAND_NOISE_WORDS.iter().any(|noise| noise.eq_ignore_ascii_case(word)))
+            .filter(|word| {
+                let lower = word.to_ascii_lowercase();
+                !(lower.ends_with("-core") || lower.ends_with("-cores"))
+            })
+            .collect();
+
+        let family = words.iter().enumerate().find_map(|(i, word)| {
+            BRAND_FAMILIES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(word))
+                .map(|(_, keep)| if *keep { i } else { i + 1 })
+        });
+        let model = match family {
+            Some(start) => &words[start..],
+            // Without a family word only a lone token such as "N100" is a model
+            None => {
+                let start = words
+                    .iter()
+                    .position(|word| !VENDOR_WORDS.iter().any(|v| v.eq_ignore_ascii_case(word)))
+                    .unwrap_or(words.len());
+                if words.len() - start != 1 {
+                    return None;
+                }
+                &words[start..]
+            }
+        };
+
+        let has_digit = model.iter().any(|word| word.bytes().any(|b| b.is_ascii_digit()));
+        has_digit.then(|| model.join(" "))
+    }
+
+    /// Nominal frequency after the "@" of strings such as "... CPU @ 2.40GHz".
+    pub fn brand_frequency_mhz(&self) -> Option<u32> {
+        let (_, frequency) = self.brand_string.rsplit_once('@')?;
+        let frequency = frequency.trim();
+        let (number, scale) = if let Some(ghz) = frequency.strip_suffix("GHz") {
+            (ghz, 1000)
+        } else {
+            (frequency.strip_suffix("MHz")?, 1)
+        };
+
+        // Fixed point, so "2.40" is exactly 2400 without floating-point rounding
+        let (whole, fraction) = number.trim().split_once('.').unwrap_or((number.trim(), ""));
+        let mut mhz = whole.parse::<u32>().ok()?.checked_mul(scale)?;
+        let mut unit = scale;
+        for digit in fraction.bytes() {
+            unit /= 10;
+            if !digit.is_ascii_digit() {
+                return None;
+            }
+            mhz += u32::from(digit - b'0') * unit;
+        }
+        Some(mhz).filter(|&mhz| mhz > 0)
     }
 }
 
@@ -75,11 +272,205 @@ impl fmt::Display for VendorInfo {
             self.vendor.as_str()
         )?;
         writeln!(f, "Brand: {}", self.brand_string)?;
-        write!(
+        writeln!(
             f,
             "Family: 0x{:X}, Model: 0x{:X}, Stepping: {}",
             self.family, self.model, self.stepping
-        )
+        )?;
+        write!(f, "Microarchitecture: {}", self.microarchitecture())?;
+        if let Some(hypervisor) = &self.hypervisor {
+            write!(f, "\nHypervisor: {}", hypervisor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Processor signature with the extended fields folded in, as `VendorInfo`
+/// stores it. Intel and AMD use the same encoding: the extended family is
+/// added above base family 0xF, and the extended model is the high nibble for
+/// base families 0x6 (Intel only) and 0xF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signature {
+    pub family: u32,
+    pub model: u32,
+    pub stepping: u32,
+}
+
+impl Signature {
+    pub fn new(family: u32, model: u32, stepping: u32) -> Self {
+        Self { family, model, stepping }
+    }
+
+    /// Decode leaf 1 EAX; the processor type and reserved bits are dropped.
+    pub fn from_raw(eax: u32) -> Self {
+        Self {
+            family: extract_family(eax),
+            model: extract_model(eax),
+            stepping: eax & 0xF,
+        }
+    }
+
+    /// Leaf 1 EAX with processor type 0, e.g. 0x806F8 for family 6, model
+    /// 0x8F, stepping 8. Fields outside what the encoding can hold (family
+    /// above 0x10E, model above 0xF below family 6 and above 0xFF from it,
+    /// stepping above 0xF) are truncated.
+    pub fn to_raw(&self) -> u32 {
+        let (base_family, extended_family) = if self.family >= 0xF {
+            (0xF, (self.family - 0xF).min(0xFF))
+        } else {
+            (self.family, 0)
+        };
+        let extended_model = if base_family >= 0x6 {
+            (self.model >> 4) & 0xF
+        } else {
+            0
+        };
+        (extended_family << 20)
+            | (extended_model << 16)
+            | (base_family << 8)
+            | ((self.model & 0xF) << 4)
+            | (self.stepping & 0xF)
+    }
+}
+
+/// Family-model-stepping in the form of Linux microcode file names, e.g.
+/// "06-8f-08" or "19-11-01".
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02x}-{:02x}-{:02x}", self.family, self.model, self.stepping)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HypervisorVendor {
+    Kvm,
+    HyperV,
+    VMware,
+    Xen,
+    /// QEMU without KVM (Tiny Code Generator).
+    Tcg,
+    VirtualBox,
+    Acrn,
+    Bhyve,
+    #[default]
+    Unknown,
+}
+
+impl HypervisorVendor {
+    /// From the 12-byte signature in EBX, ECX, EDX of leaf 0x4000_0000.
+    pub fn from_signature(signature: &str) -> Self {
+        match signature {
+            "KVMKVMKVM\0\0\0" => Self::Kvm,
+            "Microsoft Hv" => Self::HyperV,
+            "VMwareVMware" => Self::VMware,
+            "XenVMMXenVMM" => Self::Xen,
+            "TCGTCGTCGTCG" => Self::Tcg,
+            "VBoxVBoxVBox" => Self::VirtualBox,
+            "ACRNACRNACRN" => Self::Acrn,
+            "bhyve bhyve " => Self::Bhyve,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Kvm => "KVM",
+            Self::HyperV => "Hyper-V",
+            Self::VMware => "VMware",
+            Self::Xen => "Xen",
+            Self::Tcg => "QEMU TCG",
+            Self::VirtualBox => "VirtualBox",
+            Self::Acrn => "ACRN",
+            Self::Bhyve => "bhyve",
+            Self::Unknown => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for HypervisorVendor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Hypervisor interface found in the 0x4000_0000 leaf range.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypervisorInfo {
+    pub vendor: HypervisorVendor,
+    /// Raw signature, NULs trimmed.
+    pub signature: String,
+    /// Leaf holding the signature: 0x4000_0000, or 0x4000_0100 when KVM or Xen
+    /// present Hyper-V enlightenments first.
+    pub base_leaf: u32,
+    /// EAX of `base_leaf`; old KVM reports 0 here for `base_leaf + 1`.
+    pub max_leaf: u32,
+    /// Paravirt features offered by the host; `None` unless `vendor` is KVM.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kvm: Option<KvmFeatures>,
+    /// Hyper-V interface, also offered by KVM and Xen to Windows guests.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hyperv: Option<HypervInfo>,
+}
+
+impl HypervisorInfo {
+    pub fn detect() -> Option<Self> {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    pub fn detect_with(src: &dyn CpuidSource) -> Option<Self> {
+        // Leaf 1 ECX bit 31: running under a hypervisor
+        if (src.cpuid(1, 0).ecx & (1 << 31)) == 0 {
+            return None;
+        }
+
+        let mut info = Self::read(src, 0x4000_0000);
+        let hyperv = HypervInfo::detect_with(src, info.max_leaf);
+        if info.vendor == HypervisorVendor::HyperV {
+            let native = Self::read(src, 0x4000_0100);
+            if !matches!(native.vendor, HypervisorVendor::Unknown | HypervisorVendor::HyperV) {
+                info = native;
+            }
+        }
+        info.hyperv = hyperv;
+        if info.vendor == HypervisorVendor::Kvm
+            && (info.max_leaf == 0 || info.max_leaf > info.base_leaf)
+        {
+            info.kvm = Some(KvmFeatures::from_leaf(src.cpuid(info.base_leaf + 1, 0)));
+        }
+        Some(info)
+    }
+
+    fn read(src: &dyn CpuidSource, base_leaf: u32) -> Self {
+        let result = src.cpuid(base_leaf, 0);
+        let mut bytes = Vec::with_capacity(12);
+        bytes.extend_from_slice(&result.ebx.to_le_bytes());
+        bytes.extend_from_slice(&result.ecx.to_le_bytes());
+        bytes.extend_from_slice(&result.edx.to_le_bytes());
+        let raw = String::from_utf8_lossy(&bytes);
+
+        Self {
+            vendor: HypervisorVendor::from_signature(&raw),
+            signature: raw.trim_end_matches('\0').to_string(),
+            base_leaf,
+            max_leaf: result.eax,
+            kvm: None,
+            hyperv: None,
+        }
+    }
+}
+
+impl fmt::Display for HypervisorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.vendor {
+            HypervisorVendor::Unknown if !self.signature.is_empty() => {
+                write!(f, "\"{}\"", self.signature)?
+            }
+            vendor => write!(f, "{}", vendor)?,
+        }
+        write!(f, " (leaves {:#x}-{:#x})", self.base_leaf, self.max_leaf.max(self.base_leaf))
     }
 }
 
@@ -91,24 +482,59 @@ fn read_vendor_string(result: &CpuidResult) -> String {
     String::from_utf8_lossy(&bytes).to_string()
 }
 
-fn read_brand_string() -> String {
+/// Empty without the brand leaves, which older CPUs and some emulators lack;
+/// NULs and undecodable bytes are dropped rather than shown.
+fn read_brand_string(src: &dyn CpuidSource) -> String {
+    if !src.is_leaf_supported(0x8000_0004) {
+        return String::new();
+    }
+
     let mut brand = Vec::with_capacity(48);
 
     for leaf in 0x8000_0002..=0x8000_0004 {
-        let result = cpuid(leaf, 0);
+        let result = src.cpuid(leaf, 0);
         brand.extend_from_slice(&result.eax.to_le_bytes());
         brand.extend_from_slice(&result.ebx.to_le_bytes());
         brand.extend_from_slice(&result.ecx.to_le_bytes());
         brand.extend_from_slice(&result.edx.to_le_bytes());
     }
 
-    String::from_utf8_lossy(&brand)
-        .trim_end_matches('\0')
-        .trim()
-        .to_string()
+    let brand: String = String::from_utf8_lossy(&brand)
+        .chars()
+        .filter(|c| !c.is_control() && *c != char::REPLACEMENT_CHARACTER)
+        .collect();
+    brand.trim().to_string()
 }
 
-fn extract_family(eax: u32) -> u32 {
+/// Brand-string words that introduce the model number: whether the word is
+/// part of it, e.g. "Ryzen 9 7950X" but "i7-13700K" rather than "Core i7-13700K".
+const BRAND_FAMILIES: &[(&str, bool)] = &[
+    ("Core", false),
+    ("Xeon", false),
+    ("Pentium", false),
+    ("Celeron", false),
+    ("Atom", false),
+    ("KaiXian", false),
+    ("Core2", true),
+    ("Ryzen", true),
+    ("EPYC", true),
+    ("Athlon", true),
+    ("Opteron", true),
+    ("Phenom", true),
+    ("Sempron", true),
+    ("Turion", true),
+    ("C86", true),
+];
+
+/// Words that end the model part of a brand string ("with Radeon Graphics").
+const BRAND_STOP_WORDS: &[&str] = &["with", "w/", "Radeon"];
+
+/// Filler words that are never part of a model number.
+const BRAND_NOISE_WORDS: &[&str] = &["CPU", "Processor", "APU"];
+
+const VENDOR_WORDS: &[&str] = &["Intel", "AMD", "Hygon", "Zhaoxin", "VIA", "Genuine"];
+
+pub(crate) fn extract_family(eax: u32) -> u32 {
     let base_family = (eax >> 8) & 0xF;
     let extended_family = (eax >> 20) & 0xFF;
 
@@ -124,9 +550,240 @@ fn extract_model(eax: u32) -> u32 {
     let extended_model = (eax >> 16) & 0xF;
     let family = (eax >> 8) & 0xF;
 
-    if family == 0x6 || family == 0xF {
+    // Linux applies the extended model from family 6 on, which covers
+    // Zhaoxin's family 7 as well as Intel's 6 and AMD's 0xF
+    if family >= 0x6 {
         (extended_model << 4) | base_model
     } else {
         base_model
     }
 }
+
+/// KVM paravirt features from `HypervisorInfo::base_leaf + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KvmFeatures {
+    /// kvmclock at either the old or the new MSRs.
+    pub kvmclock: bool,
+    /// kvmclock is stable across CPUs; the guest may skip its own checks.
+    pub kvmclock_stable: bool,
+    pub nop_io_delay: bool,
+    pub async_pf: bool,
+    /// Async page faults are delivered as VM exits to a nested guest.
+    pub async_pf_vmexit: bool,
+    /// "Page ready" notifications arrive as an interrupt, not a #PF.
+    pub async_pf_int: bool,
+    pub steal_time: bool,
+    pub pv_eoi: bool,
+    /// Halted vCPUs waiting on a spinlock can be kicked by hypercall.
+    pub pv_unhalt: bool,
+    pub pv_tlb_flush: bool,
+    pub pv_send_ipi: bool,
+    pub pv_sched_yield: bool,
+    pub poll_control: bool,
+    /// MSI address bits 11:5 extend the destination ID past 255 CPUs.
+    pub msi_ext_dest_id: bool,
+    pub map_gpa_range: bool,
+    pub migration_control: bool,
+    /// vCPUs are never preempted, so spinning is cheaper than yielding.
+    pub realtime_hint: bool,
+}
+
+impl KvmFeatures {
+    fn from_leaf(result: CpuidResult) -> Self {
+        let bit = |n: u32| (result.eax & (1 << n)) != 0;
+        Self {
+            kvmclock: bit(0) || bit(3),
+            kvmclock_stable: bit(24),
+            nop_io_delay: bit(1),
+            async_pf: bit(4),
+            async_pf_vmexit: bit(10),
+            async_pf_int: bit(14),
+            steal_time: bit(5),
+            pv_eoi: bit(6),
+            pv_unhalt: bit(7),
+            pv_tlb_flush: bit(9),
+            pv_send_ipi: bit(11),
+            pv_sched_yield: bit(13),
+            poll_control: bit(12),
+            msi_ext_dest_id: bit(15),
+            map_gpa_range: bit(16),
+            migration_control: bit(17),
+            realtime_hint: (result.edx & 1) != 0,
+        }
+    }
+
+    /// Short labels for the offered features, e.g. `["kvmclock", "PV EOI"]`.
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.kvmclock, "kvmclock"),
+            (self.kvmclock_stable, "stable kvmclock"),
+            (self.nop_io_delay, "no I/O delay"),
+            (self.async_pf, "async PF"),
+            (self.async_pf_vmexit, "async PF VM exit"),
+            (self.async_pf_int, "async PF interrupt"),
+            (self.steal_time, "steal time"),
+            (self.pv_eoi, "PV EOI"),
+            (self.pv_unhalt, "PV unhalt"),
+            (self.pv_tlb_flush, "PV TLB flush"),
+            (self.pv_send_ipi, "PV IPI"),
+            (self.pv_sched_yield, "PV sched yield"),
+            (self.poll_control, "poll control"),
+            (self.msi_ext_dest_id, "extended MSI destination ID"),
+            (self.map_gpa_range, "GPA range mapping"),
+            (self.migration_control, "migration control"),
+            (self.realtime_hint, "realtime hint"),
+        ]
+        .into_iter()
+        .filter(|(offered, _)| *offered)
+        .map(|(_, name)| name)
+        .collect()
+    }
+}
+
+/// Hyper-V interface leaves 0x4000_0002 to 0x4000_000A.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypervInfo {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub build: u32,
+    /// Partition privilege mask: EAX low, EBX high.
+    pub privileges: u64,
+    /// Feature flags in EDX of 0x4000_0003.
+    pub features: u32,
+    /// Recommendation flags in EAX of 0x4000_0004.
+    pub recommendations: u32,
+    /// VP index MSR; needed for hypercalls that name target vCPUs.
+    pub vp_index: bool,
+    /// Partition reference TSC page, for a clocksource without VM exits.
+    pub reference_tsc: bool,
+    pub synic: bool,
+    pub synthetic_timers: bool,
+    /// EOI, ICR and TPR through synthetic MSRs.
+    pub apic_msrs: bool,
+    /// TSC and APIC timer frequencies readable from MSRs.
+    pub frequency_msrs: bool,
+    /// Local and remote TLB flushes are better done by hypercall.
+    pub enlightened_tlb_flush: bool,
+    /// Recommended: synthetic MSRs instead of APIC EOI, ICR and TPR accesses.
+    pub recommend_apic_msrs: bool,
+    pub recommend_x2apic_msrs: bool,
+    pub relaxed_timing: bool,
+    pub cluster_ipi: bool,
+    /// Processor sets in the sparse format, for more than 64 vCPUs.
+    pub ex_processor_masks: bool,
+    pub enlightened_vmcs: bool,
+    /// Spinlock attempts before notifying the hypervisor; `None` for never.
+    pub spinlock_retries: Option<u32>,
+    pub max_virtual_processors: u32,
+    pub max_logical_processors: u32,
+    /// Enlightened VMCS version range (low, high) from 0x4000_000A.
+    pub enlightened_vmcs_version: Option<(u8, u8)>,
+    /// From `read_msrs`; `None` when unavailable or unreadable.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tsc_frequency_hz: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub apic_frequency_hz: Option<u64>,
+}
+
+#[cfg(feature = "std")]
+const HV_X64_MSR_TSC_FREQUENCY: u32 = 0x4000_0022;
+#[cfg(feature = "std")]
+const HV_X64_MSR_APIC_FREQUENCY: u32 = 0x4000_0023;
+
+impl HypervInfo {
+    /// `max_leaf` is EAX of 0x4000_0000; `None` without the "Hv#1" interface.
+    fn detect_with(src: &dyn CpuidSource, max_leaf: u32) -> Option<Self> {
+        if max_leaf < 0x4000_0005 || src.cpuid(0x4000_0001, 0).eax != u32::from_le_bytes(*b"Hv#1") {
+            return None;
+        }
+
+        let version = src.cpuid(0x4000_0002, 0);
+        let privileges = src.cpuid(0x4000_0003, 0);
+        let recommendations = src.cpuid(0x4000_0004, 0);
+        let limits = src.cpuid(0x4000_0005, 0);
+        let mask = (u64::from(privileges.ebx) << 32) | u64::from(privileges.eax);
+        let recommend = |bit: u32| (recommendations.eax & (1 << bit)) != 0;
+
+        let mut info = Self {
+            major_version: (version.ebx >> 16) as u16,
+            minor_version: version.ebx as u16,
+            build: version.eax,
+            privileges: mask,
+            features: privileges.edx,
+            recommendations: recommendations.eax,
+            vp_index: (mask & (1 << 6)) != 0,
+            reference_tsc: (mask & (1 << 9)) != 0,
+            synic: (mask & (1 << 2)) != 0,
+            synthetic_timers: (mask & (1 << 3)) != 0,
+            apic_msrs: (mask & (1 << 4)) != 0,
+            frequency_msrs: (mask & (1 << 11)) != 0 && (privileges.edx & (1 << 8)) != 0,
+            enlightened_tlb_flush: recommend(1) && recommend(2),
+            recommend_apic_msrs: recommend(3),
+            recommend_x2apic_msrs: recommend(8),
+            relaxed_timing: recommend(5),
+            cluster_ipi: recommend(10),
+            ex_processor_masks: recommend(11),
+            enlightened_vmcs: recommend(14),
+            spinlock_retries: Some(recommendations.ebx).filter(|&n| n != u32::MAX),
+            max_virtual_processors: limits.eax,
+            max_logical_processors: limits.ebx,
+            enlightened_vmcs_version: None,
+            tsc_frequency_hz: None,
+            apic_frequency_hz: None,
+        };
+
+        // Nested features are only meaningful with enlightened VMCS recommended
+        if info.enlightened_vmcs && max_leaf >= 0x4000_000A {
+            let nested = src.cpuid(0x4000_000A, 0);
+            info.enlightened_vmcs_version = Some((nested.eax as u8, (nested.eax >> 8) as u8));
+        }
+        Some(info)
+    }
+
+    /// Fill the TSC and APIC timer frequencies from this CPU's MSRs.
+    #[cfg(feature = "std")]
+    pub fn read_msrs(&mut self) {
+        if self.frequency_msrs {
+            self.tsc_frequency_hz = crate::msr::read_current(HV_X64_MSR_TSC_FREQUENCY).ok();
+            self.apic_frequency_hz = crate::msr::read_current(HV_X64_MSR_APIC_FREQUENCY).ok();
+        }
+    }
+
+    /// Short labels for the available and recommended enlightenments.
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.vp_index, "VP index"),
+            (self.reference_tsc, "reference TSC"),
+            (self.synic, "SynIC"),
+            (self.synthetic_timers, "synthetic timers"),
+            (self.apic_msrs, "APIC MSRs"),
+            (self.frequency_msrs, "frequency MSRs"),
+            (self.enlightened_tlb_flush, "enlightened TLB flush"),
+            (self.recommend_x2apic_msrs, "x2APIC MSRs"),
+            (self.relaxed_timing, "relaxed timing"),
+            (self.cluster_ipi, "cluster IPI"),
+            (self.ex_processor_masks, "ExProcessorMasks"),
+            (self.enlightened_vmcs, "enlightened VMCS"),
+        ]
+        .into_iter()
+        .filter(|(offered, _)| *offered)
+        .map(|(_, name)| name)
+        .collect()
+    }
+}
+
+impl fmt::Display for HypervInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hyper-V {}.{} build {}", self.major_version, self.minor_version, self.build)?;
+        write!(f, ", {} vCPUs max", self.max_virtual_processors)?;
+        if let Some(hz) = self.tsc_frequency_hz {
+            write!(f, ", TSC {} MHz", hz / 1_000_000)?;
+        }
+        if let Some(hz) = self.apic_frequency_hz {
+            write!(f, ", APIC timer {} MHz", hz / 1_000_000)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+//! Vendor Dispatch
+//!
+//! Routes vendor-specific detection (cache, TLB and topology leaves, frequency
+//! sources, quirks) through one `VendorOps` implementation per vendor.
+
+use crate::cache::{self, CacheInfo};
+use crate::cpuid::CpuidSource;
+use crate::frequency::{self, FrequencyInfo};
+use crate::tlb::{self, TlbEntry};
+use crate::topology::{self, CpuTopology};
+use crate::vendor::CpuVendor;
+use crate::{CpuInfo, DetectOptions};
+
+pub trait VendorOps {
+    fn vendor(&self) -> CpuVendor;
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo>;
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry>;
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology;
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo;
+
+    /// Fix up the subsystems selected in `options` for known vendor deviations.
+    fn apply_quirks(
+        &self,
+        _src: &dyn CpuidSource,
+        _info: &mut CpuInfo,
+        _options: &DetectOptions,
+    ) {
+    }
+}
+
+/// Operations for the CPU behind `src`, wrapped for the hypervisor if one is present.
+pub fn detect(src: &dyn CpuidSource) -> Box<dyn VendorOps> {
+    let ops = for_vendor(CpuVendor::detect_with(src));
+
+    // Leaf 1 ECX bit 31: running under a hypervisor
+    if src.is_leaf_supported(1) && (src.cpuid(1, 0).ecx & (1 << 31)) != 0 {
+        Box::new(HypervisorOps { host: ops })
+    } else {
+        ops
+    }
+}
+
+pub fn for_vendor(vendor: CpuVendor) -> Box<dyn VendorOps> {
+    match vendor {
+        CpuVendor::Intel => Box::new(IntelOps),
+        CpuVendor::Amd => Box::new(AmdOps),
+        CpuVendor::Hygon => Box::new(HygonOps),
+        CpuVendor::Zhaoxin => Box::new(ZhaoxinOps),
+        _ => Box::new(GenericOps),
+    }
+}
+
+pub struct IntelOps;
+
+impl VendorOps for IntelOps {
+    fn vendor(&self) -> CpuVendor {
+        CpuVendor::Intel
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        cache::detect_intel(src)
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        tlb::detect_intel(src)
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        topology::detect_standard(src)
+    }
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo {
+        frequency::detect_cpuid(src)
+    }
+}
+
+pub struct AmdOps;
+
+impl VendorOps for AmdOps {
+    fn vendor(&self) -> CpuVendor {
+        CpuVendor::Amd
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        cache::detect_amd(src)
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        tlb::detect_amd(src)
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        let mut topology = if topology::extended_leaf(src).is_some() {
+            topology::detect_standard(src)
+        } else {
+            topology::detect_amd_extended(src).unwrap_or_else(|| topology::detect_standard(src))
+        };
+
+        // Leaf 1 HTT only means "multi-core" on AMD
+        topology.has_hyperthreading = topology.threads_per_core > 1;
+        topology
+    }
+
+    fn frequency(&self, _src: &dyn CpuidSource) -> FrequencyInfo {
+        // Leaves 0x15/0x16 are reserved on AMD
+        FrequencyInfo::default()
+    }
+
+    fn apply_quirks(
+        &self,
+        src: &dyn CpuidSource,
+        info: &mut CpuInfo,
+        options: &DetectOptions,
+    ) {
+        // AMD reports boost as Core Performance Boost (0x8000_0007 EDX bit 9), not leaf 6
+        if options.power
+            && src.is_leaf_supported(0x8000_0007)
+            && (src.cpuid(0x8000_0007, 0).edx & (1 << 9)) != 0
+        {
+            info.power.turbo_boost = true;
+        }
+    }
+}
+
+/// Hygon Dhyana is Zen-derived and follows the AMD leaves.
+pub struct HygonOps;
+
+impl VendorOps for HygonOps {
+    fn vendor(&self) -> CpuVendor {
+        CpuVendor::Hygon
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        AmdOps.caches(src)
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        AmdOps.tlbs(src)
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        AmdOps.topology(src)
+    }
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo {
+        AmdOps.frequency(src)
+    }
+
+    fn apply_quirks(
+        &self,
+        src: &dyn CpuidSource,
+        info: &mut CpuInfo,
+        options: &DetectOptions,
+    ) {
+        AmdOps.apply_quirks(src, info, options);
+    }
+}
+
+/// Zhaoxin implements the Intel enumeration leaves.
+pub struct ZhaoxinOps;
+
+impl VendorOps for ZhaoxinOps {
+    fn vendor(&self) -> CpuVendor {
+        CpuVendor::Zhaoxin
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        IntelOps.caches(src)
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        IntelOps.tlbs(src)
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        IntelOps.topology(src)
+    }
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo {
+        IntelOps.frequency(src)
+    }
+}
+
+/// VIA, historic and unknown vendors: try every enumeration source.
+pub struct GenericOps;
+
+impl VendorOps for GenericOps {
+    fn vendor(&self) -> CpuVendor {
+        CpuVendor::Unknown
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        let caches = cache::detect_intel(src);
+        if caches.is_empty() {
+            cache::detect_amd(src)
+        } else {
+            caches
+        }
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        let mut entries = tlb::detect_amd(src);
+        entries.extend(tlb::detect_intel(src));
+        entries
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        topology::detect_standard(src)
+    }
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo {
+        frequency::detect_cpuid(src)
+    }
+}
+
+/// Guests see the host vendor's leaves, plus hypervisor-provided timing.
+pub struct HypervisorOps {
+    host: Box<dyn VendorOps>,
+}
+
+impl VendorOps for HypervisorOps {
+    fn vendor(&self) -> CpuVendor {
+        self.host.vendor()
+    }
+
+    fn caches(&self, src: &dyn CpuidSource) -> Vec<CacheInfo> {
+        self.host.caches(src)
+    }
+
+    fn tlbs(&self, src: &dyn CpuidSource) -> Vec<TlbEntry> {
+        self.host.tlbs(src)
+    }
+
+    fn topology(&self, src: &dyn CpuidSource) -> CpuTopology {
+        self.host.topology(src)
+    }
+
+    fn frequency(&self, src: &dyn CpuidSource) -> FrequencyInfo {
+        let mut info = self.host.frequency(src);
+        frequency::apply_hypervisor_timing(src, &mut info);
+        info
+    }
+
+    fn apply_quirks(
+        &self,
+        src: &dyn CpuidSource,
+        info: &mut CpuInfo,
+        options: &DetectOptions,
+    ) {
+        self.host.apply_quirks(src, info, options);
+    }
+}
@@ -0,0 +1,141 @@
+//! XSAVE State Components
+//!
+//! Walks CPUID leaf 0xD to describe every state component the CPU can save:
+//! its size and offset in the XSAVE area, whether XCR0 (user) or IA32_XSS
+//! (supervisor) enables it, and the area sizes each XSAVE variant needs.
+
+use crate::cpuid::{CpuidSource, HardwareCpuid};
+use alloc::vec::Vec;
+
+/// Legacy x87/SSE region plus the XSAVE header; extended components follow it.
+const LEGACY_AREA: u32 = 512 + 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XsaveComponent {
+    /// Bit in XCR0/IA32_XSS and subleaf of leaf 0xD (2..=63).
+    pub index: u32,
+    pub size: u32,
+    /// Offset in the standard (non-compacted) format; 0 for supervisor state.
+    pub offset: u32,
+    /// Starts on a 64-byte boundary in the compacted format.
+    pub aligned: bool,
+    /// Enabled through IA32_XSS rather than XCR0.
+    pub supervisor: bool,
+    /// Can be disabled per thread with extended feature disable (XFD).
+    pub xfd: bool,
+}
+
+impl XsaveComponent {
+    pub fn name(&self) -> &'static str {
+        match self.index {
+            2 => "AVX (YMM upper halves)",
+            3 => "MPX bound registers",
+            4 => "MPX BNDCSR",
+            5 => "AVX-512 opmask",
+            6 => "AVX-512 ZMM upper halves",
+            7 => "AVX-512 ZMM16-31",
+            8 => "Processor Trace",
+            9 => "PKRU",
+            10 => "PASID",
+            11 => "CET user",
+            12 => "CET supervisor",
+            13 => "HDC",
+            14 => "UINTR",
+            15 => "LBR",
+            16 => "HWP",
+            17 => "AMX TILECFG",
+            18 => "AMX TILEDATA",
+            19 => "APX extended GPRs",
+            _ => "Unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XsaveInfo {
+    /// State components XCR0 can enable.
+    pub user_mask: u64,
+    /// State components IA32_XSS can enable.
+    pub supervisor_mask: u64,
+    /// Extended components (index 2 and up) in index order.
+    pub components: Vec<XsaveComponent>,
+    /// XSAVE area for every user component, standard format.
+    pub xsave_size: u32,
+    /// XSAVE area for the components currently enabled in XCR0.
+    pub xsave_enabled_size: u32,
+    /// XSAVEC area for every user component, compacted format.
+    pub xsavec_size: u32,
+    /// XSAVES area for every user and supervisor component, compacted format.
+    pub xsaves_size: u32,
+    /// XSAVES area for the components currently enabled in XCR0 | IA32_XSS.
+    pub xsaves_enabled_size: u32,
+}
+
+impl XsaveInfo {
+    pub fn detect() -> Self {
+        Self::detect_with(&HardwareCpuid)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "xsave", level = "debug", skip_all))]
+    pub fn detect_with(src: &dyn CpuidSource) -> Self {
+        if !src.is_leaf_supported(0xD) {
+            return Self::default();
+        }
+
+        let main = src.cpuid(0xD, 0);
+        let user_mask = main.eax as u64 | (main.edx as u64) << 32;
+        if user_mask == 0 {
+            return Self::default();
+        }
+        let sub1 = src.cpuid(0xD, 1);
+        let supervisor_mask = sub1.ecx as u64 | (sub1.edx as u64) << 32;
+
+        let components = (2..64)
+            .filter(|index| (user_mask | supervisor_mask) & (1 << index) != 0)
+            .map(|index| {
+                let result = src.cpuid(0xD, index);
+                XsaveComponent {
+                    index,
+                    size: result.eax,
+                    offset: result.ebx,
+                    aligned: result.ecx & (1 << 1) != 0,
+                    supervisor: result.ecx & 1 != 0,
+                    xfd: result.ecx & (1 << 2) != 0,
+                }
+            })
+            .collect();
+
+        let mut info = Self {
+            user_mask,
+            supervisor_mask,
+            components,
+            xsave_size: main.ecx,
+            xsave_enabled_size: main.ebx,
+            xsaves_enabled_size: sub1.ebx,
+            ..Self::default()
+        };
+        info.xsavec_size = info.compacted_size(user_mask);
+        info.xsaves_size = info.compacted_size(user_mask | supervisor_mask);
+        info
+    }
+
+    pub fn component(&self, index: u32) -> Option<&XsaveComponent> {
+        self.components.iter().find(|c| c.index == index)
+    }
+
+    /// Compacted-format (XSAVEC/XSAVES) area size for the components in
+    /// `mask`, e.g. the current XCR0 for a context-switch buffer.
+    pub fn compacted_size(&self, mask: u64) -> u32 {
+        // Saturates rather than overflowing on sizes from a garbage leaf 0xD
+        let mut size = LEGACY_AREA;
+        for component in self.components.iter().filter(|c| mask & (1 << c.index) != 0) {
+            if component.aligned {
+                size = size.checked_next_multiple_of(64).unwrap_or(u32::MAX);
+            }
+            size = size.saturating_add(component.size);
+        }
+        size
+    }
+}